@@ -133,6 +133,17 @@ pub mod v1 {
             )
         }
 
+        pub fn rollout_component(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!(
+                "{}.component.rollout.{host_id}",
+                prefix(topic_prefix, lattice, CTL_API_VERSION_1)
+            )
+        }
+
         pub fn stop_host(topic_prefix: &Option<String>, lattice: &str, host_id: &str) -> String {
             format!(
                 "{}.host.stop.{host_id}",
@@ -13,15 +13,16 @@ use tokio::sync::mpsc::Receiver;
 use tracing::{debug, error, instrument, trace};
 
 use crate::types::ctl::{
-    CtlResponse, ScaleComponentCommand, StartProviderCommand, StopHostCommand, StopProviderCommand,
+    ComponentRollout, CtlResponse, ProviderRestartPolicy, RolloutAction, RolloutComponentCommand,
+    ScaleComponentCommand, StartProviderCommand, StopHostCommand, StopProviderCommand,
     UpdateComponentCommand,
 };
 use crate::types::host::{Host, HostInventory, HostLabel};
 use crate::types::link::Link;
 use crate::types::registry::RegistryCredential;
 use crate::types::rpc::{
-    ComponentAuctionAck, ComponentAuctionRequest, DeleteInterfaceLinkDefinitionRequest,
-    ProviderAuctionAck, ProviderAuctionRequest,
+    AuctionRequirements, ComponentAuctionAck, ComponentAuctionRequest,
+    DeleteInterfaceLinkDefinitionRequest, ProviderAuctionAck, ProviderAuctionRequest,
 };
 use crate::{
     broker, json_deserialize, json_serialize, otel, HostLabelIdentifier, IdentifierKind, Result,
@@ -233,6 +234,29 @@ impl Client {
         self.publish_and_wait(subject, bytes).await
     }
 
+    /// Performs a component auction within the lattice like [`Client::perform_component_auction`],
+    /// additionally requiring bidding hosts to be able to provide the given resources.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn perform_component_auction_with_requirements(
+        &self,
+        component_ref: &str,
+        component_id: &str,
+        constraints: impl Into<BTreeMap<String, String>>,
+        requirements: AuctionRequirements,
+    ) -> Result<Vec<CtlResponse<ComponentAuctionAck>>> {
+        let subject = broker::v1::component_auction_subject(&self.topic_prefix, &self.lattice);
+        let bytes = json_serialize(
+            ComponentAuctionRequest::builder()
+                .component_ref(IdentifierKind::is_component_ref(component_ref)?)
+                .component_id(IdentifierKind::is_component_id(component_id)?)
+                .constraints(constraints.into())
+                .requirements(requirements)
+                .build()?,
+        )?;
+        debug!("component_auction:publish {}", &subject);
+        self.publish_and_wait(subject, bytes).await
+    }
+
     /// Performs a provider auction within the lattice, publishing a set of constraints and the
     /// metadata for the provider in question.
     ///
@@ -267,6 +291,29 @@ impl Client {
         self.publish_and_wait(subject, bytes).await
     }
 
+    /// Performs a provider auction within the lattice like [`Client::perform_provider_auction`],
+    /// additionally requiring bidding hosts to be able to provide the given resources.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn perform_provider_auction_with_requirements(
+        &self,
+        provider_ref: &str,
+        provider_id: &str,
+        constraints: impl Into<BTreeMap<String, String>>,
+        requirements: AuctionRequirements,
+    ) -> Result<Vec<CtlResponse<ProviderAuctionAck>>> {
+        let subject = broker::v1::provider_auction_subject(&self.topic_prefix, &self.lattice);
+        let bytes = json_serialize(
+            ProviderAuctionRequest::builder()
+                .provider_ref(IdentifierKind::is_provider_ref(provider_ref)?)
+                .provider_id(IdentifierKind::is_provider_id(provider_id)?)
+                .constraints(constraints.into())
+                .requirements(requirements)
+                .build()?,
+        )?;
+        debug!("provider_auction:publish {}", &subject);
+        self.publish_and_wait(subject, bytes).await
+    }
+
     /// Sends a request to the given host to scale a given component.
     ///
     /// This returns an acknowledgement of _receipt_ of the command, not a confirmation that the component scaled.
@@ -616,6 +663,51 @@ impl Client {
             component_id: IdentifierKind::is_component_id(existing_component_id)?,
             new_component_ref: IdentifierKind::is_component_ref(new_component_ref)?,
             annotations,
+            rollout: None,
+        })?;
+        match self.request_timeout(subject, bytes, self.timeout).await {
+            Ok(msg) => Ok(json_deserialize(&msg.payload)?),
+            Err(e) => Err(format!("Did not receive update component acknowledgement: {e}").into()),
+        }
+    }
+
+    /// Command a host to start a new version of an existing component alongside the running one,
+    /// splitting invocations between the two according to the supplied [`ComponentRollout`].
+    ///
+    /// The new version keeps receiving only its share of invocations until the rollout is
+    /// completed with [`Client::rollout_component`]. As with [`Client::update_component`], the host
+    /// acknowledges this request **before** the new bytes are downloaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host on which the component should be updated
+    /// * `existing_component_id` - ID of the existing component
+    /// * `new_component_ref` - New component reference that should be rolled out
+    /// * `annotations` - Annotations to place on the new version of the component
+    /// * `rollout` - How invocations should be split between the two versions
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn update_component_with_rollout(
+        &self,
+        host_id: &str,
+        existing_component_id: &str,
+        new_component_ref: &str,
+        annotations: Option<BTreeMap<String, String>>,
+        rollout: ComponentRollout,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = IdentifierKind::is_host_id(host_id)?;
+        let subject = broker::v1::commands::update_component(
+            &self.topic_prefix,
+            &self.lattice,
+            host_id.as_str(),
+        );
+        debug!("update_component_with_rollout:request {}", &subject);
+        let bytes = json_serialize(UpdateComponentCommand {
+            host_id,
+            component_id: IdentifierKind::is_component_id(existing_component_id)?,
+            new_component_ref: IdentifierKind::is_component_ref(new_component_ref)?,
+            annotations,
+            rollout: Some(rollout),
         })?;
         match self.request_timeout(subject, bytes, self.timeout).await {
             Ok(msg) => Ok(json_deserialize(&msg.payload)?),
@@ -623,6 +715,40 @@ impl Client {
         }
     }
 
+    /// Command a host to promote or roll back a component rollout started with
+    /// [`Client::update_component_with_rollout`].
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host on which the rollout is in progress
+    /// * `component_id` - ID of the component being rolled out
+    /// * `action` - Whether to promote or roll back the new version
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn rollout_component(
+        &self,
+        host_id: &str,
+        component_id: &str,
+        action: RolloutAction,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = IdentifierKind::is_host_id(host_id)?;
+        let subject = broker::v1::commands::rollout_component(
+            &self.topic_prefix,
+            &self.lattice,
+            host_id.as_str(),
+        );
+        debug!("rollout_component:request {}", &subject);
+        let bytes = json_serialize(RolloutComponentCommand {
+            host_id,
+            component_id: IdentifierKind::is_component_id(component_id)?,
+            action,
+        })?;
+        match self.request_timeout(subject, bytes, self.timeout).await {
+            Ok(msg) => Ok(json_deserialize(&msg.payload)?),
+            Err(e) => Err(format!("Did not receive rollout component acknowledgement: {e}").into()),
+        }
+    }
+
     /// Command a host to start a provider with a given OCI reference.
     ///
     /// The specified link name will be used (or "default" if none is specified).
@@ -675,6 +801,42 @@ impl Client {
         }
     }
 
+    /// Issues a command to a host to start a provider like [`Client::start_provider`], using the
+    /// given policy to restart the provider when its process exits.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn start_provider_with_restart_policy(
+        &self,
+        host_id: &str,
+        provider_ref: &str,
+        provider_id: &str,
+        annotations: Option<BTreeMap<String, String>>,
+        provider_configuration: Vec<String>,
+        restart_policy: ProviderRestartPolicy,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = IdentifierKind::is_host_id(host_id)?;
+        let subject = broker::v1::commands::start_provider(
+            &self.topic_prefix,
+            &self.lattice,
+            host_id.as_str(),
+        );
+        debug!("start_provider:request {}", &subject);
+        let mut cmd = StartProviderCommand::builder()
+            .host_id(&host_id)
+            .provider_ref(&IdentifierKind::is_provider_ref(provider_ref)?)
+            .provider_id(&IdentifierKind::is_component_id(provider_id)?)
+            .restart_policy(restart_policy);
+        if let Some(annotations) = annotations {
+            cmd = cmd.annotations(annotations);
+        }
+        let cmd = cmd.config(provider_configuration).build()?;
+        let bytes = json_serialize(cmd)?;
+
+        match self.request_timeout(subject, bytes, self.timeout).await {
+            Ok(msg) => Ok(json_deserialize(&msg.payload)?),
+            Err(e) => Err(format!("Did not receive start provider acknowledgement: {e}").into()),
+        }
+    }
+
     /// Issues a command to a host to stop a provider for the given OCI reference, link name, and
     /// contract ID.
     ///
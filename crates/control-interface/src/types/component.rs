@@ -34,6 +34,22 @@ pub struct ComponentDescription {
     /// The maximum number of concurrent requests this instance can handle
     #[serde(default)]
     pub(crate) max_instances: u32,
+
+    /// The number of requests this component is currently handling
+    #[serde(default)]
+    pub(crate) active_instances: u32,
+
+    /// Bytes of linear memory currently used by all instances of this component
+    #[serde(default)]
+    pub(crate) memory_bytes: u64,
+
+    /// The largest amount of linear memory, in bytes, used by a single instance of this component
+    #[serde(default)]
+    pub(crate) peak_memory_bytes: u64,
+
+    /// The number of seconds this component has been running on the host
+    #[serde(default)]
+    pub(crate) uptime_seconds: u64,
 }
 
 #[derive(Default, Clone, PartialEq, Eq)]
@@ -45,6 +61,10 @@ pub struct ComponentDescriptionBuilder {
     annotations: Option<BTreeMap<String, String>>,
     revision: Option<i32>,
     max_instances: Option<u32>,
+    active_instances: Option<u32>,
+    memory_bytes: Option<u64>,
+    peak_memory_bytes: Option<u64>,
+    uptime_seconds: Option<u64>,
 }
 
 impl ComponentDescriptionBuilder {
@@ -84,6 +104,30 @@ impl ComponentDescriptionBuilder {
         self
     }
 
+    #[must_use]
+    pub fn active_instances(mut self, v: u32) -> Self {
+        self.active_instances = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn memory_bytes(mut self, v: u64) -> Self {
+        self.memory_bytes = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn peak_memory_bytes(mut self, v: u64) -> Self {
+        self.peak_memory_bytes = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn uptime_seconds(mut self, v: u64) -> Self {
+        self.uptime_seconds = Some(v);
+        self
+    }
+
     pub fn build(self) -> Result<ComponentDescription> {
         Ok(ComponentDescription {
             image_ref: self
@@ -94,6 +138,10 @@ impl ComponentDescriptionBuilder {
             revision: self.revision.unwrap_or_default(),
             max_instances: self.max_instances.unwrap_or_default(),
             annotations: self.annotations,
+            active_instances: self.active_instances.unwrap_or_default(),
+            memory_bytes: self.memory_bytes.unwrap_or_default(),
+            peak_memory_bytes: self.peak_memory_bytes.unwrap_or_default(),
+            uptime_seconds: self.uptime_seconds.unwrap_or_default(),
         })
     }
 }
@@ -129,6 +177,26 @@ impl ComponentDescription {
         self.max_instances
     }
 
+    /// Get the number of requests the component is currently handling
+    pub fn active_instances(&self) -> u32 {
+        self.active_instances
+    }
+
+    /// Get the bytes of linear memory currently used by all instances of the component
+    pub fn memory_bytes(&self) -> u64 {
+        self.memory_bytes
+    }
+
+    /// Get the largest amount of linear memory, in bytes, used by a single instance of the component
+    pub fn peak_memory_bytes(&self) -> u64 {
+        self.peak_memory_bytes
+    }
+
+    /// Get the number of seconds the component has been running
+    pub fn uptime_seconds(&self) -> u64 {
+        self.uptime_seconds
+    }
+
     #[must_use]
     pub fn builder() -> ComponentDescriptionBuilder {
         ComponentDescriptionBuilder::default()
@@ -263,7 +331,11 @@ mod tests {
                 name: Some("name".into()),
                 annotations: Some(BTreeMap::from([("a".into(), "b".into())])),
                 revision: 0,
-                max_instances: 1,
+                max_instances: 2,
+                active_instances: 1,
+                memory_bytes: 131072,
+                peak_memory_bytes: 131072,
+                uptime_seconds: 10,
             },
             ComponentDescription::builder()
                 .id("id".into())
@@ -272,7 +344,11 @@ mod tests {
                 .name("name".into())
                 .annotations(BTreeMap::from([("a".into(), "b".into())]))
                 .revision(0)
-                .max_instances(1)
+                .max_instances(2)
+                .active_instances(1)
+                .memory_bytes(131072)
+                .peak_memory_bytes(131072)
+                .uptime_seconds(10)
                 .build()
                 .unwrap()
         )
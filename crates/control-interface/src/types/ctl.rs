@@ -1,5 +1,7 @@
 //! Data types used when interacting with the control interface of a wasmCloud lattice
 
+use core::time::Duration;
+
 use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
@@ -264,6 +266,10 @@ pub struct StartProviderCommand {
     /// example, autonomous agents may wish to "tag" start requests as part of a given deployment
     #[serde(default, skip_serializing_if = "Option::is_none")]
     annotations: Option<BTreeMap<String, String>>,
+    /// How the host should restart the provider when its process exits. Hosts always restart
+    /// providers that exit unexpectedly if this is not specified.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    restart_policy: Option<ProviderRestartPolicy>,
 }
 
 impl StartProviderCommand {
@@ -292,6 +298,11 @@ impl StartProviderCommand {
         self.annotations.as_ref()
     }
 
+    #[must_use]
+    pub fn restart_policy(&self) -> Option<&ProviderRestartPolicy> {
+        self.restart_policy.as_ref()
+    }
+
     #[must_use]
     pub fn builder() -> StartProviderCommandBuilder {
         StartProviderCommandBuilder::default()
@@ -307,6 +318,7 @@ pub struct StartProviderCommandBuilder {
     provider_ref: Option<String>,
     annotations: Option<BTreeMap<String, String>>,
     config: Option<Vec<String>>,
+    restart_policy: Option<ProviderRestartPolicy>,
 }
 
 impl StartProviderCommandBuilder {
@@ -345,6 +357,12 @@ impl StartProviderCommandBuilder {
         self
     }
 
+    #[must_use]
+    pub fn restart_policy(mut self, v: ProviderRestartPolicy) -> Self {
+        self.restart_policy = Some(v);
+        self
+    }
+
     pub fn build(self) -> Result<StartProviderCommand> {
         Ok(StartProviderCommand {
             provider_ref: self
@@ -358,6 +376,157 @@ impl StartProviderCommandBuilder {
                 .host_id
                 .ok_or_else(|| "host id is required for starting providers".to_string())?,
             config: self.config.unwrap_or_default(),
+            restart_policy: self.restart_policy,
+        })
+    }
+}
+
+/// When a host restarts a provider whose process exited
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartMode {
+    /// Never restart the provider
+    Never,
+    /// Restart the provider only if it exited with a non-zero status or was killed
+    OnFailure,
+    /// Restart the provider whenever it exits without being stopped
+    #[default]
+    Always,
+}
+
+/// How a host restarts a provider whose process exited.
+///
+/// Consecutive restarts are delayed by an exponentially increasing backoff, starting at
+/// `initial_backoff_ms` and doubling up to `max_backoff_ms`. A provider that stays up for longer than
+/// `max_backoff_ms` is considered recovered and its backoff and retry count are reset.
+///
+/// The default policy keeps the behavior of hosts that predate restart policies: providers are
+/// always restarted, indefinitely, 5 seconds after they exit.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ProviderRestartPolicy {
+    /// When the provider is restarted
+    #[serde(default)]
+    pub(crate) mode: RestartMode,
+    /// The maximum number of consecutive restarts, after which the provider is left stopped.
+    /// Providers are restarted indefinitely if not specified.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_retries: Option<u32>,
+    /// Delay before the first restart, in milliseconds
+    #[serde(default = "default_initial_backoff_ms")]
+    pub(crate) initial_backoff_ms: u64,
+    /// Upper bound of the delay between restarts, in milliseconds
+    #[serde(default = "default_max_backoff_ms")]
+    pub(crate) max_backoff_ms: u64,
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    5_000
+}
+
+fn default_max_backoff_ms() -> u64 {
+    5_000
+}
+
+impl Default for ProviderRestartPolicy {
+    fn default() -> Self {
+        Self {
+            mode: RestartMode::default(),
+            max_retries: None,
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+        }
+    }
+}
+
+impl ProviderRestartPolicy {
+    #[must_use]
+    pub fn mode(&self) -> RestartMode {
+        self.mode
+    }
+
+    #[must_use]
+    pub fn max_retries(&self) -> Option<u32> {
+        self.max_retries
+    }
+
+    #[must_use]
+    pub fn initial_backoff_ms(&self) -> u64 {
+        self.initial_backoff_ms
+    }
+
+    #[must_use]
+    pub fn max_backoff_ms(&self) -> u64 {
+        self.max_backoff_ms
+    }
+
+    /// The delay before restarting the provider after `restarts` consecutive restarts
+    #[must_use]
+    pub fn backoff(&self, restarts: u32) -> Duration {
+        let backoff = self
+            .initial_backoff_ms
+            .saturating_mul(2_u64.saturating_pow(restarts))
+            .min(self.max_backoff_ms);
+        Duration::from_millis(backoff)
+    }
+
+    #[must_use]
+    pub fn builder() -> ProviderRestartPolicyBuilder {
+        ProviderRestartPolicyBuilder::default()
+    }
+}
+
+/// A builder that produces [`ProviderRestartPolicy`]s
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ProviderRestartPolicyBuilder {
+    mode: Option<RestartMode>,
+    max_retries: Option<u32>,
+    initial_backoff_ms: Option<u64>,
+    max_backoff_ms: Option<u64>,
+}
+
+impl ProviderRestartPolicyBuilder {
+    #[must_use]
+    pub fn mode(mut self, v: RestartMode) -> Self {
+        self.mode = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn max_retries(mut self, v: u32) -> Self {
+        self.max_retries = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn initial_backoff_ms(mut self, v: u64) -> Self {
+        self.initial_backoff_ms = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn max_backoff_ms(mut self, v: u64) -> Self {
+        self.max_backoff_ms = Some(v);
+        self
+    }
+
+    pub fn build(self) -> Result<ProviderRestartPolicy> {
+        let initial_backoff_ms = self
+            .initial_backoff_ms
+            .unwrap_or_else(default_initial_backoff_ms);
+        // Only setting the initial backoff is never an error, but turns off the exponential backoff
+        let max_backoff_ms = self
+            .max_backoff_ms
+            .unwrap_or_else(|| default_max_backoff_ms().max(initial_backoff_ms));
+        if initial_backoff_ms > max_backoff_ms {
+            return Err("initial backoff must not exceed the maximum backoff".into());
+        }
+        Ok(ProviderRestartPolicy {
+            mode: self.mode.unwrap_or_default(),
+            max_retries: self.max_retries,
+            initial_backoff_ms,
+            max_backoff_ms,
         })
     }
 }
@@ -513,6 +682,12 @@ pub struct UpdateComponentCommand {
     /// The new image reference of the upgraded version of this component
     #[serde(default)]
     pub(crate) new_component_ref: String,
+    /// Optional rollout strategy. When specified, the new version is started alongside the
+    /// existing one and only receives a share of invocations until the rollout is promoted or
+    /// rolled back with a [`RolloutComponentCommand`]. When omitted, the component is replaced
+    /// in place.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) rollout: Option<ComponentRollout>,
 }
 
 impl UpdateComponentCommand {
@@ -536,6 +711,11 @@ impl UpdateComponentCommand {
         self.annotations.as_ref()
     }
 
+    #[must_use]
+    pub fn rollout(&self) -> Option<&ComponentRollout> {
+        self.rollout.as_ref()
+    }
+
     #[must_use]
     pub fn builder() -> UpdateComponentCommandBuilder {
         UpdateComponentCommandBuilder::default()
@@ -550,6 +730,7 @@ pub struct UpdateComponentCommandBuilder {
     component_id: Option<String>,
     new_component_ref: Option<String>,
     annotations: Option<BTreeMap<String, String>>,
+    rollout: Option<ComponentRollout>,
 }
 
 impl UpdateComponentCommandBuilder {
@@ -582,6 +763,12 @@ impl UpdateComponentCommandBuilder {
         self
     }
 
+    #[must_use]
+    pub fn rollout(mut self, v: ComponentRollout) -> Self {
+        self.rollout = Some(v);
+        self
+    }
+
     pub fn build(self) -> Result<UpdateComponentCommand> {
         Ok(UpdateComponentCommand {
             host_id: self
@@ -594,6 +781,172 @@ impl UpdateComponentCommandBuilder {
                 "new component ref is required for updating components".to_string()
             })?,
             annotations: self.annotations,
+            rollout: self.rollout,
+        })
+    }
+}
+
+/// Describes how invocations are split between the running version of a component and a new
+/// version started by an [`UpdateComponentCommand`]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ComponentRollout {
+    /// Percentage of invocations (0-100) that should be handled by the new version
+    #[serde(default)]
+    pub(crate) weight: u8,
+    /// Invocation headers that, when all present with matching values, route an invocation to
+    /// the new version regardless of `weight`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) headers: BTreeMap<String, String>,
+}
+
+impl ComponentRollout {
+    #[must_use]
+    pub fn weight(&self) -> u8 {
+        self.weight
+    }
+
+    #[must_use]
+    pub fn headers(&self) -> &BTreeMap<String, String> {
+        &self.headers
+    }
+
+    #[must_use]
+    pub fn builder() -> ComponentRolloutBuilder {
+        ComponentRolloutBuilder::default()
+    }
+}
+
+/// Builder for [`ComponentRollout`]s
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ComponentRolloutBuilder {
+    weight: Option<u8>,
+    headers: Option<BTreeMap<String, String>>,
+}
+
+impl ComponentRolloutBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn weight(mut self, v: u8) -> Self {
+        self.weight = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn headers(mut self, v: impl Into<BTreeMap<String, String>>) -> Self {
+        self.headers = Some(v.into());
+        self
+    }
+
+    pub fn build(self) -> Result<ComponentRollout> {
+        let weight = self.weight.unwrap_or_default();
+        if weight > 100 {
+            return Err("rollout weight must be a percentage between 0 and 100".into());
+        }
+        Ok(ComponentRollout {
+            weight,
+            headers: self.headers.unwrap_or_default(),
+        })
+    }
+}
+
+/// The action to take on a component rollout that is in progress
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RolloutAction {
+    /// Replace the previous version with the new version, which then receives all invocations
+    Promote,
+    /// Stop the new version, leaving the previous version to receive all invocations
+    Rollback,
+}
+
+/// A command instructing a specific host to promote or roll back a component rollout
+/// that was started by an [`UpdateComponentCommand`] with a [`ComponentRollout`]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct RolloutComponentCommand {
+    /// The component's unique ID
+    #[serde(default)]
+    pub(crate) component_id: String,
+    /// The host ID of the host on which the rollout is in progress
+    #[serde(default)]
+    pub(crate) host_id: String,
+    /// Whether to promote or roll back the new version. This is required, so that a command
+    /// missing it is rejected rather than promoting the new version
+    pub(crate) action: RolloutAction,
+}
+
+impl RolloutComponentCommand {
+    #[must_use]
+    pub fn host_id(&self) -> &str {
+        &self.host_id
+    }
+
+    #[must_use]
+    pub fn component_id(&self) -> &str {
+        &self.component_id
+    }
+
+    #[must_use]
+    pub fn action(&self) -> RolloutAction {
+        self.action
+    }
+
+    #[must_use]
+    pub fn builder() -> RolloutComponentCommandBuilder {
+        RolloutComponentCommandBuilder::default()
+    }
+}
+
+/// Builder for [`RolloutComponentCommand`]s
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct RolloutComponentCommandBuilder {
+    host_id: Option<String>,
+    component_id: Option<String>,
+    action: Option<RolloutAction>,
+}
+
+impl RolloutComponentCommandBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn host_id(mut self, v: &str) -> Self {
+        self.host_id = Some(v.into());
+        self
+    }
+
+    #[must_use]
+    pub fn component_id(mut self, v: &str) -> Self {
+        self.component_id = Some(v.into());
+        self
+    }
+
+    #[must_use]
+    pub fn action(mut self, v: RolloutAction) -> Self {
+        self.action = Some(v);
+        self
+    }
+
+    pub fn build(self) -> Result<RolloutComponentCommand> {
+        Ok(RolloutComponentCommand {
+            host_id: self
+                .host_id
+                .ok_or_else(|| "host id is required for component rollouts".to_string())?,
+            component_id: self
+                .component_id
+                .ok_or_else(|| "component id is required for component rollouts".to_string())?,
+            action: self
+                .action
+                .ok_or_else(|| "action is required for component rollouts".to_string())?,
         })
     }
 }
@@ -602,9 +955,12 @@ impl UpdateComponentCommandBuilder {
 mod tests {
     use std::collections::BTreeMap;
 
+    use core::time::Duration;
+
     use super::{
-        ScaleComponentCommand, StartProviderCommand, StopHostCommand, StopProviderCommand,
-        UpdateComponentCommand,
+        ComponentRollout, ProviderRestartPolicy, RestartMode, RolloutAction,
+        RolloutComponentCommand, ScaleComponentCommand, StartProviderCommand, StopHostCommand,
+        StopProviderCommand, UpdateComponentCommand,
     };
 
     #[test]
@@ -641,6 +997,7 @@ mod tests {
                 host_id: "host_id".into(),
                 config: vec!["p".into()],
                 annotations: Some(BTreeMap::from([("a".into(), "b".into())])),
+                restart_policy: None,
             },
             StartProviderCommand::builder()
                 .provider_id("provider_id")
@@ -691,6 +1048,7 @@ mod tests {
                 component_id: "component_id".into(),
                 new_component_ref: "new_component_ref".into(),
                 annotations: Some(BTreeMap::from([("a".into(), "b".into())])),
+                rollout: None,
             },
             UpdateComponentCommand::builder()
                 .host_id("host_id")
@@ -701,4 +1059,102 @@ mod tests {
                 .unwrap()
         )
     }
+
+    #[test]
+    fn component_rollout_builder() {
+        assert_eq!(
+            ComponentRollout {
+                weight: 10,
+                headers: BTreeMap::from([("x-canary".into(), "true".into())]),
+            },
+            ComponentRollout::builder()
+                .weight(10)
+                .headers(BTreeMap::from([("x-canary".into(), "true".into())]))
+                .build()
+                .unwrap()
+        );
+        assert!(ComponentRollout::builder().weight(101).build().is_err());
+    }
+
+    #[test]
+    fn rollout_component_command_builder() {
+        assert_eq!(
+            RolloutComponentCommand {
+                host_id: "host_id".into(),
+                component_id: "component_id".into(),
+                action: RolloutAction::Rollback,
+            },
+            RolloutComponentCommand::builder()
+                .host_id("host_id")
+                .component_id("component_id")
+                .action(RolloutAction::Rollback)
+                .build()
+                .unwrap()
+        )
+    }
+
+    #[test]
+    fn rollout_component_command_requires_action() {
+        let cmd: RolloutComponentCommand = serde_json::from_str(
+            r#"{"component_id":"component_id","host_id":"host_id","action":"rollback"}"#,
+        )
+        .unwrap();
+        assert_eq!(cmd.action(), RolloutAction::Rollback);
+        assert!(serde_json::from_str::<RolloutComponentCommand>(
+            r#"{"component_id":"component_id","host_id":"host_id"}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn provider_restart_policy_default() {
+        // Providers without a policy are restarted like they were before restart policies existed
+        let policy = ProviderRestartPolicy::default();
+        assert_eq!(policy.mode(), RestartMode::Always);
+        assert_eq!(policy.max_retries(), None);
+        assert_eq!(policy.backoff(0), Duration::from_secs(5));
+        assert_eq!(policy.backoff(10), Duration::from_secs(5));
+        assert_eq!(
+            serde_json::from_str::<ProviderRestartPolicy>("{}").unwrap(),
+            policy
+        );
+        assert_eq!(ProviderRestartPolicy::builder().build().unwrap(), policy);
+    }
+
+    #[test]
+    fn provider_restart_policy_builder() {
+        let policy = ProviderRestartPolicy::builder()
+            .mode(RestartMode::OnFailure)
+            .max_retries(3)
+            .initial_backoff_ms(1_000)
+            .max_backoff_ms(5_000)
+            .build()
+            .unwrap();
+        assert_eq!(
+            ProviderRestartPolicy {
+                mode: RestartMode::OnFailure,
+                max_retries: Some(3),
+                initial_backoff_ms: 1_000,
+                max_backoff_ms: 5_000,
+            },
+            policy
+        );
+        assert_eq!(policy.backoff(0), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(4));
+        assert_eq!(policy.backoff(3), Duration::from_secs(5));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(5));
+        assert!(ProviderRestartPolicy::builder()
+            .initial_backoff_ms(2)
+            .max_backoff_ms(1)
+            .build()
+            .is_err());
+        assert_eq!(
+            ProviderRestartPolicy::builder()
+                .initial_backoff_ms(10_000)
+                .build()
+                .unwrap()
+                .max_backoff_ms(),
+            10_000
+        );
+    }
 }
@@ -26,6 +26,19 @@ pub struct ProviderDescription {
     /// this provider instance
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub(crate) annotations: Option<BTreeMap<String, String>>,
+    /// Resident memory of the provider process in bytes, if the provider runs in its own process
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) memory_bytes: Option<u64>,
+    /// CPU used by the provider process in thousandths of a core, if the provider runs in its own
+    /// process
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) cpu_millicores: Option<u64>,
+    /// The number of seconds this provider has been running on the host
+    #[serde(default)]
+    pub(crate) uptime_seconds: u64,
+    /// The number of times the host restarted the provider after its process exited
+    #[serde(default)]
+    pub(crate) restarts: u32,
 }
 
 impl ProviderDescription {
@@ -54,6 +67,26 @@ impl ProviderDescription {
         self.annotations.as_ref()
     }
 
+    /// Get the resident memory of the provider process in bytes
+    pub fn memory_bytes(&self) -> Option<u64> {
+        self.memory_bytes
+    }
+
+    /// Get the CPU used by the provider process in thousandths of a core
+    pub fn cpu_millicores(&self) -> Option<u64> {
+        self.cpu_millicores
+    }
+
+    /// Get the number of seconds the provider has been running
+    pub fn uptime_seconds(&self) -> u64 {
+        self.uptime_seconds
+    }
+
+    /// Get the number of times the provider was restarted
+    pub fn restarts(&self) -> u32 {
+        self.restarts
+    }
+
     #[must_use]
     pub fn builder() -> ProviderDescriptionBuilder {
         ProviderDescriptionBuilder::default()
@@ -69,6 +102,10 @@ pub struct ProviderDescriptionBuilder {
     name: Option<String>,
     revision: Option<i32>,
     annotations: Option<BTreeMap<String, String>>,
+    memory_bytes: Option<u64>,
+    cpu_millicores: Option<u64>,
+    uptime_seconds: Option<u64>,
+    restarts: Option<u32>,
 }
 
 impl ProviderDescriptionBuilder {
@@ -108,6 +145,34 @@ impl ProviderDescriptionBuilder {
         self
     }
 
+    /// Resident memory of the provider process in bytes
+    #[must_use]
+    pub fn memory_bytes(mut self, v: u64) -> Self {
+        self.memory_bytes = Some(v);
+        self
+    }
+
+    /// CPU used by the provider process in thousandths of a core
+    #[must_use]
+    pub fn cpu_millicores(mut self, v: u64) -> Self {
+        self.cpu_millicores = Some(v);
+        self
+    }
+
+    /// The number of seconds this provider has been running on the host
+    #[must_use]
+    pub fn uptime_seconds(mut self, v: u64) -> Self {
+        self.uptime_seconds = Some(v);
+        self
+    }
+
+    /// The number of times the host restarted the provider after its process exited
+    #[must_use]
+    pub fn restarts(mut self, v: u32) -> Self {
+        self.restarts = Some(v);
+        self
+    }
+
     /// Build a [`ProviderDescription`]
     pub fn build(self) -> Result<ProviderDescription> {
         Ok(ProviderDescription {
@@ -116,6 +181,10 @@ impl ProviderDescriptionBuilder {
             name: self.name,
             revision: self.revision.unwrap_or_default(),
             annotations: self.annotations,
+            memory_bytes: self.memory_bytes,
+            cpu_millicores: self.cpu_millicores,
+            uptime_seconds: self.uptime_seconds.unwrap_or_default(),
+            restarts: self.restarts.unwrap_or_default(),
         })
     }
 }
@@ -135,6 +204,10 @@ mod tests {
                 name: Some("name".into()),
                 annotations: Some(BTreeMap::from([("a".into(), "b".into())])),
                 revision: 0,
+                memory_bytes: Some(1048576),
+                cpu_millicores: Some(250),
+                uptime_seconds: 10,
+                restarts: 1,
             },
             ProviderDescription::builder()
                 .id("id")
@@ -142,6 +215,10 @@ mod tests {
                 .name("name")
                 .annotations(BTreeMap::from([("a".into(), "b".into())]))
                 .revision(0)
+                .memory_bytes(1048576)
+                .cpu_millicores(250)
+                .uptime_seconds(10)
+                .restarts(1)
                 .build()
                 .unwrap()
         )
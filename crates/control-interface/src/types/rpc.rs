@@ -133,6 +133,9 @@ pub struct ComponentAuctionRequest {
     pub(crate) component_id: String,
     /// The set of constraints that must match the labels of a suitable target host
     pub(crate) constraints: BTreeMap<String, String>,
+    /// Resources that a suitable target host must be able to provide
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) requirements: Option<AuctionRequirements>,
 }
 
 impl ComponentAuctionRequest {
@@ -145,7 +148,7 @@ impl ComponentAuctionRequest {
     /// Get the component ID for the auction request
     #[must_use]
     pub fn component_id(&self) -> &str {
-        self.component_ref.as_ref()
+        self.component_id.as_ref()
     }
 
     /// Get the constraints for the auction request
@@ -154,6 +157,12 @@ impl ComponentAuctionRequest {
         &self.constraints
     }
 
+    /// Get the resource requirements for the auction request
+    #[must_use]
+    pub fn requirements(&self) -> Option<&AuctionRequirements> {
+        self.requirements.as_ref()
+    }
+
     pub fn builder() -> ComponentAuctionRequestBuilder {
         ComponentAuctionRequestBuilder::default()
     }
@@ -164,6 +173,7 @@ pub struct ComponentAuctionRequestBuilder {
     component_ref: Option<String>,
     component_id: Option<String>,
     constraints: Option<BTreeMap<String, String>>,
+    requirements: Option<AuctionRequirements>,
 }
 
 impl ComponentAuctionRequestBuilder {
@@ -185,6 +195,12 @@ impl ComponentAuctionRequestBuilder {
         self
     }
 
+    #[must_use]
+    pub fn requirements(mut self, v: AuctionRequirements) -> Self {
+        self.requirements = Some(v);
+        self
+    }
+
     pub fn build(self) -> Result<ComponentAuctionRequest> {
         Ok(ComponentAuctionRequest {
             component_ref: self
@@ -194,6 +210,7 @@ impl ComponentAuctionRequestBuilder {
                 .component_id
                 .ok_or_else(|| "component_id is required".to_string())?,
             constraints: self.constraints.unwrap_or_default(),
+            requirements: self.requirements,
         })
     }
 }
@@ -316,6 +333,10 @@ pub struct ProviderAuctionRequest {
 
     /// The set of constraints that must match the labels of a suitable target host
     pub(crate) constraints: BTreeMap<String, String>,
+
+    /// Resources that a suitable target host must be able to provide
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) requirements: Option<AuctionRequirements>,
 }
 
 impl ProviderAuctionRequest {
@@ -337,6 +358,12 @@ impl ProviderAuctionRequest {
         &self.constraints
     }
 
+    /// Get the resource requirements for the auction request
+    #[must_use]
+    pub fn requirements(&self) -> Option<&AuctionRequirements> {
+        self.requirements.as_ref()
+    }
+
     /// Build a new [`ProviderAuctionRequest`]
     #[must_use]
     pub fn builder() -> ProviderAuctionRequestBuilder {
@@ -349,6 +376,7 @@ pub struct ProviderAuctionRequestBuilder {
     provider_ref: Option<String>,
     provider_id: Option<String>,
     constraints: Option<BTreeMap<String, String>>,
+    requirements: Option<AuctionRequirements>,
 }
 
 impl ProviderAuctionRequestBuilder {
@@ -370,6 +398,12 @@ impl ProviderAuctionRequestBuilder {
         self
     }
 
+    #[must_use]
+    pub fn requirements(mut self, v: AuctionRequirements) -> Self {
+        self.requirements = Some(v);
+        self
+    }
+
     pub fn build(self) -> Result<ProviderAuctionRequest> {
         Ok(ProviderAuctionRequest {
             provider_ref: self
@@ -379,6 +413,123 @@ impl ProviderAuctionRequestBuilder {
                 .provider_id
                 .ok_or_else(|| "provider_id is required".to_string())?,
             constraints: self.constraints.unwrap_or_default(),
+            requirements: self.requirements,
+        })
+    }
+}
+
+/// Resources that a host must be able to provide to bid in an auction.
+///
+/// Hosts that cannot satisfy all of the requirements given their current workloads do not respond
+/// to the auction.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct AuctionRequirements {
+    /// Linear memory in bytes that a single instance of the component needs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) memory_bytes: Option<u64>,
+
+    /// The number of instances of the component that are expected to run concurrently
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) instances: Option<u32>,
+
+    /// CPU architecture the host must run on, as named by Rust's `std::env::consts::ARCH` (e.g. `x86_64`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) arch: Option<String>,
+
+    /// Experimental host features that must be enabled, e.g. `builtin-http-server`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) features: Vec<String>,
+
+    /// IDs of providers that must already be running on the host
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) providers: Vec<String>,
+}
+
+impl AuctionRequirements {
+    /// Get the linear memory in bytes that a single instance needs
+    #[must_use]
+    pub fn memory_bytes(&self) -> Option<u64> {
+        self.memory_bytes
+    }
+
+    /// Get the number of instances that are expected to run concurrently
+    #[must_use]
+    pub fn instances(&self) -> Option<u32> {
+        self.instances
+    }
+
+    /// Get the CPU architecture the host must run on
+    #[must_use]
+    pub fn arch(&self) -> Option<&str> {
+        self.arch.as_deref()
+    }
+
+    /// Get the experimental host features that must be enabled
+    #[must_use]
+    pub fn features(&self) -> &[String] {
+        &self.features
+    }
+
+    /// Get the IDs of providers that must already be running on the host
+    #[must_use]
+    pub fn providers(&self) -> &[String] {
+        &self.providers
+    }
+
+    #[must_use]
+    pub fn builder() -> AuctionRequirementsBuilder {
+        AuctionRequirementsBuilder::default()
+    }
+}
+
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct AuctionRequirementsBuilder {
+    memory_bytes: Option<u64>,
+    instances: Option<u32>,
+    arch: Option<String>,
+    features: Option<Vec<String>>,
+    providers: Option<Vec<String>>,
+}
+
+impl AuctionRequirementsBuilder {
+    #[must_use]
+    pub fn memory_bytes(mut self, v: u64) -> Self {
+        self.memory_bytes = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn instances(mut self, v: u32) -> Self {
+        self.instances = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn arch(mut self, v: String) -> Self {
+        self.arch = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn features(mut self, v: Vec<String>) -> Self {
+        self.features = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn providers(mut self, v: Vec<String>) -> Self {
+        self.providers = Some(v);
+        self
+    }
+
+    pub fn build(self) -> Result<AuctionRequirements> {
+        Ok(AuctionRequirements {
+            memory_bytes: self.memory_bytes,
+            instances: self.instances,
+            arch: self.arch,
+            features: self.features.unwrap_or_default(),
+            providers: self.providers.unwrap_or_default(),
         })
     }
 }
@@ -501,8 +652,8 @@ mod tests {
     use std::collections::BTreeMap;
 
     use super::{
-        ComponentAuctionAck, ComponentAuctionRequest, DeleteInterfaceLinkDefinitionRequest,
-        ProviderAuctionAck, ProviderAuctionRequest,
+        AuctionRequirements, ComponentAuctionAck, ComponentAuctionRequest,
+        DeleteInterfaceLinkDefinitionRequest, ProviderAuctionAck, ProviderAuctionRequest,
     };
 
     #[test]
@@ -530,12 +681,45 @@ mod tests {
             ComponentAuctionRequest {
                 component_ref: "component_ref".into(),
                 component_id: "component_id".into(),
-                constraints: BTreeMap::from([("a".into(), "b".into())])
+                constraints: BTreeMap::from([("a".into(), "b".into())]),
+                requirements: Some(AuctionRequirements {
+                    memory_bytes: Some(1024),
+                    instances: Some(10),
+                    ..Default::default()
+                }),
             },
             ComponentAuctionRequest::builder()
                 .component_ref("component_ref".into())
                 .component_id("component_id".into())
                 .constraints(BTreeMap::from([("a".into(), "b".into())]))
+                .requirements(
+                    AuctionRequirements::builder()
+                        .memory_bytes(1024)
+                        .instances(10)
+                        .build()
+                        .unwrap()
+                )
+                .build()
+                .unwrap()
+        )
+    }
+
+    #[test]
+    fn auction_requirements_builder() {
+        assert_eq!(
+            AuctionRequirements {
+                memory_bytes: Some(1024),
+                instances: Some(10),
+                arch: Some("aarch64".into()),
+                features: vec!["builtin-http-server".into()],
+                providers: vec!["provider_id".into()],
+            },
+            AuctionRequirements::builder()
+                .memory_bytes(1024)
+                .instances(10)
+                .arch("aarch64".into())
+                .features(vec!["builtin-http-server".into()])
+                .providers(vec!["provider_id".into()])
                 .build()
                 .unwrap()
         )
@@ -566,7 +750,8 @@ mod tests {
             ProviderAuctionRequest {
                 provider_ref: "provider_ref".into(),
                 provider_id: "provider_id".into(),
-                constraints: BTreeMap::from([("a".into(), "b".into())])
+                constraints: BTreeMap::from([("a".into(), "b".into())]),
+                requirements: None,
             },
             ProviderAuctionRequest::builder()
                 .provider_ref("provider_ref".into())
@@ -0,0 +1,245 @@
+//! Verification of [cosign](https://github.com/sigstore/cosign) key-based signatures of OCI
+//! artifacts.
+//!
+//! Cosign stores the signatures of an artifact as a separate OCI artifact in the same repository,
+//! tagged `<algorithm>-<digest>.sig` after the digest of the signed manifest. Each layer of the
+//! signature artifact is a "simple signing" payload referencing the signed digest, with the
+//! signature of the payload in a layer annotation.
+
+use std::io::BufReader;
+use std::path::Path;
+
+use anyhow::{bail, ensure, Context as _, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use oci_client::manifest::{OciDescriptor, OciImageManifest};
+use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_ASN1};
+use serde::Deserialize;
+
+/// Media type of cosign signature payloads
+pub const SIMPLE_SIGNING_MEDIA_TYPE: &str = "application/vnd.dev.cosign.simplesigning.v1+json";
+
+/// Annotation of a signature payload layer containing the base64-encoded signature of the payload
+pub const SIGNATURE_ANNOTATION: &str = "dev.cosignproject.cosign/signature";
+
+/// DER encoding of the `SubjectPublicKeyInfo` header of an uncompressed ECDSA P-256 public key,
+/// which is followed by the 65 bytes of the point
+const P256_SPKI_PREFIX: &[u8] = &[
+    0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a,
+    0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00,
+];
+
+#[derive(Deserialize)]
+struct SimpleSigning {
+    critical: Critical,
+}
+
+#[derive(Deserialize)]
+struct Critical {
+    image: Image,
+}
+
+#[derive(Deserialize)]
+struct Image {
+    #[serde(rename = "docker-manifest-digest")]
+    docker_manifest_digest: String,
+}
+
+/// Verifies cosign signatures against a set of trusted ECDSA P-256 public keys, as generated by
+/// `cosign generate-key-pair`
+#[derive(Clone, Debug, Default)]
+pub struct CosignVerifier {
+    /// Uncompressed points of the trusted public keys
+    keys: Vec<Vec<u8>>,
+}
+
+impl CosignVerifier {
+    /// Create a verifier trusting all public keys in PEM-encoded `pem`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `pem` contains no public keys, or a public key that is not an ECDSA
+    /// P-256 key
+    pub fn from_pem(pem: impl AsRef<[u8]>) -> Result<Self> {
+        let mut reader = BufReader::new(pem.as_ref());
+        let keys = rustls_pemfile::public_keys(&mut reader)
+            .map(|key| {
+                let key = key.context("failed to parse PEM public key")?;
+                key.as_ref()
+                    .strip_prefix(P256_SPKI_PREFIX)
+                    .filter(|point| point.len() == 65)
+                    .map(<[u8]>::to_vec)
+                    .context("public key is not an ECDSA P-256 key")
+            })
+            .collect::<Result<Vec<_>>>()?;
+        ensure!(!keys.is_empty(), "no public keys found");
+        Ok(Self { keys })
+    }
+
+    /// Create a verifier trusting all public keys in the PEM files at `paths`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the files cannot be read or contains invalid keys
+    pub async fn from_paths(paths: &[impl AsRef<Path>]) -> Result<Self> {
+        let mut keys = Vec::new();
+        for path in paths {
+            let path = path.as_ref();
+            let pem = tokio::fs::read(path)
+                .await
+                .with_context(|| format!("failed to read `{}`", path.display()))?;
+            let verifier = Self::from_pem(pem)
+                .with_context(|| format!("invalid public keys in `{}`", path.display()))?;
+            keys.extend(verifier.keys);
+        }
+        Ok(Self { keys })
+    }
+
+    /// Whether the verifier trusts no keys, in which case nothing can be verified
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Verify that `payload` is a signature payload for the manifest with `digest`, signed by one
+    /// of the trusted keys with the base64-encoded `signature`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the signature is invalid, was not made by a trusted key, or the payload
+    /// does not reference `digest`
+    pub fn verify(&self, digest: &str, payload: &[u8], signature: &str) -> Result<()> {
+        let signature = STANDARD
+            .decode(signature.trim())
+            .context("failed to decode signature")?;
+        ensure!(
+            self.keys.iter().any(|key| {
+                UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, key)
+                    .verify(payload, &signature)
+                    .is_ok()
+            }),
+            "signature was not made by a trusted key"
+        );
+        // Only inspect the payload once it is known to be authentic
+        let SimpleSigning {
+            critical: Critical { image },
+        } = serde_json::from_slice(payload).context("failed to parse signature payload")?;
+        ensure!(
+            image.docker_manifest_digest == digest,
+            "signature is for digest `{}`, not `{digest}`",
+            image.docker_manifest_digest
+        );
+        Ok(())
+    }
+}
+
+/// Encode the uncompressed `point` of an ECDSA P-256 public key as a PEM public key, as accepted by
+/// [`CosignVerifier::from_pem`]
+#[must_use]
+pub fn p256_public_key_pem(point: &[u8]) -> String {
+    let spki = [P256_SPKI_PREFIX, point].concat();
+    format!(
+        "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n",
+        STANDARD.encode(spki)
+    )
+}
+
+/// Returns the simple signing payload that cosign signs for the manifest with `digest` of the image
+/// at `docker_reference` (a reference without tag or digest, e.g. `localhost:5000/hello`)
+#[must_use]
+pub fn simple_signing_payload(docker_reference: &str, digest: &str) -> Vec<u8> {
+    format!(
+        r#"{{"critical":{{"identity":{{"docker-reference":"{docker_reference}"}},"image":{{"docker-manifest-digest":"{digest}"}},"type":"cosign container image signature"}},"optional":null}}"#
+    )
+    .into_bytes()
+}
+
+/// Returns the layers of a cosign signature artifact that contain a signature payload, along with
+/// the base64-encoded signature of the payload
+pub fn signature_layers(
+    manifest: &OciImageManifest,
+) -> impl Iterator<Item = (&OciDescriptor, &str)> {
+    manifest
+        .layers
+        .iter()
+        .filter(|layer| layer.media_type == SIMPLE_SIGNING_MEDIA_TYPE)
+        .filter_map(|layer| {
+            let signature = layer.annotations.as_ref()?.get(SIGNATURE_ANNOTATION)?;
+            Some((layer, signature.as_str()))
+        })
+}
+
+/// Returns the tag of the cosign signature artifact of the manifest with `digest`
+///
+/// # Errors
+///
+/// Returns an error if `digest` is not of the form `<algorithm>:<hex>`
+pub fn signature_tag(digest: &str) -> Result<String> {
+    let Some((algorithm, hex)) = digest.split_once(':') else {
+        bail!("invalid digest `{digest}`");
+    };
+    Ok(format!("{algorithm}-{hex}.sig"))
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine as _;
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair as _, ECDSA_P256_SHA256_ASN1_SIGNING};
+
+    use super::{p256_public_key_pem, signature_tag, simple_signing_payload, CosignVerifier};
+
+    const DIGEST: &str = "sha256:5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
+
+    fn key_pair() -> (EcdsaKeyPair, String) {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng)
+            .expect("failed to generate key");
+        let key = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng)
+            .expect("failed to parse key");
+        let pem = p256_public_key_pem(key.public_key().as_ref());
+        (key, pem)
+    }
+
+    fn sign(key: &EcdsaKeyPair, payload: &[u8]) -> String {
+        let signature = key
+            .sign(&SystemRandom::new(), payload)
+            .expect("failed to sign payload");
+        STANDARD.encode(signature)
+    }
+
+    fn payload(digest: &str) -> Vec<u8> {
+        simple_signing_payload("localhost:5000/hello", digest)
+    }
+
+    #[test]
+    fn verifies_signatures() {
+        let (key, pem) = key_pair();
+        let verifier = CosignVerifier::from_pem(pem).expect("failed to parse keys");
+
+        let payload = payload(DIGEST);
+        let signature = sign(&key, &payload);
+        verifier
+            .verify(DIGEST, &payload, &signature)
+            .expect("failed to verify signature");
+
+        // The signature must be for the fetched digest
+        let other = "sha256:0000000000000000000000000000000000000000000000000000000000000000";
+        assert!(verifier.verify(other, &payload, &signature).is_err());
+
+        // The signature must be made by a trusted key
+        let (other_key, _) = key_pair();
+        let signature = sign(&other_key, &payload);
+        assert!(verifier.verify(DIGEST, &payload, &signature).is_err());
+    }
+
+    #[test]
+    fn signature_tags() {
+        assert_eq!(
+            signature_tag(DIGEST).expect("failed to compute tag"),
+            "sha256-5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03.sig"
+        );
+        assert!(signature_tag("invalid").is_err());
+    }
+}
@@ -13,6 +13,11 @@ pub use link::*;
 pub mod otel;
 pub use otel::*;
 
+#[cfg(feature = "oci")]
+pub mod cosign;
+#[cfg(feature = "oci")]
+pub use cosign::CosignVerifier;
+
 #[cfg(feature = "oci")]
 pub mod oci;
 #[cfg(feature = "oci")]
@@ -1,10 +1,12 @@
 use std::env::temp_dir;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
 
 use anyhow::{bail, Context as _};
 use oci_client::client::ClientProtocol;
 use oci_client::client::ImageData;
+use oci_client::manifest::{ImageIndexEntry, OciManifest};
 use oci_client::Reference;
 use oci_wasm::WASM_LAYER_MEDIA_TYPE;
 use oci_wasm::WASM_MANIFEST_MEDIA_TYPE;
@@ -12,6 +14,7 @@ use tokio::fs;
 use tokio::io::AsyncWriteExt;
 use wascap::jwt;
 
+use crate::cosign::{self, CosignVerifier};
 use crate::RegistryConfig;
 use crate::{tls, UseParFileCache};
 
@@ -37,6 +40,7 @@ pub struct OciFetcher {
     allow_latest: bool,
     allow_insecure: bool,
     auth: oci_client::secrets::RegistryAuth,
+    signature_verifier: Option<Arc<CosignVerifier>>,
 }
 
 impl Default for OciFetcher {
@@ -46,6 +50,7 @@ impl Default for OciFetcher {
             allow_latest: false,
             allow_insecure: false,
             auth: oci_client::secrets::RegistryAuth::Anonymous,
+            signature_verifier: None,
         }
     }
 }
@@ -65,6 +70,7 @@ impl From<&RegistryConfig> for OciFetcher {
             allow_latest: *allow_latest,
             allow_insecure: *allow_insecure,
             additional_ca_paths: additional_ca_paths.clone(),
+            signature_verifier: None,
         }
     }
 }
@@ -84,6 +90,7 @@ impl From<RegistryConfig> for OciFetcher {
             allow_latest,
             allow_insecure,
             additional_ca_paths,
+            signature_verifier: None,
         }
     }
 }
@@ -126,6 +133,27 @@ fn prune_filepath(img: &str) -> String {
     img
 }
 
+/// Selects the manifest of an image index that matches the target of this host, or the manifest of
+/// a Wasm component provider if there is none, using the same mapping of provider archive targets
+/// to OCI platforms that is used to push provider archives
+fn native_platform_resolver(manifests: &[ImageIndexEntry]) -> Option<String> {
+    [
+        crate::par::native_target().as_str(),
+        provider_archive::WASM_COMPONENT_TARGET,
+    ]
+    .into_iter()
+    .filter_map(provider_archive::oci_platform)
+    .find_map(|(os, architecture)| {
+        manifests.iter().find(|entry| {
+            entry
+                .platform
+                .as_ref()
+                .is_some_and(|platform| platform.os == os && platform.architecture == architecture)
+        })
+    })
+    .map(|entry| entry.digest.clone())
+}
+
 /// A type to indicate whether there was a cache hit or miss when loading artifacts
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CacheResult {
@@ -153,7 +181,7 @@ impl OciFetcher {
         let mut digest_file = output_dir.join(&pruned_filepath).clone();
         digest_file.set_extension("digest");
 
-        let img = Reference::from_str(&img)?;
+        let mut img = Reference::from_str(&img)?;
 
         let protocol = if self.allow_insecure {
             ClientProtocol::HttpsExcept(vec![img.registry().to_string()])
@@ -175,15 +203,38 @@ impl OciFetcher {
         let c = oci_client::Client::new(oci_client::client::ClientConfig {
             protocol,
             extra_root_certificates: certs,
+            // Provider archives pushed as an image index only have the binary for this host pulled
+            platform_resolver: Some(Box::new(native_platform_resolver)),
             ..Default::default()
         });
 
+        // Verify the signature of the artifact before anything else and pin its digest, so that
+        // the verified artifact is the one that is pulled even if the reference is updated
+        if let Some(verifier) = &self.signature_verifier {
+            let (_, digest) = c
+                .pull_manifest(&img, &self.auth)
+                .await
+                .context("failed to fetch OCI manifest")?;
+            self.verify_signature(&c, &img, &digest, verifier)
+                .await
+                .with_context(|| format!("failed to verify signature of `{img}`"))?;
+            img = Reference::with_digest(
+                img.registry().to_string(),
+                img.repository().to_string(),
+                digest,
+            );
+        }
+
         // In case of a cache miss where the file does not exist, pull a fresh OCI Image
         if fs::metadata(&cache_file).await.is_ok() {
-            let (_, oci_digest) = c
+            let (manifest, mut oci_digest) = c
                 .pull_manifest(&img, &self.auth)
                 .await
                 .context("failed to fetch OCI manifest")?;
+            // The digest of the manifest selected from an image index is the one that is cached
+            if let OciManifest::ImageIndex(index) = manifest {
+                oci_digest = native_platform_resolver(&index.manifests).unwrap_or_default();
+            }
             // If the digest file doesn't exist that is ok, we just unwrap to an empty string
             let file_digest = fs::read_to_string(&digest_file).await.unwrap_or_default();
             if !oci_digest.is_empty() && !file_digest.is_empty() && file_digest == oci_digest {
@@ -218,6 +269,40 @@ impl OciFetcher {
         Ok((cache_file, CacheResult::Miss))
     }
 
+    /// Verify that the manifest with `digest` of `img` has a cosign signature made by a key
+    /// trusted by `verifier`
+    async fn verify_signature(
+        &self,
+        c: &oci_client::Client,
+        img: &Reference,
+        digest: &str,
+        verifier: &CosignVerifier,
+    ) -> anyhow::Result<()> {
+        let sig = Reference::with_tag(
+            img.registry().to_string(),
+            img.repository().to_string(),
+            cosign::signature_tag(digest)?,
+        );
+        let (manifest, _) = c
+            .pull_manifest(&sig, &self.auth)
+            .await
+            .context("failed to fetch signature manifest")?;
+        let OciManifest::Image(manifest) = manifest else {
+            bail!("signature manifest is not an image manifest");
+        };
+        for (layer, signature) in cosign::signature_layers(&manifest) {
+            let mut payload = Vec::new();
+            c.pull_blob(&sig, layer, &mut payload)
+                .await
+                .context("failed to fetch signature payload")?;
+            match verifier.verify(digest, &payload, signature) {
+                Ok(()) => return Ok(()),
+                Err(err) => tracing::debug!(?err, "ignoring invalid signature"),
+            }
+        }
+        bail!("no valid signature found for digest `{digest}`")
+    }
+
     /// Fetch component from OCI
     ///
     /// # Errors
@@ -266,6 +351,13 @@ impl OciFetcher {
             .with_context(|| format!("failed to read `{}`", path.display()))
     }
 
+    /// Used to require that fetched components and providers have a cosign signature made by a key
+    /// trusted by `verifier`
+    pub fn with_signature_verifier(mut self, verifier: Option<Arc<CosignVerifier>>) -> Self {
+        self.signature_verifier = verifier;
+        self
+    }
+
     /// Used to set additional CA paths that will be used as part of fetching components and providers
     pub fn with_additional_ca_paths(mut self, paths: &[impl AsRef<Path>]) -> Self {
         self.additional_ca_paths = paths.iter().map(AsRef::as_ref).map(PathBuf::from).collect();
@@ -3,12 +3,15 @@ use std::env::temp_dir;
 use std::path::{Path, PathBuf};
 use std::str;
 
-use anyhow::{anyhow, Context, Result};
-use provider_archive::ProviderArchive;
+use anyhow::{anyhow, ensure, Context, Result};
+use provider_archive::{ProviderArchive, WASM_COMPONENT_TARGET};
 use tokio::fs::{self, File, OpenOptions};
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use wascap::jwt;
 
+/// The preamble of a binary Wasm component
+const COMPONENT_PREAMBLE: [u8; 8] = [0x00, 0x61, 0x73, 0x6d, 0x0d, 0x00, 0x01, 0x00];
+
 fn normalize_for_filename(input: &str) -> String {
     input
         .to_lowercase()
@@ -25,7 +28,7 @@ pub enum UseParFileCache {
     Use,
 }
 
-fn native_target() -> String {
+pub(crate) fn native_target() -> String {
     format!("{ARCH}-{OS}")
 }
 
@@ -74,7 +77,63 @@ async fn open_file(path: impl AsRef<Path>) -> Result<Option<File>> {
         .with_context(|| format!("failed to open path [{}]", path.display()))
 }
 
-/// Reads a provider archive from the given path and writes it to the cache
+/// Returns whether the provider binary at the given path is a Wasm component rather than a native
+/// executable
+///
+/// # Arguments
+/// * `path` - The path to the provider binary, e.g. as returned by [`read`]
+pub async fn is_component(path: impl AsRef<Path>) -> Result<bool> {
+    let mut file = File::open(path)
+        .await
+        .context("failed to open provider binary")?;
+    let mut preamble = [0; COMPONENT_PREAMBLE.len()];
+    match file.read_exact(&mut preamble).await {
+        Ok(_) => Ok(preamble == COMPONENT_PREAMBLE),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e).context("failed to read provider binary"),
+    }
+}
+
+/// Verifies that the provider binary at the given path matches the hash recorded for its target
+/// in the provider claims, which is the native target or, for Wasm components, the
+/// [`WASM_COMPONENT_TARGET`]
+///
+/// # Arguments
+/// * `path` - The path to the provider binary, e.g. as returned by [`read`]
+/// * `claims` - The claims of the provider archive the binary was extracted from
+pub async fn verify_target_hash(
+    path: impl AsRef<Path>,
+    claims: &jwt::Claims<jwt::CapabilityProvider>,
+) -> Result<()> {
+    let bin = fs::read(path)
+        .await
+        .context("failed to read provider binary")?;
+    let target = if bin.starts_with(&COMPONENT_PREAMBLE) {
+        WASM_COMPONENT_TARGET.to_string()
+    } else {
+        native_target()
+    };
+    let expected = claims
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.target_hashes.get(&target))
+        .with_context(|| format!("provider claims do not contain a hash for target `{target}`"))?;
+    ensure!(
+        provider_archive::hash_bytes(&bin) == *expected,
+        "provider binary hash does not match the hash in its claims for target `{target}`"
+    );
+    Ok(())
+}
+
+/// Returns whether the claims of a provider archive contain a hash for the given target
+fn has_target(par: &ProviderArchive, target: &str) -> bool {
+    par.claims()
+        .and_then(|claims| claims.metadata)
+        .is_some_and(|metadata| metadata.target_hashes.contains_key(target))
+}
+
+/// Reads a provider archive from the given path and writes the binary for the native target, or
+/// the Wasm component if the archive has no native binary, to the cache
 ///
 /// # Arguments
 /// * `path` - The path to the provider archive
@@ -87,9 +146,28 @@ pub async fn read(
     provider_ref: impl AsRef<str>,
     cache: UseParFileCache,
 ) -> Result<(PathBuf, Option<jwt::Token<jwt::CapabilityProvider>>)> {
-    let par = ProviderArchive::try_load_target_from_file(path, &native_target())
-        .await
-        .map_err(|e| anyhow!(e).context("failed to load provider archive"))?;
+    let path = path.as_ref();
+    // Prefer the native binary, falling back to a Wasm component only if the archive has no
+    // native binary. Loading a target that is not in the archive fails, so whether the native
+    // binary is absent is decided by the claims, which hold the hashes of every target.
+    let mut target = native_target();
+    let par = match ProviderArchive::try_load_target_from_file(path, &target).await {
+        Ok(par) => par,
+        Err(native_err) => {
+            match ProviderArchive::try_load_target_from_file(path, WASM_COMPONENT_TARGET).await {
+                Ok(par) if !has_target(&par, &target) => {
+                    target = WASM_COMPONENT_TARGET.to_string();
+                    par
+                }
+                Ok(_) => return Err(anyhow!(native_err).context("failed to load provider archive")),
+                Err(wasm_err) => {
+                    return Err(anyhow!(native_err).context(format!(
+                        "failed to load provider archive, which has no loadable `{WASM_COMPONENT_TARGET}` target either: {wasm_err}"
+                    )))
+                }
+            }
+        }
+    };
     let claims = par.claims_token();
     let exe = cache_path(host_id, provider_ref);
 
@@ -104,7 +182,6 @@ pub async fn read(
         (UseParFileCache::Use, Some(file)) | (UseParFileCache::Ignore, Some(file)) => file,
     };
 
-    let target = native_target();
     let buf = par
         .target_bytes(&target)
         .with_context(|| format!("target `{target}` not found"))?;
@@ -113,3 +190,77 @@ pub async fn read(
 
     Ok((exe, claims))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use provider_archive::{ProviderArchive, WASM_COMPONENT_TARGET};
+    use wascap::prelude::KeyPair;
+
+    use super::{native_target, read, UseParFileCache, COMPONENT_PREAMBLE};
+
+    const NATIVE: &[u8] = b"native provider binary";
+
+    /// Writes an uncompressed provider archive with the given targets to a unique path
+    async fn write_par(name: &str, targets: &[(&str, &[u8])]) -> PathBuf {
+        let mut par = ProviderArchive::new(name, "test", None, None);
+        for (target, bytes) in targets {
+            par.add_library(target, bytes)
+                .expect("failed to add library");
+        }
+        let path =
+            std::env::temp_dir().join(format!("{name}-{}.par", KeyPair::new_module().public_key()));
+        par.write(
+            &path,
+            &KeyPair::new_account(),
+            &KeyPair::new_service(),
+            false,
+        )
+        .await
+        .expect("failed to write provider archive");
+        path
+    }
+
+    #[tokio::test]
+    async fn read_falls_back_to_component_only_without_native_target() {
+        let mut component = COMPONENT_PREAMBLE.to_vec();
+        component.extend_from_slice(b"component");
+
+        let path = write_par("component-only", &[(WASM_COMPONENT_TARGET, &component)]).await;
+        let host_id = KeyPair::new_server().public_key();
+        let (exe, _) = read(&path, &host_id, "component-only", UseParFileCache::Ignore)
+            .await
+            .expect("archive without a native binary should fall back to the component");
+        assert_eq!(
+            tokio::fs::read(&exe).await.expect("failed to read binary"),
+            component
+        );
+
+        // A native binary that does not match its hash fails to load rather than falling back
+        let target = native_target();
+        let path = write_par(
+            "tampered",
+            &[(&target, NATIVE), (WASM_COMPONENT_TARGET, &component)],
+        )
+        .await;
+        let mut archive = tokio::fs::read(&path)
+            .await
+            .expect("failed to read archive");
+        let offset = archive
+            .windows(NATIVE.len())
+            .position(|window| window == NATIVE)
+            .expect("archive should contain the native binary");
+        archive[offset] ^= 0xff;
+        tokio::fs::write(&path, archive)
+            .await
+            .expect("failed to write archive");
+        let err = read(&path, &host_id, "tampered", UseParFileCache::Ignore)
+            .await
+            .expect_err("tampered native binary should not fall back to the component");
+        assert!(
+            format!("{err:#}").contains("hash"),
+            "unexpected error: {err:#}"
+        );
+    }
+}
@@ -2,6 +2,12 @@ use std::fmt::{Debug, Formatter, Result};
 
 use serde::{Deserialize, Serialize};
 
+/// Header set on messages published on the provider config update subject when they carry rotated
+/// secrets rather than configuration. The payload of such messages is a serialized
+/// [`HashMap<String, SecretValue>`](std::collections::HashMap) of the rotated secrets, encrypted
+/// for the provider's xkey by the host's xkey.
+pub const SECRETS_UPDATE_HEADER: &str = "WasmCloud-Secrets-Update";
+
 #[derive(Deserialize, Serialize, Clone)]
 // This tagging allows deserializers to know whether the secret is a string or bytes.
 // This is especially necessary for languages where strings and bytes are treated very similarly.
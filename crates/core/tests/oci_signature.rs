@@ -0,0 +1,225 @@
+//! Verification of cosign signatures of artifacts fetched from an OCI registry, served by an
+//! in-process registry fixture
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use axum::extract::{Path, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use oci_client::client::{Config, ImageLayer};
+use oci_client::manifest::{OciDescriptor, OciImageManifest, OCI_IMAGE_MEDIA_TYPE};
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, KeyPair as _, ECDSA_P256_SHA256_ASN1_SIGNING};
+use tokio::net::TcpListener;
+use wasmcloud_core::cosign::{
+    p256_public_key_pem, signature_tag, simple_signing_payload, SIGNATURE_ANNOTATION,
+    SIMPLE_SIGNING_MEDIA_TYPE,
+};
+use wasmcloud_core::{CosignVerifier, OciFetcher, RegistryConfig, RegistryType};
+
+const REPOSITORY: &str = "signed";
+
+fn key_pair() -> anyhow::Result<(EcdsaKeyPair, CosignVerifier)> {
+    let rng = SystemRandom::new();
+    let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng)
+        .map_err(|_| anyhow::anyhow!("failed to generate key"))?;
+    let key = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng)
+        .map_err(|_| anyhow::anyhow!("failed to parse key"))?;
+    let verifier = CosignVerifier::from_pem(p256_public_key_pem(key.public_key().as_ref()))?;
+    Ok((key, verifier))
+}
+
+/// Sign the manifest with `digest` of the image at `docker_reference` with `key`, returning the
+/// signature layer that cosign would push
+fn signature_layer(
+    key: &EcdsaKeyPair,
+    docker_reference: &str,
+    digest: &str,
+) -> anyhow::Result<ImageLayer> {
+    let payload = simple_signing_payload(docker_reference, digest);
+    let signature = key
+        .sign(&SystemRandom::new(), &payload)
+        .map_err(|_| anyhow::anyhow!("failed to sign payload"))?;
+    Ok(ImageLayer::new(
+        payload,
+        SIMPLE_SIGNING_MEDIA_TYPE.into(),
+        Some(BTreeMap::from([(
+            SIGNATURE_ANNOTATION.into(),
+            STANDARD.encode(signature),
+        )])),
+    ))
+}
+
+/// Manifests and blobs of a single repository, served with the subset of the OCI distribution API
+/// used to pull artifacts
+#[derive(Default)]
+struct Registry {
+    manifests: HashMap<String, (String, Vec<u8>)>,
+    blobs: HashMap<String, Vec<u8>>,
+}
+
+impl Registry {
+    /// Store an image manifest with `config` and `layers` under `tag`, returning its digest
+    fn put_image(
+        &mut self,
+        tag: &str,
+        config: Config,
+        layers: Vec<ImageLayer>,
+    ) -> anyhow::Result<String> {
+        let manifest = OciImageManifest {
+            media_type: Some(OCI_IMAGE_MEDIA_TYPE.into()),
+            config: OciDescriptor {
+                media_type: config.media_type.clone(),
+                digest: config.sha256_digest(),
+                size: config.data.len().try_into()?,
+                ..Default::default()
+            },
+            layers: layers
+                .iter()
+                .map(|layer| {
+                    Ok(OciDescriptor {
+                        media_type: layer.media_type.clone(),
+                        digest: layer.sha256_digest(),
+                        size: layer.data.len().try_into()?,
+                        annotations: layer.annotations.clone(),
+                        ..Default::default()
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?,
+            ..Default::default()
+        };
+        self.blobs.insert(config.sha256_digest(), config.data);
+        for layer in layers {
+            self.blobs.insert(layer.sha256_digest(), layer.data);
+        }
+        let manifest = serde_json::to_vec(&manifest)?;
+        let digest =
+            ImageLayer::new(manifest.clone(), OCI_IMAGE_MEDIA_TYPE.into(), None).sha256_digest();
+        let entry = (OCI_IMAGE_MEDIA_TYPE.to_string(), manifest);
+        self.manifests.insert(tag.into(), entry.clone());
+        self.manifests.insert(digest.clone(), entry);
+        Ok(digest)
+    }
+
+    /// Serve the registry on a random local port, returning its address
+    async fn serve(self) -> anyhow::Result<String> {
+        async fn manifest(
+            State(registry): State<Arc<Registry>>,
+            Path((_, reference)): Path<(String, String)>,
+        ) -> Response {
+            let Some((media_type, manifest)) = registry.manifests.get(&reference) else {
+                return StatusCode::NOT_FOUND.into_response();
+            };
+            let digest =
+                ImageLayer::new(manifest.clone(), media_type.clone(), None).sha256_digest();
+            (
+                [
+                    (header::CONTENT_TYPE, media_type.clone()),
+                    (
+                        header::HeaderName::from_static("docker-content-digest"),
+                        digest,
+                    ),
+                ],
+                manifest.clone(),
+            )
+                .into_response()
+        }
+
+        async fn blob(
+            State(registry): State<Arc<Registry>>,
+            Path((_, digest)): Path<(String, String)>,
+        ) -> Response {
+            match registry.blobs.get(&digest) {
+                Some(blob) => blob.clone().into_response(),
+                None => StatusCode::NOT_FOUND.into_response(),
+            }
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let router = Router::new()
+            .route("/v2/", get(|| async { StatusCode::OK }))
+            .route("/v2/:repository/manifests/:reference", get(manifest))
+            .route("/v2/:repository/blobs/:digest", get(blob))
+            .with_state(Arc::new(self));
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        Ok(addr.to_string())
+    }
+}
+
+/// Serve a signed and an unsigned component, returning the references of both. The signed
+/// component is signed with `key`
+async fn serve_components(key: &EcdsaKeyPair) -> anyhow::Result<(String, String)> {
+    let component = |wasm: &[u8]| {
+        (
+            Config::new(
+                b"{}".to_vec(),
+                "application/vnd.wasmcloud.config".into(),
+                None,
+            ),
+            vec![ImageLayer::new(
+                wasm.to_vec(),
+                "application/vnd.module.wasm.content.layer.v1+wasm".into(),
+                None,
+            )],
+        )
+    };
+    let mut registry = Registry::default();
+    let (config, layers) = component(b"\0asm\x0d\0\x01\0");
+    let digest = registry.put_image("signed", config, layers)?;
+    let (config, layers) = component(b"\0asm\x01\0\0\0");
+    registry.put_image("unsigned", config, layers)?;
+
+    // The address of the registry is only known once it is served, and the signature payload
+    // only covers the digest that is verified, so any docker reference can be signed here
+    let signature = signature_layer(key, REPOSITORY, &digest)?;
+    registry.put_image(
+        &signature_tag(&digest)?,
+        Config::new(
+            b"{}".to_vec(),
+            "application/vnd.oci.image.config.v1+json".into(),
+            None,
+        ),
+        vec![signature],
+    )?;
+
+    let addr = registry.serve().await?;
+    Ok((
+        format!("{addr}/{REPOSITORY}:signed"),
+        format!("{addr}/{REPOSITORY}:unsigned"),
+    ))
+}
+
+fn fetcher(verifier: CosignVerifier) -> anyhow::Result<OciFetcher> {
+    let config = RegistryConfig::builder()
+        .reg_type(RegistryType::Oci)
+        .allow_insecure(true)
+        .build()?;
+    Ok(OciFetcher::from(config).with_signature_verifier(Some(Arc::new(verifier))))
+}
+
+#[tokio::test]
+async fn verifies_signatures_in_registry() -> anyhow::Result<()> {
+    let (key, verifier) = key_pair()?;
+    let (signed, unsigned) = serve_components(&key).await?;
+
+    let component = fetcher(verifier.clone())?
+        .fetch_component(&signed)
+        .await
+        .context("failed to fetch signed component")?;
+    assert_eq!(component, b"\0asm\x0d\0\x01\0");
+
+    // Components without a signature are rejected
+    assert!(fetcher(verifier)?.fetch_component(&unsigned).await.is_err());
+
+    // Components signed by a key that is not trusted are rejected
+    let (_, untrusted) = key_pair()?;
+    assert!(fetcher(untrusted)?.fetch_component(&signed).await.is_err());
+    Ok(())
+}
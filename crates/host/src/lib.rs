@@ -28,12 +28,13 @@ pub use policy::{
 };
 pub use secrets::Manager as SecretsManager;
 pub use wasmbus::{Host as WasmbusHost, HostConfig as WasmbusHostConfig};
-pub use wasmcloud_core::{OciFetcher, RegistryAuth, RegistryConfig, RegistryType};
+pub use wasmcloud_core::{CosignVerifier, OciFetcher, RegistryAuth, RegistryConfig, RegistryType};
 
 pub use url;
 
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use anyhow::{anyhow, bail, ensure, Context as _};
 use tokio::fs;
@@ -127,6 +128,31 @@ pub async fn fetch_component(
     allow_file_load: bool,
     additional_ca_paths: &Vec<PathBuf>,
     registry_config: &HashMap<String, RegistryConfig>,
+) -> anyhow::Result<Vec<u8>> {
+    fetch_component_with_verifier(
+        component_ref,
+        allow_file_load,
+        additional_ca_paths,
+        registry_config,
+        None,
+    )
+    .await
+}
+
+/// Fetch an component from a reference, like [`fetch_component`].
+///
+/// If `signature_verifier` is set, components fetched from OCI registries must have a cosign
+/// signature made by one of its keys.
+#[instrument(
+    level = "debug",
+    skip(allow_file_load, registry_config, signature_verifier)
+)]
+pub async fn fetch_component_with_verifier(
+    component_ref: &str,
+    allow_file_load: bool,
+    additional_ca_paths: &Vec<PathBuf>,
+    registry_config: &HashMap<String, RegistryConfig>,
+    signature_verifier: Option<&Arc<CosignVerifier>>,
 ) -> anyhow::Result<Vec<u8>> {
     match ResourceRef::try_from(component_ref)? {
         ResourceRef::File(component_ref) => {
@@ -144,6 +170,7 @@ pub async fn fetch_component(
             .map(OciFetcher::from)
             .unwrap_or_default()
             .with_additional_ca_paths(additional_ca_paths)
+            .with_signature_verifier(signature_verifier.cloned())
             .fetch_component(component_ref)
             .await
             .with_context(|| {
@@ -160,6 +187,31 @@ pub async fn fetch_provider(
     host_id: impl AsRef<str>,
     allow_file_load: bool,
     registry_config: &HashMap<String, RegistryConfig>,
+) -> anyhow::Result<(PathBuf, Option<jwt::Token<jwt::CapabilityProvider>>)> {
+    fetch_provider_with_verifier(
+        provider_ref,
+        host_id,
+        allow_file_load,
+        registry_config,
+        None,
+    )
+    .await
+}
+
+/// Fetch a provider from a reference, like [`fetch_provider`].
+///
+/// If `signature_verifier` is set, providers fetched from OCI registries must have a cosign
+/// signature made by one of its keys.
+#[instrument(
+    skip(registry_config, host_id, signature_verifier),
+    fields(provider_ref = %provider_ref.as_ref())
+)]
+pub async fn fetch_provider_with_verifier(
+    provider_ref: &ResourceRef<'_>,
+    host_id: impl AsRef<str>,
+    allow_file_load: bool,
+    registry_config: &HashMap<String, RegistryConfig>,
+    signature_verifier: Option<&Arc<CosignVerifier>>,
 ) -> anyhow::Result<(PathBuf, Option<jwt::Token<jwt::CapabilityProvider>>)> {
     match provider_ref {
         ResourceRef::File(provider_path) => {
@@ -181,6 +233,7 @@ pub async fn fetch_provider(
             .and_then(|authority| registry_config.get(authority))
             .map(OciFetcher::from)
             .unwrap_or_default()
+            .with_signature_verifier(signature_verifier.cloned())
             .fetch_provider(provider_ref, host_id)
             .await
             .with_context(|| {
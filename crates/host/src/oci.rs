@@ -20,4 +20,8 @@ pub struct Config {
     pub oci_user: Option<String>,
     /// Password for the OCI registry specified by `oci_registry`.
     pub oci_password: Option<String>,
+    /// Paths to PEM files with cosign public keys. If set, artifacts fetched from OCI registries
+    /// must have a cosign signature made by one of these keys
+    #[serde(default)]
+    pub signature_public_keys: Vec<PathBuf>,
 }
@@ -1,17 +1,159 @@
 //! Module with structs for use in managing and accessing secrets in a wasmCloud lattice
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::{bail, ensure, Context as _};
-use async_nats::{jetstream::kv::Store, Client};
+use async_nats::{jetstream::kv::Store, Client, Subscriber};
 use futures::stream;
 use futures::stream::{StreamExt, TryStreamExt};
 use secrecy::Secret;
 use tokio::sync::RwLock;
-use tracing::instrument;
+use tracing::{debug, instrument, warn};
+use wasmcloud_control_interface::Link;
 use wasmcloud_runtime::capability::secrets::store::SecretValue;
-use wasmcloud_secrets_client::Client as WasmcloudSecretsClient;
-use wasmcloud_secrets_types::{Secret as WasmcloudSecret, SecretConfig};
+use wasmcloud_secrets_client::{rotation_subject, Client as WasmcloudSecretsClient};
+use wasmcloud_secrets_types::{Secret as WasmcloudSecret, SecretConfig, SecretRotated};
+
+/// Identifies a link a provider fetched secrets for
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SecretLink {
+    /// The ID of the source of the link
+    pub source_id: String,
+    /// The ID of the target of the link
+    pub target: String,
+    /// The WIT namespace of the link
+    pub wit_namespace: String,
+    /// The WIT package of the link
+    pub wit_package: String,
+    /// The name of the link
+    pub name: String,
+}
+
+impl From<&Link> for SecretLink {
+    fn from(link: &Link) -> Self {
+        Self {
+            source_id: link.source_id().to_string(),
+            target: link.target().to_string(),
+            wit_namespace: link.wit_namespace().to_string(),
+            wit_package: link.wit_package().to_string(),
+            name: link.name().to_string(),
+        }
+    }
+}
+
+/// Identifies a secret fetched for an entity
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct CacheKey {
+    /// The name of the secret reference in the config store
+    reference: String,
+    /// The JWT of the entity
+    entity_jwt: String,
+    /// The application the entity is part of, if any
+    application: Option<String>,
+    /// The link the secret was fetched for, if it is not a secret of the entity itself
+    link: Option<SecretLink>,
+}
+
+/// A secret reference used by an entity, along with the secret last fetched with it
+#[derive(Debug)]
+struct CachedSecret {
+    /// The secret reference the secret was fetched with
+    config: SecretConfig,
+    /// The fetched secret, dropped once it expires
+    value: Option<Secret<SecretValue>>,
+    fetched_at: Instant,
+}
+
+/// Secrets fetched for entities.
+///
+/// Secret values are reused until they are older than the TTL, after which they are dropped.
+/// The secret references are kept until the entity using them stops, so that secrets which are
+/// rotated can be refreshed for running entities.
+#[derive(Debug)]
+struct Cache {
+    ttl: Duration,
+    entries: HashMap<CacheKey, CachedSecret>,
+}
+
+impl Cache {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Returns the secret previously fetched with `config`, unless it expired
+    fn get(&self, key: &CacheKey, config: &SecretConfig) -> Option<Secret<SecretValue>> {
+        let cached = self.entries.get(key)?;
+        // A changed secret reference must be fetched again
+        if cached.config != *config || cached.fetched_at.elapsed() >= self.ttl {
+            return None;
+        }
+        cached.value.clone()
+    }
+
+    /// Records that `value` was fetched with `config`. The value is only kept if caching is enabled
+    fn insert(&mut self, key: CacheKey, config: SecretConfig, value: &Secret<SecretValue>) {
+        let value = (!self.ttl.is_zero()).then(|| value.clone());
+        self.entries.insert(
+            key,
+            CachedSecret {
+                config,
+                value,
+                fetched_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Drops all secret values older than the TTL
+    fn expire(&mut self) {
+        for cached in self.entries.values_mut() {
+            if cached.fetched_at.elapsed() >= self.ttl {
+                cached.value = None;
+            }
+        }
+    }
+
+    /// Drops all secrets fetched for entities for which `is_running` returns `false`, given the
+    /// JWT of the entity
+    fn retain_entities(&mut self, is_running: impl Fn(&str) -> bool) {
+        self.entries
+            .retain(|CacheKey { entity_jwt, .. }, _| is_running(entity_jwt));
+    }
+
+    /// Returns the secret references to the secret `key` in `backend` that follow its latest
+    /// version
+    fn rotated(&self, backend: &str, key: &str) -> Vec<(CacheKey, SecretConfig)> {
+        self.entries
+            .iter()
+            // Secret references pinned to a version are not affected by new versions
+            .filter(|(_, cached)| {
+                cached.config.backend == backend
+                    && cached.config.key == key
+                    && cached.config.version.is_none()
+            })
+            .map(|(cache_key, cached)| (cache_key.clone(), cached.config.clone()))
+            .collect()
+    }
+}
+
+/// A secret that was refreshed after its secrets backend announced a new version
+#[derive(Debug)]
+pub struct RotatedSecret {
+    /// The JWT of the entity the secret was fetched for
+    pub entity_jwt: String,
+    /// The name of the application the entity is a part of, if any
+    pub application: Option<String>,
+    /// The link the secret was fetched for, if it is not a secret of the entity itself. Secrets
+    /// of links are passed to providers by putting the link again.
+    pub link: Option<SecretLink>,
+    /// The name the entity uses to look up the secret
+    pub name: String,
+    /// The new value of the secret
+    pub value: Secret<SecretValue>,
+}
 
 #[derive(Debug)]
 /// A manager for fetching secrets from a secret store, caching secrets clients for efficiency.
@@ -22,6 +164,8 @@ pub struct Manager {
     nats_client: Client,
     /// A map of backend names, e.g. nats-kv or vault, to secrets clients, used to cache clients for efficiency.
     backend_clients: Arc<RwLock<HashMap<String, Arc<WasmcloudSecretsClient>>>>,
+    /// Secrets fetched for running entities
+    cache: RwLock<Cache>,
 }
 
 impl Manager {
@@ -30,19 +174,93 @@ impl Manager {
     /// All secret references will be fetched from this configuration store and the actual secrets will be
     /// fetched by sending requests to the configured topic. If the provided secret_store_topic is None, this manager
     /// will always return an error if [`Self::fetch_secrets`] is called with a list of secrets.
+    ///
+    /// Secrets are reused for `cache_ttl` after being fetched, a zero `cache_ttl` disables caching.
     pub fn new(
         config_store: &Store,
         secret_store_topic: Option<&String>,
         nats_client: &Client,
+        cache_ttl: Duration,
     ) -> Self {
         Self {
             config_store: config_store.clone(),
             secret_store_topic: secret_store_topic.cloned(),
             nats_client: nats_client.clone(),
             backend_clients: Arc::new(RwLock::new(HashMap::new())),
+            cache: RwLock::new(Cache::new(cache_ttl)),
         }
     }
 
+    /// Subscribe to the secrets rotated by all secrets backends, returning `None` if the secret
+    /// store topic is not configured. Messages received should be passed to [`Self::rotate`].
+    pub async fn subscribe_rotations(&self) -> anyhow::Result<Option<Subscriber>> {
+        let Some(secret_store_topic) = self.secret_store_topic.as_ref() else {
+            return Ok(None);
+        };
+        let subscriber = self
+            .nats_client
+            .subscribe(rotation_subject(secret_store_topic, "*", None))
+            .await
+            .context("failed to subscribe to secret rotations")?;
+        Ok(Some(subscriber))
+    }
+
+    /// Handle a secret rotation announced by a secrets backend on the subject returned by
+    /// [`Self::subscribe_rotations`], fetching the new version of the secret for all running
+    /// entities that previously fetched it.
+    ///
+    /// Returns the refreshed secrets, which should be passed on to the entities using them.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn rotate(
+        &self,
+        msg: &async_nats::Message,
+        host_jwt: &str,
+    ) -> anyhow::Result<Vec<RotatedSecret>> {
+        // The backend name is the token before the operation in `{prefix}.{version}.{backend}.rotated`
+        let backend = msg
+            .subject
+            .rsplit('.')
+            .nth(1)
+            .context("invalid secret rotation subject")?;
+        let SecretRotated { key, version } = serde_json::from_slice(&msg.payload)
+            .context("failed to deserialize secret rotation")?;
+        debug!(backend, key, ?version, "secret rotated");
+
+        let stale = self.cache.read().await.rotated(backend, &key);
+
+        let mut rotated = Vec::with_capacity(stale.len());
+        for (cache_key, config) in stale {
+            let name = config.name.clone();
+            match self
+                .fetch_secret(
+                    config.clone(),
+                    &cache_key.entity_jwt,
+                    host_jwt,
+                    cache_key.application.as_ref(),
+                )
+                .await
+            {
+                Ok(value) => {
+                    self.cache
+                        .write()
+                        .await
+                        .insert(cache_key.clone(), config, &value);
+                    rotated.push(RotatedSecret {
+                        entity_jwt: cache_key.entity_jwt,
+                        application: cache_key.application,
+                        link: cache_key.link,
+                        name,
+                        value,
+                    });
+                }
+                Err(err) => {
+                    warn!(?err, name, "failed to fetch rotated secret");
+                }
+            }
+        }
+        Ok(rotated)
+    }
+
     /// Get the secrets client for the provided backend, creating a new client if one does not already exist.
     ///
     /// Returns an error if the secret store topic is not configured, or if the client could not be created.
@@ -83,7 +301,7 @@ impl Manager {
     /// Fetches secret references from the CONFIGDATA bucket by name and then fetches the actual secrets
     /// from the configured secret store. Any error returned from this function should result in a failure
     /// to start a component, start a provider, or establish a link as a missing secret is a critical
-    /// error.
+    /// error. Secrets previously fetched for the same entity are reused until the cache TTL elapses.
     ///
     /// # Arguments
     /// * `secret_names` - A list of secret names to fetch from the secret store
@@ -101,6 +319,34 @@ impl Manager {
         entity_jwt: Option<&String>,
         host_jwt: &str,
         application: Option<&String>,
+    ) -> anyhow::Result<HashMap<String, Secret<SecretValue>>> {
+        self.fetch_secrets_for(secret_names, entity_jwt, host_jwt, application, None)
+            .await
+    }
+
+    /// Fetches the secrets a provider uses as the source or target of `link`, like
+    /// [`Self::fetch_secrets`]. Rotations of these secrets are returned with the link they were
+    /// fetched for by [`Self::rotate`].
+    #[instrument(level = "debug", skip(host_jwt))]
+    pub async fn fetch_link_secrets(
+        &self,
+        secret_names: Vec<String>,
+        entity_jwt: Option<&String>,
+        host_jwt: &str,
+        application: Option<&String>,
+        link: &SecretLink,
+    ) -> anyhow::Result<HashMap<String, Secret<SecretValue>>> {
+        self.fetch_secrets_for(secret_names, entity_jwt, host_jwt, application, Some(link))
+            .await
+    }
+
+    async fn fetch_secrets_for(
+        &self,
+        secret_names: Vec<String>,
+        entity_jwt: Option<&String>,
+        host_jwt: &str,
+        application: Option<&String>,
+        link: Option<&SecretLink>,
     ) -> anyhow::Result<HashMap<String, Secret<SecretValue>>> {
         // If we're not fetching any secrets, return empty map successfully
         if secret_names.is_empty() {
@@ -116,59 +362,236 @@ impl Manager {
         // If we don't have an entity JWT, we can't provide its identity to the secrets backend
         let entity_jwt = entity_jwt.context("entity did not have an embedded JWT, required to fetch secrets (was this entity signed during build?)")?;
 
+        self.cache.write().await.expire();
+
         let secrets = stream::iter(secret_names.into_iter())
             // Fetch the secret reference from the config store
             .then(|secret_name| async move {
                 match self.config_store.get(&secret_name).await {
                     Ok(Some(secret)) => serde_json::from_slice::<SecretConfig>(&secret)
-                        .with_context(|| format!("failed to deserialize secret reference from config store, ensure {secret_name} is a secret reference and not configuration")),
+                        .with_context(|| format!("failed to deserialize secret reference from config store, ensure {secret_name} is a secret reference and not configuration"))
+                        .map(|secret_config| (secret_name, secret_config)),
                     Ok(None) => bail!(
                         "Secret config {secret_name} not found in config store, could not create secret request"
                     ),
                     Err(e) => bail!(e),
                 }
             })
-            // Retrieve the actual secret from the secrets backend
-            .and_then(|secret_config| async move {
-                let secrets_client = self
-                    .get_or_create_secrets_client(&secret_config.backend)
-                    .await?;
+            // Retrieve the actual secret from the cache or the secrets backend
+            .and_then(|(reference, secret_config)| async move {
+                let cache_key = CacheKey {
+                    reference,
+                    entity_jwt: entity_jwt.clone(),
+                    application: application.cloned(),
+                    link: link.cloned(),
+                };
+                if let Some(value) = self.cache.read().await.get(&cache_key, &secret_config) {
+                    return Ok((secret_config.name, value));
+                }
                 let secret_name = secret_config.name.clone();
-                let request = secret_config.try_into_request(entity_jwt, host_jwt, application).context("failed to create secret request")?;
-                secrets_client
-                    .get(request, nkeys::XKey::new())
+                let value = self
+                    .fetch_secret(secret_config.clone(), entity_jwt, host_jwt, application)
+                    .await?;
+                self.cache
+                    .write()
                     .await
-                    .map(|secret| (secret_name, secret))
-                    .map_err(|e| anyhow::anyhow!(e))
-            })
-            // Build the map of secrets depending on if the secret is a string or bytes
-            .try_fold(HashMap::new(), |mut secrets, (secret_name, secret_result)| async move {
-                match secret_result {
-                    // NOTE(brooksmtownsend): We create this map using the `secret_name` passed in on from the secret reference
-                    // because that's the name that the component/provider will use to look up the secret.
-                    WasmcloudSecret {
-                        string_secret: Some(string_secret),
-                        ..
-                    } => secrets.insert(
-                        secret_name,
-                        Secret::new(SecretValue::String(string_secret)),
-                    ),
-                    WasmcloudSecret {
-                        binary_secret: Some(binary_secret),
-                        ..
-                    } => {
-                        secrets.insert(secret_name, Secret::new(SecretValue::Bytes(binary_secret)))
-                    }
-                    WasmcloudSecret {
-                        string_secret: None,
-                        binary_secret: None,
-                        ..
-                    } => bail!("secret {secret_name} did not contain a value"),
-                };
-                Ok(secrets)
+                    .insert(cache_key, secret_config, &value);
+                // NOTE(brooksmtownsend): We create this map using the `secret_name` passed in on from the secret reference
+                // because that's the name that the component/provider will use to look up the secret.
+                Ok((secret_name, value))
             })
+            .try_collect()
             .await?;
 
         Ok(secrets)
     }
+
+    /// Drops all secrets fetched for entities that are no longer running, given a function that
+    /// returns whether the entity with the given JWT is running. Secrets are only rotated for the
+    /// entities that remain.
+    pub async fn retain_entities(&self, is_running: impl Fn(&str) -> bool) {
+        self.cache.write().await.retain_entities(is_running);
+    }
+
+    /// Fetch the secret referenced by `secret_config` from its secrets backend
+    async fn fetch_secret(
+        &self,
+        secret_config: SecretConfig,
+        entity_jwt: &str,
+        host_jwt: &str,
+        application: Option<&String>,
+    ) -> anyhow::Result<Secret<SecretValue>> {
+        let secrets_client = self
+            .get_or_create_secrets_client(&secret_config.backend)
+            .await?;
+        let secret_name = secret_config.name.clone();
+        let request = secret_config
+            .try_into_request(entity_jwt, host_jwt, application)
+            .context("failed to create secret request")?;
+        let secret = secrets_client
+            .get(request, nkeys::XKey::new())
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        // Build the secret depending on if the secret is a string or bytes
+        match secret {
+            WasmcloudSecret {
+                string_secret: Some(string_secret),
+                ..
+            } => Ok(Secret::new(SecretValue::String(string_secret))),
+            WasmcloudSecret {
+                binary_secret: Some(binary_secret),
+                ..
+            } => Ok(Secret::new(SecretValue::Bytes(binary_secret))),
+            WasmcloudSecret {
+                string_secret: None,
+                binary_secret: None,
+                ..
+            } => bail!("secret {secret_name} did not contain a value"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use secrecy::{ExposeSecret as _, Secret};
+    use wasmcloud_runtime::capability::secrets::store::SecretValue;
+    use wasmcloud_secrets_types::SecretConfig;
+
+    use super::{Cache, CacheKey, SecretLink};
+
+    fn config(key: &str, version: Option<&str>) -> SecretConfig {
+        SecretConfig::new(
+            "api-key".into(),
+            "nats-kv".into(),
+            key.into(),
+            None,
+            version.map(String::from),
+            HashMap::default(),
+        )
+    }
+
+    fn cache_key(entity_jwt: &str) -> CacheKey {
+        CacheKey {
+            reference: "SECRET_api-key".into(),
+            entity_jwt: entity_jwt.into(),
+            application: None,
+            link: None,
+        }
+    }
+
+    fn secret(value: &str) -> Secret<SecretValue> {
+        Secret::new(SecretValue::String(value.into()))
+    }
+
+    fn value(secret: Option<Secret<SecretValue>>) -> Option<String> {
+        secret.map(|secret| match secret.expose_secret() {
+            SecretValue::String(s) => s.clone(),
+            SecretValue::Bytes(_) => panic!("unexpected binary secret"),
+        })
+    }
+
+    #[test]
+    fn cache_hits() {
+        let mut cache = Cache::new(Duration::from_secs(60));
+        assert!(cache.get(&cache_key("a"), &config("key", None)).is_none());
+
+        cache.insert(cache_key("a"), config("key", None), &secret("foo"));
+        assert_eq!(
+            value(cache.get(&cache_key("a"), &config("key", None))).as_deref(),
+            Some("foo")
+        );
+        // Secrets are cached per entity
+        assert!(cache.get(&cache_key("b"), &config("key", None)).is_none());
+        // A changed secret reference is fetched again
+        assert!(cache
+            .get(&cache_key("a"), &config("key", Some("2")))
+            .is_none());
+    }
+
+    #[test]
+    fn cache_expiry() {
+        let mut cache = Cache::new(Duration::ZERO);
+        cache.insert(cache_key("a"), config("key", None), &secret("foo"));
+        assert!(cache.get(&cache_key("a"), &config("key", None)).is_none());
+        // Values are not kept with caching disabled, but the reference is
+        assert!(cache.entries[&cache_key("a")].value.is_none());
+        assert_eq!(cache.rotated("nats-kv", "key").len(), 1);
+
+        let mut cache = Cache::new(Duration::from_millis(10));
+        cache.insert(cache_key("a"), config("key", None), &secret("foo"));
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(cache.get(&cache_key("a"), &config("key", None)).is_none());
+        cache.expire();
+        assert!(cache.entries[&cache_key("a")].value.is_none());
+        assert_eq!(cache.rotated("nats-kv", "key").len(), 1);
+    }
+
+    #[test]
+    fn cache_rotation() {
+        let mut cache = Cache::new(Duration::from_secs(60));
+        cache.insert(cache_key("a"), config("key", None), &secret("foo"));
+        cache.insert(cache_key("b"), config("key", None), &secret("foo"));
+        cache.insert(cache_key("c"), config("key", Some("1")), &secret("foo"));
+        cache.insert(cache_key("d"), config("other", None), &secret("bar"));
+
+        let mut rotated: Vec<_> = cache
+            .rotated("nats-kv", "key")
+            .into_iter()
+            .map(|(CacheKey { entity_jwt, .. }, _)| entity_jwt)
+            .collect();
+        rotated.sort();
+        assert_eq!(rotated, ["a", "b"]);
+        assert!(cache.rotated("vault", "key").is_empty());
+
+        // Secrets of stopped entities are dropped and no longer rotated
+        cache.retain_entities(|entity_jwt| entity_jwt != "b");
+        assert!(cache.get(&cache_key("b"), &config("key", None)).is_none());
+        let rotated: Vec<_> = cache
+            .rotated("nats-kv", "key")
+            .into_iter()
+            .map(|(CacheKey { entity_jwt, .. }, _)| entity_jwt)
+            .collect();
+        assert_eq!(rotated, ["a"]);
+    }
+
+    #[test]
+    fn cache_links() {
+        let mut cache = Cache::new(Duration::from_secs(60));
+        let link = SecretLink {
+            source_id: "component".into(),
+            target: "provider".into(),
+            wit_namespace: "wasi".into(),
+            wit_package: "keyvalue".into(),
+            name: "default".into(),
+        };
+        let link_key = CacheKey {
+            link: Some(link.clone()),
+            ..cache_key("a")
+        };
+        cache.insert(cache_key("a"), config("key", None), &secret("foo"));
+        cache.insert(link_key.clone(), config("key", None), &secret("bar"));
+
+        // Secrets of a link are cached separately from the secrets of the entity
+        assert_eq!(
+            value(cache.get(&link_key, &config("key", None))).as_deref(),
+            Some("bar")
+        );
+        assert_eq!(
+            value(cache.get(&cache_key("a"), &config("key", None))).as_deref(),
+            Some("foo")
+        );
+
+        // Rotated secrets refer to the link they were fetched for
+        let rotated: Vec<_> = cache
+            .rotated("nats-kv", "key")
+            .into_iter()
+            .map(|(CacheKey { link, .. }, _)| link)
+            .collect();
+        assert_eq!(rotated.len(), 2);
+        assert!(rotated.contains(&None));
+        assert!(rotated.contains(&Some(link)));
+    }
 }
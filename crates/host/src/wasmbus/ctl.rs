@@ -15,8 +15,9 @@ use tracing::{debug, error, info, instrument, trace, warn};
 use wasmcloud_control_interface::{
     ComponentAuctionAck, ComponentAuctionRequest, CtlResponse,
     DeleteInterfaceLinkDefinitionRequest, HostInventory, HostLabel, HostLabelIdentifier, Link,
-    ProviderAuctionAck, ProviderAuctionRequest, RegistryCredential, ScaleComponentCommand,
-    StartProviderCommand, StopHostCommand, StopProviderCommand, UpdateComponentCommand,
+    ProviderAuctionAck, ProviderAuctionRequest, RegistryCredential, RolloutComponentCommand,
+    ScaleComponentCommand, StartProviderCommand, StopHostCommand, StopProviderCommand,
+    UpdateComponentCommand,
 };
 use wasmcloud_tracing::context::TraceContextInjector;
 
@@ -63,6 +64,13 @@ pub(crate) trait ControlInterfaceServer {
         request: UpdateComponentCommand,
     ) -> anyhow::Result<CtlResponse<()>>;
 
+    /// Handle a request to promote or roll back a component rollout. This method should return a
+    /// response indicating success or failure.
+    async fn handle_rollout_component(
+        &self,
+        request: RolloutComponentCommand,
+    ) -> anyhow::Result<CtlResponse<()>>;
+
     /// Handle a request to start a provider. This method should return a response indicating success
     /// or failure.
     async fn handle_start_provider(
@@ -168,9 +176,20 @@ impl ControlInterfaceServer for Host {
             .iter()
             .all(|(k, v)| host_labels.get(k).is_some_and(|hv| hv == v));
         let component_id_running = self.components.read().await.contains_key(component_id);
+        let requirements_satisfied = match request.requirements() {
+            Some(requirements) => match self.check_auction_requirements(requirements).await {
+                Ok(()) => true,
+                Err(e) => {
+                    info!(component_id, "auction requirements not satisfied: {e:#}");
+                    false
+                }
+            },
+            None => true,
+        };
 
-        // This host can run the component if all constraints are satisfied and the component is not already running
-        if constraints_satisfied && !component_id_running {
+        // This host can run the component if all constraints and requirements are satisfied and
+        // the component is not already running
+        if constraints_satisfied && requirements_satisfied && !component_id_running {
             Ok(Some(CtlResponse::ok(
                 ComponentAuctionAck::from_component_host_and_constraints(
                     component_ref,
@@ -204,9 +223,19 @@ impl ControlInterfaceServer for Host {
         let constraints_satisfied = constraints
             .iter()
             .all(|(k, v)| host_labels.get(k).is_some_and(|hv| hv == v));
-        let providers = self.providers.read().await;
-        let provider_running = providers.contains_key(provider_id);
-        if constraints_satisfied && !provider_running {
+        let provider_running = self.providers.read().await.contains_key(provider_id);
+        let requirements_satisfied = match request.requirements() {
+            Some(requirements) => match self.check_auction_requirements(requirements).await {
+                Ok(()) => true,
+                Err(e) => {
+                    info!(provider_id, "auction requirements not satisfied: {e:#}");
+                    false
+                }
+            },
+            None => true,
+        };
+
+        if constraints_satisfied && requirements_satisfied && !provider_running {
             Ok(Some(CtlResponse::ok(
                 ProviderAuctionAck::builder()
                     .provider_ref(provider_ref.into())
@@ -385,12 +414,21 @@ impl ControlInterfaceServer for Host {
                         Arc::clone(&component_ref),
                         &host_id,
                         None,
+                        None,
                     )
                     .await
                 {
                     error!(%component_ref, %component_id, err = ?e, "failed to update component after scale");
+                    self.publish_component_update_failed(
+                        &host_id,
+                        &component_id,
+                        &component_ref,
+                        &e,
+                    )
+                    .await;
                 }
             }
+            self.remove_stopped_secrets().await;
         });
 
         Ok(CtlResponse::<()>::success(message))
@@ -408,11 +446,13 @@ impl ControlInterfaceServer for Host {
         let annotations = request.annotations().cloned();
         let new_component_ref = request.new_component_ref();
         let host_id = request.host_id();
+        let rollout = request.rollout().cloned();
 
         debug!(
             component_id,
             new_component_ref,
             ?annotations,
+            ?rollout,
             "handling update component"
         );
 
@@ -452,16 +492,51 @@ impl ControlInterfaceServer for Host {
                     Arc::clone(&new_component_ref),
                     &host_id,
                     annotations,
+                    rollout,
                 )
                 .await
             {
                 error!(%new_component_ref, %component_id, err = ?e, "failed to update component");
+                self.publish_component_update_failed(
+                    &host_id,
+                    &component_id,
+                    &new_component_ref,
+                    &e,
+                )
+                .await;
             }
+            self.remove_stopped_secrets().await;
         });
 
         Ok(CtlResponse::<()>::success(message))
     }
 
+    #[instrument(level = "debug", skip_all)]
+    async fn handle_rollout_component(
+        &self,
+        request: RolloutComponentCommand,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        let component_id = request.component_id();
+        let host_id = request.host_id();
+        let action = request.action();
+
+        debug!(component_id, ?action, "handling rollout component");
+
+        let res = self
+            .handle_rollout_component_task(component_id, host_id, action)
+            .await;
+        self.remove_stopped_secrets().await;
+        match res {
+            Ok(component_ref) => Ok(CtlResponse::<()>::success(format!(
+                "component {component_id} is running {component_ref}"
+            ))),
+            Err(e) => {
+                error!(component_id, ?action, err = ?e, "failed to complete component rollout");
+                Ok(CtlResponse::error(&format!("{e:#}")))
+            }
+        }
+    }
+
     #[instrument(level = "debug", skip_all)]
     async fn handle_start_provider(
         self: Arc<Self>,
@@ -499,6 +574,7 @@ impl ControlInterfaceServer for Host {
                     provider_ref,
                     annotations.cloned().unwrap_or_default(),
                     &host_id,
+                    request.restart_policy().cloned().unwrap_or_default(),
                 )
                 .await
             {
@@ -543,6 +619,8 @@ impl ControlInterfaceServer for Host {
             shutdown,
             ..
         } = entry.remove();
+        drop(providers);
+        self.remove_stopped_secrets().await;
 
         // Set the shutdown flag to true to stop health checks and config updates. Also
         // prevents restarting the provider but does not stop the provider process.
@@ -8,7 +8,7 @@ use tracing::instrument;
 use ulid::Ulid;
 use uuid::Uuid;
 use wascap::jwt;
-use wasmcloud_control_interface::Link;
+use wasmcloud_control_interface::{ComponentRollout, Link, RolloutAction};
 
 fn format_component_claims(claims: &jwt::Claims<jwt::Component>) -> serde_json::Value {
     let issuer = &claims.issuer;
@@ -100,6 +100,51 @@ pub fn component_scale_failed(
     }
 }
 
+pub fn component_update_failed(
+    host_id: impl AsRef<str>,
+    component_id: impl AsRef<str>,
+    new_image_ref: impl AsRef<str>,
+    error: &anyhow::Error,
+) -> serde_json::Value {
+    json!({
+        "host_id": host_id.as_ref(),
+        "component_id": component_id.as_ref(),
+        "new_image_ref": new_image_ref.as_ref(),
+        "error": format!("{error:#}"),
+    })
+}
+
+pub fn component_rollout_started(
+    host_id: impl AsRef<str>,
+    component_id: impl AsRef<str>,
+    image_ref: impl AsRef<str>,
+    new_image_ref: impl AsRef<str>,
+    rollout: &ComponentRollout,
+) -> serde_json::Value {
+    json!({
+        "host_id": host_id.as_ref(),
+        "component_id": component_id.as_ref(),
+        "image_ref": image_ref.as_ref(),
+        "new_image_ref": new_image_ref.as_ref(),
+        "weight": rollout.weight(),
+        "headers": rollout.headers(),
+    })
+}
+
+pub fn component_rollout_completed(
+    host_id: impl AsRef<str>,
+    component_id: impl AsRef<str>,
+    image_ref: impl AsRef<str>,
+    action: RolloutAction,
+) -> serde_json::Value {
+    json!({
+        "host_id": host_id.as_ref(),
+        "component_id": component_id.as_ref(),
+        "image_ref": image_ref.as_ref(),
+        "action": action,
+    })
+}
+
 pub fn linkdef_set(link: &Link) -> serde_json::Value {
     json!({
         "source_id": link.source_id(),
@@ -235,6 +280,26 @@ pub fn provider_stopped(
     })
 }
 
+pub fn provider_crashed(
+    host_id: impl AsRef<str>,
+    provider_id: impl AsRef<str>,
+    exit_code: Option<i32>,
+    status: impl AsRef<str>,
+    stderr: Vec<String>,
+    restarts: u32,
+    restarting: bool,
+) -> serde_json::Value {
+    json!({
+        "host_id": host_id.as_ref(),
+        "provider_id": provider_id.as_ref(),
+        "exit_code": exit_code,
+        "status": status.as_ref(),
+        "stderr": stderr,
+        "restarts": restarts,
+        "restarting": restarting,
+    })
+}
+
 pub fn provider_health_check(
     host_id: impl AsRef<str>,
     provider_id: impl AsRef<str>,
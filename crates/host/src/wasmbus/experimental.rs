@@ -2,7 +2,7 @@ use tracing::warn;
 
 /// Feature flags to enable experimental functionality in the host. Flags are disabled
 /// by default and must be explicitly enabled.
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct Features {
     /// Enable the built-in HTTP server capability provider
     /// that can be started with the reference wasmcloud+builtin://http-server
@@ -12,6 +12,8 @@ pub struct Features {
     pub(crate) builtin_messaging_nats: bool,
     /// Enable the wasmcloud:messaging@v3 interface support in the host
     pub(crate) wasmcloud_messaging_v3: bool,
+    /// Enable running capability providers implemented as Wasm components
+    pub(crate) component_providers: bool,
 }
 
 impl Features {
@@ -37,6 +39,17 @@ impl Features {
         self.wasmcloud_messaging_v3 = true;
         self
     }
+
+    /// Enable running capability providers implemented as Wasm components
+    pub fn enable_component_providers(mut self) -> Self {
+        self.component_providers = true;
+        self
+    }
+
+    /// Whether all features enabled in `other` are also enabled in this set of flags
+    pub(crate) fn contains(&self, other: Self) -> bool {
+        (*self | other) == *self
+    }
 }
 
 /// This enables unioning feature flags together
@@ -48,6 +61,7 @@ impl std::ops::BitOr for Features {
             builtin_http_server: self.builtin_http_server || rhs.builtin_http_server,
             builtin_messaging_nats: self.builtin_messaging_nats || rhs.builtin_messaging_nats,
             wasmcloud_messaging_v3: self.wasmcloud_messaging_v3 || rhs.wasmcloud_messaging_v3,
+            component_providers: self.component_providers || rhs.component_providers,
         }
     }
 }
@@ -74,6 +88,9 @@ impl From<&str> for Features {
             "wasmcloud-messaging-v3" | "wasmcloud_messaging_v3" => {
                 Self::new().enable_wasmcloud_messaging_v3()
             }
+            "component-providers" | "component_providers" => {
+                Self::new().enable_component_providers()
+            }
             _ => {
                 warn!(%s, "unknown feature flag");
                 Self::new()
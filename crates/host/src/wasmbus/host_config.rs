@@ -51,6 +51,11 @@ pub struct Host {
     pub oci_opts: OciConfig,
     /// Whether to allow loading component or provider components from the filesystem
     pub allow_file_load: bool,
+    /// Whether to require components and providers to be signed with embedded claims
+    pub require_signed_artifacts: bool,
+    /// Account public keys of the issuers whose components and providers may be started. If empty,
+    /// artifacts signed by any issuer are accepted
+    pub trusted_issuers: Vec<String>,
     /// Whether or not structured logging is enabled
     pub enable_structured_logging: bool,
     /// Log level to pass to capability providers to use. Should be parsed from a [`tracing::Level`]
@@ -63,6 +68,8 @@ pub struct Host {
     pub policy_service_config: PolicyService,
     /// topic for wasmCloud secrets backend
     pub secrets_topic_prefix: Option<String>,
+    /// How long secrets fetched from the secrets backend are reused before being fetched again
+    pub secrets_cache_ttl: Duration,
     /// The semver version of the host. This is used by a consumer of this crate to indicate the
     /// host version (which may differ from the crate version)
     pub version: String,
@@ -119,12 +126,15 @@ impl Default for Host {
             provider_shutdown_delay: None,
             oci_opts: OciConfig::default(),
             allow_file_load: false,
+            require_signed_artifacts: false,
+            trusted_issuers: Vec::new(),
             enable_structured_logging: false,
             log_level: LogLevel::Info,
             config_service_enabled: false,
             otel_config: OtelConfig::default(),
             policy_service_config: PolicyService::default(),
             secrets_topic_prefix: None,
+            secrets_cache_ttl: Duration::from_secs(30),
             version: env!("CARGO_PKG_VERSION").to_string(),
             max_execution_time: Duration::from_millis(10 * 60 * 1000),
             // 10 MB
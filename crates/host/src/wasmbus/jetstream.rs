@@ -1,11 +1,14 @@
 //! Host interactions with JetStream, including processing of KV entries and
 //! storing/retrieving component specifications.
 
+use std::collections::{BTreeSet, HashMap};
+
 use anyhow::{anyhow, ensure, Context as _};
 use async_nats::jetstream::kv::{Entry as KvEntry, Operation, Store};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info, instrument, warn};
-use wasmcloud_control_interface::Link;
+use wascap::jwt;
+use wasmcloud_control_interface::{ComponentRollout, Link};
 
 use crate::wasmbus::claims::{Claims, StoredClaims};
 use crate::wasmbus::component_import_links;
@@ -21,6 +24,9 @@ pub struct ComponentSpecification {
     /// All outbound links from this component to other components, used for routing when calling a component `import`
     #[serde(default)]
     pub(crate) links: Vec<Link>,
+    /// A new version of the component that is being rolled out alongside the one at `url`, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) rollout: Option<RolloutSpecification>,
     ////
     // Possible additions in the future, left in as comments to facilitate discussion
     ////
@@ -38,10 +44,80 @@ impl ComponentSpecification {
         Self {
             url: url.as_ref().to_string(),
             links: Vec::new(),
+            rollout: None,
         }
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+/// A new version of a component that is running alongside the current version and receives a share
+/// of its invocations until the rollout is promoted or rolled back.
+pub struct RolloutSpecification {
+    /// The URL of the new version of the component
+    pub(crate) url: String,
+    /// How invocations are split between the current and the new version
+    #[serde(flatten)]
+    pub(crate) strategy: ComponentRollout,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+/// A revocation of component and provider JWTs, stored under `REVOKED_{key}` where `key` is the
+/// public key of either the subject of the revoked JWTs or the account that issued them. Hosts
+/// refuse to start components and providers with a revoked JWT.
+pub struct Revocation {
+    /// IDs (`jti`) of the revoked JWTs. If empty, all JWTs of the subject or issuer are revoked
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub(crate) ids: BTreeSet<String>,
+    /// If set, only JWTs issued before this time (in seconds since the Unix epoch) are revoked, so
+    /// that JWTs reissued afterwards are accepted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) issued_before: Option<u64>,
+    /// Whether components and providers that are already running with a revoked JWT are stopped
+    #[serde(default)]
+    pub(crate) stop_running: bool,
+}
+
+impl Revocation {
+    /// Whether this revocation applies to a JWT with the given `claims`, assuming that its key
+    /// matches their subject or issuer
+    pub(crate) fn revokes<T>(&self, claims: &jwt::Claims<T>) -> bool {
+        (self.ids.is_empty() || self.ids.contains(&claims.id))
+            && self
+                .issued_before
+                .map_or(true, |issued_before| claims.issued_at < issued_before)
+    }
+}
+
+/// The revocations known to a host, keyed by the subject or issuer they apply to
+#[derive(Debug, Default)]
+pub(crate) struct Revocations(HashMap<String, Revocation>);
+
+impl Revocations {
+    /// Process a put of the `REVOKED_{key}` entry, returning whether components and providers that
+    /// are already running with a revoked JWT must be stopped
+    pub(crate) fn put(&mut self, key: &str, value: &[u8]) -> anyhow::Result<bool> {
+        let revocation: Revocation =
+            serde_json::from_slice(value).context("failed to decode revocation")?;
+        let stop_running = revocation.stop_running;
+        self.0.insert(key.to_string(), revocation);
+        Ok(stop_running)
+    }
+
+    /// Process a deletion of the `REVOKED_{key}` entry
+    pub(crate) fn delete(&mut self, key: &str) {
+        self.0.remove(key);
+    }
+
+    /// Whether a component or provider JWT has been revoked, either for its subject or by its issuer
+    pub(crate) fn is_revoked<T>(&self, claims: &jwt::Claims<T>) -> bool {
+        [&claims.subject, &claims.issuer].into_iter().any(|key| {
+            self.0
+                .get(key)
+                .is_some_and(|revocation| revocation.revokes(claims))
+        })
+    }
+}
+
 impl super::Host {
     /// Retrieve a component specification based on the provided ID. The outer Result is for errors
     /// accessing the store, and the inner option indicates if the spec exists.
@@ -224,6 +300,36 @@ impl super::Host {
         Ok(())
     }
 
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn process_revocation_put(
+        &self,
+        key: impl AsRef<str>,
+        value: impl AsRef<[u8]>,
+    ) -> anyhow::Result<()> {
+        let key = key.as_ref();
+
+        debug!(key, "process revocation entry put");
+
+        let stop_running = self.revocations.write().await.put(key, value.as_ref())?;
+        if stop_running {
+            self.stop_revoked().await?;
+        }
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn process_revocation_delete(
+        &self,
+        key: impl AsRef<str>,
+    ) -> anyhow::Result<()> {
+        let key = key.as_ref();
+
+        debug!(key, "process revocation entry deletion");
+
+        self.revocations.write().await.delete(key);
+        Ok(())
+    }
+
     #[instrument(level = "trace", skip_all)]
     pub(crate) async fn process_entry(
         &self,
@@ -256,6 +362,12 @@ impl super::Host {
             (Operation::Delete, Some(("CLAIMS", pubkey))) => {
                 self.process_claims_delete(pubkey, value).await
             }
+            (Operation::Put, Some(("REVOKED", key))) => {
+                self.process_revocation_put(key, value).await
+            }
+            (Operation::Delete | Operation::Purge, Some(("REVOKED", key))) => {
+                self.process_revocation_delete(key).await
+            }
             (operation, Some(("REFMAP", id))) => {
                 // TODO: process REFMAP entries
                 debug!(?operation, id, "ignoring REFMAP entry");
@@ -297,3 +409,86 @@ pub(crate) async fn create_bucket(
         Err(err) => Err(anyhow!(err).context(format!("failed to create bucket '{bucket}'"))),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use wascap::jwt;
+
+    use super::{Revocation, Revocations};
+
+    const ISSUER: &str = "AISSUER";
+    const SUBJECT: &str = "MSUBJECT";
+
+    fn claims(id: &str, issued_at: u64) -> jwt::Claims<jwt::Component> {
+        let mut claims = jwt::Claims::default();
+        claims.id = id.into();
+        claims.issued_at = issued_at;
+        claims.issuer = ISSUER.into();
+        claims.subject = SUBJECT.into();
+        claims
+    }
+
+    #[test]
+    fn revocation_revokes() {
+        let all = Revocation::default();
+        assert!(all.revokes(&claims("jwt1", 100)));
+
+        let ids = Revocation {
+            ids: BTreeSet::from(["jwt1".into()]),
+            ..Default::default()
+        };
+        assert!(ids.revokes(&claims("jwt1", 100)));
+        assert!(!ids.revokes(&claims("jwt2", 100)));
+
+        let time_bounded = Revocation {
+            issued_before: Some(200),
+            ..Default::default()
+        };
+        assert!(time_bounded.revokes(&claims("jwt1", 100)));
+        assert!(!time_bounded.revokes(&claims("jwt2", 200)));
+        assert!(!time_bounded.revokes(&claims("jwt3", 300)));
+
+        let ids_time_bounded = Revocation {
+            ids: BTreeSet::from(["jwt1".into(), "jwt3".into()]),
+            issued_before: Some(200),
+            ..Default::default()
+        };
+        assert!(ids_time_bounded.revokes(&claims("jwt1", 100)));
+        assert!(!ids_time_bounded.revokes(&claims("jwt2", 100)));
+        assert!(!ids_time_bounded.revokes(&claims("jwt3", 300)));
+    }
+
+    #[test]
+    fn revocations_entries() {
+        let mut revocations = Revocations::default();
+        assert!(!revocations.is_revoked(&claims("jwt1", 100)));
+
+        // Revocations apply to JWTs of the subject
+        assert!(!revocations
+            .put(SUBJECT, br#"{"ids":["jwt1"]}"#)
+            .expect("failed to put revocation"));
+        assert!(revocations.is_revoked(&claims("jwt1", 100)));
+        assert!(!revocations.is_revoked(&claims("jwt2", 100)));
+        revocations.delete(SUBJECT);
+        assert!(!revocations.is_revoked(&claims("jwt1", 100)));
+
+        // Revocations apply to JWTs issued by the issuer
+        assert!(revocations
+            .put(ISSUER, br#"{"issued_before":200,"stop_running":true}"#)
+            .expect("failed to put revocation"));
+        assert!(revocations.is_revoked(&claims("jwt1", 100)));
+        assert!(!revocations.is_revoked(&claims("jwt2", 300)));
+
+        // Revocations of other keys do not apply
+        revocations.delete(ISSUER);
+        revocations
+            .put("MOTHER", b"{}")
+            .expect("failed to put revocation");
+        assert!(!revocations.is_revoked(&claims("jwt1", 100)));
+
+        assert!(revocations.put(SUBJECT, b"not json").is_err());
+        assert!(!revocations.is_revoked(&claims("jwt1", 100)));
+    }
+}
@@ -3,11 +3,12 @@
 use core::sync::atomic::Ordering;
 
 use std::collections::hash_map::Entry;
-use std::collections::{hash_map, BTreeMap, HashMap};
+use std::collections::{hash_map, BTreeMap, HashMap, HashSet};
 use std::env::consts::{ARCH, FAMILY, OS};
 use std::future::Future;
 use std::num::NonZeroUsize;
 use std::ops::Deref;
+use std::path::Path;
 use std::pin::Pin;
 use std::str::FromStr;
 use std::sync::atomic::AtomicBool;
@@ -21,15 +22,16 @@ use bytes::{BufMut, Bytes, BytesMut};
 use claims::{Claims, StoredClaims};
 use cloudevents::{EventBuilder, EventBuilderV10};
 use ctl::ControlInterfaceServer;
-use futures::future::Either;
+use futures::future::{self, Either};
 use futures::stream::{AbortHandle, Abortable, SelectAll};
 use futures::{join, stream, try_join, Stream, StreamExt, TryFutureExt, TryStreamExt};
 use hyper_util::rt::{TokioExecutor, TokioIo};
 use nkeys::{KeyPair, KeyPairType, XKey};
-use providers::Provider;
+use providers::{Provider, ProviderProcess};
 use secrecy::Secret;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sysinfo::{Pid, PidExt as _, ProcessExt as _, System, SystemExt as _};
 use tokio::io::AsyncWrite;
 use tokio::net::TcpListener;
 use tokio::spawn;
@@ -41,25 +43,28 @@ use tracing::{debug, debug_span, error, info, instrument, trace, warn, Instrumen
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use wascap::jwt;
 use wasmcloud_control_interface::{
-    ComponentAuctionAck, ComponentAuctionRequest, ComponentDescription, CtlResponse,
-    DeleteInterfaceLinkDefinitionRequest, HostInventory, HostLabel, HostLabelIdentifier, Link,
-    ProviderAuctionAck, ProviderAuctionRequest, ProviderDescription, RegistryCredential,
+    AuctionRequirements, ComponentAuctionAck, ComponentAuctionRequest, ComponentDescription,
+    ComponentRollout, CtlResponse, DeleteInterfaceLinkDefinitionRequest, HostInventory, HostLabel,
+    HostLabelIdentifier, Link, ProviderAuctionAck, ProviderAuctionRequest, ProviderDescription,
+    ProviderRestartPolicy, RegistryCredential, RolloutAction, RolloutComponentCommand,
     ScaleComponentCommand, StartProviderCommand, StopHostCommand, StopProviderCommand,
     UpdateComponentCommand,
 };
-use wasmcloud_core::{ComponentId, CTL_API_VERSION_1};
+use wasmcloud_core::{provider_config_update_subject, ComponentId, CTL_API_VERSION_1};
 use wasmcloud_runtime::capability::secrets::store::SecretValue;
 use wasmcloud_runtime::component::WrpcServeEvent;
-use wasmcloud_runtime::Runtime;
+use wasmcloud_runtime::{ComponentConfig, Runtime};
 use wasmcloud_secrets_types::SECRET_PREFIX;
 use wasmcloud_tracing::context::TraceContextInjector;
 use wasmcloud_tracing::{global, InstrumentationScope, KeyValue};
 
 use crate::registry::RegistryCredentialExt;
+use crate::secrets::SecretLink;
 use crate::wasmbus::jetstream::create_bucket;
 use crate::{
-    fetch_component, HostMetrics, OciConfig, PolicyHostInfo, PolicyManager, PolicyResponse,
-    RegistryAuth, RegistryConfig, RegistryType, ResourceRef, SecretsManager,
+    fetch_component_with_verifier, CosignVerifier, HostMetrics, OciConfig, PolicyHostInfo,
+    PolicyManager, PolicyResponse, RegistryAuth, RegistryConfig, RegistryType, ResourceRef,
+    SecretsManager,
 };
 
 mod claims;
@@ -69,6 +74,7 @@ mod experimental;
 mod handler;
 mod jetstream;
 mod providers;
+mod rollout;
 
 pub mod config;
 /// wasmCloud host configuration
@@ -76,10 +82,12 @@ pub mod host_config;
 
 pub use self::experimental::Features;
 pub use self::host_config::Host as HostConfig;
-pub use jetstream::ComponentSpecification;
+use jetstream::Revocations;
+pub use jetstream::{ComponentSpecification, Revocation, RolloutSpecification};
 
 use self::config::{BundleGenerator, ConfigBundle};
 use self::handler::Handler;
+use self::rollout::{Invocation, Rollout, Rollouts, TrafficSplit};
 
 const MAX_INVOCATION_CHANNEL_SIZE: usize = 5000;
 const MIN_INVOCATION_CHANNEL_SIZE: usize = 256;
@@ -217,6 +225,7 @@ struct Component {
     image_reference: Arc<str>,
     events: mpsc::Sender<WrpcServeEvent<<WrpcServer as wrpc_transport::Serve>::Context>>,
     permits: Arc<Semaphore>,
+    started_at: Instant,
 }
 
 impl Deref for Component {
@@ -236,6 +245,11 @@ struct WrpcServer {
     annotations: Arc<Annotations>,
     policy_manager: Arc<PolicyManager>,
     metrics: Arc<HostMetrics>,
+    /// Rollouts in progress on the host, used to divert invocations to new component versions
+    rollouts: Rollouts,
+    /// Set when serving a new version of a component that is being rolled out, in which case
+    /// invocations are received from the current version rather than directly over NATS
+    canary: Option<Arc<TrafficSplit>>,
 }
 
 struct InvocationContext {
@@ -275,10 +289,33 @@ impl wrpc_transport::Serve for WrpcServer {
             + 'static,
     > {
         debug!("serving invocations");
-        let invocations = self.nats.serve(instance, func, paths).await?;
-
         let func: Arc<str> = Arc::from(func);
         let instance: Arc<str> = Arc::from(instance);
+
+        let invocations: Pin<Box<dyn Stream<Item = anyhow::Result<Invocation>> + Send>> =
+            if let Some(canary) = &self.canary {
+                Box::pin(canary.subscribe(&instance, &func).map(Ok))
+            } else {
+                let invocations = self.nats.serve(&instance, &func, paths).await?;
+                let rollouts = Arc::clone(&self.rollouts);
+                let id = Arc::clone(&self.id);
+                let func = Arc::clone(&func);
+                let instance = Arc::clone(&instance);
+                Box::pin(invocations.filter_map(move |invocation| {
+                    let split = rollouts
+                        .read()
+                        .unwrap_or_else(std::sync::PoisonError::into_inner)
+                        .get(&*id)
+                        .map(|Rollout { split, .. }| Arc::clone(split));
+                    let invocation = match (invocation, split) {
+                        (Ok(invocation), Some(split)) => {
+                            split.divert(&instance, &func, invocation).map(Ok)
+                        }
+                        (invocation, _) => Some(invocation),
+                    };
+                    future::ready(invocation)
+                }))
+            };
         let annotations = Arc::clone(&self.annotations);
         let id = Arc::clone(&self.id);
         let image_reference = Arc::clone(&self.image_reference);
@@ -377,7 +414,15 @@ pub struct Host {
     secrets_manager: Arc<SecretsManager>,
     /// The provider map is a map of provider component ID to provider
     providers: RwLock<HashMap<String, Provider>>,
+    /// Used to collect resource usage of provider processes
+    system: std::sync::Mutex<System>,
+    /// Component rollouts in progress, keyed by component ID
+    rollouts: Rollouts,
+    /// Revoked JWTs, keyed by the public key of their subject or issuer
+    revocations: RwLock<Revocations>,
     registry_config: RwLock<HashMap<String, RegistryConfig>>,
+    /// Verifies signatures of artifacts fetched from OCI registries, if configured
+    signature_verifier: Option<Arc<CosignVerifier>>,
     runtime: Runtime,
     start_at: Instant,
     stop_tx: watch::Sender<Option<Instant>>,
@@ -696,6 +741,9 @@ impl Host {
         let (stop_tx, stop_rx) = watch::channel(None);
 
         let (runtime, _epoch) = Runtime::builder()
+            .component_config(ComponentConfig {
+                require_signature: config.require_signed_artifacts,
+            })
             .max_execution_time(config.max_execution_time)
             .max_linear_memory(config.max_linear_memory)
             .max_components(config.max_components)
@@ -719,6 +767,7 @@ impl Host {
         let (queue_abort, queue_abort_reg) = AbortHandle::new_pair();
         let (heartbeat_abort, heartbeat_abort_reg) = AbortHandle::new_pair();
         let (data_watch_abort, data_watch_abort_reg) = AbortHandle::new_pair();
+        let (secret_rotations_abort, secret_rotations_abort_reg) = AbortHandle::new_pair();
 
         let supplemental_config = if config.config_service_enabled {
             load_supplemental_config(&ctl_nats, &config.lattice, &labels).await?
@@ -728,6 +777,14 @@ impl Host {
 
         let registry_config = RwLock::new(supplemental_config.registry_config.unwrap_or_default());
         merge_registry_config(&registry_config, config.oci_opts.clone()).await;
+        let signature_verifier = if config.oci_opts.signature_public_keys.is_empty() {
+            None
+        } else {
+            let verifier = CosignVerifier::from_paths(&config.oci_opts.signature_public_keys)
+                .await
+                .context("failed to load OCI signature public keys")?;
+            Some(Arc::new(verifier))
+        };
 
         let policy_manager = PolicyManager::new(
             ctl_nats.clone(),
@@ -757,7 +814,12 @@ impl Host {
             &config_data,
             config.secrets_topic_prefix.as_ref(),
             &ctl_nats,
+            config.secrets_cache_ttl,
         ));
+        let secret_rotations = secrets_manager
+            .subscribe_rotations()
+            .await
+            .context("failed to subscribe to secret rotations")?;
 
         let scope = InstrumentationScope::builder("wasmcloud-host")
             .with_version(config.version.clone())
@@ -870,7 +932,11 @@ impl Host {
             policy_manager,
             secrets_manager,
             providers: RwLock::default(),
+            system: std::sync::Mutex::new(System::new()),
+            rollouts: Arc::default(),
+            revocations: RwLock::default(),
             registry_config,
+            signature_verifier,
             runtime,
             start_at,
             stop_rx,
@@ -986,6 +1052,20 @@ impl Host {
             }
         });
 
+        if let Some(secret_rotations) = secret_rotations {
+            spawn({
+                let host = Arc::clone(&host);
+                Abortable::new(secret_rotations, secret_rotations_abort_reg).for_each(move |msg| {
+                    let host = Arc::clone(&host);
+                    async move {
+                        if let Err(err) = host.handle_secret_rotation(&msg).await {
+                            error!(?err, subject = %msg.subject, "failed to handle secret rotation");
+                        }
+                    }
+                })
+            });
+        }
+
         // Process existing data without emitting events
         data.keys()
             .await
@@ -1018,6 +1098,7 @@ impl Host {
             queue_abort.abort();
             data_watch_abort.abort();
             host.policy_manager.policy_changes.abort();
+            secret_rotations_abort.abort();
             let _ = try_join!(queue, data_watch, heartbeat).context("failed to await tasks")?;
             host.publish_event(
                 "host_stopped",
@@ -1058,11 +1139,19 @@ impl Host {
             let components = self.components.read().await;
             stream::iter(components.iter())
                 .filter_map(|(id, component)| async move {
+                    let max_instances = component.max_instances.get().min(Semaphore::MAX_PERMITS);
+                    let active_instances =
+                        max_instances.saturating_sub(component.permits.available_permits());
+                    let memory = component.memory_usage();
                     let mut description = ComponentDescription::builder()
                         .id(id.into())
                         .image_ref(component.image_reference.to_string())
                         .annotations(component.annotations.clone().into_iter().collect())
                         .max_instances(component.max_instances.get().try_into().unwrap_or(u32::MAX))
+                        .active_instances(active_instances.try_into().unwrap_or(u32::MAX))
+                        .memory_bytes(memory.current())
+                        .peak_memory_bytes(memory.peak())
+                        .uptime_seconds(component.started_at.elapsed().as_secs())
                         .revision(
                             component
                                 .claims()
@@ -1090,50 +1179,69 @@ impl Host {
                 .await
         };
 
-        let providers: Vec<_> = self
-            .providers
-            .read()
-            .await
-            .iter()
-            .map(
-                |(
-                    provider_id,
-                    Provider {
-                        annotations,
-                        claims_token,
-                        image_ref,
-                        ..
+        let providers: Vec<_> = {
+            let providers = self.providers.read().await;
+            let mut system = self
+                .system
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            providers
+                .iter()
+                .map(
+                    |(
+                        provider_id,
+                        Provider {
+                            annotations,
+                            claims_token,
+                            image_ref,
+                            process,
+                            started_at,
+                            ..
+                        },
+                    )| {
+                        let mut provider_description = ProviderDescription::builder()
+                            .id(provider_id)
+                            .image_ref(image_ref)
+                            .uptime_seconds(started_at.elapsed().as_secs())
+                            .restarts(process.restarts.load(Ordering::Relaxed));
+                        // Builtin providers run within the host process and have no process ID
+                        let pid = Pid::from_u32(process.id.load(Ordering::Relaxed));
+                        if pid.as_u32() != 0 && system.refresh_process(pid) {
+                            if let Some(stats) = system.process(pid) {
+                                // CPU usage is reported as a percentage of a single core
+                                let cpu_millicores = (stats.cpu_usage() * 10.0).round() as u64;
+                                provider_description = provider_description
+                                    .memory_bytes(stats.memory())
+                                    .cpu_millicores(cpu_millicores);
+                            }
+                        }
+                        if let Some(name) = claims_token
+                            .as_ref()
+                            .and_then(|claims| claims.claims.metadata.as_ref())
+                            .and_then(|metadata| metadata.name.as_ref())
+                        {
+                            provider_description = provider_description.name(name);
+                        }
+                        provider_description
+                            .annotations(
+                                annotations
+                                    .clone()
+                                    .into_iter()
+                                    .collect::<BTreeMap<String, String>>(),
+                            )
+                            .revision(
+                                claims_token
+                                    .as_ref()
+                                    .and_then(|claims| claims.claims.metadata.as_ref())
+                                    .and_then(|jwt::CapabilityProvider { rev, .. }| *rev)
+                                    .unwrap_or_default(),
+                            )
+                            .build()
+                            .expect("failed to build provider description")
                     },
-                )| {
-                    let mut provider_description = ProviderDescription::builder()
-                        .id(provider_id)
-                        .image_ref(image_ref);
-                    if let Some(name) = claims_token
-                        .as_ref()
-                        .and_then(|claims| claims.claims.metadata.as_ref())
-                        .and_then(|metadata| metadata.name.as_ref())
-                    {
-                        provider_description = provider_description.name(name);
-                    }
-                    provider_description
-                        .annotations(
-                            annotations
-                                .clone()
-                                .into_iter()
-                                .collect::<BTreeMap<String, String>>(),
-                        )
-                        .revision(
-                            claims_token
-                                .as_ref()
-                                .and_then(|claims| claims.claims.metadata.as_ref())
-                                .and_then(|jwt::CapabilityProvider { rev, .. }| *rev)
-                                .unwrap_or_default(),
-                        )
-                        .build()
-                        .expect("failed to build provider description")
-                },
-            )
-            .collect();
+                )
+                .collect()
+        };
 
         let uptime = self.start_at.elapsed();
         HostInventory::builder()
@@ -1149,6 +1257,220 @@ impl Host {
             .expect("failed to build host inventory")
     }
 
+    /// Whether the host only starts artifacts that are signed, optionally by trusted issuers
+    fn enforces_signatures(&self) -> bool {
+        self.host_config.require_signed_artifacts || !self.host_config.trusted_issuers.is_empty()
+    }
+
+    /// Ensure that a component or provider is signed by a trusted issuer, if the host requires it,
+    /// and that its JWT has not been revoked
+    async fn verify_issuer<T>(&self, claims: Option<&jwt::Claims<T>>) -> anyhow::Result<()> {
+        let Some(claims) = claims else {
+            ensure!(
+                !self.enforces_signatures(),
+                "artifact is not signed, but the host only starts signed artifacts"
+            );
+            return Ok(());
+        };
+        let trusted_issuers = &self.host_config.trusted_issuers;
+        ensure!(
+            trusted_issuers.is_empty() || trusted_issuers.contains(&claims.issuer),
+            "artifact issuer `{}` is not trusted",
+            claims.issuer
+        );
+        ensure!(
+            !self.is_revoked(claims).await,
+            "JWT `{}` of `{}` has been revoked",
+            claims.id,
+            claims.subject
+        );
+        Ok(())
+    }
+
+    /// Whether a component or provider JWT has been revoked, either for its subject or by its issuer
+    async fn is_revoked<T>(&self, claims: &jwt::Claims<T>) -> bool {
+        self.revocations.read().await.is_revoked(claims)
+    }
+
+    /// Stop all components and providers running on this host with a revoked JWT
+    #[instrument(level = "debug", skip_all)]
+    async fn stop_revoked(&self) -> anyhow::Result<()> {
+        let host_id = self.host_key.public_key();
+        // Roll back rollouts whose new version has a revoked JWT, as the version that it is rolled
+        // out alongside may not be revoked
+        let revoked_canaries: Vec<_> = {
+            let revocations = self.revocations.read().await;
+            self.rollouts
+                .read()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .iter()
+                .filter(|(_, Rollout { component, .. })| {
+                    component
+                        .claims()
+                        .is_some_and(|claims| revocations.is_revoked(claims))
+                })
+                .map(|(id, _)| id.clone())
+                .collect()
+        };
+        for id in revoked_canaries {
+            warn!(
+                component_id = id,
+                "rolling back rollout of a component with a revoked JWT"
+            );
+            self.handle_rollout_component_task(&id, &host_id, RolloutAction::Rollback)
+                .await
+                .context("failed to roll back rollout of revoked component")?;
+        }
+
+        let mut revoked_components = Vec::new();
+        for (id, component) in self.components.read().await.iter() {
+            if let Some(claims) = component.claims() {
+                if self.is_revoked(claims).await {
+                    revoked_components.push(id.clone());
+                }
+            }
+        }
+        for id in revoked_components {
+            let Some(component) = self.components.write().await.remove(&id) else {
+                continue;
+            };
+            self.stop_component(&component, &host_id)
+                .await
+                .context("failed to stop revoked component")?;
+            if let Some(Rollout { component, .. }) = self.remove_rollout(&id) {
+                self.stop_component(&component, &host_id)
+                    .await
+                    .context("failed to stop rolled out revoked component")?;
+            }
+            warn!(component_id = id, "stopped component with a revoked JWT");
+            self.publish_event(
+                "component_scaled",
+                event::component_scaled(
+                    component.claims(),
+                    &component.annotations,
+                    &host_id,
+                    0_usize,
+                    &component.image_reference,
+                    &component.id,
+                ),
+            )
+            .await?;
+        }
+        self.remove_stopped_secrets().await;
+
+        let mut revoked_providers = Vec::new();
+        for (id, provider) in self.providers.read().await.iter() {
+            if let Some(token) = &provider.claims_token {
+                if self.is_revoked(&token.claims).await {
+                    revoked_providers.push(id.clone());
+                }
+            }
+        }
+        for id in revoked_providers {
+            warn!(provider_id = id, "stopping provider with a revoked JWT");
+            <Self as ControlInterfaceServer>::handle_stop_provider(
+                self,
+                StopProviderCommand::builder()
+                    .host_id(&host_id)
+                    .provider_id(&id)
+                    .build()
+                    .map_err(|e| anyhow!(e))
+                    .context("failed to build stop provider command")?,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Ensure that a component is signed by a trusted issuer and matches the hash in its claims, if
+    /// the host requires it
+    async fn verify_component(
+        &self,
+        wasm: &[u8],
+        claims: Option<&jwt::Claims<jwt::Component>>,
+    ) -> anyhow::Result<()> {
+        self.verify_issuer(claims).await?;
+        if let Some(claims) = claims.filter(|_| self.enforces_signatures()) {
+            let module_hash = claims
+                .metadata
+                .as_ref()
+                .map(|metadata| metadata.module_hash.as_str())
+                .context("component claims do not contain a module hash")?;
+            ensure!(
+                wascap::wasm::module_hash(wasm).context("failed to compute module hash")?
+                    == module_hash,
+                "component does not match the module hash in its claims"
+            );
+        }
+        Ok(())
+    }
+
+    /// Ensure that a provider is signed by a trusted issuer and that its binary at `path` matches
+    /// the hash in its claims, if the host requires it
+    async fn verify_provider(
+        &self,
+        path: &Path,
+        claims: Option<&jwt::Claims<jwt::CapabilityProvider>>,
+    ) -> anyhow::Result<()> {
+        self.verify_issuer(claims).await?;
+        if let Some(claims) = claims.filter(|_| self.enforces_signatures()) {
+            wasmcloud_core::par::verify_target_hash(path, claims).await?;
+        }
+        Ok(())
+    }
+
+    /// Ensure that this host can provide the resources required to bid in an auction, given the
+    /// workloads it is already running
+    #[instrument(level = "debug", skip_all)]
+    async fn check_auction_requirements(
+        &self,
+        requirements: &AuctionRequirements,
+    ) -> anyhow::Result<()> {
+        let running_providers = self.providers.read().await.keys().cloned().collect();
+        let (reserved_instances, reserved_memory) = {
+            let components = self.components.read().await;
+            let reserved_instances = components
+                .values()
+                .map(|component| component.max_instances.get())
+                .sum();
+            // Running components may grow every instance up to the peak memory of a single
+            // instance so far, of which the currently allocated memory is no longer available
+            let reserved_memory = components
+                .values()
+                .map(|component| {
+                    let memory = component.memory_usage();
+                    let instances =
+                        u64::try_from(component.max_instances.get()).unwrap_or(u64::MAX);
+                    memory
+                        .peak()
+                        .saturating_mul(instances)
+                        .saturating_sub(memory.current())
+                })
+                .fold(0, u64::saturating_add);
+            (reserved_instances, reserved_memory)
+        };
+        let available_memory = if requirements.memory_bytes().is_some() {
+            let mut system = self
+                .system
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            system.refresh_memory();
+            system.available_memory()
+        } else {
+            0
+        };
+        AuctionCapacity {
+            experimental_features: self.experimental_features,
+            running_providers,
+            reserved_instances,
+            max_instances: usize::try_from(self.host_config.max_components).unwrap_or(usize::MAX),
+            max_linear_memory: self.host_config.max_linear_memory,
+            available_memory,
+            reserved_memory,
+        }
+        .check(requirements)
+    }
+
     #[instrument(level = "debug", skip_all)]
     async fn heartbeat(&self) -> anyhow::Result<serde_json::Value> {
         trace!("generating heartbeat");
@@ -1178,6 +1500,7 @@ impl Host {
         max_instances: NonZeroUsize,
         mut component: wasmcloud_runtime::Component<Handler>,
         handler: Handler,
+        canary: Option<Arc<TrafficSplit>>,
     ) -> anyhow::Result<Arc<Component>> {
         trace!(
             component_ref = ?image_reference,
@@ -1210,6 +1533,8 @@ impl Host {
                     annotations: Arc::new(annotations.clone()),
                     policy_manager: Arc::clone(&self.policy_manager),
                     metrics: Arc::clone(&self.metrics),
+                    rollouts: Arc::clone(&self.rollouts),
+                    canary,
                 },
                 handler.clone(),
                 events_tx.clone(),
@@ -1303,6 +1628,7 @@ impl Host {
             annotations: annotations.clone(),
             max_instances,
             image_reference,
+            started_at: Instant::now(),
         }))
     }
 
@@ -1322,6 +1648,9 @@ impl Host {
     ) -> anyhow::Result<&'a mut Arc<Component>> {
         debug!(?component_ref, ?max_instances, "starting new component");
 
+        self.verify_component(wasm, claims.as_ref())
+            .await
+            .context("refusing to start component")?;
         if let Some(ref claims) = claims {
             self.store_claims(Claims::Component(claims.clone()))
                 .await
@@ -1360,6 +1689,7 @@ impl Host {
                 max_instances,
                 component,
                 handler,
+                None,
             )
             .await
             .context("failed to instantiate component")?;
@@ -1393,11 +1723,12 @@ impl Host {
     #[instrument(level = "trace", skip_all)]
     async fn fetch_component(&self, component_ref: &str) -> anyhow::Result<Vec<u8>> {
         let registry_config = self.registry_config.read().await;
-        fetch_component(
+        fetch_component_with_verifier(
             component_ref,
             self.host_config.allow_file_load,
             &self.host_config.oci_opts.additional_ca_paths,
             &registry_config,
+            self.signature_verifier.as_ref(),
         )
         .await
         .context("failed to fetch component")
@@ -1610,6 +1941,11 @@ impl Host {
                 self.stop_component(&component, host_id)
                     .await
                     .context("failed to stop component in response to scale to zero")?;
+                if let Some(Rollout { component, .. }) = self.remove_rollout(&component_id) {
+                    self.stop_component(&component, host_id).await.context(
+                        "failed to stop rolled out component in response to scale to zero",
+                    )?;
+                }
 
                 info!(?component_ref, "component stopped");
                 event::component_scaled(
@@ -1661,6 +1997,7 @@ impl Host {
                             max,
                             component.component.clone(),
                             handler,
+                            None,
                         )
                         .await
                         .context("failed to instantiate component")?;
@@ -1695,94 +2032,290 @@ impl Host {
         <Self as ControlInterfaceServer>::handle_update_component(self, cmd).await
     }
 
+    /// Publish a `component_update_failed` event, since update failures happen after the update
+    /// request was acknowledged
+    async fn publish_component_update_failed(
+        &self,
+        host_id: &str,
+        component_id: &str,
+        new_component_ref: &str,
+        error: &anyhow::Error,
+    ) {
+        if let Err(e) = self
+            .publish_event(
+                "component_update_failed",
+                event::component_update_failed(host_id, component_id, new_component_ref, error),
+            )
+            .await
+        {
+            error!(%new_component_ref, %component_id, err = ?e, "failed to publish component update failed event");
+        }
+    }
+
     async fn handle_update_component_task(
         &self,
         component_id: Arc<str>,
         new_component_ref: Arc<str>,
         host_id: &str,
         annotations: Option<BTreeMap<String, String>>,
+        rollout: Option<ComponentRollout>,
     ) -> anyhow::Result<()> {
-        // NOTE: This block is specifically scoped to ensure we drop the read lock on `self.components` before
-        // we attempt to grab a write lock.
-        let component = {
-            let components = self.components.read().await;
-            let existing_component = components
-                .get(&*component_id)
-                .context("component not found")?;
-            let annotations = annotations.unwrap_or_default().into_iter().collect();
-
-            // task is a no-op if the component image reference is the same
-            if existing_component.image_reference == new_component_ref {
-                info!(%component_id, %new_component_ref, "component already updated");
-                return Ok(());
-            }
+        // NOTE: The component is cloned out of the map to ensure we drop the read lock on `self.components`
+        // before we attempt to grab a write lock.
+        let existing_component = self
+            .components
+            .read()
+            .await
+            .get(&*component_id)
+            .map(Arc::clone)
+            .context("component not found")?;
+        let annotations = annotations.unwrap_or_default().into_iter().collect();
+
+        // task is a no-op if the component image reference is the same
+        if existing_component.image_reference == new_component_ref {
+            info!(%component_id, %new_component_ref, "component already updated");
+            return Ok(());
+        }
 
-            let new_component = self.fetch_component(&new_component_ref).await?;
-            let new_component = wasmcloud_runtime::Component::new(&self.runtime, &new_component)
-                .context("failed to initialize component")?;
-            let new_claims = new_component.claims().cloned();
-            if let Some(ref claims) = new_claims {
-                self.store_claims(Claims::Component(claims.clone()))
+        let wasm = self.fetch_component(&new_component_ref).await?;
+        let new_component = wasmcloud_runtime::Component::new(&self.runtime, &wasm)
+            .context("failed to initialize component")?;
+        let new_claims = new_component.claims().cloned();
+        self.verify_component(&wasm, new_claims.as_ref())
+            .await
+            .context("refusing to update component")?;
+        if let Some(ref claims) = new_claims {
+            self.store_claims(Claims::Component(claims.clone()))
+                .await
+                .context("failed to store claims")?;
+        }
+
+        let max = existing_component.max_instances;
+        let split = rollout
+            .as_ref()
+            .map(|rollout| Arc::new(TrafficSplit::new(rollout)));
+        let Ok(component) = self
+            .instantiate_component(
+                &annotations,
+                Arc::clone(&new_component_ref),
+                Arc::clone(&component_id),
+                max,
+                new_component,
+                existing_component.handler.copy_for_new(),
+                split.clone(),
+            )
+            .await
+        else {
+            bail!("failed to instantiate component from new reference");
+        };
+
+        if let (Some(strategy), Some(split)) = (rollout, split) {
+            // Invocations are only diverted once the new version is able to serve them
+            let previous = self
+                .rollouts
+                .write()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .insert(component_id.to_string(), Rollout { component, split });
+            if let Some(previous) = previous {
+                self.stop_component(&previous.component, host_id)
                     .await
-                    .context("failed to store claims")?;
+                    .context("failed to stop component from previous rollout")?;
             }
 
-            let max = existing_component.max_instances;
-            let Ok(component) = self
-                .instantiate_component(
-                    &annotations,
-                    Arc::clone(&new_component_ref),
-                    Arc::clone(&component_id),
-                    max,
-                    new_component,
-                    existing_component.handler.copy_for_new(),
-                )
-                .await
-            else {
-                bail!("failed to instantiate component from new reference");
-            };
+            let mut component_spec = self
+                .get_component_spec(&component_id)
+                .await?
+                .unwrap_or_else(|| {
+                    ComponentSpecification::new(&existing_component.image_reference)
+                });
+            component_spec.rollout = Some(RolloutSpecification {
+                url: new_component_ref.to_string(),
+                strategy: strategy.clone(),
+            });
+            self.store_component_spec(&component_id, &component_spec)
+                .await?;
 
-            info!(%new_component_ref, "component updated");
+            info!(%new_component_ref, "component rollout started");
             self.publish_event(
-                "component_scaled",
-                event::component_scaled(
-                    new_claims.as_ref(),
-                    &component.annotations,
+                "component_rollout_started",
+                event::component_rollout_started(
                     host_id,
-                    max,
-                    new_component_ref,
                     &component_id,
+                    &existing_component.image_reference,
+                    new_component_ref,
+                    &strategy,
                 ),
             )
             .await?;
+            return Ok(());
+        }
 
-            // TODO(#1548): If this errors, we need to rollback
-            self.stop_component(&component, host_id)
-                .await
-                .context("failed to stop old component")?;
-            self.publish_event(
-                "component_scaled",
-                event::component_scaled(
-                    component.claims(),
-                    &component.annotations,
-                    host_id,
-                    0_usize,
-                    &component.image_reference,
-                    &component.id,
-                ),
-            )
-            .await?;
-
-            component
-        };
+        info!(%new_component_ref, "component updated");
+        self.publish_event(
+            "component_scaled",
+            event::component_scaled(
+                new_claims.as_ref(),
+                &component.annotations,
+                host_id,
+                max,
+                new_component_ref,
+                &component_id,
+            ),
+        )
+        .await?;
 
         self.components
             .write()
             .await
             .insert(component_id.to_string(), component);
+
+        // TODO(#1548): If this errors, we need to rollback
+        self.stop_component(&existing_component, host_id)
+            .await
+            .context("failed to stop old component")?;
+        self.publish_event(
+            "component_scaled",
+            event::component_scaled(
+                existing_component.claims(),
+                &existing_component.annotations,
+                host_id,
+                0_usize,
+                &existing_component.image_reference,
+                &existing_component.id,
+            ),
+        )
+        .await?;
+
         Ok(())
     }
 
+    #[instrument(level = "debug", skip_all)]
+    async fn handle_rollout_component(
+        &self,
+        payload: impl AsRef<[u8]>,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        let cmd = serde_json::from_slice::<RolloutComponentCommand>(payload.as_ref())
+            .context("failed to deserialize component rollout command")?;
+        <Self as ControlInterfaceServer>::handle_rollout_component(self, cmd).await
+    }
+
+    /// Promotes or rolls back the rollout of `component_id`, returning the image reference of the
+    /// version of the component that is running afterwards
+    #[instrument(level = "debug", skip(self))]
+    async fn handle_rollout_component_task(
+        &self,
+        component_id: &str,
+        host_id: &str,
+        action: RolloutAction,
+    ) -> anyhow::Result<Arc<str>> {
+        let Rollout {
+            component: canary, ..
+        } = self
+            .remove_rollout(component_id)
+            .with_context(|| format!("no rollout in progress for component {component_id}"))?;
+        let existing_component = self
+            .components
+            .read()
+            .await
+            .get(component_id)
+            .map(Arc::clone);
+
+        let image_reference = match (action, existing_component) {
+            (RolloutAction::Rollback, existing_component) => {
+                self.stop_component(&canary, host_id)
+                    .await
+                    .context("failed to stop rolled out component")?;
+                info!(component_ref = ?canary.image_reference, "component rollout rolled back");
+                existing_component.map_or_else(
+                    || Arc::clone(&canary.image_reference),
+                    |component| Arc::clone(&component.image_reference),
+                )
+            }
+            (RolloutAction::Promote, None) => {
+                self.stop_component(&canary, host_id)
+                    .await
+                    .context("failed to stop rolled out component")?;
+                bail!("component {component_id} is no longer running, rollout cannot be promoted");
+            }
+            (RolloutAction::Promote, Some(existing_component)) => {
+                // The rolled out component only serves invocations diverted to it by the current
+                // version, so it must be instantiated again to serve invocations over NATS directly
+                let max = existing_component.max_instances;
+                let component = self
+                    .instantiate_component(
+                        &canary.annotations,
+                        Arc::clone(&canary.image_reference),
+                        Arc::clone(&canary.id),
+                        max,
+                        canary.component.clone(),
+                        canary.handler.copy_for_new(),
+                        None,
+                    )
+                    .await
+                    .context("failed to instantiate promoted component")?;
+                self.components
+                    .write()
+                    .await
+                    .insert(component_id.to_string(), component);
+                self.stop_component(&existing_component, host_id)
+                    .await
+                    .context("failed to stop old component")?;
+                self.stop_component(&canary, host_id)
+                    .await
+                    .context("failed to stop rolled out component")?;
+
+                info!(component_ref = ?canary.image_reference, "component rollout promoted");
+                self.publish_event(
+                    "component_scaled",
+                    event::component_scaled(
+                        canary.claims(),
+                        &canary.annotations,
+                        host_id,
+                        max,
+                        &canary.image_reference,
+                        component_id,
+                    ),
+                )
+                .await?;
+                self.publish_event(
+                    "component_scaled",
+                    event::component_scaled(
+                        existing_component.claims(),
+                        &existing_component.annotations,
+                        host_id,
+                        0_usize,
+                        &existing_component.image_reference,
+                        component_id,
+                    ),
+                )
+                .await?;
+                Arc::clone(&canary.image_reference)
+            }
+        };
+
+        if let Some(mut component_spec) = self.get_component_spec(component_id).await? {
+            component_spec.url = image_reference.to_string();
+            component_spec.rollout = None;
+            self.store_component_spec(component_id, &component_spec)
+                .await?;
+        }
+
+        self.publish_event(
+            "component_rollout_completed",
+            event::component_rollout_completed(host_id, component_id, &image_reference, action),
+        )
+        .await?;
+        Ok(image_reference)
+    }
+
+    /// Stops diverting invocations of `component_id` and returns the rollout, if one was in progress
+    fn remove_rollout(&self, component_id: &str) -> Option<Rollout> {
+        self.rollouts
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(component_id)
+    }
+
     #[instrument(level = "debug", skip_all)]
     async fn handle_start_provider(
         self: Arc<Self>,
@@ -1801,6 +2334,7 @@ impl Host {
         provider_ref: &str,
         annotations: BTreeMap<String, String>,
         host_id: &str,
+        restart_policy: ProviderRestartPolicy,
     ) -> anyhow::Result<()> {
         trace!(provider_ref, provider_id, "start provider task");
 
@@ -1810,11 +2344,12 @@ impl Host {
         let (path, claims_token) = match &provider_ref {
             ResourceRef::Builtin(..) => (None, None),
             _ => {
-                let (path, claims_token) = crate::fetch_provider(
+                let (path, claims_token) = crate::fetch_provider_with_verifier(
                     &provider_ref,
                     host_id,
                     self.host_config.allow_file_load,
                     &registry_config,
+                    self.signature_verifier.as_ref(),
                 )
                 .await
                 .context("failed to fetch provider")?;
@@ -1822,6 +2357,11 @@ impl Host {
             }
         };
         let claims = claims_token.as_ref().map(|t| t.claims.clone());
+        if let Some(path) = &path {
+            self.verify_provider(path, claims.as_ref())
+                .await
+                .context("refusing to start provider")?;
+        }
 
         if let Some(claims) = claims.clone() {
             self.store_claims(Claims::Provider(claims))
@@ -1877,7 +2417,33 @@ impl Host {
             // Used by provider child tasks (health check, config watch, process restarter) to
             // know when to shutdown.
             let shutdown = Arc::new(AtomicBool::new(false));
+            let process = Arc::<ProviderProcess>::default();
             let tasks = match (path, &provider_ref) {
+                (Some(path), ..) if wasmcloud_core::par::is_component(&path).await? => {
+                    ensure!(
+                        self.experimental_features.component_providers,
+                        "feature `component-providers` is not enabled, denying start"
+                    );
+                    let wasm = tokio::fs::read(&path)
+                        .await
+                        .with_context(|| format!("failed to read `{}`", path.display()))?;
+                    Arc::clone(&self)
+                        .start_component_provider(
+                            &wasm,
+                            host_data,
+                            Arc::clone(&config_bundle),
+                            provider_xkey,
+                            provider_id,
+                            // Arguments to allow regenerating configuration later
+                            config_names.to_vec(),
+                            claims_token.clone(),
+                            annotations.clone(),
+                            shutdown.clone(),
+                            Arc::clone(&process),
+                            restart_policy,
+                        )
+                        .await?
+                }
                 (Some(path), ..) => {
                     Arc::clone(&self)
                         .start_binary_provider(
@@ -1891,6 +2457,8 @@ impl Host {
                             claims_token.clone(),
                             annotations.clone(),
                             shutdown.clone(),
+                            Arc::clone(&process),
+                            restart_policy,
                         )
                         .await?
                 }
@@ -1938,6 +2506,8 @@ impl Host {
                 image_ref: provider_ref.as_ref().to_string(),
                 xkey,
                 shutdown,
+                process,
+                started_at: Instant::now(),
             });
         } else {
             bail!("provider is already running with that ID")
@@ -2092,6 +2662,11 @@ impl Host {
                 .await
                 .map(Some)
                 .map(serialize_ctl_response),
+            (Some("component"), Some("rollout"), Some(_host_id), None) => self
+                .handle_rollout_component(message.payload)
+                .await
+                .map(Some)
+                .map(serialize_ctl_response),
             // Provider commands
             (Some("provider"), Some("auction"), None, None) => self
                 .handle_auction_provider(message.payload)
@@ -2296,6 +2871,34 @@ impl Host {
     /// Publishes a link to a provider running on this host to handle.
     #[instrument(level = "debug", skip_all)]
     async fn put_provider_link(&self, provider: &Provider, link: &Link) -> anyhow::Result<()> {
+        self.publish_provider_link(
+            provider,
+            link,
+            injector_to_headers(&TraceContextInjector::default_with_span()),
+        )
+        .await
+    }
+
+    /// Publishes a link to a provider running on this host again after secrets of the link were
+    /// rotated, so that the provider receives the link with the new secrets even though it is
+    /// already linked.
+    #[instrument(level = "debug", skip_all)]
+    async fn put_provider_link_secrets(
+        &self,
+        provider: &Provider,
+        link: &Link,
+    ) -> anyhow::Result<()> {
+        let mut headers = injector_to_headers(&TraceContextInjector::default_with_span());
+        headers.insert(wasmcloud_core::secrets::SECRETS_UPDATE_HEADER, "true");
+        self.publish_provider_link(provider, link, headers).await
+    }
+
+    async fn publish_provider_link(
+        &self,
+        provider: &Provider,
+        link: &Link,
+        headers: async_nats::HeaderMap,
+    ) -> anyhow::Result<()> {
         let provider_link = self
             .resolve_link_config(
                 link.clone(),
@@ -2316,7 +2919,7 @@ impl Host {
                     "wasmbus.rpc.{lattice}.{}.linkdefs.put",
                     provider.xkey.public_key()
                 ),
-                injector_to_headers(&TraceContextInjector::default_with_span()),
+                headers,
                 payload.clone(),
             )
             .await
@@ -2371,6 +2974,19 @@ impl Host {
         config_names: &[String],
         entity_jwt: Option<&String>,
         application: Option<&String>,
+    ) -> anyhow::Result<(ConfigBundle, HashMap<String, Secret<SecretValue>>)> {
+        self.fetch_config_and_secrets_for(config_names, entity_jwt, application, None)
+            .await
+    }
+
+    /// Fetch configuration and secrets like [`Self::fetch_config_and_secrets`], recording that
+    /// the secrets are used by a provider for `link` if one is given
+    async fn fetch_config_and_secrets_for(
+        &self,
+        config_names: &[String],
+        entity_jwt: Option<&String>,
+        application: Option<&String>,
+        link: Option<&SecretLink>,
     ) -> anyhow::Result<(ConfigBundle, HashMap<String, Secret<SecretValue>>)> {
         let (secret_names, config_names) = config_names
             .iter()
@@ -2383,13 +2999,173 @@ impl Host {
             .await
             .context("Unable to fetch requested config")?;
 
-        let secrets = self
+        let secrets = if let Some(link) = link {
+            self.secrets_manager
+                .fetch_link_secrets(
+                    secret_names,
+                    entity_jwt,
+                    &self.host_token.jwt,
+                    application,
+                    link,
+                )
+                .await
+        } else {
+            self.secrets_manager
+                .fetch_secrets(secret_names, entity_jwt, &self.host_token.jwt, application)
+                .await
+        }
+        .context("Unable to fetch requested secrets")?;
+
+        Ok((config, secrets))
+    }
+
+    /// Drop the secrets fetched for components and providers that are no longer running
+    async fn remove_stopped_secrets(&self) {
+        let mut components: HashSet<String> = self
+            .rollouts
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .values()
+            .filter_map(|Rollout { component, .. }| Some(component.claims()?.id.clone()))
+            .collect();
+        components.extend(
+            self.components
+                .read()
+                .await
+                .values()
+                .filter_map(|component| Some(component.claims()?.id.clone())),
+        );
+        let providers: HashSet<String> = self
+            .providers
+            .read()
+            .await
+            .values()
+            .filter_map(|provider| Some(provider.claims_token.as_ref()?.jwt.clone()))
+            .collect();
+        self.secrets_manager
+            .retain_entities(|entity_jwt| {
+                // Components are identified by the ID of their JWT, like in `handle_secret_rotation`
+                providers.contains(entity_jwt)
+                    || jwt::Claims::<jwt::Component>::decode(entity_jwt)
+                        .is_ok_and(|claims| components.contains(&claims.id))
+            })
+            .await;
+    }
+
+    /// Refresh the secrets of running components and providers after a secrets backend announced
+    /// that a secret was rotated. Components read the new value on their next access of the
+    /// secret, providers receive their own rotated secrets as a configuration update and rotated
+    /// secrets of their links as a put of the link.
+    #[instrument(level = "debug", skip_all)]
+    async fn handle_secret_rotation(&self, msg: &async_nats::Message) -> anyhow::Result<()> {
+        // Only rotate secrets for entities that are still running
+        self.remove_stopped_secrets().await;
+        let rotated = self
             .secrets_manager
-            .fetch_secrets(secret_names, entity_jwt, &self.host_token.jwt, application)
+            .rotate(msg, &self.host_token.jwt)
+            .await?;
+        if rotated.is_empty() {
+            return Ok(());
+        }
+
+        for component in self.components.read().await.values() {
+            let Some(claims) = component.claims() else {
+                continue;
+            };
+            let application = component.annotations.get("wasmcloud.dev/appspec");
+            let mut secrets = component.handler.secrets.write().await;
+            for secret in &rotated {
+                // Secrets are fetched with the JWT of the entity, which is identified by its ID
+                let is_entity = jwt::Claims::<jwt::Component>::decode(&secret.entity_jwt)
+                    .is_ok_and(|entity| entity.id == claims.id);
+                if is_entity && secret.application.as_ref() == application {
+                    debug!(component_id = %component.id, name = secret.name, "refreshing rotated secret");
+                    secrets.insert(secret.name.clone(), secret.value.clone());
+                }
+            }
+        }
+
+        let links: Vec<Link> = self
+            .links
+            .read()
             .await
-            .context("Unable to fetch requested secrets")?;
+            .values()
+            .flatten()
+            .cloned()
+            .collect();
+        for (provider_id, provider) in self.providers.read().await.iter() {
+            let Some(ref claims_token) = provider.claims_token else {
+                continue;
+            };
+            let application = provider.annotations.get("wasmcloud.dev/appspec");
+            let provider_rotated: Vec<_> = rotated
+                .iter()
+                .filter(|secret| {
+                    secret.entity_jwt == claims_token.jwt
+                        && secret.application.as_ref() == application
+                })
+                .collect();
 
-        Ok((config, secrets))
+            // Secrets of links are passed to the provider by putting the links again
+            let rotated_links: HashSet<&SecretLink> = provider_rotated
+                .iter()
+                .filter_map(|secret| secret.link.as_ref())
+                .collect();
+            for link in links
+                .iter()
+                .filter(|link| rotated_links.contains(&SecretLink::from(*link)))
+            {
+                debug!(
+                    provider_id,
+                    source_id = link.source_id(),
+                    target = link.target(),
+                    link_name = link.name(),
+                    "sending link with rotated secrets to provider"
+                );
+                if let Err(err) = self.put_provider_link_secrets(provider, link).await {
+                    error!(%err, provider_id, "failed to publish link with rotated secrets to provider");
+                }
+            }
+
+            // NOTE(brooksmtownsend): This trait import is used here to ensure we're only exposing secret
+            // values when we need them.
+            use secrecy::ExposeSecret;
+            let secrets: HashMap<String, wasmcloud_core::secrets::SecretValue> = provider_rotated
+                .into_iter()
+                .filter(|secret| secret.link.is_none())
+                .map(|secret| match secret.value.expose_secret() {
+                    SecretValue::String(s) => (
+                        secret.name.clone(),
+                        wasmcloud_core::secrets::SecretValue::String(s.to_owned()),
+                    ),
+                    SecretValue::Bytes(b) => (
+                        secret.name.clone(),
+                        wasmcloud_core::secrets::SecretValue::Bytes(b.to_owned()),
+                    ),
+                })
+                .collect();
+            if secrets.is_empty() {
+                continue;
+            }
+            debug!(provider_id, "sending rotated secrets to provider");
+            let payload = serde_json::to_vec(&secrets)
+                .map(|secrets| self.secrets_xkey.seal(&secrets, &provider.xkey))
+                .context("failed to serialize and encrypt rotated secrets")??;
+            let mut headers = async_nats::HeaderMap::new();
+            headers.insert(wasmcloud_core::secrets::SECRETS_UPDATE_HEADER, "true");
+            if let Err(err) = self
+                .rpc_nats
+                .publish_with_headers(
+                    provider_config_update_subject(&self.host_config.lattice, provider_id),
+                    headers,
+                    payload.into(),
+                )
+                .await
+            {
+                error!(%err, provider_id, "failed to publish rotated secrets to provider");
+            }
+        }
+        Ok(())
     }
 
     /// Validates that the provided configuration names exist in the store and are valid.
@@ -2444,11 +3220,22 @@ impl Host {
         application: Option<&String>,
         provider_xkey: &XKey,
     ) -> anyhow::Result<wasmcloud_core::InterfaceLinkDefinition> {
+        let secret_link = SecretLink::from(&link);
         let (source_bundle, raw_source_secrets) = self
-            .fetch_config_and_secrets(link.source_config().as_slice(), provider_jwt, application)
+            .fetch_config_and_secrets_for(
+                link.source_config().as_slice(),
+                provider_jwt,
+                application,
+                Some(&secret_link),
+            )
             .await?;
         let (target_bundle, raw_target_secrets) = self
-            .fetch_config_and_secrets(link.target_config().as_slice(), provider_jwt, application)
+            .fetch_config_and_secrets_for(
+                link.target_config().as_slice(),
+                provider_jwt,
+                application,
+                Some(&secret_link),
+            )
             .await?;
 
         let source_config = source_bundle.get_config().await;
@@ -2577,8 +3364,144 @@ fn injector_to_headers(injector: &TraceContextInjector) -> async_nats::header::H
         .collect()
 }
 
+/// A snapshot of the capabilities and resources of a host, that auction requirements are checked
+/// against
+struct AuctionCapacity {
+    experimental_features: Features,
+    running_providers: HashSet<String>,
+    /// Component instances already reserved by running components
+    reserved_instances: usize,
+    max_instances: usize,
+    max_linear_memory: u64,
+    /// Memory available on the host in bytes. Only used if memory is required
+    available_memory: u64,
+    /// Memory in bytes that running components may still allocate, which is not available to
+    /// new instances
+    reserved_memory: u64,
+}
+
+impl AuctionCapacity {
+    /// Check whether the host satisfies the given requirements, returning the first unmet one
+    fn check(&self, requirements: &AuctionRequirements) -> anyhow::Result<()> {
+        if let Some(arch) = requirements.arch() {
+            ensure!(
+                arch == ARCH,
+                "host architecture `{ARCH}` does not match `{arch}`"
+            );
+        }
+        for name in requirements.features() {
+            let feature = Features::from(name.as_str());
+            ensure!(
+                feature != Features::new() && self.experimental_features.contains(feature),
+                "feature `{name}` is not enabled"
+            );
+        }
+        for provider_id in requirements.providers() {
+            ensure!(
+                self.running_providers.contains(provider_id),
+                "provider `{provider_id}` is not running"
+            );
+        }
+
+        if let Some(instances) = requirements.instances() {
+            let reserved = self.reserved_instances;
+            let capacity = self.max_instances;
+            ensure!(
+                reserved.saturating_add(instances.try_into().unwrap_or(usize::MAX)) <= capacity,
+                "{instances} instances exceed the remaining capacity of {} instances",
+                capacity.saturating_sub(reserved)
+            );
+        }
+
+        if let Some(memory_bytes) = requirements.memory_bytes() {
+            ensure!(
+                memory_bytes <= self.max_linear_memory,
+                "{memory_bytes} bytes of linear memory exceed the maximum of {} bytes per instance",
+                self.max_linear_memory
+            );
+            let required =
+                memory_bytes.saturating_mul(requirements.instances().unwrap_or(1).into());
+            let available = self.available_memory.saturating_sub(self.reserved_memory);
+            ensure!(
+                required <= available,
+                "{required} bytes of linear memory exceed the {available} bytes available on the host after reservations of running components"
+            );
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use std::collections::HashSet;
+    use std::env::consts::ARCH;
+
+    use wasmcloud_control_interface::AuctionRequirements;
+
+    use super::experimental::Features;
+    use super::AuctionCapacity;
+
+    fn capacity() -> AuctionCapacity {
+        AuctionCapacity {
+            experimental_features: Features::new().enable_builtin_http_server(),
+            running_providers: HashSet::from(["http-server".to_string()]),
+            reserved_instances: 80,
+            max_instances: 100,
+            max_linear_memory: 10 * 1024 * 1024,
+            available_memory: 96 * 1024 * 1024,
+            reserved_memory: 32 * 1024 * 1024,
+        }
+    }
+
+    #[test]
+    fn auction_requirements_match() {
+        let capacity = capacity();
+        capacity
+            .check(&AuctionRequirements::default())
+            .expect("no requirements should match");
+        capacity
+            .check(
+                &AuctionRequirements::builder()
+                    .arch(ARCH.to_string())
+                    .features(vec!["builtin-http-server".into()])
+                    .providers(vec!["http-server".into()])
+                    .instances(20)
+                    .memory_bytes(2 * 1024 * 1024)
+                    .build()
+                    .expect("failed to build requirements"),
+            )
+            .expect("requirements within the host capacity should match");
+    }
+
+    #[test]
+    fn auction_requirements_mismatch() {
+        let capacity = capacity();
+        for requirements in [
+            AuctionRequirements::builder().arch("not-an-arch".into()),
+            AuctionRequirements::builder().features(vec!["builtin-messaging-nats".into()]),
+            AuctionRequirements::builder().features(vec!["unknown-feature".into()]),
+            AuctionRequirements::builder().providers(vec!["kv-redis".into()]),
+            // Only 20 instances remain
+            AuctionRequirements::builder().instances(21),
+            // Above the per-instance maximum
+            AuctionRequirements::builder().memory_bytes(11 * 1024 * 1024),
+            // Within the per-instance maximum, but not available for all instances
+            AuctionRequirements::builder()
+                .memory_bytes(5 * 1024 * 1024)
+                .instances(20),
+            // Available on the host, but reserved by running components
+            AuctionRequirements::builder()
+                .memory_bytes(7 * 1024 * 1024)
+                .instances(10),
+        ] {
+            let requirements = requirements.build().expect("failed to build requirements");
+            assert!(
+                capacity.check(&requirements).is_err(),
+                "{requirements:?} should not match"
+            );
+        }
+    }
+
     // Ensure that the helper function to translate a list of links into a map of imports works as expected
     #[test]
     fn can_compute_component_links() {
@@ -2,12 +2,12 @@
 //!
 //! The root of this module includes functionality for running and managing provider binaries. The
 //! submodules contain builtin implementations of wasmCloud capabilities providers.
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::env;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use anyhow::{bail, Context as _};
@@ -18,15 +18,18 @@ use bytes::Bytes;
 use cloudevents::EventBuilderV10;
 use futures::{stream, Future, StreamExt};
 use nkeys::XKey;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process;
 use tokio::sync::RwLock;
-use tokio::task::JoinSet;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::Instant;
 use tracing::{error, instrument, trace, warn};
 use uuid::Uuid;
 use wascap::jwt::{CapabilityProvider, Token};
+use wasmcloud_control_interface::ProviderRestartPolicy;
 use wasmcloud_core::{provider_config_update_subject, HealthCheckResponse, HostData, OtelConfig};
 use wasmcloud_runtime::capability::secrets::store::SecretValue;
+use wasmcloud_runtime::ProviderComponent;
 use wasmcloud_tracing::context::TraceContextInjector;
 
 use crate::jwt;
@@ -37,6 +40,14 @@ use super::Host;
 
 mod http_server;
 mod messaging_nats;
+mod sandbox;
+mod supervisor;
+
+use sandbox::{Sandbox, SandboxConfig};
+use supervisor::{supervise, ProviderExit, ProviderHost, Supervised};
+
+/// The number of lines of a provider's stderr output that are kept to report when it crashes
+const STDERR_TAIL_LINES: usize = 50;
 
 /// An Provider instance
 #[derive(Debug)]
@@ -50,6 +61,60 @@ pub(crate) struct Provider {
     pub(crate) shutdown: Arc<AtomicBool>,
     /// Tasks running the provider, health check, and config watcher
     pub(crate) tasks: JoinSet<()>,
+    /// State of the provider process, which is never running for builtin providers as they run
+    /// within the host process
+    pub(crate) process: Arc<ProviderProcess>,
+    /// When the provider was started
+    pub(crate) started_at: Instant,
+}
+
+/// State of a provider process shared between the host and the task supervising it
+#[derive(Debug, Default)]
+pub(crate) struct ProviderProcess {
+    /// ID of the provider process, `0` if it is not running
+    pub(crate) id: AtomicU32,
+    /// The number of times the provider process was restarted
+    pub(crate) restarts: AtomicU32,
+    /// The last lines the provider process wrote to stderr
+    stderr: Mutex<VecDeque<String>>,
+}
+
+impl ProviderProcess {
+    /// Forward the stderr output of `child` to the host's stderr, keeping the last lines to be
+    /// reported if the provider crashes
+    fn capture_stderr(self: &Arc<Self>, child: &mut process::Child) -> Option<JoinHandle<()>> {
+        let stderr = child.stderr.take()?;
+        let process = Arc::clone(self);
+        Some(tokio::spawn(async move {
+            let mut stderr = BufReader::new(stderr);
+            let mut host_stderr = tokio::io::stderr();
+            let mut line = Vec::new();
+            while let Ok(n) = stderr.read_until(b'\n', &mut line).await {
+                if n == 0 {
+                    break;
+                }
+                let _ = host_stderr.write_all(&line).await;
+                let mut tail = process
+                    .stderr
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner);
+                if tail.len() == STDERR_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(String::from_utf8_lossy(&line).trim_end().to_string());
+                line.clear();
+            }
+        }))
+    }
+
+    /// Take the last lines the provider process wrote to stderr
+    fn take_stderr(&self) -> Vec<String> {
+        self.stderr
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .drain(..)
+            .collect()
+    }
 }
 
 impl Host {
@@ -211,6 +276,8 @@ impl Host {
         claims_token: Option<Token<CapabilityProvider>>,
         annotations: BTreeMap<String, String>,
         shutdown: Arc<AtomicBool>,
+        process: Arc<ProviderProcess>,
+        restart_policy: ProviderRestartPolicy,
     ) -> anyhow::Result<JoinSet<()>> {
         trace!("spawn provider process");
 
@@ -230,6 +297,8 @@ impl Host {
                     claims_token,
                     annotations,
                     shutdown.clone(),
+                    process,
+                    restart_policy,
                 )
                 .await?,
         );
@@ -247,7 +316,8 @@ impl Host {
         Ok(tasks)
     }
 
-    /// Run and supervise a binary provider, restarting it if it exits prematurely.
+    /// Run and supervise a binary provider, restarting it according to `restart_policy` if it exits
+    /// prematurely.
     #[allow(clippy::too_many_arguments)]
     async fn run_provider(
         self: Arc<Self>,
@@ -260,123 +330,309 @@ impl Host {
         claims_token: Option<Token<CapabilityProvider>>,
         annotations: BTreeMap<String, String>,
         shutdown: Arc<AtomicBool>,
+        process: Arc<ProviderProcess>,
+        restart_policy: ProviderRestartPolicy,
     ) -> anyhow::Result<impl Future<Output = ()>> {
         let host_data =
             serde_json::to_vec(&host_data).context("failed to serialize provider data")?;
+        let sandbox = SandboxConfig::from_annotations(&annotations)
+            .context("invalid provider sandbox configuration")?
+            .map(|config| Sandbox::new(&provider_id, &config))
+            .transpose()
+            .context("failed to set up provider sandbox")?;
 
         // If there's any issues starting the provider, we want to exit immediately
-        let child = Arc::new(RwLock::new(
-            provider_command(&path, host_data)
-                .await
-                .context("failed to configure binary provider command")?,
-        ));
-        let lattice = Arc::clone(&self.host_config.lattice);
+        let mut child = provider_command(&path, host_data, sandbox.as_ref())
+            .await
+            .context("failed to configure binary provider command")?;
+        process
+            .id
+            .store(child.id().unwrap_or_default(), Ordering::Relaxed);
+        let stderr_task = process.capture_stderr(&mut child);
+        let provider = BinaryProvider {
+            path,
+            sandbox,
+            child,
+            stderr_task,
+            process: Arc::clone(&process),
+        };
+        let host_data = ProviderHostData::new(
+            self,
+            provider_id.clone(),
+            provider_xkey,
+            config_names,
+            claims_token,
+            annotations,
+            config_bundle,
+        );
         Ok(async move {
-            // Use a JoinSet to manage the config watcher task so that
-            // it can be cancelled on drop and replaced with new config
-            // when a provider restarts
-            let mut config_task = JoinSet::new();
-            config_task.spawn(watch_config(
-                Arc::clone(&self.rpc_nats),
-                Arc::clone(&config_bundle),
-                Arc::clone(&lattice),
-                provider_id.clone(),
-            ));
-            loop {
-                let mut child = child.write().await;
-                match child.wait().await {
-                    Ok(status) => {
-                        // When the provider is shutting down, don't restart it
-                        if shutdown.load(Ordering::Relaxed) {
-                            trace!(
-                                path = ?path.display(),
-                                status = ?status,
-                                "provider exited but will not be restarted since it's shutting down",
-                            );
-                            // Avoid a hot loop by waiting 1s before checking the status again
-                            tokio::time::sleep(Duration::from_secs(1)).await;
-                            continue;
-                        }
+            supervise(
+                provider,
+                host_data,
+                &provider_id,
+                &restart_policy,
+                &shutdown,
+                &process,
+            )
+            .await;
+        })
+    }
 
-                        warn!(
-                            path = ?path.display(),
-                            status = ?status,
-                            "restarting provider that exited while being supervised",
-                        );
-
-                        let (host_data, new_config_bundle) = match self
-                            .prepare_provider_config(
-                                &config_names,
-                                claims_token.as_ref(),
-                                &provider_id,
-                                &provider_xkey,
-                                &annotations,
-                            )
-                            .await
-                            .map(|(host_data, config)| {
-                                (
-                                    serde_json::to_vec(&host_data)
-                                        .context("failed to serialize provider data"),
-                                    Arc::new(RwLock::new(config)),
-                                )
-                            }) {
-                            Ok((Ok(host_data), new_config_bundle)) => {
-                                (host_data, new_config_bundle)
-                            }
-                            Err(e) => {
-                                error!(err = ?e, "failed to prepare provider host data while restarting");
-                                shutdown.store(true, Ordering::Relaxed);
-                                return;
-                            }
-                            Ok((Err(e), _)) => {
-                                error!(err = ?e, "failed to serialize provider host data while restarting");
-                                shutdown.store(true, Ordering::Relaxed);
-                                return;
-                            }
-                        };
-
-                        // Stop the config watcher and start a new one with the new config bundle
-                        config_task.abort_all();
-                        config_task.spawn(watch_config(
-                            Arc::clone(&self.rpc_nats),
-                            new_config_bundle,
-                            Arc::clone(&lattice),
-                            provider_id.clone(),
-                        ));
-
-                        // Restart the provider by attempting to re-execute the binary with the same
-                        // host data
-                        let Ok(child_cmd) = provider_command(&path, host_data).await else {
-                            error!(path = ?path.display(), "failed to restart provider");
-                            shutdown.store(true, Ordering::Relaxed);
-                            return;
-                        };
-                        *child = child_cmd;
-
-                        // To avoid a tight loop, we wait 5 seconds after restarting. In the worst case,
-                        // the provider will continually execute and exit every 5 seconds.
-                        tokio::time::sleep(Duration::from_secs(5)).await;
-                    }
-                    Err(e) => {
-                        error!(
-                            path = ?path.display(),
-                            err = ?e,
-                            "failed to wait for provider to execute",
-                        );
-
-                        shutdown.store(true, Ordering::Relaxed);
-                        return;
-                    }
-                }
+    /// Start a provider implemented as a Wasm component
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn start_component_provider(
+        self: Arc<Self>,
+        wasm: &[u8],
+        host_data: HostData,
+        config: Arc<RwLock<ConfigBundle>>,
+        provider_xkey: XKey,
+        provider_id: &str,
+        config_names: Vec<String>,
+        claims_token: Option<Token<CapabilityProvider>>,
+        annotations: BTreeMap<String, String>,
+        shutdown: Arc<AtomicBool>,
+        process: Arc<ProviderProcess>,
+        restart_policy: ProviderRestartPolicy,
+    ) -> anyhow::Result<JoinSet<()>> {
+        trace!("compile provider component");
+
+        let component = ProviderComponent::new(&self.runtime, wasm)
+            .context("failed to compile provider component")?;
+        let host_data =
+            serde_json::to_vec(&host_data).context("failed to serialize provider data")?;
+
+        let mut tasks = JoinSet::new();
+
+        // Proxy RUST_LOG to (Rust) providers, so they can use the same module-level directives
+        let env = env::var("RUST_LOG")
+            .map(|rust_log| ("RUST_LOG".to_string(), rust_log))
+            .into_iter()
+            .collect();
+        let provider = ComponentProvider {
+            component,
+            provider_id: provider_id.to_string(),
+            host_data,
+            env,
+        };
+        let host_data = ProviderHostData::new(
+            Arc::clone(&self),
+            provider_id.to_string(),
+            provider_xkey,
+            config_names,
+            claims_token,
+            annotations,
+            config,
+        );
+        // Spawn a task running the component, which is restarted according to the restart
+        // policy just like a binary provider process
+        let provider_id_owned = provider_id.to_string();
+        tasks.spawn(async move {
+            supervise(
+                provider,
+                host_data,
+                &provider_id_owned,
+                &restart_policy,
+                &shutdown,
+                &process,
+            )
+            .await;
+        });
+
+        // Spawn a task to check the health of the provider every 30 seconds
+        tasks.spawn(check_health(
+            Arc::clone(&self.rpc_nats),
+            self.ctl_nats.clone(),
+            self.event_builder.clone(),
+            Arc::clone(&self.host_config.lattice),
+            self.host_key.public_key(),
+            provider_id.to_string(),
+        ));
+
+        Ok(tasks)
+    }
+}
+
+/// Prepares the host data of a provider when it is restarted, keeping a task watching the latest
+/// configuration of the provider
+struct ProviderHostData {
+    host: Arc<Host>,
+    provider_id: String,
+    provider_xkey: XKey,
+    config_names: Vec<String>,
+    claims_token: Option<Token<CapabilityProvider>>,
+    annotations: BTreeMap<String, String>,
+    /// Task watching the configuration of the provider, replaced with a new one when the
+    /// provider restarts
+    config_task: JoinSet<()>,
+}
+
+impl ProviderHostData {
+    fn new(
+        host: Arc<Host>,
+        provider_id: String,
+        provider_xkey: XKey,
+        config_names: Vec<String>,
+        claims_token: Option<Token<CapabilityProvider>>,
+        annotations: BTreeMap<String, String>,
+        config_bundle: Arc<RwLock<ConfigBundle>>,
+    ) -> Self {
+        let mut config_task = JoinSet::new();
+        config_task.spawn(watch_config(
+            Arc::clone(&host.rpc_nats),
+            config_bundle,
+            Arc::clone(&host.host_config.lattice),
+            provider_id.clone(),
+        ));
+        Self {
+            host,
+            provider_id,
+            provider_xkey,
+            config_names,
+            claims_token,
+            annotations,
+            config_task,
+        }
+    }
+}
+
+impl ProviderHost for ProviderHostData {
+    async fn host_data(&mut self) -> anyhow::Result<Vec<u8>> {
+        let (host_data, config) = self
+            .host
+            .prepare_provider_config(
+                &self.config_names,
+                self.claims_token.as_ref(),
+                &self.provider_id,
+                &self.provider_xkey,
+                &self.annotations,
+            )
+            .await?;
+        let host_data =
+            serde_json::to_vec(&host_data).context("failed to serialize provider data")?;
+
+        // Stop the config watcher and start a new one with the new config bundle
+        self.config_task.abort_all();
+        self.config_task.spawn(watch_config(
+            Arc::clone(&self.host.rpc_nats),
+            Arc::new(RwLock::new(config)),
+            Arc::clone(&self.host.host_config.lattice),
+            self.provider_id.clone(),
+        ));
+        Ok(host_data)
+    }
+
+    async fn crashed(
+        &mut self,
+        exit: &ProviderExit,
+        stderr: Vec<String>,
+        restarts: u32,
+        restarting: bool,
+    ) {
+        if let Err(e) = self
+            .host
+            .publish_event(
+                "provider_crashed",
+                event::provider_crashed(
+                    self.host.host_key.public_key(),
+                    &self.provider_id,
+                    exit.code,
+                    &exit.status,
+                    stderr,
+                    restarts,
+                    restarting,
+                ),
+            )
+            .await
+        {
+            warn!(
+                ?e,
+                provider_id = self.provider_id,
+                "failed to publish provider crashed event"
+            );
+        }
+    }
+}
+
+/// A provider binary running as a child process of the host
+struct BinaryProvider {
+    path: PathBuf,
+    sandbox: Option<Sandbox>,
+    child: process::Child,
+    stderr_task: Option<JoinHandle<()>>,
+    process: Arc<ProviderProcess>,
+}
+
+impl Supervised for BinaryProvider {
+    async fn wait(&mut self) -> anyhow::Result<ProviderExit> {
+        let status =
+            self.child.wait().await.with_context(|| {
+                format!("failed to wait for provider [{}]", self.path.display())
+            })?;
+        self.process.id.store(0, Ordering::Relaxed);
+        if let Some(stderr_task) = self.stderr_task.take() {
+            // Give the provider's remaining stderr output a chance to be read
+            let _ = tokio::time::timeout(Duration::from_secs(1), stderr_task).await;
+        }
+        trace!(path = ?self.path.display(), ?status, "provider process exited");
+        Ok(status.into())
+    }
+
+    async fn restart(&mut self, host_data: Vec<u8>) -> anyhow::Result<()> {
+        // Restart the provider by re-executing the binary with the new host data
+        let mut child = provider_command(&self.path, host_data, self.sandbox.as_ref())
+            .await
+            .with_context(|| format!("failed to restart provider [{}]", self.path.display()))?;
+        self.process
+            .id
+            .store(child.id().unwrap_or_default(), Ordering::Relaxed);
+        self.stderr_task = self.process.capture_stderr(&mut child);
+        self.child = child;
+        Ok(())
+    }
+}
+
+/// A provider implemented as a Wasm component, which runs within the host while it is waited on
+struct ComponentProvider {
+    component: ProviderComponent,
+    provider_id: String,
+    host_data: Vec<u8>,
+    env: Vec<(String, String)>,
+}
+
+impl Supervised for ComponentProvider {
+    async fn wait(&mut self) -> anyhow::Result<ProviderExit> {
+        let mut stdin = STANDARD.encode(&self.host_data).into_bytes();
+        stdin.extend_from_slice(b"\r\n");
+        let (success, status) = match self.component.run(stdin, &self.env).await {
+            Ok(true) => (true, "provider component returned successfully".to_string()),
+            Ok(false) => (false, "provider component returned an error".to_string()),
+            Err(e) => {
+                warn!(err = ?e, provider_id = self.provider_id, "provider component failed");
+                (false, format!("provider component failed: {e:#}"))
             }
+        };
+        Ok(ProviderExit {
+            success,
+            code: None,
+            status,
         })
     }
+
+    async fn restart(&mut self, host_data: Vec<u8>) -> anyhow::Result<()> {
+        self.host_data = host_data;
+        Ok(())
+    }
 }
 
-/// Using the provided path as the provider binary, start the provider process and
-/// pass the host data to it over stdin. Returns the child process handle which
+/// Using the provided path as the provider binary, start the provider process, within `sandbox` if
+/// set, and pass the host data to it over stdin. Returns the child process handle which
 /// has already been spawned.
-async fn provider_command(path: &Path, host_data: Vec<u8>) -> anyhow::Result<process::Child> {
+async fn provider_command(
+    path: &Path,
+    host_data: Vec<u8>,
+    sandbox: Option<&Sandbox>,
+) -> anyhow::Result<process::Child> {
     let mut child_cmd = process::Command::new(path);
     // Prevent the provider from inheriting the host's environment, with the exception of
     // the following variables we manually add back
@@ -395,8 +651,14 @@ async fn provider_command(path: &Path, host_data: Vec<u8>) -> anyhow::Result<pro
         let _ = child_cmd.env("RUST_LOG", rust_log);
     }
 
+    // Sandboxed providers don't receive any of the variables above
+    if let Some(sandbox) = sandbox {
+        sandbox.apply(&mut child_cmd);
+    }
+
     let mut child = child_cmd
         .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
         .kill_on_drop(true)
         .spawn()
         .context("failed to spawn provider process")?;
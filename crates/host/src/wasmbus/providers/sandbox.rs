@@ -0,0 +1,499 @@
+//! Opt-in isolation of native provider processes on Linux.
+//!
+//! A sandbox is requested per provider by setting the [`SANDBOX_ANNOTATION`] annotation on a
+//! [`StartProviderCommand`](wasmcloud_control_interface::StartProviderCommand) to `true`. A
+//! sandboxed provider:
+//!
+//! - starts with an empty environment, except for `TMPDIR`
+//! - gets a private temporary directory that is removed once the provider stops
+//! - cannot gain privileges and is denied a set of system calls that providers have no use for
+//! - optionally runs as a separate user and within a cgroup v2 limiting its memory and CPU usage
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context as _};
+use tokio::process;
+
+/// Annotation enabling the sandbox for a provider when set to `true`
+pub(crate) const SANDBOX_ANNOTATION: &str = "wasmcloud.dev/sandbox";
+/// Annotation setting the maximum amount of memory, in bytes, that a sandboxed provider may use
+pub(crate) const SANDBOX_MEMORY_ANNOTATION: &str = "wasmcloud.dev/sandbox-memory-bytes";
+/// Annotation setting the maximum CPU time, in millicores, that a sandboxed provider may use
+pub(crate) const SANDBOX_CPU_ANNOTATION: &str = "wasmcloud.dev/sandbox-cpu-millicores";
+/// Annotation setting the name or numeric ID of the user a sandboxed provider runs as
+pub(crate) const SANDBOX_USER_ANNOTATION: &str = "wasmcloud.dev/sandbox-user";
+/// Annotation disabling system call filtering for a sandboxed provider when set to `false`
+pub(crate) const SANDBOX_SECCOMP_ANNOTATION: &str = "wasmcloud.dev/sandbox-seccomp";
+
+/// Sandbox settings of a provider, parsed from the annotations it was started with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SandboxConfig {
+    memory_bytes: Option<u64>,
+    cpu_millicores: Option<u64>,
+    user: Option<String>,
+    seccomp: bool,
+}
+
+impl SandboxConfig {
+    /// Parse the sandbox settings from provider annotations, returning `None` if the provider
+    /// should not be sandboxed
+    pub(crate) fn from_annotations(
+        annotations: &BTreeMap<String, String>,
+    ) -> anyhow::Result<Option<Self>> {
+        let enabled = annotations
+            .get(SANDBOX_ANNOTATION)
+            .map(|v| parse_bool(SANDBOX_ANNOTATION, v))
+            .transpose()?
+            .unwrap_or_default();
+        if !enabled {
+            if let Some(key) = annotations
+                .keys()
+                .find(|key| key.starts_with(SANDBOX_ANNOTATION) && *key != SANDBOX_ANNOTATION)
+            {
+                bail!("annotation `{key}` requires `{SANDBOX_ANNOTATION}` to be set to `true`");
+            }
+            return Ok(None);
+        }
+        let parse_u64 = |key: &str| {
+            annotations
+                .get(key)
+                .map(|v| {
+                    v.parse::<u64>()
+                        .with_context(|| format!("invalid value `{v}` for annotation `{key}`"))
+                })
+                .transpose()
+        };
+        let memory_bytes = parse_u64(SANDBOX_MEMORY_ANNOTATION)?;
+        let cpu_millicores = parse_u64(SANDBOX_CPU_ANNOTATION)?;
+        if memory_bytes == Some(0) || cpu_millicores == Some(0) {
+            bail!("sandbox memory and CPU limits must be greater than zero");
+        }
+        let seccomp = annotations
+            .get(SANDBOX_SECCOMP_ANNOTATION)
+            .map(|v| parse_bool(SANDBOX_SECCOMP_ANNOTATION, v))
+            .transpose()?
+            .unwrap_or(true);
+        Ok(Some(Self {
+            memory_bytes,
+            cpu_millicores,
+            user: annotations.get(SANDBOX_USER_ANNOTATION).cloned(),
+            seccomp,
+        }))
+    }
+}
+
+fn parse_bool(key: &str, value: &str) -> anyhow::Result<bool> {
+    value
+        .parse()
+        .with_context(|| format!("invalid value `{value}` for annotation `{key}`"))
+}
+
+/// Resources set up to isolate a provider process, which are released when dropped.
+///
+/// A single sandbox is used for every restart of a provider.
+#[derive(Debug)]
+pub(crate) struct Sandbox {
+    resources: SandboxResources,
+    #[cfg(target_os = "linux")]
+    inner: linux::Isolation,
+}
+
+/// Files and directories created for a sandbox, which are removed when dropped, including when
+/// setting up the rest of the sandbox fails
+#[derive(Debug)]
+struct SandboxResources {
+    /// Private temporary directory of the provider
+    tmp_dir: PathBuf,
+    /// The cgroup the provider runs in, if its resources are limited
+    cgroup: Option<PathBuf>,
+}
+
+impl Sandbox {
+    /// Set up a sandbox for the provider identified by `provider_id`
+    #[cfg(target_os = "linux")]
+    pub(crate) fn new(provider_id: &str, config: &SandboxConfig) -> anyhow::Result<Self> {
+        let user = config.user.as_deref().map(linux::lookup_user).transpose()?;
+        let mut resources = SandboxResources {
+            tmp_dir: linux::create_tmp_dir(provider_id, user)?,
+            cgroup: None,
+        };
+        if config.memory_bytes.is_some() || config.cpu_millicores.is_some() {
+            resources.cgroup = Some(linux::create_cgroup(
+                provider_id,
+                config.memory_bytes,
+                config.cpu_millicores,
+            )?);
+        }
+        let inner = linux::Isolation::new(resources.cgroup.as_deref(), user, config.seccomp)?;
+        Ok(Self { resources, inner })
+    }
+
+    /// Set up a sandbox for the provider identified by `provider_id`
+    #[cfg(not(target_os = "linux"))]
+    pub(crate) fn new(_provider_id: &str, _config: &SandboxConfig) -> anyhow::Result<Self> {
+        bail!("provider sandboxing is only supported on Linux")
+    }
+
+    /// Configure `cmd` to start the provider within the sandbox
+    pub(crate) fn apply(&self, cmd: &mut process::Command) {
+        cmd.env_clear();
+        cmd.env("TMPDIR", &self.resources.tmp_dir);
+        #[cfg(target_os = "linux")]
+        self.inner.apply(cmd);
+    }
+}
+
+impl Drop for SandboxResources {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.tmp_dir) {
+            tracing::debug!(?e, tmp_dir = ?self.tmp_dir, "failed to remove provider temporary directory");
+        }
+        if let Some(cgroup) = &self.cgroup {
+            // Kill any process left in the cgroup, since it can only be removed once empty
+            let _ = std::fs::write(cgroup.join("cgroup.kill"), "1");
+            if let Err(e) = std::fs::remove_dir(cgroup) {
+                tracing::debug!(?e, ?cgroup, "failed to remove provider cgroup");
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::ffi::CString;
+    use std::fs::{self, DirBuilder};
+    use std::io;
+    use std::os::unix::fs::DirBuilderExt as _;
+    use std::path::{Path, PathBuf};
+
+    use anyhow::{bail, Context as _};
+    use nix::unistd::User;
+    use tokio::process;
+    use uuid::Uuid;
+
+    /// The cgroup v2 hierarchy mount point
+    const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+    /// The period, in microseconds, over which the CPU limit of a provider is enforced
+    const CPU_PERIOD_US: u64 = 100_000;
+
+    #[cfg(target_arch = "x86_64")]
+    const AUDIT_ARCH: Option<u32> = Some(0xC000_003E);
+    #[cfg(target_arch = "aarch64")]
+    const AUDIT_ARCH: Option<u32> = Some(0xC000_00B7);
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    const AUDIT_ARCH: Option<u32> = None;
+
+    /// System calls that sandboxed providers are denied, as they are only useful to administer
+    /// the system or to escape the sandbox
+    const DENIED_SYSCALLS: &[libc::c_long] = &[
+        libc::SYS_acct,
+        libc::SYS_add_key,
+        libc::SYS_bpf,
+        libc::SYS_chroot,
+        libc::SYS_clock_settime,
+        libc::SYS_delete_module,
+        libc::SYS_finit_module,
+        libc::SYS_init_module,
+        libc::SYS_kexec_load,
+        libc::SYS_keyctl,
+        libc::SYS_mount,
+        libc::SYS_open_by_handle_at,
+        libc::SYS_perf_event_open,
+        libc::SYS_pivot_root,
+        libc::SYS_process_vm_readv,
+        libc::SYS_process_vm_writev,
+        libc::SYS_ptrace,
+        libc::SYS_reboot,
+        libc::SYS_request_key,
+        libc::SYS_setdomainname,
+        libc::SYS_sethostname,
+        libc::SYS_setns,
+        libc::SYS_settimeofday,
+        libc::SYS_swapoff,
+        libc::SYS_swapon,
+        libc::SYS_umount2,
+        libc::SYS_unshare,
+        libc::SYS_userfaultfd,
+    ];
+
+    /// Isolation applied to the provider process between `fork` and `exec`
+    #[derive(Debug)]
+    pub(super) struct Isolation {
+        /// Path of the `cgroup.procs` file of the provider cgroup
+        cgroup_procs: Option<CString>,
+        user: Option<(u32, u32)>,
+        seccomp: Option<Vec<libc::sock_filter>>,
+    }
+
+    impl Isolation {
+        pub(super) fn new(
+            cgroup: Option<&Path>,
+            user: Option<(u32, u32)>,
+            seccomp: bool,
+        ) -> anyhow::Result<Self> {
+            let cgroup_procs = cgroup
+                .map(|cgroup| {
+                    CString::new(
+                        cgroup
+                            .join("cgroup.procs")
+                            .into_os_string()
+                            .into_encoded_bytes(),
+                    )
+                })
+                .transpose()
+                .context("invalid cgroup path")?;
+            let seccomp = seccomp.then(seccomp_filter).transpose()?;
+            Ok(Self {
+                cgroup_procs,
+                user,
+                seccomp,
+            })
+        }
+
+        pub(super) fn apply(&self, cmd: &mut process::Command) {
+            let cgroup_procs = self.cgroup_procs.clone();
+            let user = self.user;
+            let seccomp = self.seccomp.clone();
+            // SAFETY: the closure runs in the forked child and only performs system calls,
+            // without allocating or taking locks
+            unsafe {
+                cmd.pre_exec(move || {
+                    // Join the cgroup before dropping privileges, which may be required to do so
+                    if let Some(cgroup_procs) = &cgroup_procs {
+                        let fd =
+                            libc::open(cgroup_procs.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+                        if fd < 0 {
+                            return Err(io::Error::last_os_error());
+                        }
+                        let n = libc::write(fd, b"0".as_ptr().cast(), 1);
+                        libc::close(fd);
+                        if n != 1 {
+                            return Err(io::Error::last_os_error());
+                        }
+                    }
+                    if let Some((uid, gid)) = user {
+                        if libc::setgroups(0, core::ptr::null()) != 0
+                            || libc::setgid(gid) != 0
+                            || libc::setuid(uid) != 0
+                        {
+                            return Err(io::Error::last_os_error());
+                        }
+                    }
+                    if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                    if let Some(filter) = &seccomp {
+                        let prog = libc::sock_fprog {
+                            len: filter.len() as libc::c_ushort,
+                            filter: filter.as_ptr().cast_mut(),
+                        };
+                        if libc::prctl(
+                            libc::PR_SET_SECCOMP,
+                            libc::SECCOMP_MODE_FILTER,
+                            &prog as *const libc::sock_fprog,
+                        ) != 0
+                        {
+                            return Err(io::Error::last_os_error());
+                        }
+                    }
+                    Ok(())
+                });
+            }
+        }
+    }
+
+    /// Look up a user by name or numeric ID, returning its user and primary group IDs
+    pub(super) fn lookup_user(user: &str) -> anyhow::Result<(u32, u32)> {
+        let found = if let Ok(uid) = user.parse() {
+            User::from_uid(nix::unistd::Uid::from_raw(uid))
+        } else {
+            User::from_name(user)
+        }
+        .with_context(|| format!("failed to look up sandbox user `{user}`"))?
+        .with_context(|| format!("sandbox user `{user}` does not exist"))?;
+        Ok((found.uid.as_raw(), found.gid.as_raw()))
+    }
+
+    /// Create a temporary directory only accessible to the provider
+    pub(super) fn create_tmp_dir(
+        provider_id: &str,
+        user: Option<(u32, u32)>,
+    ) -> anyhow::Result<PathBuf> {
+        let tmp_dir = std::env::temp_dir().join(format!(
+            "wasmcloud-provider-{provider_id}-{}",
+            Uuid::new_v4()
+        ));
+        DirBuilder::new()
+            .mode(0o700)
+            .create(&tmp_dir)
+            .with_context(|| format!("failed to create `{}`", tmp_dir.display()))?;
+        if let Some((uid, gid)) = user {
+            if let Err(e) = std::os::unix::fs::chown(&tmp_dir, Some(uid), Some(gid)) {
+                let _ = fs::remove_dir(&tmp_dir);
+                return Err(e).context("failed to change owner of provider temporary directory");
+            }
+        }
+        Ok(tmp_dir)
+    }
+
+    /// Create a cgroup for the provider below the cgroup of the host, limiting its memory and CPU
+    /// usage.
+    ///
+    /// Controllers can only be enabled for a cgroup that does not contain processes itself, so
+    /// the host must either run in the root cgroup of its cgroup namespace or in a cgroup
+    /// delegated to it that already has the `memory` and `cpu` controllers enabled.
+    pub(super) fn create_cgroup(
+        provider_id: &str,
+        memory_bytes: Option<u64>,
+        cpu_millicores: Option<u64>,
+    ) -> anyhow::Result<PathBuf> {
+        let own = fs::read_to_string("/proc/self/cgroup").context("failed to read host cgroup")?;
+        let Some(own) = own.lines().find_map(|line| line.strip_prefix("0::")) else {
+            bail!("the host is not running in a cgroup v2 hierarchy");
+        };
+        let parent = Path::new(CGROUP_ROOT).join(own.trim_start_matches('/'));
+        let controllers = fs::read_to_string(parent.join("cgroup.subtree_control"))
+            .context("failed to read enabled cgroup controllers")?;
+        let enabled = |name: &str| controllers.split_whitespace().any(|c| c == name);
+        if !enabled("memory") || !enabled("cpu") {
+            fs::write(parent.join("cgroup.subtree_control"), "+memory +cpu").with_context(
+                || {
+                    format!(
+                        "failed to enable memory and CPU controllers for `{}`",
+                        parent.display()
+                    )
+                },
+            )?;
+        }
+
+        let cgroup = parent.join(format!(
+            "wasmcloud-provider-{provider_id}-{}",
+            Uuid::new_v4()
+        ));
+        fs::create_dir(&cgroup)
+            .with_context(|| format!("failed to create cgroup `{}`", cgroup.display()))?;
+        let limits = memory_bytes
+            .map(|bytes| fs::write(cgroup.join("memory.max"), bytes.to_string()))
+            .transpose()
+            .and_then(|_| {
+                cpu_millicores
+                    .map(|millicores| {
+                        let quota = millicores.saturating_mul(CPU_PERIOD_US) / 1000;
+                        fs::write(cgroup.join("cpu.max"), format!("{quota} {CPU_PERIOD_US}"))
+                    })
+                    .transpose()
+            });
+        if let Err(e) = limits {
+            let _ = fs::remove_dir(&cgroup);
+            return Err(e).context("failed to set provider cgroup limits");
+        }
+        Ok(cgroup)
+    }
+
+    /// Build a seccomp filter denying [`DENIED_SYSCALLS`] with `EPERM`
+    fn seccomp_filter() -> anyhow::Result<Vec<libc::sock_filter>> {
+        let Some(arch) = AUDIT_ARCH else {
+            bail!("system call filtering is not supported on this architecture");
+        };
+        // Offsets of the fields of `struct seccomp_data`
+        const NR: u32 = 0;
+        const ARCH: u32 = 4;
+        let stmt = |code: u32, k: u32| libc::sock_filter {
+            code: code as u16,
+            jt: 0,
+            jf: 0,
+            k,
+        };
+        let jump = |code: u32, k: u32, jt: u8, jf: u8| libc::sock_filter {
+            code: code as u16,
+            jt,
+            jf,
+            k,
+        };
+        let deny = stmt(
+            libc::BPF_RET | libc::BPF_K,
+            libc::SECCOMP_RET_ERRNO | libc::EPERM as u32,
+        );
+
+        let mut filter = vec![
+            // Kill processes using a different system call ABI, which the filter does not cover
+            stmt(libc::BPF_LD | libc::BPF_W | libc::BPF_ABS, ARCH),
+            jump(libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K, arch, 1, 0),
+            stmt(libc::BPF_RET | libc::BPF_K, libc::SECCOMP_RET_KILL_PROCESS),
+            stmt(libc::BPF_LD | libc::BPF_W | libc::BPF_ABS, NR),
+        ];
+        if cfg!(target_arch = "x86_64") {
+            // Deny the x32 ABI, which shares the architecture of x86_64
+            filter.push(jump(
+                libc::BPF_JMP | libc::BPF_JGE | libc::BPF_K,
+                0x4000_0000,
+                0,
+                1,
+            ));
+            filter.push(deny);
+        }
+        for nr in DENIED_SYSCALLS {
+            let nr = u32::try_from(*nr).context("invalid system call number")?;
+            filter.push(jump(libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K, nr, 0, 1));
+            filter.push(deny);
+        }
+        filter.push(stmt(libc::BPF_RET | libc::BPF_K, libc::SECCOMP_RET_ALLOW));
+        Ok(filter)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::{SandboxConfig, SandboxResources, SANDBOX_ANNOTATION, SANDBOX_MEMORY_ANNOTATION};
+
+    #[test]
+    fn parses_annotations() {
+        assert_eq!(
+            SandboxConfig::from_annotations(&BTreeMap::default()).expect("failed to parse"),
+            None
+        );
+
+        let annotations = BTreeMap::from([
+            (SANDBOX_ANNOTATION.into(), "true".into()),
+            (SANDBOX_MEMORY_ANNOTATION.into(), "67108864".into()),
+            ("wasmcloud.dev/sandbox-cpu-millicores".into(), "500".into()),
+            ("wasmcloud.dev/sandbox-seccomp".into(), "false".into()),
+        ]);
+        assert_eq!(
+            SandboxConfig::from_annotations(&annotations).expect("failed to parse"),
+            Some(SandboxConfig {
+                memory_bytes: Some(64 * 1024 * 1024),
+                cpu_millicores: Some(500),
+                user: None,
+                seccomp: false,
+            })
+        );
+
+        let annotations = BTreeMap::from([(SANDBOX_MEMORY_ANNOTATION.into(), "1024".into())]);
+        assert!(SandboxConfig::from_annotations(&annotations).is_err());
+
+        let annotations = BTreeMap::from([
+            (SANDBOX_ANNOTATION.into(), "true".into()),
+            (SANDBOX_MEMORY_ANNOTATION.into(), "lots".into()),
+        ]);
+        assert!(SandboxConfig::from_annotations(&annotations).is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn releases_resources_when_dropped() {
+        let tmp_dir = super::linux::create_tmp_dir("releases-resources", None)
+            .expect("failed to create temporary directory");
+        let resources = SandboxResources {
+            tmp_dir: tmp_dir.clone(),
+            cgroup: None,
+        };
+        assert!(tmp_dir.is_dir());
+        // Dropped when setting up the rest of the sandbox fails, as well as with the sandbox
+        drop(resources);
+        assert!(!tmp_dir.exists());
+    }
+}
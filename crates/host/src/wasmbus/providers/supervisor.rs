@@ -0,0 +1,302 @@
+//! Supervision of providers, restarting them according to their restart policy when they exit
+//!
+//! Binary providers and component providers are run differently, but are restarted and have their
+//! crashes reported the same way by [`supervise`].
+
+use std::process::ExitStatus;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tokio::time::Instant;
+use tracing::{error, trace, warn};
+use wasmcloud_control_interface::{ProviderRestartPolicy, RestartMode};
+
+use super::ProviderProcess;
+
+/// How a supervised provider exited
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct ProviderExit {
+    /// Whether the provider exited successfully
+    pub(super) success: bool,
+    /// The exit code of the provider, if it exited with one
+    pub(super) code: Option<i32>,
+    /// A description of how the provider exited
+    pub(super) status: String,
+}
+
+impl From<ExitStatus> for ProviderExit {
+    fn from(status: ExitStatus) -> Self {
+        Self {
+            success: status.success(),
+            code: status.code(),
+            status: status.to_string(),
+        }
+    }
+}
+
+/// A running provider that is supervised by [`supervise`]
+pub(super) trait Supervised {
+    /// Wait for the provider to exit, returning how it exited. Returns an error if the provider
+    /// can no longer be waited on, in which case it is not restarted.
+    async fn wait(&mut self) -> anyhow::Result<ProviderExit>;
+
+    /// Start the provider again after it exited, passing it `host_data`
+    async fn restart(&mut self, host_data: Vec<u8>) -> anyhow::Result<()>;
+}
+
+/// The host of a supervised provider, which prepares the host data passed to it when it is
+/// restarted and is told about its crashes
+pub(super) trait ProviderHost {
+    /// Prepare the serialized host data for a restart of the provider, which includes fetching
+    /// its configuration and secrets again
+    async fn host_data(&mut self) -> anyhow::Result<Vec<u8>>;
+
+    /// Called when the provider exited unsuccessfully, with the last lines it wrote to stderr,
+    /// the number of times it was restarted so far and whether it is going to be restarted
+    async fn crashed(
+        &mut self,
+        exit: &ProviderExit,
+        stderr: Vec<String>,
+        restarts: u32,
+        restarting: bool,
+    );
+}
+
+/// Supervise a running provider, restarting it according to `restart_policy` whenever it exits
+/// with fresh host data from `host`, which is told about every unsuccessful exit.
+///
+/// Returns once the provider exited and is not restarted, either because of its restart policy,
+/// because it failed to restart, or because `shutdown` was set. `shutdown` is set when returning
+/// for any other reason than the provider being stopped.
+pub(super) async fn supervise(
+    mut provider: impl Supervised,
+    mut host: impl ProviderHost,
+    provider_id: &str,
+    restart_policy: &ProviderRestartPolicy,
+    shutdown: &AtomicBool,
+    process: &ProviderProcess,
+) {
+    let mut started_at = Instant::now();
+    // Restarts since the provider last stayed up for longer than the maximum backoff
+    let mut retries = 0;
+    loop {
+        let exit = match provider.wait().await {
+            Ok(exit) => exit,
+            Err(e) => {
+                error!(err = ?e, provider_id, "failed to wait for provider to exit");
+                shutdown.store(true, Ordering::Relaxed);
+                return;
+            }
+        };
+        // When the provider is shutting down, don't restart it
+        if shutdown.load(Ordering::Relaxed) {
+            trace!(
+                provider_id,
+                "provider exited but will not be restarted since it's shutting down"
+            );
+            return;
+        }
+
+        if started_at.elapsed().as_millis() > restart_policy.max_backoff_ms().into() {
+            retries = 0;
+        }
+        let restart = match restart_policy.mode() {
+            RestartMode::Never => false,
+            RestartMode::OnFailure => !exit.success,
+            RestartMode::Always => true,
+        } && restart_policy
+            .max_retries()
+            .map_or(true, |max_retries| retries < max_retries);
+        if !exit.success {
+            host.crashed(
+                &exit,
+                process.take_stderr(),
+                process.restarts.load(Ordering::Relaxed),
+                restart,
+            )
+            .await;
+        }
+        if !restart {
+            warn!(
+                provider_id,
+                success = exit.success,
+                retries,
+                "provider exited and will not be restarted",
+            );
+            shutdown.store(true, Ordering::Relaxed);
+            return;
+        }
+
+        let backoff = restart_policy.backoff(retries);
+        warn!(
+            provider_id,
+            success = exit.success,
+            ?backoff,
+            "restarting provider that exited while being supervised",
+        );
+        tokio::time::sleep(backoff).await;
+        // The provider may have been stopped while waiting to restart it
+        if shutdown.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let restarted = match host.host_data().await {
+            Ok(host_data) => provider.restart(host_data).await,
+            Err(e) => Err(e.context("failed to prepare provider host data")),
+        };
+        if let Err(e) = restarted {
+            error!(err = ?e, provider_id, "failed to restart provider");
+            shutdown.store(true, Ordering::Relaxed);
+            return;
+        }
+        process.restarts.fetch_add(1, Ordering::Relaxed);
+        started_at = Instant::now();
+        retries = retries.saturating_add(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use wasmcloud_control_interface::{ProviderRestartPolicy, RestartMode};
+    use wasmcloud_runtime::{ProviderComponent, Runtime};
+
+    use super::super::{ComponentProvider, ProviderProcess};
+    use super::{supervise, ProviderExit, ProviderHost};
+
+    /// Returns a provider component whose `wasi:cli/run` export runs `body`, which must leave the
+    /// discriminant of the returned `result` on the stack
+    fn provider_component(body: &str) -> ComponentProvider {
+        let wasm = wat::parse_str(format!(
+            r#"
+(component
+  (core module $m
+    (func (export "run") (result i32) {body}))
+  (core instance $i (instantiate $m))
+  (func $run (result (result)) (canon lift (core func $i "run")))
+  (instance $run (export "run" (func $run)))
+  (export "wasi:cli/run@0.2.1" (instance $run)))
+"#
+        ))
+        .expect("failed to parse provider component");
+        let (rt, _epoch) = Runtime::new().expect("failed to construct runtime");
+        ComponentProvider {
+            component: ProviderComponent::new(&rt, &wasm)
+                .expect("failed to compile provider component"),
+            provider_id: "provider".to_string(),
+            host_data: b"{}".to_vec(),
+            env: Vec::new(),
+        }
+    }
+
+    /// Provider host counting how many times the provider was restarted, and recording whether
+    /// the provider was going to be restarted for every crash
+    #[derive(Clone, Default)]
+    struct CountingHost {
+        restarts: Arc<AtomicUsize>,
+        crashes: Arc<Mutex<Vec<bool>>>,
+    }
+
+    impl CountingHost {
+        fn restarts(&self) -> usize {
+            self.restarts.load(Ordering::Relaxed)
+        }
+
+        fn crashes(&self) -> Vec<bool> {
+            self.crashes.lock().expect("crashes lock poisoned").clone()
+        }
+    }
+
+    impl ProviderHost for CountingHost {
+        async fn host_data(&mut self) -> anyhow::Result<Vec<u8>> {
+            self.restarts.fetch_add(1, Ordering::Relaxed);
+            Ok(b"{}".to_vec())
+        }
+
+        async fn crashed(
+            &mut self,
+            exit: &ProviderExit,
+            _stderr: Vec<String>,
+            _restarts: u32,
+            restarting: bool,
+        ) {
+            assert!(!exit.success);
+            self.crashes
+                .lock()
+                .expect("crashes lock poisoned")
+                .push(restarting);
+        }
+    }
+
+    /// Returns a restart policy with short backoffs, which still considers providers crashing
+    /// within a minute to not have recovered
+    fn restart_policy(mode: RestartMode, max_retries: u32) -> ProviderRestartPolicy {
+        ProviderRestartPolicy::builder()
+            .mode(mode)
+            .max_retries(max_retries)
+            .initial_backoff_ms(1)
+            .max_backoff_ms(60_000)
+            .build()
+            .expect("failed to build restart policy")
+    }
+
+    #[tokio::test]
+    async fn component_provider_restarts_after_crash() {
+        let host = CountingHost::default();
+        let shutdown = AtomicBool::new(false);
+        let process = ProviderProcess::default();
+        supervise(
+            provider_component("unreachable"),
+            host.clone(),
+            "provider",
+            &restart_policy(RestartMode::OnFailure, 2),
+            &shutdown,
+            &process,
+        )
+        .await;
+
+        // The provider is restarted until it runs out of retries, then marked as shut down
+        assert_eq!(process.restarts.load(Ordering::Relaxed), 2);
+        assert_eq!(host.restarts(), 2);
+        assert!(shutdown.load(Ordering::Relaxed));
+        // Every crash is reported, the last one as not being restarted
+        assert_eq!(host.crashes(), [true, true, false]);
+    }
+
+    #[tokio::test]
+    async fn component_provider_stops_cleanly() {
+        // A provider exiting successfully is not restarted on failure only
+        let host = CountingHost::default();
+        let shutdown = AtomicBool::new(false);
+        let process = ProviderProcess::default();
+        supervise(
+            provider_component("i32.const 0"),
+            host.clone(),
+            "provider",
+            &restart_policy(RestartMode::OnFailure, 2),
+            &shutdown,
+            &process,
+        )
+        .await;
+        assert_eq!(process.restarts.load(Ordering::Relaxed), 0);
+        assert_eq!(host.restarts(), 0);
+        assert!(shutdown.load(Ordering::Relaxed));
+        // Exiting successfully is not a crash
+        assert!(host.crashes().is_empty());
+
+        // A provider that is being stopped is never restarted
+        let shutdown = AtomicBool::new(true);
+        supervise(
+            provider_component("unreachable"),
+            host.clone(),
+            "provider",
+            &restart_policy(RestartMode::Always, 2),
+            &shutdown,
+            &process,
+        )
+        .await;
+        assert_eq!(process.restarts.load(Ordering::Relaxed), 0);
+        assert_eq!(host.restarts(), 0);
+    }
+}
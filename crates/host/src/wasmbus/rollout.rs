@@ -0,0 +1,165 @@
+//! Splitting of invocations between a running component and a new version of it that is being
+//! rolled out alongside, see [`wasmcloud_control_interface::ComponentRollout`].
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, RwLock};
+
+use async_nats::HeaderMap;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use wasmcloud_control_interface::ComponentRollout;
+use wasmcloud_core::ComponentId;
+
+use super::Component;
+
+/// Size of the buffer of invocations diverted to a new component version for a single export
+const DIVERTED_INVOCATION_CHANNEL_SIZE: usize = 256;
+
+/// An invocation accepted by the wRPC NATS transport that has not yet been handled
+pub(crate) type Invocation = (
+    Option<HeaderMap>,
+    <wrpc_transport_nats::Client as wrpc_transport::Serve>::Outgoing,
+    <wrpc_transport_nats::Client as wrpc_transport::Serve>::Incoming,
+);
+
+/// Rollouts in progress on a host, keyed by the ID of the component being rolled out
+pub(crate) type Rollouts = Arc<RwLock<HashMap<ComponentId, Rollout>>>;
+
+/// A new version of a component that is running alongside the current one
+#[derive(Clone, Debug)]
+pub(crate) struct Rollout {
+    /// The new version of the component
+    pub(crate) component: Arc<Component>,
+    /// Decides which invocations are handled by the new version
+    pub(crate) split: Arc<TrafficSplit>,
+}
+
+/// Diverts invocations received by the current version of a component to a new version.
+///
+/// The current version of the component owns the wRPC subscriptions. For every invocation it
+/// accepts, it asks the [`TrafficSplit`] whether the new version should handle it instead and,
+/// if so, forwards it over a channel that the new version serves from.
+#[derive(Debug)]
+pub(crate) struct TrafficSplit {
+    weight: u64,
+    headers: BTreeMap<String, String>,
+    counter: AtomicU64,
+    targets: Mutex<HashMap<String, mpsc::Sender<Invocation>>>,
+}
+
+impl TrafficSplit {
+    pub(crate) fn new(rollout: &ComponentRollout) -> Self {
+        Self {
+            weight: rollout.weight().min(100).into(),
+            headers: rollout.headers().clone(),
+            counter: AtomicU64::default(),
+            targets: Mutex::default(),
+        }
+    }
+
+    /// Register the new version of the component as able to handle invocations of `func` in
+    /// `instance`, returning the stream of invocations diverted to it
+    pub(crate) fn subscribe(&self, instance: &str, func: &str) -> ReceiverStream<Invocation> {
+        let (tx, rx) = mpsc::channel(DIVERTED_INVOCATION_CHANNEL_SIZE);
+        self.targets
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(format!("{instance}#{func}"), tx);
+        ReceiverStream::new(rx)
+    }
+
+    /// Whether an invocation carrying `headers` should be handled by the new version
+    fn is_diverted(&self, headers: Option<&HeaderMap>) -> bool {
+        if !self.headers.is_empty()
+            && headers.is_some_and(|headers| {
+                self.headers.iter().all(|(name, value)| {
+                    headers
+                        .get(name.as_str())
+                        .is_some_and(|v| v.as_str() == value)
+                })
+            })
+        {
+            return true;
+        }
+        // Counting invocations rather than sampling keeps the split exact over every 100 invocations
+        self.weight > 0 && self.counter.fetch_add(1, Ordering::Relaxed) % 100 < self.weight
+    }
+
+    /// Divert `invocation` to the new version of the component if it should handle it.
+    ///
+    /// Returns the invocation back if it must be handled by the current version, which is also the
+    /// case if the new version does not serve `func` or cannot currently accept invocations.
+    pub(crate) fn divert(
+        &self,
+        instance: &str,
+        func: &str,
+        invocation: Invocation,
+    ) -> Option<Invocation> {
+        if !self.is_diverted(invocation.0.as_ref()) {
+            return Some(invocation);
+        }
+        let targets = self
+            .targets
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let Some(tx) = targets.get(&format!("{instance}#{func}")) else {
+            return Some(invocation);
+        };
+        match tx.try_send(invocation) {
+            Ok(()) => None,
+            Err(
+                mpsc::error::TrySendError::Full(invocation)
+                | mpsc::error::TrySendError::Closed(invocation),
+            ) => Some(invocation),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use async_nats::HeaderMap;
+    use wasmcloud_control_interface::ComponentRollout;
+
+    use super::TrafficSplit;
+
+    #[test]
+    fn splits_by_weight() {
+        let split = TrafficSplit::new(
+            &ComponentRollout::builder()
+                .weight(10)
+                .build()
+                .expect("failed to build rollout"),
+        );
+        let diverted = (0..1000).filter(|_| split.is_diverted(None)).count();
+        assert_eq!(diverted, 100);
+
+        let split = TrafficSplit::new(
+            &ComponentRollout::builder()
+                .build()
+                .expect("failed to build rollout"),
+        );
+        assert!(!(0..100).any(|_| split.is_diverted(None)));
+    }
+
+    #[test]
+    fn splits_by_headers() {
+        let split = TrafficSplit::new(
+            &ComponentRollout::builder()
+                .headers(BTreeMap::from([("x-canary".into(), "true".into())]))
+                .build()
+                .expect("failed to build rollout"),
+        );
+        let mut headers = HeaderMap::new();
+        headers.insert("x-canary", "true");
+        assert!(split.is_diverted(Some(&headers)));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-canary", "false");
+        assert!(!split.is_diverted(Some(&headers)));
+        assert!(!split.is_diverted(None));
+    }
+}
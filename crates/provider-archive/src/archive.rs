@@ -353,31 +353,7 @@ impl ProviderArchive {
             claims,
         });
 
-        let mut header = tokio_tar::Header::new_gnu();
-        header.set_path(CLAIMS_JWT_FILE)?;
-        header.set_size(claims_jwt.len() as u64);
-        header.set_cksum();
-        par.append_data(&mut header, CLAIMS_JWT_FILE, Cursor::new(claims_jwt))
-            .await?;
-
-        if let Some(world) = &self.wit {
-            let mut header = tokio_tar::Header::new_gnu();
-            header.set_path(WIT_WORLD_FILE)?;
-            header.set_size(world.len() as u64);
-            header.set_cksum();
-            par.append_data(&mut header, WIT_WORLD_FILE, Cursor::new(world))
-                .await?;
-        }
-
-        for (tgt, lib) in &self.libraries {
-            let mut header = tokio_tar::Header::new_gnu();
-            let path = format!("{tgt}.bin");
-            header.set_path(&path)?;
-            header.set_size(lib.len() as u64);
-            header.set_cksum();
-            par.append_data(&mut header, &path, Cursor::new(lib))
-                .await?;
-        }
+        append_entries(&mut par, &claims_jwt, self.wit.as_deref(), &self.libraries).await?;
 
         // Completes the process of packing a .par archive
         let mut inner = par.into_inner().await?;
@@ -387,6 +363,65 @@ impl ProviderArchive {
 
         Ok(())
     }
+
+    /// Generates a compressed Provider Archive (PAR) containing only the binary for the given
+    /// target, along with the WIT world and the signed claims of this archive.
+    ///
+    /// The claims are not re-signed and still contain the hashes of every target, so the returned
+    /// archive can be loaded and verified like any other archive. This requires the claims of this
+    /// archive to already be signed, i.e. it must have been written or loaded.
+    pub async fn target_archive(&self, target: &str) -> Result<Vec<u8>> {
+        let token = self
+            .token
+            .as_ref()
+            .ok_or("Provider archive claims have not been signed")?;
+        let library = self
+            .libraries
+            .get_key_value(target)
+            .ok_or_else(|| format!("Target [{target}] not found in provider archive"))?;
+
+        let mut par = tokio_tar::Builder::new(GzipEncoder::with_quality(Vec::new(), Level::Best));
+        append_entries(&mut par, &token.jwt, self.wit.as_deref(), [library]).await?;
+
+        let mut inner = par.into_inner().await?;
+        inner.shutdown().await?;
+        Ok(inner.into_inner())
+    }
+}
+
+/// Appends the claims, WIT world and libraries of a provider archive to a tar builder
+async fn append_entries<'a, W: AsyncWrite + Send + Unpin>(
+    par: &mut tokio_tar::Builder<W>,
+    claims_jwt: &str,
+    wit: Option<&[u8]>,
+    libraries: impl IntoIterator<Item = (&'a String, &'a Vec<u8>)>,
+) -> Result<()> {
+    let mut header = tokio_tar::Header::new_gnu();
+    header.set_path(CLAIMS_JWT_FILE)?;
+    header.set_size(claims_jwt.len() as u64);
+    header.set_cksum();
+    par.append_data(&mut header, CLAIMS_JWT_FILE, Cursor::new(claims_jwt))
+        .await?;
+
+    if let Some(world) = wit {
+        let mut header = tokio_tar::Header::new_gnu();
+        header.set_path(WIT_WORLD_FILE)?;
+        header.set_size(world.len() as u64);
+        header.set_cksum();
+        par.append_data(&mut header, WIT_WORLD_FILE, Cursor::new(world))
+            .await?;
+    }
+
+    for (tgt, lib) in libraries {
+        let mut header = tokio_tar::Header::new_gnu();
+        let path = format!("{tgt}.bin");
+        header.set_path(&path)?;
+        header.set_size(lib.len() as u64);
+        header.set_cksum();
+        par.append_data(&mut header, &path, Cursor::new(lib))
+            .await?;
+    }
+    Ok(())
 }
 
 fn validate_hashes(
@@ -435,7 +470,9 @@ fn generate_hashes(
     hm
 }
 
-fn hash_bytes(bytes: &[u8]) -> String {
+/// Computes the hash of a provider binary, as recorded in the `target_hashes` of provider claims
+#[must_use]
+pub fn hash_bytes(bytes: &[u8]) -> String {
     let digest = sha256_digest(bytes).unwrap();
     HEXUPPER.encode(digest.as_ref())
 }
@@ -597,6 +634,43 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn target_archive() -> Result<()> {
+        let mut arch =
+            ProviderArchive::new("Testing", "wasmCloud", Some(4), Some("0.0.4".to_string()));
+        arch.add_library("aarch64-linux", b"blahblah")?;
+        arch.add_library("x86_64-linux", b"bloobloo")?;
+        arch.add_wit_world(b"world")?;
+
+        assert!(
+            arch.target_archive("x86_64-linux").await.is_err(),
+            "Unsigned archives should not be split"
+        );
+
+        let issuer = KeyPair::new_account();
+        let subject = KeyPair::new_service();
+        let tempdir = tempfile::tempdir()?;
+        arch.write(tempdir.path().join("multi.par"), &issuer, &subject, false)
+            .await?;
+
+        let bytes = arch.target_archive("x86_64-linux").await?;
+        let target = ProviderArchive::try_load(&bytes).await?;
+        assert_eq!(target.targets(), vec!["x86_64-linux".to_string()]);
+        assert_eq!(
+            target.target_bytes("x86_64-linux"),
+            Some(b"bloobloo".to_vec())
+        );
+        assert_eq!(target.wit_world(), Some(b"world".as_slice()));
+        assert_eq!(
+            target.claims_token().map(|token| token.jwt),
+            arch.claims_token().map(|token| token.jwt),
+            "Claims should not be re-signed"
+        );
+        assert!(arch.target_archive("mips-linux").await.is_err());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn compression_roundtrip() -> Result<()> {
         let mut arch =
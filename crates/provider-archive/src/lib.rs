@@ -1,4 +1,6 @@
 mod archive;
+mod platform;
 
 pub type Result<T> = ::std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
-pub use archive::ProviderArchive;
+pub use archive::{hash_bytes, ProviderArchive};
+pub use platform::{oci_platform, target_for_oci_platform, WASM_COMPONENT_TARGET};
@@ -0,0 +1,70 @@
+//! Conversions between provider archive targets (e.g. `x86_64-linux`) and the OCI platforms
+//! (e.g. `linux/amd64`) used to select a target from an OCI image index
+
+/// The target of providers implemented as Wasm components, which can run on any host
+pub const WASM_COMPONENT_TARGET: &str = "wasm32-wasip2";
+
+/// Rust architecture names and their Go (`GOARCH`) equivalents, as used by OCI platforms
+const ARCHITECTURES: [(&str, &str); 5] = [
+    ("x86_64", "amd64"),
+    ("aarch64", "arm64"),
+    ("x86", "386"),
+    ("powerpc64", "ppc64"),
+    ("loongarch64", "loong64"),
+];
+
+/// Rust OS names and their Go (`GOOS`) equivalents, as used by OCI platforms
+const OPERATING_SYSTEMS: [(&str, &str); 1] = [("macos", "darwin")];
+
+/// Returns the OCI platform `(os, architecture)` for a provider archive target, or `None` if the
+/// target is not of the form `{arch}-{os}`
+#[must_use]
+pub fn oci_platform(target: &str) -> Option<(String, String)> {
+    let (arch, os) = target.split_once('-')?;
+    let arch = ARCHITECTURES
+        .iter()
+        .find_map(|(rust, go)| (*rust == arch).then_some(*go))
+        .unwrap_or(arch);
+    let os = OPERATING_SYSTEMS
+        .iter()
+        .find_map(|(rust, go)| (*rust == os).then_some(*go))
+        .unwrap_or(os);
+    Some((os.to_string(), arch.to_string()))
+}
+
+/// Returns the provider archive target for an OCI platform
+#[must_use]
+pub fn target_for_oci_platform(os: &str, architecture: &str) -> String {
+    let arch = ARCHITECTURES
+        .iter()
+        .find_map(|(rust, go)| (*go == architecture).then_some(*rust))
+        .unwrap_or(architecture);
+    let os = OPERATING_SYSTEMS
+        .iter()
+        .find_map(|(rust, go)| (*go == os).then_some(*rust))
+        .unwrap_or(os);
+    format!("{arch}-{os}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip_targets() {
+        for (target, os, arch) in [
+            ("x86_64-linux", "linux", "amd64"),
+            ("aarch64-macos", "darwin", "arm64"),
+            ("x86_64-windows", "windows", "amd64"),
+            ("riscv64-linux", "linux", "riscv64"),
+            (WASM_COMPONENT_TARGET, "wasip2", "wasm32"),
+        ] {
+            assert_eq!(
+                oci_platform(target),
+                Some((os.to_string(), arch.to_string()))
+            );
+            assert_eq!(target_for_oci_platform(os, arch), target);
+        }
+        assert_eq!(oci_platform("nonsense"), None);
+    }
+}
@@ -187,6 +187,13 @@ impl ProviderInitConfig for &ProviderInitState {
 pub trait ProviderConfigUpdate: Send + Sync {
     /// Get the configuration values associated with the configuration update
     fn get_values(&self) -> &HashMap<String, String>;
+
+    /// Get the secrets that were rotated by the secrets backend, if this update was caused by
+    /// rotated secrets rather than changed configuration. Secrets that were not rotated are not
+    /// included.
+    fn get_secrets(&self) -> Option<&HashMap<String, SecretValue>> {
+        None
+    }
 }
 
 impl ProviderConfigUpdate for &HashMap<String, String> {
@@ -258,6 +265,9 @@ pub trait Provider<E = anyhow::Error>: Sync {
     /// bundles of configuration that are relevant to this provider, and this method
     /// helps the provider handle those changes.
     ///
+    /// When the secrets backend rotates secrets of the provider, the rotated secrets are delivered
+    /// as an update of the current configuration, see [`ProviderConfigUpdate::get_secrets`].
+    ///
     /// For more information on *how* these updates are delivered, see `run_provider()`
     ///
     /// # Arguments
@@ -278,6 +288,9 @@ pub trait Provider<E = anyhow::Error>: Sync {
     /// [Links](https://wasmcloud.com/docs/concepts/runtime-linking) are uni-directional -- a "source"
     /// operates as one end of the link, linking to a "target". When a link is created on the lattice, and
     /// this provider is the source, this method is called.
+    ///
+    /// This method is called again for a link that was already received when secrets of the link
+    /// are rotated, with the new secrets in `config`.
     fn receive_link_config_as_source(
         &self,
         config: LinkConfig<'_>,
@@ -293,6 +306,9 @@ pub trait Provider<E = anyhow::Error>: Sync {
     /// [Links](https://wasmcloud.com/docs/concepts/runtime-linking) are uni-directional -- a "source"
     /// operates as one end of the link, linking to a "target". When a link is created on the lattice, and
     /// this provider is the target, this method is called.
+    ///
+    /// This method is called again for a link that was already received when secrets of the link
+    /// are rotated, with the new secrets in `config`.
     fn receive_link_config_as_target(
         &self,
         config: LinkConfig<'_>,
@@ -23,7 +23,7 @@ use tokio::{select, spawn, try_join};
 use tracing::{debug, error, info, instrument, trace, warn, Instrument as _};
 use wasmcloud_core::nats::convert_header_map_to_hashmap;
 use wasmcloud_core::rpc::{health_subject, link_del_subject, link_put_subject, shutdown_subject};
-use wasmcloud_core::secrets::SecretValue;
+use wasmcloud_core::secrets::{SecretValue, SECRETS_UPDATE_HEADER};
 use wasmcloud_core::{
     provider_config_update_subject, HealthCheckRequest, HealthCheckResponse, HostData,
     InterfaceLinkDefinition, LatticeTarget,
@@ -36,7 +36,10 @@ use wasmcloud_tracing::context::attach_span_context;
 use wrpc_transport::InvokeExt as _;
 
 use crate::error::{ProviderInitError, ProviderInitResult};
-use crate::{with_connection_event_logging, Context, LinkConfig, Provider, DEFAULT_NATS_ADDR};
+use crate::{
+    with_connection_event_logging, Context, LinkConfig, Provider, ProviderConfigUpdate,
+    DEFAULT_NATS_ADDR,
+};
 
 /// Name of the header that should be passed for invocations that identifies the source
 const WRPC_SOURCE_ID_HEADER_NAME: &str = "source-id";
@@ -257,7 +260,7 @@ async fn subscribe_link_put(
     mut quit: broadcast::Receiver<()>,
     lattice: &str,
     provider_xkey: &str,
-) -> ProviderInitResult<mpsc::Receiver<(InterfaceLinkDefinition, oneshot::Sender<()>)>> {
+) -> ProviderInitResult<mpsc::Receiver<(LinkPut, oneshot::Sender<()>)>> {
     let (link_put_tx, link_put_rx) = mpsc::channel(1);
     let mut sub = nats
         .subscribe(link_put_subject(lattice, provider_xkey))
@@ -276,8 +279,15 @@ async fn subscribe_link_put(
                         tracing::field::display(&ld.interfaces.join(",")),
                     );
                     span.record("link_name", tracing::field::display(&ld.name));
+                    let put = LinkPut {
+                        link: ld,
+                        secrets_update: msg
+                            .headers
+                            .as_ref()
+                            .is_some_and(|headers| headers.get(SECRETS_UPDATE_HEADER).is_some()),
+                    };
                     let (tx, rx) = oneshot::channel();
-                    if let Err(err) = link_put_tx.send((ld, tx)).await {
+                    if let Err(err) = link_put_tx.send((put, tx)).await {
                         error!(%err, "failed to send link put request");
                         continue;
                     }
@@ -337,7 +347,7 @@ async fn subscribe_config_update(
     mut quit: broadcast::Receiver<()>,
     lattice: &str,
     provider_key: &str,
-) -> ProviderInitResult<mpsc::Receiver<(HashMap<String, String>, oneshot::Sender<()>)>> {
+) -> ProviderInitResult<mpsc::Receiver<(ConfigUpdate, oneshot::Sender<()>)>> {
     let (config_update_tx, config_update_rx) = mpsc::channel(1);
     let mut sub = nats
         .subscribe(provider_config_update_subject(lattice, provider_key).to_subject())
@@ -345,7 +355,18 @@ async fn subscribe_config_update(
     spawn({
         async move {
             process_until_quit!(sub, quit, msg, {
-                match serde_json::from_slice::<HashMap<String, String>>(&msg.payload) {
+                let update = if msg
+                    .headers
+                    .as_ref()
+                    .is_some_and(|headers| headers.get(SECRETS_UPDATE_HEADER).is_some())
+                {
+                    // Rotated secrets are decrypted once they reach the provider connection
+                    Ok(ConfigUpdate::Secrets(msg.payload))
+                } else {
+                    serde_json::from_slice::<HashMap<String, String>>(&msg.payload)
+                        .map(ConfigUpdate::Config)
+                };
+                match update {
                     Ok(update) => {
                         let (tx, rx) = oneshot::channel();
                         // Perform the config update on the host
@@ -373,9 +394,40 @@ async fn subscribe_config_update(
 pub struct ProviderCommandReceivers {
     health: mpsc::Receiver<(HealthCheckRequest, oneshot::Sender<HealthCheckResponse>)>,
     shutdown: mpsc::Receiver<oneshot::Sender<()>>,
-    link_put: mpsc::Receiver<(InterfaceLinkDefinition, oneshot::Sender<()>)>,
+    link_put: mpsc::Receiver<(LinkPut, oneshot::Sender<()>)>,
     link_del: mpsc::Receiver<(InterfaceLinkDefinition, oneshot::Sender<()>)>,
-    config_update: mpsc::Receiver<(HashMap<String, String>, oneshot::Sender<()>)>,
+    config_update: mpsc::Receiver<(ConfigUpdate, oneshot::Sender<()>)>,
+}
+
+/// A link put received on the provider link put subject
+struct LinkPut {
+    link: InterfaceLinkDefinition,
+    /// Whether the host put the link again because secrets of the link were rotated
+    secrets_update: bool,
+}
+
+/// An update received on the provider config update subject
+enum ConfigUpdate {
+    /// The configuration of the provider changed
+    Config(HashMap<String, String>),
+    /// Secrets of the provider were rotated, encrypted for the provider xkey
+    Secrets(Bytes),
+}
+
+/// A [`ProviderConfigUpdate`] carrying rotated secrets along with the current configuration
+struct SecretsUpdate {
+    config: HashMap<String, String>,
+    secrets: HashMap<String, SecretValue>,
+}
+
+impl ProviderConfigUpdate for &SecretsUpdate {
+    fn get_values(&self) -> &HashMap<String, String> {
+        &self.config
+    }
+
+    fn get_secrets(&self) -> Option<&HashMap<String, SecretValue>> {
+        Some(&self.secrets)
+    }
 }
 
 impl ProviderCommandReceivers {
@@ -675,6 +727,8 @@ pub async fn handle_provider_commands(
         mut config_update,
     }: ProviderCommandReceivers,
 ) {
+    // The current configuration of the provider, passed along with rotated secrets
+    let mut config = connection.config.clone();
     loop {
         select! {
             // run until we receive a shutdown request from host
@@ -726,9 +780,10 @@ pub async fn handle_provider_commands(
                 };
             }
             req = link_put.recv() => {
-                if let Some((ld, tx)) = req {
-                    // If the link has already been put, return early
-                    if connection.is_linked(&ld.source_id, &ld.target, &ld.wit_namespace, &ld.wit_package, &ld.name).await {
+                if let Some((LinkPut { link: ld, secrets_update }, tx)) = req {
+                    // If the link has already been put, return early, unless it is put again to
+                    // pass rotated secrets of the link to the provider
+                    if !secrets_update && connection.is_linked(&ld.source_id, &ld.target, &ld.wit_namespace, &ld.wit_package, &ld.name).await {
                         warn!(
                             source = &ld.source_id,
                             target = &ld.target,
@@ -777,10 +832,35 @@ pub async fn handle_provider_commands(
                 };
             }
             req = config_update.recv() => {
-                if let Some((cfg, tx)) = req {
-                    // Notify the provider that some config has been updated
-                    if let Err(e) = provider.on_config_update(&cfg).await {
-                        error!(error = %e, "failed to pass through config update for provider");
+                if let Some((update, tx)) = req {
+                    match update {
+                        ConfigUpdate::Config(cfg) => {
+                            // Notify the provider that some config has been updated
+                            if let Err(e) = provider.on_config_update(&cfg).await {
+                                error!(error = %e, "failed to pass through config update for provider");
+                            }
+                            config = cfg;
+                        }
+                        ConfigUpdate::Secrets(secrets) => {
+                            match decrypt_link_secret(
+                                Some(&secrets),
+                                &connection.provider_xkey,
+                                &connection.host_xkey,
+                            ) {
+                                Ok(secrets) => {
+                                    let update = SecretsUpdate {
+                                        config: config.clone(),
+                                        secrets,
+                                    };
+                                    if let Err(e) = provider.on_config_update(&update).await {
+                                        error!(error = %e, "failed to pass through secrets update for provider");
+                                    }
+                                }
+                                Err(e) => {
+                                    error!(error = %e, "failed to decrypt rotated secrets");
+                                }
+                            }
+                        }
                     }
 
                     if tx.send(()).is_err() {
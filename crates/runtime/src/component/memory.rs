@@ -0,0 +1,85 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use std::sync::Arc;
+
+/// Linear memory used by instances of a [Component](super::Component)
+#[derive(Debug, Default)]
+pub struct MemoryUsage {
+    current: AtomicU64,
+    peak: AtomicU64,
+}
+
+impl MemoryUsage {
+    /// Bytes of linear memory currently allocated by all live instances of the component
+    #[must_use]
+    pub fn current(&self) -> u64 {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    /// Largest amount of linear memory, in bytes, allocated by a single instance of the component
+    #[must_use]
+    pub fn peak(&self) -> u64 {
+        self.peak.load(Ordering::Relaxed)
+    }
+}
+
+/// Tracks linear memory growth of a single store and accounts it in a shared [`MemoryUsage`]
+pub(crate) struct MemoryTracker {
+    usage: Arc<MemoryUsage>,
+    /// Bytes of linear memory allocated in this store
+    size: u64,
+    /// Bytes accounted for the last growth, which are given back if the growth fails
+    growing: u64,
+}
+
+impl MemoryTracker {
+    pub(crate) fn new(usage: Arc<MemoryUsage>) -> Self {
+        Self {
+            usage,
+            size: 0,
+            growing: 0,
+        }
+    }
+}
+
+impl wasmtime::ResourceLimiter for MemoryTracker {
+    fn memory_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> anyhow::Result<bool> {
+        // Limits are enforced by the engine, this only observes growth
+        let delta = desired
+            .saturating_sub(current)
+            .try_into()
+            .unwrap_or(u64::MAX);
+        self.growing = delta;
+        self.size = self.size.saturating_add(delta);
+        self.usage.current.fetch_add(delta, Ordering::Relaxed);
+        self.usage.peak.fetch_max(self.size, Ordering::Relaxed);
+        Ok(true)
+    }
+
+    fn memory_grow_failed(&mut self, _error: anyhow::Error) -> anyhow::Result<()> {
+        let delta = core::mem::take(&mut self.growing);
+        self.size = self.size.saturating_sub(delta);
+        self.usage.current.fetch_sub(delta, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: usize,
+        _desired: usize,
+        _maximum: Option<usize>,
+    ) -> anyhow::Result<bool> {
+        Ok(true)
+    }
+}
+
+impl Drop for MemoryTracker {
+    fn drop(&mut self) {
+        self.usage.current.fetch_sub(self.size, Ordering::Relaxed);
+    }
+}
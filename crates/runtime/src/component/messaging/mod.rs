@@ -23,7 +23,12 @@ where
     ) -> anyhow::Result<Result<(), String>> {
         // Set the parent of the current context to the span passed in
         Span::current().set_parent(cx.deref().context());
-        let mut store = new_store(&self.engine, self.handler.clone(), self.max_execution_time);
+        let mut store = new_store(
+            &self.engine,
+            self.handler.clone(),
+            self.max_execution_time,
+            &self.memory,
+        );
 
         // If wasmcloud:messaging@0.3.0 is enabled and we can instantiate the 0.3.0 bindings,
         // handle the message using 0.3.0. Otherwise, use the 0.2.0 bindings.
@@ -4,6 +4,8 @@ use core::ops::Deref;
 use core::pin::Pin;
 use core::time::Duration;
 
+use std::sync::Arc;
+
 use anyhow::{ensure, Context as _};
 use futures::{Stream, TryStreamExt as _};
 use tokio::io::{AsyncRead, AsyncReadExt as _};
@@ -26,10 +28,13 @@ use crate::capability::{self, wrpc};
 use crate::experimental::Features;
 use crate::Runtime;
 
+use self::memory::MemoryTracker;
+
 pub use bus::Bus;
 pub use bus1_0_0::Bus as Bus1_0_0;
 pub use config::Config;
 pub use logging::Logging;
+pub use memory::MemoryUsage;
 pub use messaging::v0_2::Messaging as Messaging0_2;
 pub use messaging::v0_3::{
     Client as MessagingClient0_3, GuestMessage as MessagingGuestMessage0_3,
@@ -44,6 +49,7 @@ mod config;
 mod http;
 mod keyvalue;
 mod logging;
+mod memory;
 pub(crate) mod messaging;
 mod secrets;
 
@@ -179,6 +185,7 @@ where
     instance_pre: wasmtime::component::InstancePre<Ctx<H>>,
     max_execution_time: Duration,
     experimental_features: Features,
+    memory: Arc<MemoryUsage>,
 }
 
 impl<H> Debug for Component<H>
@@ -198,6 +205,7 @@ fn new_store<H: Handler>(
     engine: &wasmtime::Engine,
     handler: H,
     max_execution_time: Duration,
+    memory: &Arc<MemoryUsage>,
 ) -> wasmtime::Store<Ctx<H>> {
     let table = ResourceTable::new();
     let wasi = WasiCtxBuilder::new()
@@ -215,8 +223,10 @@ fn new_store<H: Handler>(
             shared_resources: SharedResourceTable::default(),
             timeout: max_execution_time,
             parent_context: None,
+            memory: MemoryTracker::new(Arc::clone(memory)),
         },
     );
+    store.limiter(|ctx| &mut ctx.memory);
     store.set_epoch_deadline(max_execution_time.as_secs());
     store
 }
@@ -284,6 +294,10 @@ where
         }
         let engine = rt.engine.clone();
         let claims_token = claims_token(wasm)?;
+        ensure!(
+            claims_token.is_some() || !rt.component_config.require_signature,
+            "component is not signed, but signatures are required"
+        );
         let claims = claims_token.map(|c| c.claims);
         let component = wasmtime::component::Component::new(&engine, wasm)
             .context("failed to compile component")?;
@@ -400,6 +414,7 @@ where
             instance_pre,
             max_execution_time: rt.max_execution_time,
             experimental_features: rt.experimental_features,
+            memory: Arc::default(),
         })
     }
 
@@ -443,6 +458,12 @@ where
         self.claims.as_ref()
     }
 
+    /// Linear memory used by instances of this [Component] and all of its clones
+    #[must_use]
+    pub fn memory_usage(&self) -> &MemoryUsage {
+        &self.memory
+    }
+
     /// Instantiates the component given a handler and event channel
     pub fn instantiate<C>(
         &self,
@@ -456,6 +477,7 @@ where
             max_execution_time: self.max_execution_time,
             events,
             experimental_features: self.experimental_features,
+            memory: Arc::clone(&self.memory),
         }
     }
 
@@ -516,13 +538,18 @@ where
                     let engine = self.engine.clone();
                     let handler = handler.clone();
                     let pre = self.instance_pre.clone();
+                    let memory = Arc::clone(&self.memory);
                     debug!(?name, "serving root function");
                     let func = srv
                         .serve_function(
                             move || {
                                 let span = info_span!("call_instance_function");
-                                let mut store =
-                                    new_store(&engine, handler.clone(), max_execution_time);
+                                let mut store = new_store(
+                                    &engine,
+                                    handler.clone(),
+                                    max_execution_time,
+                                    &memory,
+                                );
                                 store.data_mut().parent_context = Some(span.context());
                                 store
                             },
@@ -576,6 +603,7 @@ where
                                 let engine = self.engine.clone();
                                 let handler = handler.clone();
                                 let pre = self.instance_pre.clone();
+                                let memory = Arc::clone(&self.memory);
                                 debug!(?instance_name, ?name, "serving instance function");
                                 let func = srv
                                     .serve_function(
@@ -585,6 +613,7 @@ where
                                                 &engine,
                                                 handler.clone(),
                                                 max_execution_time,
+                                                &memory,
                                             );
                                             store.data_mut().parent_context = Some(span.context());
                                             store
@@ -679,6 +708,7 @@ where
     max_execution_time: Duration,
     events: mpsc::Sender<WrpcServeEvent<C>>,
     experimental_features: Features,
+    memory: Arc<MemoryUsage>,
 }
 
 impl<H, C> Clone for Instance<H, C>
@@ -693,6 +723,7 @@ where
             max_execution_time: self.max_execution_time,
             events: self.events.clone(),
             experimental_features: self.experimental_features,
+            memory: Arc::clone(&self.memory),
         }
     }
 }
@@ -710,6 +741,7 @@ where
     shared_resources: SharedResourceTable,
     timeout: Duration,
     parent_context: Option<opentelemetry::Context>,
+    memory: MemoryTracker,
 }
 
 impl<H: Handler> WasiView for Ctx<H> {
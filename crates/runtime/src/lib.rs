@@ -22,7 +22,11 @@ pub mod runtime;
 /// wasmCloud I/O functionality
 pub mod io;
 
+/// Capability providers implemented as Wasm components
+pub mod provider;
+
 pub use component::{Component, ComponentConfig};
+pub use provider::ProviderComponent;
 pub use runtime::*;
 
 pub use async_trait::async_trait;
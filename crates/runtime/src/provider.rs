@@ -0,0 +1,112 @@
+use crate::Runtime;
+
+use core::fmt::{self, Debug};
+
+use anyhow::{ensure, Context as _};
+use tracing::instrument;
+use wasmtime::component::{Linker, ResourceTable};
+use wasmtime_wasi::bindings::CommandPre;
+use wasmtime_wasi::pipe::MemoryInputPipe;
+use wasmtime_wasi::{I32Exit, WasiCtx, WasiCtxBuilder, WasiView};
+
+struct ProviderCtx {
+    wasi: WasiCtx,
+    table: ResourceTable,
+}
+
+impl WasiView for ProviderCtx {
+    fn table(&mut self) -> &mut ResourceTable {
+        &mut self.table
+    }
+
+    fn ctx(&mut self) -> &mut WasiCtx {
+        &mut self.wasi
+    }
+}
+
+/// A capability provider implemented as a Wasm component exporting `wasi:cli/run`.
+///
+/// Like a native provider executable, the component receives its host data on stdin and is
+/// expected to connect to the lattice itself, for which it is given access to the network through
+/// `wasi:sockets`. It has no access to the filesystem or to the environment of the host.
+#[derive(Clone)]
+pub struct ProviderComponent {
+    engine: wasmtime::Engine,
+    pre: CommandPre<ProviderCtx>,
+}
+
+impl Debug for ProviderComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProviderComponent")
+            .field("runtime", &"wasmtime")
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProviderComponent {
+    /// Compiles a provider component using [Runtime].
+    ///
+    /// # Errors
+    ///
+    /// Fails if `wasm` is not a component, or if it does not export `wasi:cli/run`
+    #[instrument(level = "trace", skip_all)]
+    pub fn new(rt: &Runtime, wasm: &[u8]) -> anyhow::Result<Self> {
+        ensure!(
+            wasmparser::Parser::is_component(wasm),
+            "provider is not a Wasm component"
+        );
+        let engine = rt.engine.clone();
+        let component = wasmtime::component::Component::new(&engine, wasm)
+            .context("failed to compile provider component")?;
+        let mut linker = Linker::new(&engine);
+        wasmtime_wasi::add_to_linker_async(&mut linker).context("failed to link core WASI")?;
+        let pre = linker
+            .instantiate_pre(&component)
+            .context("failed to pre-instantiate provider component")?;
+        let pre =
+            CommandPre::new(pre).context("provider component does not export `wasi:cli/run`")?;
+        Ok(Self { engine, pre })
+    }
+
+    /// Runs the provider until it exits, passing `stdin` to it as its standard input and `env` as
+    /// its environment. Returns whether the provider exited successfully.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the provider could not be instantiated or trapped
+    #[instrument(level = "debug", skip_all)]
+    pub async fn run(&self, stdin: Vec<u8>, env: &[(String, String)]) -> anyhow::Result<bool> {
+        let wasi = WasiCtxBuilder::new()
+            .args(&["provider.wasm"])
+            .stdin(MemoryInputPipe::new(stdin))
+            .inherit_stdout()
+            .inherit_stderr()
+            .envs(env)
+            .inherit_network()
+            .allow_ip_name_lookup(true)
+            .build();
+        let mut store = wasmtime::Store::new(
+            &self.engine,
+            ProviderCtx {
+                wasi,
+                table: ResourceTable::new(),
+            },
+        );
+        // Providers run for as long as they are not stopped, so rather than being interrupted they
+        // yield to the executor every epoch
+        store.epoch_deadline_async_yield_and_update(1);
+
+        let command = self
+            .pre
+            .instantiate_async(&mut store)
+            .await
+            .context("failed to instantiate provider component")?;
+        match command.wasi_cli_run().call_run(&mut store).await {
+            Ok(res) => Ok(res.is_ok()),
+            Err(err) => match err.downcast_ref::<I32Exit>() {
+                Some(I32Exit(code)) => Ok(*code == 0),
+                None => Err(err).context("provider component trapped"),
+            },
+        }
+    }
+}
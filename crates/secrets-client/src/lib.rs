@@ -1,9 +1,12 @@
 use async_nats::HeaderMap;
 use nkeys::XKey;
 use wasmcloud_secrets_types::{
-    Secret, SecretRequest, SecretResponse, RESPONSE_XKEY, WASMCLOUD_HOST_XKEY,
+    Secret, SecretRequest, SecretResponse, RESPONSE_XKEY, SECRET_ROTATED_OPERATION,
+    WASMCLOUD_HOST_XKEY,
 };
 
+pub mod server;
+
 /// Default API version of the secrets API implementation in wasmCloud
 const DEFAULT_API_VERSION: &str = "v1alpha1";
 
@@ -56,6 +59,18 @@ impl SecretsTopic {
     pub fn server_xkey(&self) -> String {
         format!("{}.{}", self.0, "server_xkey")
     }
+
+    pub fn rotated(&self) -> String {
+        format!("{}.{}", self.0, SECRET_ROTATED_OPERATION)
+    }
+}
+
+/// Returns the subject on which `backend` announces rotated secrets, see
+/// [`SecretRotated`](wasmcloud_secrets_types::SecretRotated).
+///
+/// `backend` may be a NATS wildcard to subscribe to the rotations of all backends.
+pub fn rotation_subject(prefix: &str, backend: &str, api_version: Option<&str>) -> String {
+    SecretsTopic::new(prefix, backend, api_version).rotated()
 }
 
 /// NATS client that can be used to interact with secrets
@@ -0,0 +1,109 @@
+//! Serving of the wasmCloud secrets API over NATS for [`SecretsServer`] implementations, so that
+//! secrets backends only need to implement fetching secrets.
+
+use async_nats::{HeaderMap, Message, Subject};
+use futures::StreamExt as _;
+use nkeys::XKey;
+use wasmcloud_secrets_types::{
+    GetSecretError, SecretRequest, SecretResponse, SecretsServer, RESPONSE_XKEY,
+    WASMCLOUD_HOST_XKEY,
+};
+
+use crate::SecretsTopic;
+
+/// Serve the secrets API of `backend` with `server` until the NATS connection is closed.
+///
+/// Requests are received on `{prefix}.{version}.{backend}.>` in `queue_group`, so that multiple
+/// instances of a backend can share the load. Secret requests are decrypted with `transit_xkey`,
+/// the public key of which must be returned by [`SecretsServer::server_xkey`], and responses are
+/// encrypted for the xkey of the requesting host.
+///
+/// Operations other than `server_xkey` and `get` are answered with an error, backends serving
+/// additional operations should subscribe to them separately.
+pub async fn serve(
+    nats: &async_nats::Client,
+    prefix: &str,
+    backend: &str,
+    api_version: Option<&str>,
+    queue_group: &str,
+    transit_xkey: &XKey,
+    server: &impl SecretsServer,
+) -> Result<(), async_nats::SubscribeError> {
+    let topic = SecretsTopic::new(prefix, backend, api_version);
+    let mut sub = nats
+        .queue_subscribe(format!("{}.*", topic.0), queue_group.to_string())
+        .await?;
+    let (get, server_xkey) = (topic.get(), topic.server_xkey());
+    while let Some(msg) = sub.next().await {
+        // Requests without a reply subject cannot be answered, e.g. rotation announcements
+        let Some(reply) = msg.reply.clone() else {
+            continue;
+        };
+        if msg.subject.as_str() == server_xkey {
+            let _ = nats
+                .publish(reply, server.server_xkey().public_key().into())
+                .await;
+        } else if msg.subject.as_str() == get {
+            handle_get(nats, &msg, reply, transit_xkey, server).await;
+        } else {
+            let _ = nats
+                .publish(reply, format!("unknown operation {}", msg.subject).into())
+                .await;
+        }
+    }
+    Ok(())
+}
+
+async fn handle_get(
+    nats: &async_nats::Client,
+    msg: &Message,
+    reply: Subject,
+    transit_xkey: &XKey,
+    server: &impl SecretsServer,
+) {
+    match get_secret(msg, transit_xkey, server).await {
+        Ok((headers, payload)) => {
+            let _ = nats
+                .publish_with_headers(reply, headers, payload.into())
+                .await;
+        }
+        Err(e) => {
+            let _ = nats.publish(reply, SecretResponse::from(e).into()).await;
+        }
+    }
+}
+
+/// Decrypt the secret request in `msg`, fetch the secret from `server` and encrypt the response
+/// for the requesting host
+async fn get_secret(
+    msg: &Message,
+    transit_xkey: &XKey,
+    server: &impl SecretsServer,
+) -> Result<(HeaderMap, Vec<u8>), GetSecretError> {
+    if msg.payload.is_empty() {
+        return Err(GetSecretError::InvalidPayload);
+    }
+    let host_xkey = msg
+        .headers
+        .as_ref()
+        .ok_or(GetSecretError::InvalidHeaders)?
+        .get(WASMCLOUD_HOST_XKEY)
+        .ok_or(GetSecretError::InvalidXKey)?;
+    let host_xkey =
+        XKey::from_public_key(host_xkey.as_str()).map_err(|_| GetSecretError::InvalidXKey)?;
+    let request = transit_xkey
+        .open(&msg.payload, &host_xkey)
+        .map_err(|_| GetSecretError::DecryptionError)?;
+    let request: SecretRequest =
+        serde_json::from_slice(&request).map_err(|_| GetSecretError::InvalidRequest)?;
+
+    let response = serde_json::to_vec(&server.get(request).await?)
+        .map_err(|e| GetSecretError::Other(e.to_string()))?;
+    let response_xkey = XKey::new();
+    let response = response_xkey
+        .seal(&response, &host_xkey)
+        .map_err(|_| GetSecretError::EncryptionError)?;
+    let mut headers = HeaderMap::new();
+    headers.insert(RESPONSE_XKEY, response_xkey.public_key().as_str());
+    Ok((headers, response))
+}
@@ -0,0 +1,245 @@
+//! A wasmCloud secrets backend serving secrets from an encrypted local file.
+//!
+//! The file contains a JSON [`SecretsFile`], encrypted with an xkey for itself. Each secret lists
+//! the public keys of the components and providers that are allowed to read it.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use nkeys::XKey;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use wascap::jwt::{CapabilityProvider, Claims, Component};
+use wasmcloud_secrets_types::{
+    GetSecretError, Secret, SecretRequest, SecretResponse, SecretsServer,
+};
+
+/// A secret stored in a [`SecretsFile`]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FileSecret {
+    /// The value of a string secret
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub string: Option<String>,
+    /// The value of a binary secret
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary: Option<Vec<u8>>,
+    /// The public keys of the components and providers allowed to read the secret
+    #[serde(default)]
+    pub entities: HashSet<String>,
+}
+
+/// The contents of a secrets file, mapping the keys of secrets to the secrets
+pub type SecretsFile = HashMap<String, FileSecret>;
+
+/// Encrypt the JSON [`SecretsFile`] in `plaintext` with `encryption_xkey`
+///
+/// # Errors
+///
+/// Returns an error if `plaintext` is not a valid [`SecretsFile`] or encryption fails
+pub fn encrypt(plaintext: &[u8], encryption_xkey: &XKey) -> anyhow::Result<Vec<u8>> {
+    serde_json::from_slice::<SecretsFile>(plaintext).context("invalid secrets file")?;
+    encryption_xkey
+        .seal(plaintext, encryption_xkey)
+        .context("failed to encrypt secrets file")
+}
+
+/// Decrypt a secrets file encrypted by [`encrypt`] with `encryption_xkey`, returning the JSON
+/// [`SecretsFile`]
+///
+/// # Errors
+///
+/// Returns an error if decryption fails
+pub fn decrypt(ciphertext: &[u8], encryption_xkey: &XKey) -> anyhow::Result<Vec<u8>> {
+    encryption_xkey
+        .open(ciphertext, encryption_xkey)
+        .context("failed to decrypt secrets file")
+}
+
+/// Look up the secret requested by the entity with public key `entity` in `file`
+fn lookup(
+    file: &SecretsFile,
+    entity: &str,
+    request: &SecretRequest,
+) -> Result<Secret, GetSecretError> {
+    // Secrets in files are neither versioned nor structured
+    if request.version.is_some() || request.field.is_some() {
+        return Err(GetSecretError::InvalidRequest);
+    }
+    let secret = file
+        .get(&request.key)
+        .ok_or(GetSecretError::SecretNotFound)?;
+    if !secret.entities.contains(entity) {
+        return Err(GetSecretError::Unauthorized);
+    }
+    Ok(Secret {
+        version: String::new(),
+        string_secret: secret.string.clone(),
+        binary_secret: secret.binary.clone(),
+    })
+}
+
+/// The `Api` struct implements a secrets backend serving secrets from an encrypted file.
+pub struct Api {
+    /// The server's transit XKey, used to decrypt secret requests sent to the server.
+    server_transit_xkey: XKey,
+    /// The XKey the secrets file is encrypted with.
+    encryption_xkey: XKey,
+    /// The path of the encrypted secrets file.
+    path: PathBuf,
+    /// The NATS client used to communicate with wasmCloud hosts.
+    pub client: async_nats::Client,
+    /// The base subject for all secrets operations. Should default to `wasmcloud.secrets`.
+    subject_base: String,
+    /// The name of this backend. It must be unique for every {subject_base} + name combination.
+    pub name: String,
+    /// The NATS queue group of the backend instances.
+    queue_group: String,
+    /// The version of the secrets API that this backend implements.
+    api_version: String,
+}
+
+impl Api {
+    /// Create a new file secrets backend serving the secrets file at `path`, which is encrypted
+    /// with `encryption_xkey`
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        server_xkey: XKey,
+        encryption_xkey: XKey,
+        path: PathBuf,
+        client: async_nats::Client,
+        subject_base: String,
+        name: String,
+        queue_group: String,
+        api_version: String,
+    ) -> Self {
+        Self {
+            server_transit_xkey: server_xkey,
+            encryption_xkey,
+            path,
+            client,
+            subject_base,
+            name,
+            queue_group,
+            api_version,
+        }
+    }
+
+    /// Read and decrypt the secrets file
+    pub async fn read_file(&self) -> anyhow::Result<SecretsFile> {
+        let ciphertext = tokio::fs::read(&self.path)
+            .await
+            .with_context(|| format!("failed to read `{}`", self.path.display()))?;
+        let plaintext = decrypt(&ciphertext, &self.encryption_xkey)?;
+        serde_json::from_slice(&plaintext).context("invalid secrets file")
+    }
+
+    /// Run the secrets backend. This function will block until the NATS connection is closed.
+    pub async fn run(&self) -> anyhow::Result<()> {
+        // Fail early if the file cannot be served
+        self.read_file().await?;
+        info!(
+            subject = format!("{}.{}.{}", self.subject_base, self.api_version, self.name),
+            "Starting listener"
+        );
+        wasmcloud_secrets_client::server::serve(
+            &self.client,
+            &self.subject_base,
+            &self.name,
+            Some(&self.api_version),
+            &self.queue_group,
+            &self.server_transit_xkey,
+            self,
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SecretsServer for Api {
+    async fn get(&self, request: SecretRequest) -> Result<SecretResponse, GetSecretError> {
+        if let Err(e) = request.context.valid_claims() {
+            return Err(GetSecretError::InvalidEntityJWT(e.to_string()));
+        }
+        let entity = match (
+            Claims::<Component>::decode(&request.context.entity_jwt),
+            Claims::<CapabilityProvider>::decode(&request.context.entity_jwt),
+        ) {
+            (Ok(c), _) => c.subject,
+            (_, Ok(p)) => p.subject,
+            (Err(e), _) => return Err(GetSecretError::InvalidEntityJWT(e.to_string())),
+        };
+
+        // The file is read on every request so that changes take effect immediately
+        let file = self.read_file().await.map_err(|e| {
+            error!(error = ?e, "failed to read secrets file");
+            GetSecretError::UpstreamError(e.to_string())
+        })?;
+        let secret = lookup(&file, &entity, &request)?;
+        Ok(SecretResponse {
+            secret: Some(secret),
+            ..Default::default()
+        })
+    }
+
+    fn server_xkey(&self) -> XKey {
+        XKey::from_public_key(self.server_transit_xkey.public_key().as_str())
+            .expect("public key of a valid xkey must be valid")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nkeys::XKey;
+    use wasmcloud_secrets_types::{Context, GetSecretError, SecretRequest};
+
+    use super::{decrypt, encrypt, lookup, SecretsFile};
+
+    const ENTITY: &str = "MAVCGEGKMVT5UCIDSHJO25VHD2VDNDRA3LIHYH2TPIUQS7JCMS472AFJ";
+
+    fn request(key: &str) -> SecretRequest {
+        SecretRequest {
+            key: key.to_string(),
+            field: None,
+            version: None,
+            context: Context::default(),
+        }
+    }
+
+    #[test]
+    fn test_encrypted_file_lookup() {
+        let plaintext = format!(
+            r#"{{"db-password":{{"string":"sup3rs3cr3t","entities":["{ENTITY}"]}},"tls-key":{{"binary":[1,2,3]}}}}"#
+        );
+        let key = XKey::new();
+        let ciphertext = encrypt(plaintext.as_bytes(), &key).expect("failed to encrypt");
+        assert!(decrypt(&ciphertext, &XKey::new()).is_err());
+        let file: SecretsFile =
+            serde_json::from_slice(&decrypt(&ciphertext, &key).expect("failed to decrypt"))
+                .expect("failed to parse file");
+
+        let secret = lookup(&file, ENTITY, &request("db-password")).expect("failed to look up");
+        assert_eq!(secret.string_secret.as_deref(), Some("sup3rs3cr3t"));
+        assert!(matches!(
+            lookup(&file, ENTITY, &request("tls-key")),
+            Err(GetSecretError::Unauthorized)
+        ));
+        assert!(matches!(
+            lookup(&file, ENTITY, &request("missing")),
+            Err(GetSecretError::SecretNotFound)
+        ));
+        let mut versioned = request("db-password");
+        versioned.version = Some("1".to_string());
+        assert!(matches!(
+            lookup(&file, ENTITY, &versioned),
+            Err(GetSecretError::InvalidRequest)
+        ));
+    }
+
+    #[test]
+    fn test_encrypt_rejects_invalid_files() {
+        assert!(encrypt(b"not json", &XKey::new()).is_err());
+    }
+}
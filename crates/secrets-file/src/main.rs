@@ -0,0 +1,138 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use nkeys::XKey;
+use secrets_file::Api;
+
+#[derive(Parser)]
+#[command(about, version, name = "secrets-file")]
+/// A secrets backend for wasmCloud that serves secrets from an encrypted local file. Included in
+/// this CLI are commands to encrypt and decrypt the secrets file
+struct Args {
+    #[command(name = "command", subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the file secrets backend
+    Run(RunCommand),
+    /// Encrypt a JSON secrets file
+    Encrypt(CryptCommand),
+    /// Decrypt an encrypted secrets file to JSON
+    Decrypt(CryptCommand),
+}
+
+#[derive(Parser)]
+struct RunCommand {
+    /// The XKey the secrets file is encrypted with.
+    #[clap(short, long, env = "ENCRYPTION_XKEY_SEED")]
+    encryption_xkey_seed: String,
+    /// The server's transit XKey, used to decrypt secret requests sent to the server.
+    #[clap(short, long, env = "TRANSIT_XKEY_SEED")]
+    transit_xkey_seed: String,
+    /// The path of the encrypted secrets file
+    #[clap(short, long, env = "SECRETS_FILE")]
+    file: PathBuf,
+    /// The subject prefix to use for all requests to the secrets backend, defaults to `wasmcloud.secrets`
+    #[clap(short, long, default_value = "wasmcloud.secrets")]
+    subject_base: String,
+    /// The name of the secrets backend, defaults to `file`
+    #[clap(short = 'n', long, default_value = "file")]
+    name: String,
+    /// The NATS queue group to use for running multiple instances of the secrets backend
+    #[clap(long, default_value = "wasmcloud_secrets.file")]
+    nats_queue_group: String,
+    /// The NATS address to connect to where the backend is running
+    #[clap(long, default_value = "127.0.0.1:4222")]
+    nats_address: String,
+    /// The NATS credentials file to use when connecting
+    #[clap(long, env = "NATS_CREDSFILE")]
+    nats_creds_file: Option<String>,
+    /// The API version to use for the secrets backend
+    #[clap(long, default_value = "v1alpha1")]
+    secrets_api_version: String,
+}
+
+#[derive(Parser)]
+struct CryptCommand {
+    /// The XKey the secrets file is encrypted with.
+    #[clap(short, long, env = "ENCRYPTION_XKEY_SEED")]
+    encryption_xkey_seed: String,
+    /// The path of the file to read
+    input: PathBuf,
+    /// The path of the file to write, defaults to standard output
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt::init();
+
+    match Args::parse().command {
+        Command::Run(args) => run(args).await,
+        Command::Encrypt(args) => crypt(args, secrets_file::encrypt),
+        Command::Decrypt(args) => crypt(args, secrets_file::decrypt),
+    }
+}
+
+async fn run(args: RunCommand) -> anyhow::Result<()> {
+    let server_xkey = XKey::from_seed(&args.transit_xkey_seed)
+        .context("failed to create server key from seed")?;
+    let encryption_xkey = XKey::from_seed(&args.encryption_xkey_seed)
+        .context("failed to create encryption key from seed")?;
+    let nats_client = match args.nats_creds_file {
+        Some(creds_file) => async_nats::ConnectOptions::new()
+            .credentials_file(&creds_file)
+            .await
+            .with_context(|| format!("failed to read NATS credentials file '{creds_file}'"))?
+            .connect(&args.nats_address)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to connect to NATS at {} with credentials file '{creds_file}'",
+                    args.nats_address
+                )
+            })?,
+        None => async_nats::connect(&args.nats_address)
+            .await
+            .with_context(|| format!("failed to connect to NATS at {}", args.nats_address))?,
+    };
+
+    let api = Api::new(
+        server_xkey,
+        encryption_xkey,
+        args.file,
+        nats_client,
+        args.subject_base,
+        args.name.clone(),
+        args.nats_queue_group,
+        args.secrets_api_version,
+    );
+
+    println!("Starting secrets backend '{}'", args.name);
+    api.run().await
+}
+
+fn crypt(
+    args: CryptCommand,
+    f: impl FnOnce(&[u8], &XKey) -> anyhow::Result<Vec<u8>>,
+) -> anyhow::Result<()> {
+    let encryption_xkey = XKey::from_seed(&args.encryption_xkey_seed)
+        .context("failed to create encryption key from seed")?;
+    let input = std::fs::read(&args.input)
+        .with_context(|| format!("failed to read '{}'", args.input.display()))?;
+    let output = f(&input, &encryption_xkey)?;
+    match args.output {
+        Some(path) => std::fs::write(&path, output)
+            .with_context(|| format!("failed to write '{}'", path.display())),
+        None => {
+            use std::io::Write as _;
+            std::io::stdout()
+                .write_all(&output)
+                .context("failed to write to stdout")
+        }
+    }
+}
@@ -14,16 +14,24 @@ use bytes::Bytes;
 use exponential_backoff::Backoff;
 use futures::StreamExt;
 use nkeys::XKey;
-use std::{collections::HashSet, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 use tracing::{debug, error, info, warn};
 use wascap::jwt::{CapabilityProvider, Host};
 use wascap::prelude::{validate_token, Claims, Component};
 use wasmcloud_secrets_types::*;
 
+use crate::policy;
 use crate::types::*;
 
 const OPERATION_INDEX: usize = 3;
 
+/// The prefix of the keys in the state bucket that map the revisions of a secret from before a
+/// key rotation to the revisions that hold the re-encrypted values.
+const REVISIONS_PREFIX: &str = "revisions.";
+
 /// The `Api` struct implements the functionality of this secrets backend.
 pub struct Api {
     /// The server's public XKey, used to decrypt secrets sent to the server.
@@ -32,6 +40,11 @@ pub struct Api {
     /// This _must_ always be the same value after the first time a secret is written otherwise you
     /// will *not* able to decrypt it!
     encryption_xkey: XKey,
+    /// Encryption keys that were previously used to encrypt secrets in NATS KV. These are only
+    /// used to decrypt secrets while the bucket is being re-encrypted with `encryption_xkey`.
+    previous_encryption_xkeys: Vec<XKey>,
+    /// The subject to publish an [`AuditEvent`] to for every `get` request, if any.
+    audit_subject: Option<String>,
     /// The NATS client used to communicate with wasmCloud hosts and the KV backend.
     pub client: async_nats::Client,
     /// The base subject for all secrets operations. Should default to `wasmcloud.secrets`.
@@ -57,7 +70,7 @@ impl Api {
 
     /// The name of the stream used to coordinate write access to the KV bucket.
     fn lock_stream_name(&self) -> String {
-        format!("SECRETS_{}_state_lock", self.name)
+        lock_stream_name(&self.name)
     }
 
     pub fn subject(&self) -> String {
@@ -65,7 +78,7 @@ impl Api {
     }
 
     pub fn state_bucket_name(&self) -> String {
-        format!("SECRETS_{}_state", self.name)
+        state_bucket_name(&self.name)
     }
 
     /// Retrieve the state bucket used to store mappings of entities to secrets.
@@ -76,21 +89,7 @@ impl Api {
 
     /// Retrieve the lock stream used to coordinate write access to the state bucket.
     async fn ensure_state_lock_stream(&self) -> anyhow::Result<()> {
-        let name = self.lock_stream_name();
-        let js = jetstream::new(self.client.clone());
-        js.get_or_create_stream(StreamConfig {
-            name: name.clone(),
-            description: Some("Lock stream for secrets state".to_string()),
-            discard: DiscardPolicy::New,
-            discard_new_per_subject: true,
-            storage: StorageType::Memory,
-            max_messages_per_subject: 1,
-            max_age: Duration::from_secs(3),
-            subjects: vec![format!("{}.*", self.lock_stream_name())],
-            ..Default::default()
-        })
-        .await?;
-        Ok(())
+        ensure_state_lock_stream(&self.client, &self.name).await
     }
 
     async fn handle_put_secret(&self, msg: &Message, reply: Subject) {
@@ -184,8 +183,24 @@ impl Api {
             return;
         };
 
-        match store.put(secret.key, encrypted_value.into()).await {
+        match store.put(&secret.key, encrypted_value.into()).await {
             Ok(revision) => {
+                // Let hosts know that a new version of the secret exists so that they can refresh
+                // it for the entities already using it
+                let rotated = SecretRotated {
+                    key: secret.key,
+                    version: Some(revision.to_string()),
+                };
+                if let Err(e) = self
+                    .client
+                    .publish(
+                        format!("{}.{SECRET_ROTATED_OPERATION}", self.subject()),
+                        serde_json::to_vec(&rotated).unwrap().into(),
+                    )
+                    .await
+                {
+                    warn!(error = %e, "failed to publish secret rotation");
+                }
                 let resp = PutSecretResponse::from(revision);
                 let _ = self
                     .client
@@ -458,61 +473,11 @@ impl Api {
     }
 
     async fn get_lock(&self, subject: String) -> anyhow::Result<PublishAck> {
-        // TODO: make this all configurable
-        let max_attempts = 5;
-        let min_interval = Duration::from_millis(100);
-        let max_interval = Duration::from_millis(1000);
-        let backoff = Backoff::new(max_attempts, min_interval, max_interval);
-
-        // Wrap the retries in timeout to ensure the code runs for a given period
-        let result = tokio::time::timeout(Duration::from_secs(3), async {
-            for duration in backoff {
-                // Attempt to request a lock
-                let resp = match self.client.request(subject.clone(), "lock".into()).await {
-                    Ok(msg) => msg,
-                    Err(e) => match duration {
-                        Some(duration) => {
-                            debug!("Error locking state stream: {}", e);
-                            tokio::time::sleep(duration).await;
-                            continue;
-                        }
-                        None => {
-                            debug!("Error locking state stream: {}", e);
-                            return Err(anyhow::anyhow!("timed out getting lock"));
-                        }
-                    },
-                };
-
-                // Parse NATS response to lock request
-                match serde_json::from_slice(&resp.payload) {
-                    Ok(Response::Ok(p)) => return Ok(p),
-                    Ok(Response::Err { error: e }) => match duration {
-                        Some(duration) => {
-                            debug!("Error locking state stream: {:?}", e);
-                            tokio::time::sleep(duration).await;
-                            continue;
-                        }
-                        None => {
-                            debug!("Error locking state stream: {}", e);
-                            return Err(anyhow::anyhow!("unable to get lock"));
-                        }
-                    },
-                    Err(e) => {
-                        error!("Error locking state stream: {}", e);
-                        return Err(anyhow::anyhow!("error publishing message"));
-                    }
-                }
-            }
-            Err(anyhow::anyhow!(
-                "reached maximum attempts while attempting to get a lock"
-            ))
-        })
-        .await;
-        result.map_err(|_e| anyhow::anyhow!("timed out getting lock"))?
+        get_lock(&self.client, subject).await
     }
 
-    // TODO: add a way to specify labels that should apply to this mapping. That way you can
-    // provide host labels that should grant an entity access to a secret.
+    // NOTE: Host labels are not part of mappings, they are restricted by the `host_labels`
+    // property of the policy of secret references instead. See the `policy` module.
     async fn add_mapping(&self, entity: String, values: HashSet<String>) -> anyhow::Result<()> {
         let c = jetstream::new(self.client.clone());
         let subject = format!("{}.{}", self.lock_stream_name(), entity);
@@ -580,6 +545,8 @@ impl Api {
         Self {
             server_transit_xkey: server_xkey,
             encryption_xkey,
+            previous_encryption_xkeys: Vec::new(),
+            audit_subject: None,
             client,
             subject_base,
             name,
@@ -589,11 +556,84 @@ impl Api {
             api_version,
         }
     }
-}
 
-#[async_trait]
-impl SecretsServer for Api {
-    async fn get(&self, request: SecretRequest) -> Result<SecretResponse, GetSecretError> {
+    /// Allow decrypting secrets that were encrypted with a previous encryption key, so that the
+    /// backend keeps serving secrets while the bucket is re-encrypted with a new key.
+    pub fn with_previous_encryption_xkey(mut self, xkey: XKey) -> Self {
+        self.previous_encryption_xkeys.push(xkey);
+        self
+    }
+
+    /// Publish an [`AuditEvent`] to the given subject for every `get` request.
+    pub fn with_audit_subject(mut self, subject: impl Into<String>) -> Self {
+        self.audit_subject = Some(subject.into());
+        self
+    }
+
+    /// Decrypt a value stored in the secrets bucket, falling back to previous encryption keys
+    fn decrypt(&self, value: &[u8]) -> Result<Vec<u8>, GetSecretError> {
+        std::iter::once(&self.encryption_xkey)
+            .chain(&self.previous_encryption_xkeys)
+            .find_map(|xkey| xkey.open(value, xkey).ok())
+            .ok_or(GetSecretError::DecryptionError)
+    }
+
+    async fn publish_audit_event(
+        &self,
+        subject: &str,
+        request: &SecretRequest,
+        response: &Result<SecretResponse, GetSecretError>,
+    ) {
+        let (version, result, error) = match response {
+            Ok(SecretResponse {
+                secret: Some(secret),
+                ..
+            }) => (Some(secret.version.clone()), AuditResult::Success, None),
+            Ok(_) => (request.version.clone(), AuditResult::Success, None),
+            Err(e) => (
+                request.version.clone(),
+                AuditResult::Failure,
+                Some(e.to_string()),
+            ),
+        };
+        let event = AuditEvent {
+            backend: self.name.clone(),
+            subject: Claims::<Component>::decode(&request.context.entity_jwt)
+                .map(|claims| claims.subject)
+                .or_else(|_| {
+                    Claims::<CapabilityProvider>::decode(&request.context.entity_jwt)
+                        .map(|claims| claims.subject)
+                })
+                .ok(),
+            host: Claims::<Host>::decode(&request.context.host_jwt)
+                .map(|claims| claims.subject)
+                .ok(),
+            key: request.key.clone(),
+            version,
+            result,
+            error,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+        };
+        let payload = match serde_json::to_vec(&event) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!(error = %e, "failed to serialize audit event");
+                return;
+            }
+        };
+        if let Err(e) = self
+            .client
+            .publish(subject.to_string(), payload.into())
+            .await
+        {
+            warn!(error = %e, subject, "failed to publish audit event");
+        }
+    }
+
+    async fn fetch(&self, request: &SecretRequest) -> Result<SecretResponse, GetSecretError> {
         // First validate the entity JWT
         if let Err(e) = request.context.valid_claims() {
             return Err(GetSecretError::InvalidEntityJWT(e.to_string()));
@@ -619,31 +659,50 @@ impl SecretsServer for Api {
             Claims::decode(&request.context.entity_jwt);
         let provider_claims: wascap::Result<Claims<CapabilityProvider>> =
             Claims::decode(&request.context.entity_jwt);
-        let subject = match (component_claims, provider_claims) {
-            (Ok(c), _) => c.subject,
-            (_, Ok(p)) => p.subject,
+        let (subject, issuer) = match (component_claims, provider_claims) {
+            (Ok(c), _) => (c.subject, c.issuer),
+            (_, Ok(p)) => (p.subject, p.issuer),
             (Err(e), _) => return Err(GetSecretError::InvalidEntityJWT(e.to_string())),
         };
 
+        // Entities are granted access to secrets by mappings of either their own public key or
+        // the account that issued them, so that access survives entities being issued new keys
         let store = self
             .state_bucket()
             .await
             .map_err(|e| GetSecretError::UpstreamError(e.to_string()))?;
-        let entry = store
-            .get(&subject)
-            .await
-            .map_err(|e| GetSecretError::UpstreamError(e.to_string()))?;
-
-        if entry.is_none() {
-            return Err(GetSecretError::Unauthorized);
+        let mut values: HashSet<String> = HashSet::new();
+        for entity in [&subject, &issuer] {
+            let entry = store
+                .get(entity)
+                .await
+                .map_err(|e| GetSecretError::UpstreamError(e.to_string()))?;
+            if let Some(entry) = entry {
+                let mapped: HashSet<String> = serde_json::from_slice(&entry)
+                    .map_err(|e| GetSecretError::UpstreamError(e.to_string()))?;
+                values.extend(mapped);
+            }
         }
-        let values: HashSet<String> = serde_json::from_slice(&entry.unwrap())
-            .map_err(|e| GetSecretError::UpstreamError(e.to_string()))?;
 
         if !values.contains(&request.key) {
             return Err(GetSecretError::Unauthorized);
         }
 
+        // Mapped entities must also satisfy the policy of the secret reference
+        let policy = policy::parse(&request.context.application.policy)?;
+        let host_labels = host_claims
+            .metadata
+            .and_then(|host| host.labels)
+            .unwrap_or_default();
+        policy::evaluate(
+            &policy,
+            &policy::PolicyContext {
+                application: request.context.application.name.as_deref(),
+                issuer: &issuer,
+                host_labels: &host_labels,
+            },
+        )?;
+
         let js = jetstream::new(self.client.clone());
         let secrets = js
             .get_key_value(&self.bucket)
@@ -653,6 +712,10 @@ impl SecretsServer for Api {
         let entry = match &request.version {
             Some(v) => {
                 let revision = str::parse::<u64>(v).map_err(|_| GetSecretError::InvalidRequest)?;
+                // Revisions that were re-encrypted by a key rotation live on under a new revision
+                let revision = resolve_revision(&store, &request.key, revision)
+                    .await
+                    .map_err(|e| GetSecretError::UpstreamError(e.to_string()))?;
 
                 let mut key_hist = secrets
                     .history(&request.key)
@@ -677,10 +740,7 @@ impl SecretsServer for Api {
             ..Default::default()
         };
 
-        let decrypted = self
-            .encryption_xkey
-            .open(&entry.value, &self.encryption_xkey)
-            .map_err(|_| GetSecretError::DecryptionError)?;
+        let decrypted = self.decrypt(&entry.value)?;
 
         match String::from_utf8(decrypted) {
             Ok(s) => {
@@ -697,6 +757,17 @@ impl SecretsServer for Api {
         };
         Ok(response)
     }
+}
+
+#[async_trait]
+impl SecretsServer for Api {
+    async fn get(&self, request: SecretRequest) -> Result<SecretResponse, GetSecretError> {
+        let response = self.fetch(&request).await;
+        if let Some(subject) = &self.audit_subject {
+            self.publish_audit_event(subject, &request, &response).await;
+        }
+        response
+    }
 
     fn server_xkey(&self) -> XKey {
         let xkey = XKey::from_public_key(self.server_transit_xkey.public_key().as_str()).unwrap();
@@ -704,6 +775,156 @@ impl SecretsServer for Api {
     }
 }
 
+/// The name of the bucket that the backend with the given name keeps its state in.
+pub fn state_bucket_name(name: &str) -> String {
+    format!("SECRETS_{name}_state")
+}
+
+/// The name of the stream that the backend with the given name uses to coordinate write access to
+/// its state bucket.
+fn lock_stream_name(name: &str) -> String {
+    format!("SECRETS_{name}_state_lock")
+}
+
+/// Create the lock stream of the backend with the given name, if it does not exist yet.
+pub(crate) async fn ensure_state_lock_stream(
+    client: &async_nats::Client,
+    name: &str,
+) -> anyhow::Result<()> {
+    let lock_stream_name = lock_stream_name(name);
+    let js = jetstream::new(client.clone());
+    js.get_or_create_stream(StreamConfig {
+        name: lock_stream_name.clone(),
+        description: Some("Lock stream for secrets state".to_string()),
+        discard: DiscardPolicy::New,
+        discard_new_per_subject: true,
+        storage: StorageType::Memory,
+        max_messages_per_subject: 1,
+        max_age: Duration::from_secs(3),
+        subjects: vec![format!("{lock_stream_name}.*")],
+        ..Default::default()
+    })
+    .await?;
+    Ok(())
+}
+
+/// Lock the given subject of a lock stream, returning the ack of the lock message, which must be
+/// deleted from the stream to release the lock.
+async fn get_lock(client: &async_nats::Client, subject: String) -> anyhow::Result<PublishAck> {
+    // TODO: make this all configurable
+    let max_attempts = 5;
+    let min_interval = Duration::from_millis(100);
+    let max_interval = Duration::from_millis(1000);
+    let backoff = Backoff::new(max_attempts, min_interval, max_interval);
+
+    // Wrap the retries in timeout to ensure the code runs for a given period
+    let result = tokio::time::timeout(Duration::from_secs(3), async {
+        for duration in backoff {
+            // Attempt to request a lock
+            let resp = match client.request(subject.clone(), "lock".into()).await {
+                Ok(msg) => msg,
+                Err(e) => match duration {
+                    Some(duration) => {
+                        debug!("Error locking state stream: {}", e);
+                        tokio::time::sleep(duration).await;
+                        continue;
+                    }
+                    None => {
+                        debug!("Error locking state stream: {}", e);
+                        return Err(anyhow::anyhow!("timed out getting lock"));
+                    }
+                },
+            };
+
+            // Parse NATS response to lock request
+            match serde_json::from_slice(&resp.payload) {
+                Ok(Response::Ok(p)) => return Ok(p),
+                Ok(Response::Err { error: e }) => match duration {
+                    Some(duration) => {
+                        debug!("Error locking state stream: {:?}", e);
+                        tokio::time::sleep(duration).await;
+                        continue;
+                    }
+                    None => {
+                        debug!("Error locking state stream: {}", e);
+                        return Err(anyhow::anyhow!("unable to get lock"));
+                    }
+                },
+                Err(e) => {
+                    error!("Error locking state stream: {}", e);
+                    return Err(anyhow::anyhow!("error publishing message"));
+                }
+            }
+        }
+        Err(anyhow::anyhow!(
+            "reached maximum attempts while attempting to get a lock"
+        ))
+    })
+    .await;
+    result.map_err(|_e| anyhow::anyhow!("timed out getting lock"))?
+}
+
+/// Lock the revision mappings of the given secret in the state bucket of the backend with the
+/// given name, returning the sequence of the lock message, which must be deleted from the lock
+/// stream to release the lock.
+///
+/// Subjects of the lock stream only have a single token after the stream name, so dots in the
+/// name of the secret are replaced. Secrets whose names only differ in dots and underscores share
+/// a lock.
+pub(crate) async fn lock_revisions(
+    client: &async_nats::Client,
+    name: &str,
+    key: &str,
+) -> anyhow::Result<u64> {
+    let subject = format!(
+        "{}.{}",
+        lock_stream_name(name),
+        revisions_key(key).replace('.', "_")
+    );
+    Ok(get_lock(client, subject).await?.sequence)
+}
+
+/// Release a lock taken with [`lock_revisions`].
+pub(crate) async fn unlock_revisions(
+    client: &async_nats::Client,
+    name: &str,
+    sequence: u64,
+) -> anyhow::Result<()> {
+    let js = jetstream::new(client.clone());
+    js.get_stream(lock_stream_name(name))
+        .await?
+        .delete_message(sequence)
+        .await?;
+    Ok(())
+}
+
+/// The key in the state bucket that holds the revision mappings of the given secret.
+pub(crate) fn revisions_key(key: &str) -> String {
+    format!("{REVISIONS_PREFIX}{key}")
+}
+
+/// Read the mapping of revisions of the given secret from before a key rotation to the
+/// revisions holding the re-encrypted values.
+pub(crate) async fn revision_map(state: &Store, key: &str) -> anyhow::Result<HashMap<u64, u64>> {
+    match state.get(revisions_key(key)).await? {
+        Some(entry) => Ok(serde_json::from_slice(&entry)?),
+        None => Ok(HashMap::new()),
+    }
+}
+
+/// Resolve a revision of the given secret to the revision that currently holds its value.
+pub(crate) async fn resolve_revision(
+    state: &Store,
+    key: &str,
+    revision: u64,
+) -> anyhow::Result<u64> {
+    Ok(revision_map(state, key)
+        .await?
+        .get(&revision)
+        .copied()
+        .unwrap_or(revision))
+}
+
 pub(crate) async fn find_key_rev(h: &mut History, revision: u64) -> Option<Entry> {
     while let Some(entry) = h.next().await {
         if let Ok(entry) = entry {
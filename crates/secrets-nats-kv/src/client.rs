@@ -1,12 +1,16 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::{bail, ensure, Context};
-use async_nats::jetstream;
+use async_nats::jetstream::{self, kv::Operation};
+use futures::TryStreamExt;
 use wasmcloud_secrets_types::Secret;
 
 pub const SECRETS_API_VERSION: &str = "v1alpha1";
 
-use crate::{find_key_rev, PutSecretError, PutSecretRequest, PutSecretResponse};
+use crate::{
+    ensure_state_lock_stream, find_key_rev, lock_revisions, revision_map, revisions_key,
+    state_bucket_name, unlock_revisions, PutSecretError, PutSecretRequest, PutSecretResponse,
+};
 
 /// Helper function wrapper around [`put_secret`] that allows putting multiple secrets in the secret store.
 /// See the documentation for [`put_secret`] for more information.
@@ -137,6 +141,168 @@ pub async fn get_secret(
     Ok(secret)
 }
 
+/// Re-encrypt every revision of every secret in the NATS KV backed secret store with a new
+/// encryption key. Like [`get_secret`], this works directly against the KV store.
+///
+/// Each revision is written back to the bucket under a new revision, and the mapping from the
+/// old revision to the new one is recorded in the state bucket so that secret references that
+/// pin a version keep resolving. Running backends only need to be started with the new key as
+/// their encryption key and the old key as a previous encryption key before running this, so that
+/// they can keep serving both old and re-encrypted revisions while the rotation is in progress.
+/// Secrets that were already re-encrypted with the new key are skipped, so an interrupted rotation
+/// can be run again.
+///
+/// Re-encrypted revisions are written only if no other revision of the secret was written since
+/// its history was read, so a rotation fails rather than writing re-encrypted revisions after a
+/// value written concurrently. Revisions already discarded to respect the history limit of the
+/// bucket are not re-encrypted, and the re-encrypted revisions never exceed that limit.
+///
+/// Returns the number of revisions that were re-encrypted.
+///
+/// # Arguments
+/// - `nats_client` - the NATS client connected to a server that the secret store is accessible on, jetstream enabled
+/// - `secret_bucket_name` - the name of the secret bucket to re-encrypt
+/// - `name` - the name of the secret store, which names the bucket it keeps its state in
+/// - `encryption_xkey` - the encryption key the secrets are currently encrypted with. Must be constructed from a seed key
+/// - `new_encryption_xkey` - the encryption key to re-encrypt the secrets with. Must be constructed from a seed key
+pub async fn rotate_encryption_key(
+    nats_client: &async_nats::Client,
+    secret_bucket_name: &str,
+    name: &str,
+    encryption_xkey: &nkeys::XKey,
+    new_encryption_xkey: &nkeys::XKey,
+) -> anyhow::Result<usize> {
+    let js = jetstream::new(nats_client.clone());
+    let secrets = js.get_key_value(secret_bucket_name).await?;
+    let state = js.get_key_value(state_bucket_name(name)).await?;
+    ensure_state_lock_stream(nats_client, name).await?;
+
+    let keys: Vec<String> = secrets
+        .keys()
+        .await
+        .context("failed to list secrets")?
+        .try_collect()
+        .await
+        .context("failed to list secrets")?;
+
+    // Every revision of a secret is written again, so the history limit of the bucket must be able
+    // to hold all of them, or re-encrypted revisions would be discarded while writing the rest
+    let max_history = secrets
+        .status()
+        .await
+        .context("failed to get status of secret bucket")?
+        .history();
+
+    let mut rotated = 0;
+    for key in keys {
+        let history: Vec<_> = secrets
+            .history(&key)
+            .await
+            .with_context(|| format!("failed to get history for secret '{key}'"))?
+            .try_collect()
+            .await
+            .with_context(|| format!("failed to get history for secret '{key}'"))?;
+        let Some(mut last_revision) = history.last().map(|entry| entry.revision) else {
+            continue;
+        };
+        let entries: Vec<_> = history
+            .into_iter()
+            .filter(|entry| entry.operation == Operation::Put)
+            .collect();
+        ensure!(
+            entries.len() as i64 <= max_history,
+            "secret '{key}' has more revisions than the history limit of the bucket"
+        );
+
+        let mut values = Vec::with_capacity(entries.len());
+        let mut current = true;
+        for entry in &entries {
+            let value = match encryption_xkey.open(&entry.value, encryption_xkey) {
+                Ok(value) => {
+                    current = false;
+                    value
+                }
+                Err(_) => new_encryption_xkey
+                    .open(&entry.value, new_encryption_xkey)
+                    .with_context(|| {
+                        format!(
+                            "failed to decrypt revision {} of secret '{key}' with either key",
+                            entry.revision
+                        )
+                    })?,
+            };
+            values.push(value);
+        }
+        if current {
+            continue;
+        }
+
+        // The secret and its revision mappings are written under the state lock, like every other
+        // write to the state bucket, so that a failed lock leaves the secret untouched
+        let lock = lock_revisions(nats_client, name, &key)
+            .await
+            .with_context(|| format!("failed to lock revisions of secret '{key}'"))?;
+        let written = async {
+            // Write every revision in order, so that the latest revision remains the latest
+            // value. Each write expects the previous one to be the last revision, so that the
+            // rotation fails if the secret is written concurrently.
+            let mut revisions = HashMap::with_capacity(entries.len());
+            for (entry, value) in entries.iter().zip(values) {
+                let encrypted = new_encryption_xkey
+                    .seal(&value, new_encryption_xkey)
+                    .context("failed to encrypt secret with the new encryption key")?;
+                last_revision = secrets
+                    .update(&key, encrypted.into(), last_revision)
+                    .await
+                    .with_context(|| {
+                        format!("failed to write re-encrypted secret '{key}', was it written during the rotation?")
+                    })?;
+                revisions.insert(entry.revision, last_revision);
+            }
+            store_revisions(&state, &key, revisions).await
+        }
+        .await;
+        unlock_revisions(nats_client, name, lock)
+            .await
+            .with_context(|| format!("failed to unlock revisions of secret '{key}'"))?;
+        written?;
+
+        for entry in &entries {
+            // Revisions may already have been discarded to respect the history limit of the bucket
+            let _ = secrets.stream.delete_message(entry.revision).await;
+        }
+        rotated += entries.len();
+    }
+
+    Ok(rotated)
+}
+
+/// Point the revisions of a secret that were remapped by previous rotations to the revisions
+/// holding their re-encrypted values, and add the given mappings.
+async fn store_revisions(
+    state: &jetstream::kv::Store,
+    key: &str,
+    revisions: HashMap<u64, u64>,
+) -> anyhow::Result<()> {
+    let mut map = revision_map(state, key).await?;
+    for revision in map.values_mut() {
+        if let Some(new) = revisions.get(revision) {
+            *revision = *new;
+        }
+    }
+    map.extend(revisions);
+    state
+        .put(
+            revisions_key(key),
+            serde_json::to_vec(&map)
+                .context("failed to serialize revisions")?
+                .into(),
+        )
+        .await
+        .with_context(|| format!("failed to store revisions of secret '{key}'"))?;
+    Ok(())
+}
+
 /// Add the allowed secrets a given public key is allowed to access
 ///
 /// # Arguments
@@ -37,6 +37,8 @@ enum Command {
     AddMapping(AddSecretMappingCommand),
     /// Remove a secret mapping from the NATS KV secrets backend
     RemoveMapping(RemoveSecretMappingCommand),
+    /// Re-encrypt all secrets in the NATS KV secrets backend with a new encryption key
+    RotateKey(RotateKeyCommand),
 }
 
 #[derive(Parser)]
@@ -44,12 +46,20 @@ struct RunCommand {
     /// The server's encryption XKey, used to encrypt secrets before storing in NATS.
     #[clap(short, long, env = "ENCRYPTION_XKEY_SEED")]
     encryption_xkey_seed: String,
+    /// Encryption XKeys that secrets were previously encrypted with. These are only used to
+    /// decrypt secrets while they are being re-encrypted with `rotate-key`.
+    #[clap(long, env = "PREVIOUS_ENCRYPTION_XKEY_SEEDS", value_delimiter = ',')]
+    previous_encryption_xkey_seeds: Vec<String>,
     /// The server's transit XKey, used to decrypt secrets sent to the server.
     #[clap(short, long, env = "TRANSIT_XKEY_SEED")]
     transit_xkey_seed: String,
     /// The subject prefix to use for all requests to the secrets backend, defaults to `wasmcloud.secrets`
     #[clap(short, long, default_value = "wasmcloud.secrets")]
     subject_base: String,
+    /// The subject to publish an audit event to for every secret request. Audit events are not
+    /// published if this is not set.
+    #[clap(long, env = "SECRETS_AUDIT_SUBJECT")]
+    audit_subject: Option<String>,
     /// The name of the secrets backend, defaults to `nats-kv`
     #[clap(short = 'n', long, default_value = "nats-kv")]
     name: String,
@@ -147,7 +157,8 @@ struct AddSecretMappingCommand {
     /// The subject prefix to use for all requests to the secrets backend, defaults to `wasmcloud.secrets`
     #[clap(short, long, default_value = "wasmcloud.secrets")]
     subject_base: String,
-    /// The public key identity of the entity that is allowed to access the secrets
+    /// The public key identity of the entity that is allowed to access the secrets, or of the
+    /// account that issued the entities that are allowed to access the secrets
     public_key: String,
     /// The names of the secrets that the public key is allowed to access. Can be specified multiple times.
     #[clap(long = "secret")]
@@ -175,6 +186,28 @@ struct RemoveSecretMappingCommand {
     global: GlobalOpts,
 }
 
+#[derive(Parser, Debug, Clone)]
+struct RotateKeyCommand {
+    /// The encryption XKey that secrets are currently encrypted with.
+    #[clap(short, long, env = "ENCRYPTION_XKEY_SEED")]
+    encryption_xkey_seed: String,
+    /// The encryption XKey to re-encrypt secrets with.
+    #[clap(long, env = "NEW_ENCRYPTION_XKEY_SEED")]
+    new_encryption_xkey_seed: String,
+    /// The NATS address to connect to where the backend is running
+    #[clap(long, default_value = "127.0.0.1:4222")]
+    nats_address: String,
+    /// The name of the secrets backend, defaults to `nats-kv`
+    #[clap(short = 'n', long, default_value = "nats-kv")]
+    name: String,
+    /// The NATS KV bucket used for storing secrets
+    #[clap(short = 'b', long, default_value = "WASMCLOUD_SECRETS")]
+    secrets_bucket: String,
+
+    #[command(flatten)]
+    global: GlobalOpts,
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
@@ -187,6 +220,7 @@ async fn main() -> anyhow::Result<()> {
         Command::Get(args) => get(args).await,
         Command::AddMapping(args) => add_mapping(args).await,
         Command::RemoveMapping(args) => remove_mapping(args).await,
+        Command::RotateKey(args) => rotate_key(args).await,
     }
 }
 
@@ -217,7 +251,7 @@ async fn run(args: RunCommand) -> anyhow::Result<()> {
             .with_context(|| format!("failed to connect to NATS at {}", args.nats_address))?,
     };
 
-    let api = Api::new(
+    let mut api = Api::new(
         server_xkey,
         encryption_xkey,
         nats_client,
@@ -228,6 +262,14 @@ async fn run(args: RunCommand) -> anyhow::Result<()> {
         args.nats_queue_base,
         args.secrets_api_version,
     );
+    for seed in args.previous_encryption_xkey_seeds {
+        let xkey =
+            XKey::from_seed(&seed).context("failed to create previous encryption key from seed")?;
+        api = api.with_previous_encryption_xkey(xkey);
+    }
+    if let Some(subject) = args.audit_subject {
+        api = api.with_audit_subject(subject);
+    }
 
     println!("Starting secrets backend '{}'", args.name);
     api.run().await
@@ -417,3 +459,49 @@ async fn remove_mapping(args: RemoveSecretMappingCommand) -> anyhow::Result<()>
     );
     Ok(())
 }
+
+async fn rotate_key(args: RotateKeyCommand) -> anyhow::Result<()> {
+    let nats_client = match args.global.nats_creds_file {
+        Some(creds_file) => async_nats::ConnectOptions::new()
+            .credentials_file(creds_file.clone())
+            .await
+            .context(format!(
+                "failed to read NATS credentials file '{}'",
+                &creds_file
+            ))?
+            .connect(&args.nats_address)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to connect to NATS at {} with credentials file '{}'",
+                    args.nats_address, creds_file
+                )
+            })?,
+        None => async_nats::connect(&args.nats_address)
+            .await
+            .with_context(|| format!("failed to connect to NATS at {}", args.nats_address))?,
+    };
+
+    let encryption_xkey = XKey::from_seed(&args.encryption_xkey_seed)
+        .context("failed to create encryption key from seed")?;
+    let new_encryption_xkey = XKey::from_seed(&args.new_encryption_xkey_seed)
+        .context("failed to create new encryption key from seed")?;
+    ensure!(
+        encryption_xkey.public_key() != new_encryption_xkey.public_key(),
+        "the new encryption key must differ from the current encryption key"
+    );
+
+    let rotated = client::rotate_encryption_key(
+        &nats_client,
+        &args.secrets_bucket,
+        &args.name,
+        &encryption_xkey,
+        &new_encryption_xkey,
+    )
+    .await?;
+    println!(
+        "Re-encrypted {rotated} secret revisions in bucket '{}'",
+        args.secrets_bucket
+    );
+    Ok(())
+}
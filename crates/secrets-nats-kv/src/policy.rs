@@ -0,0 +1,186 @@
+//! Evaluation of the policy properties of secret references.
+//!
+//! Policy properties narrow the access granted by mappings: an entity mapped to a secret can only
+//! read it if the request also satisfies every property of the secret reference's policy. The
+//! supported properties are:
+//!
+//! - `application`: the name of the application the entity must be a part of
+//! - `issuers`: the account keys, one of which must have issued the entity's JWT
+//! - `host_labels`: the labels, as `key=value` pairs, that the requesting host must have
+//!
+//! List properties may be given either as JSON arrays and objects or as comma-separated strings,
+//! as set by `wash secrets put --property`. Other properties are freeform and may be meant for
+//! other backends, so they are ignored with a warning.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+use tracing::warn;
+use wasmcloud_secrets_types::{GetSecretError, Policy, SECRET_POLICY_PROPERTIES_TYPE};
+
+/// The name of the application the entity must be a part of
+pub const APPLICATION_PROPERTY: &str = "application";
+/// The account keys allowed to issue the entity's JWT
+pub const ISSUERS_PROPERTY: &str = "issuers";
+/// The labels that the requesting host must have
+pub const HOST_LABELS_PROPERTY: &str = "host_labels";
+
+/// The facts about a secret request that policies are evaluated against
+pub(crate) struct PolicyContext<'a> {
+    /// The name of the application of the entity, if any
+    pub application: Option<&'a str>,
+    /// The issuer of the entity's JWT
+    pub issuer: &'a str,
+    /// The labels of the requesting host
+    pub host_labels: &'a HashMap<String, String>,
+}
+
+/// Parse the serialized [`Policy`] sent with a secret request, an empty string being a policy
+/// without properties
+pub(crate) fn parse(policy: &str) -> Result<Policy, GetSecretError> {
+    if policy.is_empty() {
+        return Ok(Policy::default());
+    }
+    let policy: Policy = serde_json::from_str(policy)
+        .map_err(|e| GetSecretError::PolicyError(format!("invalid policy: {e}")))?;
+    if policy.policy_type() != SECRET_POLICY_PROPERTIES_TYPE {
+        return Err(GetSecretError::PolicyError(format!(
+            "unsupported policy type `{}`",
+            policy.policy_type()
+        )));
+    }
+    Ok(policy)
+}
+
+/// Evaluate `policy` against `ctx`, returning [`GetSecretError::Unauthorized`] if any property is
+/// not satisfied and [`GetSecretError::PolicyError`] if a property is invalid
+pub(crate) fn evaluate(policy: &Policy, ctx: &PolicyContext<'_>) -> Result<(), GetSecretError> {
+    for (name, value) in policy.properties() {
+        let satisfied = match name.as_str() {
+            APPLICATION_PROPERTY => {
+                let application = value.as_str().ok_or_else(|| invalid(name))?;
+                ctx.application == Some(application)
+            }
+            ISSUERS_PROPERTY => list(value)
+                .ok_or_else(|| invalid(name))?
+                .contains(&ctx.issuer),
+            HOST_LABELS_PROPERTY => labels(value)
+                .ok_or_else(|| invalid(name))?
+                .into_iter()
+                .all(|(k, v)| ctx.host_labels.get(k).map(String::as_str) == Some(v)),
+            other => {
+                warn!(property = other, "ignoring unknown policy property");
+                continue;
+            }
+        };
+        if !satisfied {
+            return Err(GetSecretError::Unauthorized);
+        }
+    }
+    Ok(())
+}
+
+fn invalid(name: &str) -> GetSecretError {
+    GetSecretError::PolicyError(format!("invalid value for policy property `{name}`"))
+}
+
+/// Parse a JSON array of strings or a comma-separated string
+fn list(value: &Value) -> Option<Vec<&str>> {
+    match value {
+        Value::String(s) => Some(s.split(',').map(str::trim).collect()),
+        Value::Array(values) => values.iter().map(Value::as_str).collect(),
+        _ => None,
+    }
+}
+
+/// Parse a JSON object of strings or a comma-separated string of `key=value` pairs
+fn labels(value: &Value) -> Option<Vec<(&str, &str)>> {
+    match value {
+        Value::String(s) => s
+            .split(',')
+            .map(|pair| pair.split_once('=').map(|(k, v)| (k.trim(), v.trim())))
+            .collect(),
+        Value::Object(labels) => labels
+            .iter()
+            .map(|(k, v)| v.as_str().map(|v| (k.as_str(), v)))
+            .collect(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+    use wasmcloud_secrets_types::{GetSecretError, Policy};
+
+    use super::{evaluate, parse, PolicyContext};
+
+    const ISSUER: &str = "ACZBUFUCUBYM3EGEIV6C2TSC5AWG52WI5TQM2CXDIZRU2R7D3IJYXUZQ";
+
+    fn policy(properties: serde_json::Value) -> Policy {
+        let serde_json::Value::Object(properties) = properties else {
+            panic!("properties must be an object");
+        };
+        let policy = Policy::new(properties.into_iter().collect());
+        parse(&serde_json::to_string(&policy).expect("failed to serialize policy"))
+            .expect("failed to parse policy")
+    }
+
+    #[test]
+    fn test_evaluate_policy() {
+        let host_labels = HashMap::from([
+            ("region".to_string(), "us-east".to_string()),
+            ("tier".to_string(), "prod".to_string()),
+        ]);
+        let ctx = PolicyContext {
+            application: Some("petclinic"),
+            issuer: ISSUER,
+            host_labels: &host_labels,
+        };
+
+        assert!(evaluate(&parse("").expect("failed to parse policy"), &ctx).is_ok());
+        assert!(evaluate(
+            &policy(json!({
+                "application": "petclinic",
+                "issuers": format!("AOTHER, {ISSUER}"),
+                "host_labels": "region=us-east",
+            })),
+            &ctx
+        )
+        .is_ok());
+        assert!(evaluate(
+            &policy(json!({
+                "issuers": [ISSUER],
+                "host_labels": { "region": "us-east", "tier": "prod" },
+            })),
+            &ctx
+        )
+        .is_ok());
+
+        assert!(matches!(
+            evaluate(&policy(json!({ "application": "other" })), &ctx),
+            Err(GetSecretError::Unauthorized)
+        ));
+        assert!(matches!(
+            evaluate(&policy(json!({ "issuers": "AOTHER" })), &ctx),
+            Err(GetSecretError::Unauthorized)
+        ));
+        assert!(matches!(
+            evaluate(&policy(json!({ "host_labels": "region=eu-west" })), &ctx),
+            Err(GetSecretError::Unauthorized)
+        ));
+        assert!(matches!(
+            evaluate(&policy(json!({ "host_labels": "region" })), &ctx),
+            Err(GetSecretError::PolicyError(_))
+        ));
+        // Unrelated properties do not prevent secrets from resolving
+        assert!(evaluate(&policy(json!({ "unknown": "value" })), &ctx).is_ok());
+        assert!(evaluate(
+            &policy(json!({ "application": "petclinic", "team": "payments" })),
+            &ctx
+        )
+        .is_ok());
+    }
+}
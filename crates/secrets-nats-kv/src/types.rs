@@ -56,3 +56,35 @@ impl From<u64> for PutSecretResponse {
         }
     }
 }
+
+/// The outcome of a `get` request recorded in an [`AuditEvent`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditResult {
+    /// The secret was returned to the requesting host
+    Success,
+    /// The request was rejected or failed, the reason is included in the event
+    Failure,
+}
+
+/// A structured audit event published by the backend for every `get` request it handles.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent {
+    /// The name of the backend that handled the request
+    pub backend: String,
+    /// The public key of the entity the secret was requested for, if its JWT could be decoded
+    pub subject: Option<String>,
+    /// The public key of the host that requested the secret, if its JWT could be decoded
+    pub host: Option<String>,
+    /// The key of the requested secret
+    pub key: String,
+    /// The version that was returned, or the version that was requested if the request failed
+    pub version: Option<String>,
+    /// Whether the secret was returned to the host
+    pub result: AuditResult,
+    /// The reason the request failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Milliseconds since the Unix epoch at which the request was handled
+    pub timestamp: u64,
+}
@@ -1,12 +1,13 @@
 use std::collections::HashSet;
 
 use async_nats::{jetstream, Client};
+use futures::StreamExt;
 use nkeys::{KeyPair, XKey};
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
-use secrets_nats_kv::{Api, PutSecretRequest, PutSecretResponse};
+use secrets_nats_kv::{client, Api, AuditEvent, AuditResult, PutSecretRequest, PutSecretResponse};
 use std::collections::HashMap;
 use wascap::jwt::{Claims, ClaimsBuilder, Component, Host};
-use wasmcloud_secrets_types::{Application, Context, SecretRequest, WASMCLOUD_HOST_XKEY};
+use wasmcloud_secrets_types::{Application, Context, Policy, SecretRequest, WASMCLOUD_HOST_XKEY};
 
 const SUBJECT_BASE: &str = "kvstore_test";
 const NAME_BASE: &str = "nats-kv";
@@ -281,7 +282,476 @@ async fn integration_test_kvstore_version() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn integration_test_kvstore_issuer_mapping_policy() -> anyhow::Result<()> {
+    let client = async_nats::connect("127.0.0.1:4222").await?;
+
+    let encryption_xkey = XKey::new();
+    let server_xkey = XKey::new();
+    let request_key = XKey::new();
+
+    let (api, name) = setup_api(
+        client.clone(),
+        encryption_xkey.seed().unwrap(),
+        server_xkey.seed().unwrap(),
+    );
+
+    let base_sub = api.subject();
+    let _suite = Suite { name: name.clone() };
+    tokio::spawn(async move {
+        api.run().await.unwrap();
+    });
+    // Give the server some time to start
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    let value = PutSecretRequest {
+        key: "test".to_string(),
+        string_secret: Some("value".to_string()),
+        ..Default::default()
+    };
+    let value = serde_json::to_string(&value).unwrap();
+    let v = request_key.seal(value.as_bytes(), &server_xkey).unwrap();
+    let mut headers = async_nats::HeaderMap::new();
+    headers.insert(WASMCLOUD_HOST_XKEY, request_key.public_key().as_str());
+    client
+        .request_with_headers(format!("{base_sub}.put_secret"), headers, v.into())
+        .await?;
+
+    // Map the secret to the account rather than the component
+    let account = wascap::prelude::KeyPair::new_account();
+    let response = client
+        .request(
+            format!("{base_sub}.add_mapping.{}", account.public_key()),
+            serde_json::to_vec(&HashSet::from(["test"]))?.into(),
+        )
+        .await?;
+    assert_eq!(response.payload.to_vec(), b"ok");
+
+    let host_key = KeyPair::new_server();
+    let host_jwt = ClaimsBuilder::<Host>::new()
+        .issuer(account.public_key().as_str())
+        .subject(host_key.public_key().as_str())
+        .with_metadata(Host::new(
+            "test".to_string(),
+            HashMap::from([("region".to_string(), "us-east".to_string())]),
+        ))
+        .build()
+        .encode(&account)?;
+    let request = |policy: HashMap<String, serde_json::Value>| -> anyhow::Result<SecretRequest> {
+        // Every request is made by a newly keyed component issued by the mapped account
+        let entity_jwt = ClaimsBuilder::<Component>::new()
+            .issuer(account.public_key().as_str())
+            .subject(KeyPair::new_module().public_key().as_str())
+            .build()
+            .encode(&account)?;
+        Ok(SecretRequest {
+            key: "test".to_string(),
+            field: None,
+            context: Context {
+                entity_jwt,
+                host_jwt: host_jwt.clone(),
+                application: Application {
+                    name: Some("test".to_string()),
+                    policy: serde_json::to_string(&Policy::new(policy))?,
+                },
+            },
+            version: None,
+        })
+    };
+
+    let secrets_client = wasmcloud_secrets_client::Client::new_with_version(
+        &name,
+        SUBJECT_BASE,
+        client.clone(),
+        Some(TEST_API_VERSION),
+    )
+    .await?;
+
+    let resp = secrets_client
+        .get(
+            request(HashMap::from([
+                ("application".to_string(), "test".into()),
+                ("host_labels".to_string(), "region=us-east".into()),
+            ]))?,
+            XKey::new(),
+        )
+        .await?;
+    assert_eq!(resp.string_secret.unwrap(), "value");
+
+    // Properties meant for other backends are ignored
+    let resp = secrets_client
+        .get(
+            request(HashMap::from([("team".to_string(), "payments".into())]))?,
+            XKey::new(),
+        )
+        .await?;
+    assert_eq!(resp.string_secret.unwrap(), "value");
+
+    let denied = secrets_client
+        .get(
+            request(HashMap::from([(
+                "host_labels".to_string(),
+                "region=eu-west".into(),
+            )]))?,
+            XKey::new(),
+        )
+        .await;
+    assert!(denied.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn integration_test_kvstore_rotate_key_audit() -> anyhow::Result<()> {
+    let client = async_nats::connect("127.0.0.1:4222").await?;
+
+    let old_encryption_xkey = XKey::new();
+    let encryption_xkey = XKey::new();
+    let server_xkey = XKey::new();
+    let request_key = XKey::new();
+
+    let (api, name) = setup_api(
+        client.clone(),
+        encryption_xkey.seed().unwrap(),
+        server_xkey.seed().unwrap(),
+    );
+    let audit_subject = format!("{name}.audit");
+    let api = api
+        .with_previous_encryption_xkey(XKey::from_seed(&old_encryption_xkey.seed().unwrap())?)
+        .with_audit_subject(audit_subject.clone());
+
+    let base_sub = api.subject();
+    let _suite = Suite { name: name.clone() };
+    tokio::spawn(async move {
+        api.run().await.unwrap();
+    });
+    // Give the server some time to start
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    // Write revisions encrypted with the old key, as a backend running with it would have
+    let js = jetstream::new(client.clone());
+    let secrets = js.get_key_value(&name).await?;
+    for value in ["first", "second"] {
+        let encrypted = old_encryption_xkey
+            .seal(value.as_bytes(), &old_encryption_xkey)
+            .unwrap();
+        secrets.put("test", encrypted.into()).await?;
+    }
+
+    let account = wascap::prelude::KeyPair::new_account();
+    let component_key = KeyPair::new_module();
+    let claims: Claims<Component> = ClaimsBuilder::new()
+        .issuer(account.public_key().as_str())
+        .subject(component_key.public_key().as_str())
+        .build();
+    let entity_jwt = claims.encode(&account)?;
+
+    let mut v: HashSet<String> = HashSet::new();
+    v.insert("test".to_string());
+    let payload = serde_json::to_string(&v).unwrap();
+    let response = client
+        .request(
+            format!("{base_sub}.add_mapping.{}", component_key.public_key()),
+            payload.into(),
+        )
+        .await?;
+    assert_eq!(response.payload.to_vec(), b"ok");
+
+    let host_key = KeyPair::new_server();
+    let claims: Claims<Host> = ClaimsBuilder::new()
+        .issuer(account.public_key().as_str())
+        .subject(host_key.public_key().as_str())
+        .with_metadata(Host::new("test".to_string(), HashMap::new()))
+        .build();
+    let host_jwt = claims.encode(&account)?;
+    let request = |version: Option<&str>| SecretRequest {
+        key: "test".to_string(),
+        field: None,
+        context: Context {
+            entity_jwt: entity_jwt.clone(),
+            host_jwt: host_jwt.clone(),
+            application: Application {
+                name: Some("test".to_string()),
+                policy: "".to_string(),
+            },
+        },
+        version: version.map(ToString::to_string),
+    };
+
+    let mut audit = client.subscribe(audit_subject).await?;
+    let nats_client = async_nats::connect("127.0.0.1:4222").await?;
+    let secrets_client = wasmcloud_secrets_client::Client::new_with_version(
+        &name,
+        SUBJECT_BASE,
+        nats_client,
+        Some(TEST_API_VERSION),
+    )
+    .await?;
+
+    // Secrets encrypted with the previous key are still served
+    let resp = secrets_client
+        .get(
+            request(Some("1")),
+            XKey::from_seed(&request_key.seed().unwrap())?,
+        )
+        .await?;
+    assert_eq!(resp.string_secret.unwrap(), "first");
+
+    let msg = audit.next().await.expect("audit event should be published");
+    let event: AuditEvent = serde_json::from_slice(&msg.payload)?;
+    assert_eq!(event.backend, name);
+    assert_eq!(event.key, "test");
+    assert_eq!(event.subject, Some(component_key.public_key()));
+    assert_eq!(event.host, Some(host_key.public_key()));
+    assert_eq!(event.version, Some("1".to_string()));
+    assert_eq!(event.result, AuditResult::Success);
+
+    let rotated = client::rotate_encryption_key(
+        &client,
+        &name,
+        &name,
+        &old_encryption_xkey,
+        &encryption_xkey,
+    )
+    .await?;
+    assert_eq!(rotated, 2);
+
+    // Every revision is now encrypted with the new key, and pinned versions keep resolving
+    let mut history = secrets.history("test").await?;
+    while let Some(entry) = history.next().await {
+        let entry = entry?;
+        assert!(encryption_xkey.open(&entry.value, &encryption_xkey).is_ok());
+    }
+    let resp = secrets_client
+        .get(
+            request(Some("1")),
+            XKey::from_seed(&request_key.seed().unwrap())?,
+        )
+        .await?;
+    assert_eq!(resp.string_secret.unwrap(), "first");
+    let resp = secrets_client
+        .get(
+            request(None),
+            XKey::from_seed(&request_key.seed().unwrap())?,
+        )
+        .await?;
+    assert_eq!(resp.string_secret.unwrap(), "second");
+
+    // Rotating again is a no-op
+    let rotated = client::rotate_encryption_key(
+        &client,
+        &name,
+        &name,
+        &old_encryption_xkey,
+        &encryption_xkey,
+    )
+    .await?;
+    assert_eq!(rotated, 0);
+
+    // Denied requests are audited too
+    let unknown = SecretRequest {
+        key: "unknown".to_string(),
+        ..request(None)
+    };
+    assert!(secrets_client.get(unknown, request_key).await.is_err());
+    let event = loop {
+        let msg = audit.next().await.expect("audit event should be published");
+        let event: AuditEvent = serde_json::from_slice(&msg.payload)?;
+        if event.key == "unknown" {
+            break event;
+        }
+    };
+    assert_eq!(event.result, AuditResult::Failure);
+    assert!(event.error.is_some());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn integration_test_kvstore_rotate_key_history_limit() -> anyhow::Result<()> {
+    let client = async_nats::connect("127.0.0.1:4222").await?;
+
+    let old_encryption_xkey = XKey::new();
+    let encryption_xkey = XKey::new();
+    let (api, name) = setup_api_with_history(
+        client.clone(),
+        encryption_xkey.seed().unwrap(),
+        XKey::new().seed().unwrap(),
+        3,
+    );
+    let _suite = Suite { name: name.clone() };
+    tokio::spawn(async move {
+        api.run().await.unwrap();
+    });
+    // Give the server some time to start
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    // Write more revisions than the bucket keeps, encrypted with the old key
+    let js = jetstream::new(client.clone());
+    let secrets = js.get_key_value(&name).await?;
+    let mut revisions = Vec::new();
+    for value in ["first", "second", "third", "fourth", "fifth"] {
+        let encrypted = old_encryption_xkey
+            .seal(value.as_bytes(), &old_encryption_xkey)
+            .unwrap();
+        revisions.push(secrets.put("test", encrypted.into()).await?);
+    }
+
+    let rotated = client::rotate_encryption_key(
+        &client,
+        &name,
+        &name,
+        &old_encryption_xkey,
+        &encryption_xkey,
+    )
+    .await?;
+    assert_eq!(rotated, 3);
+
+    // The revisions that were kept are re-encrypted, and none of them were discarded
+    let mut values = Vec::new();
+    let mut history = secrets.history("test").await?;
+    while let Some(entry) = history.next().await {
+        let entry = entry?;
+        values.push(String::from_utf8(
+            encryption_xkey.open(&entry.value, &encryption_xkey)?,
+        )?);
+    }
+    assert_eq!(values, ["third", "fourth", "fifth"]);
+
+    // Pinned versions of the kept revisions still resolve to their values
+    let account = KeyPair::new_account();
+    let component_key = KeyPair::new_module();
+    let entity_jwt = ClaimsBuilder::<Component>::new()
+        .issuer(account.public_key().as_str())
+        .subject(component_key.public_key().as_str())
+        .build()
+        .encode(&account)?;
+    let host_jwt = ClaimsBuilder::<Host>::new()
+        .issuer(account.public_key().as_str())
+        .subject(KeyPair::new_server().public_key().as_str())
+        .with_metadata(Host::new("test".to_string(), HashMap::new()))
+        .build()
+        .encode(&account)?;
+    let response = client
+        .request(
+            format!(
+                "{SUBJECT_BASE}.{TEST_API_VERSION}.{name}.add_mapping.{}",
+                component_key.public_key()
+            ),
+            serde_json::to_string(&HashSet::from(["test"]))?.into(),
+        )
+        .await?;
+    assert_eq!(response.payload.to_vec(), b"ok");
+
+    let secrets_client = wasmcloud_secrets_client::Client::new_with_version(
+        &name,
+        SUBJECT_BASE,
+        client.clone(),
+        Some(TEST_API_VERSION),
+    )
+    .await?;
+    for (revision, value) in revisions
+        .iter()
+        .zip(["first", "second", "third", "fourth", "fifth"])
+    {
+        let request = SecretRequest {
+            key: "test".to_string(),
+            field: None,
+            context: Context {
+                entity_jwt: entity_jwt.clone(),
+                host_jwt: host_jwt.clone(),
+                application: Application::default(),
+            },
+            version: Some(revision.to_string()),
+        };
+        let resp = secrets_client.get(request, XKey::new()).await;
+        // Revisions discarded before the rotation stay discarded
+        if *revision < revisions[2] {
+            assert!(resp.is_err());
+        } else {
+            assert_eq!(resp?.string_secret.unwrap(), value);
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn integration_test_kvstore_rotate_key_state_lock() -> anyhow::Result<()> {
+    let client = async_nats::connect("127.0.0.1:4222").await?;
+
+    let old_encryption_xkey = XKey::new();
+    let encryption_xkey = XKey::new();
+    let (api, name) = setup_api(
+        client.clone(),
+        encryption_xkey.seed().unwrap(),
+        XKey::new().seed().unwrap(),
+    );
+    let _suite = Suite { name: name.clone() };
+    tokio::spawn(async move {
+        api.run().await.unwrap();
+    });
+    // Give the server some time to start
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    let js = jetstream::new(client.clone());
+    let secrets = js.get_key_value(&name).await?;
+    let encrypted = old_encryption_xkey
+        .seal(b"value", &old_encryption_xkey)
+        .unwrap();
+    secrets.put("test", encrypted.into()).await?;
+
+    // Rotation fails without touching the secret while another writer holds the state lock
+    let lock = js
+        .publish(
+            format!("SECRETS_{name}_state_lock.revisions_test"),
+            "lock".into(),
+        )
+        .await?
+        .await?;
+    let err = client::rotate_encryption_key(
+        &client,
+        &name,
+        &name,
+        &old_encryption_xkey,
+        &encryption_xkey,
+    )
+    .await
+    .expect_err("rotation should fail while the state is locked");
+    assert!(err.to_string().contains("failed to lock revisions"));
+    let entry = secrets.entry("test").await?.unwrap();
+    assert!(old_encryption_xkey
+        .open(&entry.value, &old_encryption_xkey)
+        .is_ok());
+
+    // Once the lock is released, the rotation goes through
+    js.get_stream(format!("SECRETS_{name}_state_lock"))
+        .await?
+        .delete_message(lock.sequence)
+        .await?;
+    let rotated = client::rotate_encryption_key(
+        &client,
+        &name,
+        &name,
+        &old_encryption_xkey,
+        &encryption_xkey,
+    )
+    .await?;
+    assert_eq!(rotated, 1);
+
+    Ok(())
+}
+
 fn setup_api(client: Client, enc_seed: String, server_seed: String) -> (Api, String) {
+    setup_api_with_history(client, enc_seed, server_seed, 64)
+}
+
+fn setup_api_with_history(
+    client: Client,
+    enc_seed: String,
+    server_seed: String,
+    max_secret_history: usize,
+) -> (Api, String) {
     let server_xkey = XKey::from_seed(&server_seed).unwrap();
     let encryption_key = XKey::from_seed(&enc_seed).unwrap();
 
@@ -300,7 +770,7 @@ fn setup_api(client: Client, enc_seed: String, server_seed: String) -> (Api, Str
             SUBJECT_BASE.to_string(),
             name.clone(),
             name.clone(),
-            64,
+            max_secret_history,
             "wasmcloud_secrets_test".to_string(),
             TEST_API_VERSION.to_string(),
         ),
@@ -28,6 +28,10 @@ pub const SECRET_POLICY_PROPERTIES_TYPE: &str = "properties.secret.wasmcloud.dev
 /// The prefix for all secret keys in the config store
 pub const SECRET_PREFIX: &str = "SECRET";
 
+/// The operation on which secrets backends announce that a new version of a secret was stored,
+/// published as `{prefix}.{version}.{backend}.rotated`
+pub const SECRET_ROTATED_OPERATION: &str = "rotated";
+
 /// The request context for retrieving a secret
 #[derive(Serialize, Deserialize, Default)]
 pub struct Context {
@@ -150,6 +154,17 @@ pub struct Secret {
     pub binary_secret: Option<Vec<u8>>,
 }
 
+/// A notification published by a secrets backend when a new version of a secret is stored, so
+/// that hosts can refresh the secret for the components and providers that are already using it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SecretRotated {
+    /// The key of the secret in the secret store, matching [`SecretRequest::key`].
+    pub key: String,
+    /// The new version of the secret, if the secret store versions secrets.
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
 /// The representation of a secret reference in the config store.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SecretConfig {
@@ -346,6 +361,16 @@ impl Policy {
             ..Default::default()
         }
     }
+
+    /// Returns the type of the policy, used to version the format of the properties
+    pub fn policy_type(&self) -> &str {
+        &self.policy_type
+    }
+
+    /// Returns the properties of the policy, which are evaluated by the secrets backend
+    pub fn properties(&self) -> &HashMap<String, serde_json::Value> {
+        &self.properties
+    }
 }
 
 #[async_trait]
@@ -394,4 +419,17 @@ mod test {
             )
         );
     }
+
+    #[test]
+    fn test_secret_rotated_version_optional() {
+        let rotated: crate::SecretRotated =
+            serde_json::from_str(r#"{"key":"db-password"}"#).expect("should deserialize");
+        assert_eq!(
+            rotated,
+            crate::SecretRotated {
+                key: "db-password".to_string(),
+                version: None,
+            }
+        );
+    }
 }
@@ -0,0 +1,224 @@
+//! A wasmCloud secrets backend serving secrets stored in the KV v2 secrets engine of HashiCorp
+//! Vault.
+//!
+//! The `key` of a [`SecretRequest`] is the path of the secret in the mount, the `field` selects
+//! a field of the secret data and the `version` is a KV v2 version number. A secret can only be
+//! read by the components and providers whose public keys are listed, separated by commas, in
+//! the [`ENTITIES_METADATA_KEY`] custom metadata of the secret. Which secrets the backend can
+//! read at all is determined by the policies of the Vault token used by the backend.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use nkeys::XKey;
+use tracing::{error, info};
+use vaultrs::api::kv2::requests::ReadSecretRequest;
+use vaultrs::client::{VaultClient, VaultClientSettingsBuilder};
+use vaultrs::error::ClientError;
+use wascap::jwt::{CapabilityProvider, Claims, Component};
+use wasmcloud_secrets_types::{
+    GetSecretError, Secret, SecretRequest, SecretResponse, SecretsServer,
+};
+
+/// The custom metadata key of a secret listing the public keys of the entities allowed to read it
+pub const ENTITIES_METADATA_KEY: &str = "wasmcloud-entities";
+
+/// Check that the entity with public key `entity` is listed in the custom metadata of a secret
+fn authorize(
+    custom_metadata: Option<&HashMap<String, String>>,
+    entity: &str,
+) -> Result<(), GetSecretError> {
+    let allowed = custom_metadata
+        .and_then(|metadata| metadata.get(ENTITIES_METADATA_KEY))
+        .is_some_and(|entities| entities.split(',').any(|key| key.trim() == entity));
+    if allowed {
+        Ok(())
+    } else {
+        Err(GetSecretError::Unauthorized)
+    }
+}
+
+/// Map errors returned by Vault to the errors of the secrets API
+fn vault_error(e: ClientError, key: &str) -> GetSecretError {
+    match e {
+        ClientError::APIError { code: 404, .. } => GetSecretError::SecretNotFound,
+        ClientError::APIError { code: 403, .. } => GetSecretError::Unauthorized,
+        e => {
+            error!(error = %e, key, "failed to read secret from Vault");
+            GetSecretError::UpstreamError(e.to_string())
+        }
+    }
+}
+
+/// The `Api` struct implements a secrets backend proxying requests to Vault.
+pub struct Api {
+    /// The server's transit XKey, used to decrypt secret requests sent to the server.
+    server_transit_xkey: XKey,
+    /// The client used to read secrets from Vault.
+    vault: VaultClient,
+    /// The mount path of the KV v2 secrets engine.
+    mount: String,
+    /// The NATS client used to communicate with wasmCloud hosts.
+    pub client: async_nats::Client,
+    /// The base subject for all secrets operations. Should default to `wasmcloud.secrets`.
+    subject_base: String,
+    /// The name of this backend. It must be unique for every {subject_base} + name combination.
+    pub name: String,
+    /// The NATS queue group of the backend instances.
+    queue_group: String,
+    /// The version of the secrets API that this backend implements.
+    api_version: String,
+}
+
+impl Api {
+    /// Create a new Vault secrets backend reading secrets from the KV v2 engine mounted at `mount`
+    /// of the Vault server at `vault_address`, authenticating with `vault_token`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        server_xkey: XKey,
+        vault_address: &str,
+        vault_token: &str,
+        mount: String,
+        client: async_nats::Client,
+        subject_base: String,
+        name: String,
+        queue_group: String,
+        api_version: String,
+    ) -> anyhow::Result<Self> {
+        let settings = VaultClientSettingsBuilder::default()
+            .address(vault_address)
+            .token(vault_token)
+            .build()?;
+        Ok(Self {
+            server_transit_xkey: server_xkey,
+            vault: VaultClient::new(settings)?,
+            mount,
+            client,
+            subject_base,
+            name,
+            queue_group,
+            api_version,
+        })
+    }
+
+    /// Run the secrets backend. This function will block until the NATS connection is closed.
+    pub async fn run(&self) -> anyhow::Result<()> {
+        info!(
+            subject = format!("{}.{}.{}", self.subject_base, self.api_version, self.name),
+            "Starting listener"
+        );
+        wasmcloud_secrets_client::server::serve(
+            &self.client,
+            &self.subject_base,
+            &self.name,
+            Some(&self.api_version),
+            &self.queue_group,
+            &self.server_transit_xkey,
+            self,
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SecretsServer for Api {
+    async fn get(&self, request: SecretRequest) -> Result<SecretResponse, GetSecretError> {
+        if let Err(e) = request.context.valid_claims() {
+            return Err(GetSecretError::InvalidEntityJWT(e.to_string()));
+        }
+        let entity = match (
+            Claims::<Component>::decode(&request.context.entity_jwt),
+            Claims::<CapabilityProvider>::decode(&request.context.entity_jwt),
+        ) {
+            (Ok(c), _) => c.subject,
+            (_, Ok(p)) => p.subject,
+            (Err(e), _) => return Err(GetSecretError::InvalidEntityJWT(e.to_string())),
+        };
+
+        let version = request
+            .version
+            .as_deref()
+            .map(str::parse::<u64>)
+            .transpose()
+            .map_err(|_| GetSecretError::InvalidRequest)?;
+
+        // Check that the entity may read the secret before reading its data
+        let metadata = vaultrs::kv2::read_metadata(&self.vault, &self.mount, &request.key)
+            .await
+            .map_err(|e| vault_error(e, &request.key))?;
+        authorize(metadata.custom_metadata.as_ref(), &entity)?;
+
+        let endpoint = ReadSecretRequest::builder()
+            .mount(&self.mount)
+            .path(&request.key)
+            .version(version)
+            .build()
+            .map_err(|e| GetSecretError::Other(e.to_string()))?;
+        let response = vaultrs::api::exec_with_result(&self.vault, endpoint)
+            .await
+            .map_err(|e| vault_error(e, &request.key))?;
+
+        let value = match request.field {
+            Some(field) => match response.data.get(&field) {
+                Some(serde_json::Value::String(value)) => value.clone(),
+                Some(value) => value.to_string(),
+                None => return Err(GetSecretError::SecretNotFound),
+            },
+            // Without a field, return the whole secret data
+            None => response.data.to_string(),
+        };
+        Ok(SecretResponse {
+            secret: Some(Secret {
+                version: response.metadata.version.to_string(),
+                string_secret: Some(value),
+                binary_secret: None,
+            }),
+            ..Default::default()
+        })
+    }
+
+    fn server_xkey(&self) -> XKey {
+        XKey::from_public_key(self.server_transit_xkey.public_key().as_str())
+            .expect("public key of a valid xkey must be valid")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use wasmcloud_secrets_types::GetSecretError;
+
+    use super::{authorize, ENTITIES_METADATA_KEY};
+
+    const ENTITY: &str = "MAVCGEGKMVT5UCIDSHJO25VHD2VDNDRA3LIHYH2TPIUQS7JCMS472AFJ";
+    const OTHER: &str = "VAG3QITQQ2ODAOWB5TTQSDJ53XK3SHBEIFNK4AYJ5RKAX2UNSCAPHA5M";
+
+    fn metadata(entities: &str) -> HashMap<String, String> {
+        HashMap::from([(ENTITIES_METADATA_KEY.to_string(), entities.to_string())])
+    }
+
+    #[test]
+    fn test_authorize() {
+        assert!(authorize(Some(&metadata(ENTITY)), ENTITY).is_ok());
+        assert!(authorize(Some(&metadata(&format!("{OTHER}, {ENTITY}"))), ENTITY).is_ok());
+        assert!(matches!(
+            authorize(Some(&metadata(OTHER)), ENTITY),
+            Err(GetSecretError::Unauthorized)
+        ));
+        // Secrets without allowed entities can not be read by any entity
+        assert!(matches!(
+            authorize(Some(&metadata("")), ENTITY),
+            Err(GetSecretError::Unauthorized)
+        ));
+        assert!(matches!(
+            authorize(Some(&HashMap::new()), ENTITY),
+            Err(GetSecretError::Unauthorized)
+        ));
+        assert!(matches!(
+            authorize(None, ENTITY),
+            Err(GetSecretError::Unauthorized)
+        ));
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::Context;
+use clap::Parser;
+use nkeys::XKey;
+use secrets_vault::Api;
+
+#[derive(Parser)]
+#[command(about, version, name = "secrets-vault")]
+/// A secrets backend for wasmCloud that serves secrets stored in HashiCorp Vault
+struct Args {
+    #[command(name = "command", subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Run the Vault secrets backend
+    Run(RunCommand),
+}
+
+#[derive(Parser)]
+struct RunCommand {
+    /// The server's transit XKey, used to decrypt secret requests sent to the server.
+    #[clap(short, long, env = "TRANSIT_XKEY_SEED")]
+    transit_xkey_seed: String,
+    /// The address of the Vault server
+    #[clap(long, env = "VAULT_ADDR", default_value = "http://127.0.0.1:8200")]
+    vault_address: String,
+    /// The Vault token used to read secrets. The policies of the token determine which secrets
+    /// can be served.
+    #[clap(long, env = "VAULT_TOKEN", hide_env_values = true)]
+    vault_token: String,
+    /// The mount path of the KV v2 secrets engine
+    #[clap(long, default_value = "secret")]
+    mount: String,
+    /// The subject prefix to use for all requests to the secrets backend, defaults to `wasmcloud.secrets`
+    #[clap(short, long, default_value = "wasmcloud.secrets")]
+    subject_base: String,
+    /// The name of the secrets backend, defaults to `vault`
+    #[clap(short = 'n', long, default_value = "vault")]
+    name: String,
+    /// The NATS queue group to use for running multiple instances of the secrets backend
+    #[clap(long, default_value = "wasmcloud_secrets.vault")]
+    nats_queue_group: String,
+    /// The NATS address to connect to where the backend is running
+    #[clap(long, default_value = "127.0.0.1:4222")]
+    nats_address: String,
+    /// The NATS credentials file to use when connecting
+    #[clap(long, env = "NATS_CREDSFILE")]
+    nats_creds_file: Option<String>,
+    /// The API version to use for the secrets backend
+    #[clap(long, default_value = "v1alpha1")]
+    secrets_api_version: String,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let Args {
+        command: Command::Run(args),
+    } = Args::parse();
+
+    let server_xkey = XKey::from_seed(&args.transit_xkey_seed)
+        .context("failed to create server key from seed")?;
+    let nats_client = match args.nats_creds_file {
+        Some(creds_file) => async_nats::ConnectOptions::new()
+            .credentials_file(&creds_file)
+            .await
+            .with_context(|| format!("failed to read NATS credentials file '{creds_file}'"))?
+            .connect(&args.nats_address)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to connect to NATS at {} with credentials file '{creds_file}'",
+                    args.nats_address
+                )
+            })?,
+        None => async_nats::connect(&args.nats_address)
+            .await
+            .with_context(|| format!("failed to connect to NATS at {}", args.nats_address))?,
+    };
+
+    let api = Api::new(
+        server_xkey,
+        &args.vault_address,
+        &args.vault_token,
+        args.mount,
+        nats_client,
+        args.subject_base,
+        args.name.clone(),
+        args.nats_queue_group,
+        args.secrets_api_version,
+    )
+    .context("failed to create Vault client")?;
+
+    println!("Starting secrets backend '{}'", args.name);
+    api.run().await
+}
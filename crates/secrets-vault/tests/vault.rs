@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use nkeys::{KeyPair, XKey};
+use secrets_vault::{Api, ENTITIES_METADATA_KEY};
+use vaultrs::api::kv2::requests::SetSecretMetadataRequest;
+use vaultrs::client::{VaultClient, VaultClientSettingsBuilder};
+use wascap::jwt::{Claims, ClaimsBuilder, Component, Host};
+use wasmcloud_secrets_client::Client;
+use wasmcloud_secrets_types::{Application, Context, SecretRequest};
+
+const SUBJECT_BASE: &str = "vault_test";
+const NAME: &str = "vault";
+const TEST_API_VERSION: &str = "test";
+const VAULT_ADDRESS: &str = "http://127.0.0.1:8200";
+const VAULT_TOKEN: &str = "root";
+
+fn context(entity: &KeyPair) -> anyhow::Result<Context> {
+    let account = KeyPair::new_account();
+    let component: Claims<Component> = ClaimsBuilder::new()
+        .issuer(account.public_key().as_str())
+        .subject(entity.public_key().as_str())
+        .build();
+    let host: Claims<Host> = ClaimsBuilder::new()
+        .issuer(account.public_key().as_str())
+        .subject(KeyPair::new_server().public_key().as_str())
+        .with_metadata(Host::new("test".to_string(), HashMap::new()))
+        .build();
+    Ok(Context {
+        entity_jwt: component.encode(&account)?,
+        host_jwt: host.encode(&account)?,
+        application: Application::default(),
+    })
+}
+
+#[tokio::test]
+#[ignore = "requires a dev-mode Vault server with root token `root` and a NATS server"]
+async fn integration_test_vault_get() -> anyhow::Result<()> {
+    let nats = async_nats::connect("127.0.0.1:4222").await?;
+    let vault = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(VAULT_ADDRESS)
+            .token(VAULT_TOKEN)
+            .build()?,
+    )?;
+    vaultrs::kv2::set(
+        &vault,
+        "secret",
+        "wasmcloud-test",
+        &HashMap::from([("password", "hunter2")]),
+    )
+    .await?;
+    vaultrs::kv2::set(
+        &vault,
+        "secret",
+        "wasmcloud-test",
+        &HashMap::from([("password", "hunter3")]),
+    )
+    .await?;
+    let entity = KeyPair::new_module();
+    vaultrs::kv2::set_metadata(
+        &vault,
+        "secret",
+        "wasmcloud-test",
+        Some(
+            SetSecretMetadataRequest::builder().custom_metadata(HashMap::from([(
+                ENTITIES_METADATA_KEY.to_string(),
+                entity.public_key(),
+            )])),
+        ),
+    )
+    .await?;
+
+    let api = Api::new(
+        XKey::new(),
+        VAULT_ADDRESS,
+        VAULT_TOKEN,
+        "secret".to_string(),
+        nats.clone(),
+        SUBJECT_BASE.to_string(),
+        NAME.to_string(),
+        "vault_test".to_string(),
+        TEST_API_VERSION.to_string(),
+    )?;
+    tokio::spawn(async move { api.run().await });
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let client = Client::new_with_version(NAME, SUBJECT_BASE, nats, Some(TEST_API_VERSION)).await?;
+    let latest = client
+        .get(
+            SecretRequest {
+                key: "wasmcloud-test".to_string(),
+                field: Some("password".to_string()),
+                version: None,
+                context: context(&entity)?,
+            },
+            XKey::new(),
+        )
+        .await?;
+    assert_eq!(latest.string_secret.as_deref(), Some("hunter3"));
+
+    let previous = client
+        .get(
+            SecretRequest {
+                key: "wasmcloud-test".to_string(),
+                field: Some("password".to_string()),
+                version: Some((latest.version.parse::<u64>()? - 1).to_string()),
+                context: context(&entity)?,
+            },
+            XKey::new(),
+        )
+        .await?;
+    assert_eq!(previous.string_secret.as_deref(), Some("hunter2"));
+
+    let missing = client
+        .get(
+            SecretRequest {
+                key: "wasmcloud-missing".to_string(),
+                field: None,
+                version: None,
+                context: context(&entity)?,
+            },
+            XKey::new(),
+        )
+        .await;
+    assert!(missing.is_err());
+
+    let unauthorized = client
+        .get(
+            SecretRequest {
+                key: "wasmcloud-test".to_string(),
+                field: Some("password".to_string()),
+                version: None,
+                context: context(&KeyPair::new_module())?,
+            },
+            XKey::new(),
+        )
+        .await;
+    assert!(unauthorized.is_err());
+    Ok(())
+}
@@ -32,7 +32,7 @@ const SECTION_WC_JWT: &str = "wasmcloud_jwt";
 pub fn extract_claims(contents: impl AsRef<[u8]>) -> Result<Option<Token<Component>>> {
     use wasmparser::Payload::{ComponentSection, CustomSection, End, ModuleSection};
 
-    let target_hash = compute_hash(&strip_custom_section(contents.as_ref())?)?;
+    let target_hash = module_hash(contents.as_ref())?;
     let parser = wasmparser::Parser::new(0);
     let mut depth = 0;
     for payload in parser.parse_all(contents.as_ref()) {
@@ -61,6 +61,15 @@ pub fn extract_claims(contents: impl AsRef<[u8]>) -> Result<Option<Token<Compone
     Ok(None)
 }
 
+/// Computes the hash of a WebAssembly module or component, excluding any embedded claims, which
+/// signed claims record as their `module_hash`
+///
+/// # Errors
+/// Will return an error if the module cannot be parsed
+pub fn module_hash(contents: impl AsRef<[u8]>) -> Result<String> {
+    compute_hash(&strip_custom_section(contents.as_ref())?)
+}
+
 /// This function will embed a set of claims inside the bytecode of a WebAssembly module. The claims
 /// are converted into a JWT and signed using the provided `KeyPair`.
 /// According to the WebAssembly [custom section](https://webassembly.github.io/spec/core/appendix/custom.html)
@@ -335,6 +344,36 @@ mod test {
         }
     }
 
+    #[test]
+    fn module_hash_ignores_claims() {
+        let dec_module = BASE64.decode(WASM_BASE64.as_bytes()).unwrap();
+
+        let kp = KeyPair::new_account();
+        let claims = Claims {
+            metadata: Some(Component::new(
+                "testing".to_string(),
+                Some(vec![]),
+                false,
+                Some(1),
+                Some(String::new()),
+                None,
+            )),
+            expires: None,
+            id: nuid::next().to_string(),
+            issued_at: 0,
+            issuer: kp.public_key(),
+            subject: "test.wasm".to_string(),
+            not_before: None,
+            wascap_revision: Some(WASCAP_INTERNAL_REVISION),
+        };
+        let modified_bytecode = embed_claims(&dec_module, &claims, &kp).unwrap();
+        let hash = module_hash(&dec_module).unwrap();
+        assert_eq!(module_hash(&modified_bytecode).unwrap(), hash);
+
+        let token = extract_claims(&modified_bytecode).unwrap().unwrap();
+        assert_eq!(token.claims.metadata.unwrap().module_hash, hash);
+    }
+
     #[test]
     fn claims_doublesign_roundtrip() {
         // Verify that we can sign a previously signed module by stripping the old
@@ -0,0 +1,1168 @@
+//! Apply wadm manifests directly to a lattice through the control interface, without wadm
+//!
+//! Applying a manifest compares it to what is running in the lattice, reports the changes that
+//! are needed, and then makes them with auctions, scale and update commands, links and configs.
+//! Components and providers that are started are annotated with the name of the application, so
+//! that they are removed when they are no longer part of the manifest.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use anyhow::{anyhow, bail, ensure, Context as _, Result};
+use clap::Args;
+use console::style;
+use serde::Serialize;
+use serde_json::json;
+use wadm_types::{
+    Component, ConfigProperty, Manifest, Properties, TraitProperty, DAEMONSCALER_TRAIT,
+};
+use wash_lib::app::{load_app_manifest, AppManifest};
+use wash_lib::cli::{CliConnectionOpts, CommandOutput};
+use wash_lib::common::get_all_inventories;
+use wash_lib::config::WashConnectionOptions;
+use wash_lib::generate::emoji;
+use wasmcloud_control_interface::{Client as CtlClient, HostInventory, Link};
+
+/// Link name used by wadm for links that do not specify one
+pub(crate) const DEFAULT_LINK_NAME: &str = "default";
+
+/// Annotation of the application that a component or provider belongs to, as used by wadm
+const APP_ANNOTATION: &str = "wasmcloud.dev/appspec";
+
+/// Annotation of the tool that manages a component or provider
+const MANAGED_BY_ANNOTATION: &str = "wasmcloud.dev/managed-by";
+
+/// Value of [`MANAGED_BY_ANNOTATION`] for everything started by `wash apply`
+const MANAGED_BY: &str = "wash";
+
+#[derive(Debug, Clone, Args)]
+pub struct ApplyCommand {
+    /// Path or URL of the manifest to apply, or `-` to read it from STDIN
+    #[clap(short = 'f', long = "file")]
+    pub file: String,
+
+    /// Only report the changes that are needed, without making them
+    #[clap(long = "dry-run")]
+    pub dry_run: bool,
+
+    #[clap(flatten)]
+    pub opts: CliConnectionOpts,
+}
+
+/// Where a component or provider is started
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Placement {
+    /// On a specific host
+    Host(String),
+    /// On the first host that responds to an auction with the given label constraints
+    Auction(BTreeMap<String, String>),
+}
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Placement::Host(host_id) => write!(f, "on host [{host_id}]"),
+            Placement::Auction(constraints) if constraints.is_empty() => {
+                write!(f, "on a host selected by auction")
+            }
+            Placement::Auction(constraints) => write!(
+                f,
+                "on a host selected by auction with labels [{}]",
+                constraints
+                    .iter()
+                    .map(|(k, v)| format!("{k}={v}"))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+        }
+    }
+}
+
+/// A single change to the lattice
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    PutConfig {
+        name: String,
+        properties: HashMap<String, String>,
+        /// Whether the config already exists with other values
+        exists: bool,
+    },
+    UpdateComponent {
+        id: String,
+        host_id: String,
+        from: String,
+        to: String,
+    },
+    StartComponent {
+        id: String,
+        image: String,
+        instances: u32,
+        placement: Placement,
+        config: Vec<String>,
+    },
+    ScaleComponent {
+        id: String,
+        image: String,
+        host_id: String,
+        from: u32,
+        to: u32,
+        config: Vec<String>,
+    },
+    StartProvider {
+        id: String,
+        image: String,
+        placement: Placement,
+        config: Vec<String>,
+    },
+    StopProvider {
+        id: String,
+        host_id: String,
+    },
+    PutLink {
+        link: Link,
+        /// Whether the link replaces an existing link with the same source, name and package
+        replaces: bool,
+    },
+    DeleteLink {
+        link: Link,
+    },
+}
+
+impl Action {
+    /// Order in which actions are applied, so that configs exist before they are used, and
+    /// links are in place before anything is stopped
+    fn stage(&self) -> u8 {
+        match self {
+            Action::PutConfig { .. } => 0,
+            Action::UpdateComponent { .. } => 1,
+            Action::StartComponent { .. } => 2,
+            Action::ScaleComponent { to, .. } if *to > 0 => 2,
+            Action::StartProvider { .. } => 3,
+            Action::PutLink { .. } => 4,
+            Action::DeleteLink { .. } => 5,
+            Action::ScaleComponent { .. } => 6,
+            Action::StopProvider { .. } => 7,
+        }
+    }
+}
+
+/// Describe a link as `[source] -(namespace:package/interfaces)-> [target] (name)`
+fn describe_link(link: &Link) -> String {
+    format!(
+        "[{}] -({}:{}/{{{}}})-> [{}] ({})",
+        link.source_id(),
+        link.wit_namespace(),
+        link.wit_package(),
+        link.interfaces().join(","),
+        link.target(),
+        link.name(),
+    )
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::PutConfig { name, exists, .. } => {
+                write!(f, "{} config [{name}]", if *exists { "~" } else { "+" })
+            }
+            Action::UpdateComponent {
+                id,
+                host_id,
+                from,
+                to,
+            } => write!(f, "~ component [{id}] on host [{host_id}]: {from} -> {to}"),
+            Action::StartComponent {
+                id,
+                image,
+                instances,
+                placement,
+                ..
+            } => write!(
+                f,
+                "+ component [{id}] ({image}) with {instances} instance(s) {placement}"
+            ),
+            Action::ScaleComponent {
+                id, host_id, to: 0, ..
+            } => write!(f, "- component [{id}] on host [{host_id}]"),
+            Action::ScaleComponent {
+                id,
+                host_id,
+                from,
+                to,
+                ..
+            } => write!(
+                f,
+                "~ component [{id}] on host [{host_id}]: {from} -> {to} instance(s)"
+            ),
+            Action::StartProvider {
+                id,
+                image,
+                placement,
+                ..
+            } => write!(f, "+ provider [{id}] ({image}) {placement}"),
+            Action::StopProvider { id, host_id } => {
+                write!(f, "- provider [{id}] on host [{host_id}]")
+            }
+            Action::PutLink { link, replaces } => write!(
+                f,
+                "{} link {}",
+                if *replaces { "~" } else { "+" },
+                describe_link(link)
+            ),
+            Action::DeleteLink { link } => write!(f, "- link {}", describe_link(link)),
+        }
+    }
+}
+
+/// Changes needed to bring a lattice in line with a manifest
+#[derive(Debug, Default)]
+pub struct Plan {
+    pub actions: Vec<Action>,
+    /// Parts of the manifest that cannot be applied without wadm
+    pub notes: Vec<String>,
+}
+
+/// The parts of a lattice that a manifest is compared to
+#[derive(Debug, Default)]
+pub struct LatticeState {
+    pub inventories: Vec<HostInventory>,
+    pub links: Vec<Link>,
+    /// Current values of the configs that the manifest defines, by name
+    pub configs: HashMap<String, HashMap<String, String>>,
+}
+
+impl LatticeState {
+    /// Fetch the state of the lattice that is relevant to a manifest
+    async fn fetch(ctl_client: &CtlClient, manifest: &Manifest) -> Result<Self> {
+        let inventories = get_all_inventories(ctl_client).await?;
+        let links = ctl_client
+            .get_links()
+            .await
+            .map_err(|e| anyhow!(e).context("failed to get links"))?
+            .into_data()
+            .unwrap_or_default();
+        let mut configs = HashMap::new();
+        for name in inline_configs(manifest)?.into_keys() {
+            let config = ctl_client
+                .get_config(&name)
+                .await
+                .map_err(|e| anyhow!(e).context("failed to get config"))?
+                .into_data()
+                .unwrap_or_default();
+            if !config.is_empty() {
+                configs.insert(name, config);
+            }
+        }
+        Ok(Self {
+            inventories,
+            links,
+            configs,
+        })
+    }
+}
+
+/// How the instances of a component or provider are placed on hosts
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Scaler {
+    /// A number of instances on hosts with the given labels
+    Spread {
+        instances: u32,
+        requirements: BTreeMap<String, String>,
+    },
+    /// A number of instances on every host that has the labels of any of the spreads
+    Daemon {
+        instances: u32,
+        spreads: Vec<BTreeMap<String, String>>,
+    },
+}
+
+impl Scaler {
+    /// The scaler of a component, defaulting to a single instance on any host
+    fn of(component: &Component, notes: &mut Vec<String>) -> Self {
+        let Some((trait_type, scaler)) =
+            component
+                .traits
+                .iter()
+                .flatten()
+                .find_map(|t| match &t.properties {
+                    TraitProperty::SpreadScaler(scaler) => Some((&t.trait_type, scaler)),
+                    _ => None,
+                })
+        else {
+            return Scaler::Spread {
+                instances: 1,
+                requirements: BTreeMap::new(),
+            };
+        };
+        let instances = u32::try_from(scaler.instances).unwrap_or(u32::MAX);
+        if trait_type == DAEMONSCALER_TRAIT {
+            return Scaler::Daemon {
+                instances,
+                spreads: scaler
+                    .spread
+                    .iter()
+                    .map(|s| s.requirements.clone())
+                    .collect(),
+            };
+        }
+        // wadm weighs spreads that do not specify a weight at 100
+        let spread = scaler.spread.iter().max_by_key(|s| s.weight.unwrap_or(100));
+        if scaler.spread.len() > 1 {
+            notes.push(format!(
+                "component [{}] has multiple spreads, only the requirements of spread [{}] are used",
+                component.name,
+                spread.map(|s| s.name.as_str()).unwrap_or_default(),
+            ));
+        }
+        Scaler::Spread {
+            instances,
+            requirements: spread.map(|s| s.requirements.clone()).unwrap_or_default(),
+        }
+    }
+
+    /// Returns true if a host with the given labels runs instances under this scaler
+    fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        let has = |requirements: &BTreeMap<String, String>| {
+            requirements.iter().all(|(k, v)| labels.get(k) == Some(v))
+        };
+        match self {
+            Scaler::Spread { requirements, .. } => has(requirements),
+            Scaler::Daemon { spreads, .. } => spreads.is_empty() || spreads.iter().any(has),
+        }
+    }
+}
+
+/// Compute the ID of a component in a manifest, in the same way as wadm
+pub(crate) fn component_id(manifest: &Manifest, component: &Component) -> String {
+    let id = match &component.properties {
+        Properties::Component { properties } => properties.id.as_ref(),
+        Properties::Capability { properties } => properties.id.as_ref(),
+    };
+    id.cloned().unwrap_or_else(|| {
+        let normalize = |s: &str| {
+            s.to_lowercase()
+                .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+        };
+        format!(
+            "{}-{}",
+            normalize(&manifest.metadata.name),
+            normalize(&component.name)
+        )
+    })
+}
+
+/// Links declared by the components of a manifest
+pub(crate) fn links(manifest: &Manifest) -> Result<Vec<(Link, Vec<&ConfigProperty>)>> {
+    let ids = manifest
+        .spec
+        .components
+        .iter()
+        .map(|c| (c.name.as_str(), component_id(manifest, c)))
+        .collect::<HashMap<_, _>>();
+    let mut links = Vec::new();
+    for component in &manifest.spec.components {
+        for link in component
+            .traits
+            .iter()
+            .flatten()
+            .filter_map(|t| match &t.properties {
+                TraitProperty::Link(link) => Some(link),
+                _ => None,
+            })
+        {
+            let source_config = link
+                .source
+                .iter()
+                .flat_map(|s| &s.config)
+                .collect::<Vec<_>>();
+            let target_config = &link.target.config;
+            // Targets that are not part of this manifest are assumed to be referenced by ID
+            let target = ids
+                .get(link.target.name.as_str())
+                .cloned()
+                .unwrap_or_else(|| link.target.name.clone());
+            let built = Link::builder()
+                .source_id(&component_id(manifest, component))
+                .target(&target)
+                .name(link.name.as_deref().unwrap_or(DEFAULT_LINK_NAME))
+                .wit_namespace(&link.namespace)
+                .wit_package(&link.package)
+                .interfaces(link.interfaces.clone())
+                .source_config(source_config.iter().map(|c| c.name.clone()).collect())
+                .target_config(target_config.iter().map(|c| c.name.clone()).collect())
+                .build()
+                .map_err(|e| anyhow!(e).context("failed to build link"))?;
+            links.push((
+                built,
+                source_config.into_iter().chain(target_config).collect(),
+            ));
+        }
+    }
+    Ok(links)
+}
+
+/// Ensure that no component, provider or link of a manifest uses secrets, which are only
+/// delivered when the manifest is deployed with wadm
+pub(crate) fn ensure_no_secrets(manifest: &Manifest) -> Result<()> {
+    let app = &manifest.metadata.name;
+    for component in &manifest.spec.components {
+        let secrets = match &component.properties {
+            Properties::Component { properties } => &properties.secrets,
+            Properties::Capability { properties } => &properties.secrets,
+        };
+        ensure!(
+            secrets.is_empty(),
+            "component [{}] of application [{app}] uses secrets, which are only supported when deploying with wadm",
+            component.name,
+        );
+        for link in component
+            .traits
+            .iter()
+            .flatten()
+            .filter_map(|t| match &t.properties {
+                TraitProperty::Link(link) => Some(link),
+                _ => None,
+            })
+        {
+            let source_secrets = link.source.as_ref().is_some_and(|s| !s.secrets.is_empty());
+            ensure!(
+                !source_secrets && link.target.secrets.is_empty(),
+                "link from component [{}] to [{}] of application [{app}] uses secrets, which are only supported when deploying with wadm",
+                component.name,
+                link.target.name,
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Configs that are defined inline in a manifest, by name
+fn inline_configs(manifest: &Manifest) -> Result<BTreeMap<String, HashMap<String, String>>> {
+    let component_configs = manifest
+        .spec
+        .components
+        .iter()
+        .flat_map(|component| match &component.properties {
+            Properties::Component { properties } => &properties.config,
+            Properties::Capability { properties } => &properties.config,
+        });
+    let links = links(manifest)?;
+    let link_configs = links
+        .iter()
+        .flat_map(|(_, configs)| configs.iter().copied());
+    let mut configs = BTreeMap::new();
+    for config in component_configs.chain(link_configs) {
+        let Some(properties) = &config.properties else {
+            continue;
+        };
+        if let Some(previous) = configs.insert(config.name.clone(), properties.clone()) {
+            ensure!(
+                previous == *properties,
+                "config [{}] is defined more than once with different values",
+                config.name
+            );
+        }
+    }
+    Ok(configs)
+}
+
+/// Returns true if a component or provider was started for the given application
+fn belongs_to(annotations: Option<&BTreeMap<String, String>>, app: &str) -> bool {
+    annotations
+        .and_then(|annotations| annotations.get(APP_ANNOTATION))
+        .is_some_and(|name| name == app)
+}
+
+/// Compute the changes needed to bring a lattice in line with a manifest
+pub fn plan(manifest: &Manifest, state: &LatticeState) -> Result<Plan> {
+    ensure_no_secrets(manifest)?;
+    let app = &manifest.metadata.name;
+    let mut plan = Plan::default();
+
+    for (name, properties) in inline_configs(manifest)? {
+        match state.configs.get(&name) {
+            Some(current) if *current == properties => {}
+            current => plan.actions.push(Action::PutConfig {
+                name,
+                properties,
+                exists: current.is_some(),
+            }),
+        }
+    }
+
+    let mut ids = BTreeSet::new();
+    for component in &manifest.spec.components {
+        let id = component_id(manifest, component);
+        let (image, application, configs) = match &component.properties {
+            Properties::Component { properties } => (
+                &properties.image,
+                &properties.application,
+                &properties.config,
+            ),
+            Properties::Capability { properties } => (
+                &properties.image,
+                &properties.application,
+                &properties.config,
+            ),
+        };
+        let Some(image) = image else {
+            if application.is_some() {
+                plan.notes.push(format!(
+                    "shared application component [{}] is not supported without wadm, ignoring",
+                    component.name
+                ));
+            }
+            continue;
+        };
+        ids.insert(id.clone());
+        let config = configs.iter().map(|c| c.name.clone()).collect::<Vec<_>>();
+        let scaler = Scaler::of(component, &mut plan.notes);
+        match component.properties {
+            Properties::Component { .. } => {
+                plan_component(&mut plan, state, &id, image, &scaler, config);
+            }
+            Properties::Capability { .. } => {
+                plan_provider(&mut plan, state, &id, image, &scaler, config);
+            }
+        }
+    }
+
+    // Remove what was previously applied for this application, but is no longer in the manifest
+    let mut removed = BTreeSet::new();
+    for inventory in &state.inventories {
+        for component in inventory.components() {
+            if belongs_to(component.annotations(), app) && !ids.contains(component.id()) {
+                removed.insert(component.id().to_string());
+                plan.actions.push(Action::ScaleComponent {
+                    id: component.id().to_string(),
+                    image: component.image_ref().to_string(),
+                    host_id: inventory.host_id().to_string(),
+                    from: component.max_instances(),
+                    to: 0,
+                    config: Vec::new(),
+                });
+            }
+        }
+        for provider in inventory.providers() {
+            if belongs_to(provider.annotations(), app) && !ids.contains(provider.id()) {
+                removed.insert(provider.id().to_string());
+                plan.actions.push(Action::StopProvider {
+                    id: provider.id().to_string(),
+                    host_id: inventory.host_id().to_string(),
+                });
+            }
+        }
+    }
+
+    // Links are identified by their source, name and package
+    let key = |link: &Link| {
+        (
+            link.source_id().to_string(),
+            link.name().to_string(),
+            link.wit_namespace().to_string(),
+            link.wit_package().to_string(),
+        )
+    };
+    let desired = links(manifest)?
+        .into_iter()
+        .map(|(link, _)| (key(&link), link))
+        .collect::<BTreeMap<_, _>>();
+    for (link_key, link) in &desired {
+        match state.links.iter().find(|l| key(l) == *link_key) {
+            Some(current) if current == link => {}
+            current => plan.actions.push(Action::PutLink {
+                link: link.clone(),
+                replaces: current.is_some(),
+            }),
+        }
+    }
+    for link in &state.links {
+        let source = link.source_id();
+        if (ids.contains(source) || removed.contains(source)) && !desired.contains_key(&key(link)) {
+            plan.actions.push(Action::DeleteLink { link: link.clone() });
+        }
+    }
+
+    plan.actions.sort_by_key(Action::stage);
+    Ok(plan)
+}
+
+/// Plan the changes needed to run a component as described by its scaler
+fn plan_component(
+    plan: &mut Plan,
+    state: &LatticeState,
+    id: &str,
+    image: &str,
+    scaler: &Scaler,
+    config: Vec<String>,
+) {
+    let running = state
+        .inventories
+        .iter()
+        .filter_map(|inventory| {
+            inventory
+                .components()
+                .iter()
+                .find(|c| c.id() == id)
+                .map(|c| (inventory.host_id(), c))
+        })
+        .collect::<Vec<_>>();
+    for (host_id, component) in &running {
+        if component.image_ref() != image {
+            plan.actions.push(Action::UpdateComponent {
+                id: id.to_string(),
+                host_id: host_id.to_string(),
+                from: component.image_ref().to_string(),
+                to: image.to_string(),
+            });
+        }
+    }
+
+    let mut targets = BTreeMap::new();
+    match scaler {
+        Scaler::Spread {
+            instances,
+            requirements,
+        } => {
+            // Keep as many running instances as possible where they are
+            let mut remaining = *instances;
+            for (host_id, component) in &running {
+                let keep = component.max_instances().min(remaining);
+                remaining -= keep;
+                targets.insert(*host_id, keep);
+            }
+            if remaining > 0 {
+                match running.first() {
+                    Some((host_id, _)) => {
+                        *targets.entry(*host_id).or_default() += remaining;
+                    }
+                    None => plan.actions.push(Action::StartComponent {
+                        id: id.to_string(),
+                        image: image.to_string(),
+                        instances: remaining,
+                        placement: Placement::Auction(requirements.clone()),
+                        config: config.clone(),
+                    }),
+                }
+            }
+        }
+        Scaler::Daemon { instances, .. } => {
+            for inventory in &state.inventories {
+                let count = if scaler.matches(inventory.labels()) {
+                    *instances
+                } else {
+                    0
+                };
+                targets.insert(inventory.host_id(), count);
+            }
+        }
+    }
+
+    for (host_id, to) in targets {
+        let from = running
+            .iter()
+            .find(|(h, _)| *h == host_id)
+            .map_or(0, |(_, c)| c.max_instances());
+        if from == to {
+            continue;
+        }
+        plan.actions.push(if from == 0 {
+            Action::StartComponent {
+                id: id.to_string(),
+                image: image.to_string(),
+                instances: to,
+                placement: Placement::Host(host_id.to_string()),
+                config: config.clone(),
+            }
+        } else {
+            Action::ScaleComponent {
+                id: id.to_string(),
+                image: image.to_string(),
+                host_id: host_id.to_string(),
+                from,
+                to,
+                config: config.clone(),
+            }
+        });
+    }
+}
+
+/// Plan the changes needed to run a provider as described by its scaler
+fn plan_provider(
+    plan: &mut Plan,
+    state: &LatticeState,
+    id: &str,
+    image: &str,
+    scaler: &Scaler,
+    config: Vec<String>,
+) {
+    let running = state
+        .inventories
+        .iter()
+        .filter_map(|inventory| {
+            inventory
+                .providers()
+                .iter()
+                .find(|p| p.id() == id)
+                .map(|p| (inventory, p))
+        })
+        .collect::<Vec<_>>();
+    for (inventory, provider) in &running {
+        if let Some(current) = provider.image_ref().filter(|current| *current != image) {
+            plan.notes.push(format!(
+                "provider [{id}] on host [{}] runs [{current}] rather than [{image}], providers cannot be updated in place so stop it to replace it",
+                inventory.host_id()
+            ));
+        }
+    }
+
+    match scaler {
+        Scaler::Spread {
+            instances,
+            requirements,
+        } => {
+            if *instances > 1 {
+                plan.notes.push(format!(
+                    "provider [{id}] is started on a single host without wadm"
+                ));
+            }
+            if running.is_empty() && *instances > 0 {
+                plan.actions.push(Action::StartProvider {
+                    id: id.to_string(),
+                    image: image.to_string(),
+                    placement: Placement::Auction(requirements.clone()),
+                    config,
+                });
+            }
+        }
+        Scaler::Daemon { .. } => {
+            for inventory in &state.inventories {
+                let is_running = running
+                    .iter()
+                    .any(|(i, _)| i.host_id() == inventory.host_id());
+                match (scaler.matches(inventory.labels()), is_running) {
+                    (true, false) => plan.actions.push(Action::StartProvider {
+                        id: id.to_string(),
+                        image: image.to_string(),
+                        placement: Placement::Host(inventory.host_id().to_string()),
+                        config: config.clone(),
+                    }),
+                    (false, true) => plan.actions.push(Action::StopProvider {
+                        id: id.to_string(),
+                        host_id: inventory.host_id().to_string(),
+                    }),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Select the host to start a component or provider on
+async fn select_host(
+    ctl_client: &CtlClient,
+    placement: &Placement,
+    image: &str,
+    id: &str,
+    provider: bool,
+) -> Result<String> {
+    let constraints = match placement {
+        Placement::Host(host_id) => return Ok(host_id.clone()),
+        Placement::Auction(constraints) => constraints.clone(),
+    };
+    let host_id = if provider {
+        ctl_client
+            .perform_provider_auction(image, id, constraints)
+            .await
+            .map_err(|e| anyhow!(e).context("failed to perform provider auction"))?
+            .into_iter()
+            .find_map(|ack| ack.into_data())
+            .map(|ack| ack.host_id().to_string())
+    } else {
+        ctl_client
+            .perform_component_auction(image, id, constraints)
+            .await
+            .map_err(|e| anyhow!(e).context("failed to perform component auction"))?
+            .into_iter()
+            .find_map(|ack| ack.into_data())
+            .map(|ack| ack.host_id().to_string())
+    };
+    host_id.with_context(|| format!("no host responded to the auction for [{id}]"))
+}
+
+/// Make a single change to the lattice
+async fn execute(ctl_client: &CtlClient, app: &str, action: &Action) -> Result<()> {
+    let annotations = BTreeMap::from([
+        (APP_ANNOTATION.to_string(), app.to_string()),
+        (MANAGED_BY_ANNOTATION.to_string(), MANAGED_BY.to_string()),
+    ]);
+    let ack = match action {
+        Action::PutConfig {
+            name, properties, ..
+        } => ctl_client.put_config(name, properties.clone()).await,
+        Action::UpdateComponent {
+            id, host_id, to, ..
+        } => {
+            ctl_client
+                .update_component(host_id, id, to, Some(annotations))
+                .await
+        }
+        Action::StartComponent {
+            id,
+            image,
+            instances,
+            placement,
+            config,
+        } => {
+            let host_id = select_host(ctl_client, placement, image, id, false).await?;
+            ctl_client
+                .scale_component(
+                    &host_id,
+                    image,
+                    id,
+                    *instances,
+                    Some(annotations),
+                    config.clone(),
+                )
+                .await
+        }
+        Action::ScaleComponent {
+            id,
+            image,
+            host_id,
+            to,
+            config,
+            ..
+        } => {
+            ctl_client
+                .scale_component(host_id, image, id, *to, Some(annotations), config.clone())
+                .await
+        }
+        Action::StartProvider {
+            id,
+            image,
+            placement,
+            config,
+        } => {
+            let host_id = select_host(ctl_client, placement, image, id, true).await?;
+            ctl_client
+                .start_provider(&host_id, image, id, Some(annotations), config.clone())
+                .await
+        }
+        Action::StopProvider { id, host_id } => ctl_client.stop_provider(host_id, id).await,
+        Action::PutLink { link, .. } => ctl_client.put_link(link.clone()).await,
+        Action::DeleteLink { link } => {
+            ctl_client
+                .delete_link(
+                    link.source_id(),
+                    link.name(),
+                    link.wit_namespace(),
+                    link.wit_package(),
+                )
+                .await
+        }
+    }
+    .map_err(|e| anyhow!(e))?;
+    ensure!(ack.succeeded(), "{}", ack.message());
+    Ok(())
+}
+
+/// Load a wadm manifest from a path, URL or STDIN
+async fn load_manifest(source: &str) -> Result<Manifest> {
+    let AppManifest::SerializedModel(manifest) = load_app_manifest(source.parse()?).await? else {
+        bail!("expected the path or URL of a manifest, or `-` to read it from STDIN");
+    };
+    serde_yaml::from_value(manifest).context("failed to parse manifest")
+}
+
+pub async fn handle_command(cmd: ApplyCommand) -> Result<CommandOutput> {
+    let manifest = load_manifest(&cmd.file).await?;
+    let app = &manifest.metadata.name;
+    let wco: WashConnectionOptions = cmd.opts.try_into()?;
+    let ctl_client = wco.into_ctl_client(None).await?;
+
+    let state = LatticeState::fetch(&ctl_client, &manifest).await?;
+    let plan = plan(&manifest, &state)?;
+    for note in &plan.notes {
+        eprintln!("{} {note}", emoji::WARN);
+    }
+    let actions = json!(plan.actions);
+    if plan.actions.is_empty() {
+        return Ok(CommandOutput::new(
+            format!("{} Application [{app}] is up to date", emoji::GREEN_CHECK),
+            HashMap::from([("actions".to_string(), actions)]),
+        ));
+    }
+
+    eprintln!(
+        "{}",
+        style(format!("Changes to application [{app}]:")).bold()
+    );
+    for action in &plan.actions {
+        let line = action.to_string();
+        let line = match line.chars().next() {
+            Some('+') => style(line).green(),
+            Some('-') => style(line).red(),
+            _ => style(line).yellow(),
+        };
+        eprintln!("  {line}");
+    }
+    if cmd.dry_run {
+        return Ok(CommandOutput::new(
+            format!(
+                "{} change(s) needed to apply [{app}], run without --dry-run to apply them",
+                plan.actions.len()
+            ),
+            HashMap::from([("actions".to_string(), actions)]),
+        ));
+    }
+
+    for action in &plan.actions {
+        execute(&ctl_client, app, action)
+            .await
+            .with_context(|| format!("failed to apply change [{action}]"))?;
+    }
+    Ok(CommandOutput::new(
+        format!(
+            "{} Applied {} change(s) to application [{app}]",
+            emoji::GREEN_CHECK,
+            plan.actions.len()
+        ),
+        HashMap::from([
+            ("applied".to_string(), json!(true)),
+            ("actions".to_string(), actions),
+        ]),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use wadm_types::{
+        CapabilityProperties, ComponentProperties, LinkProperty, Metadata, SecretProperty,
+        SecretSourceProperty, Specification, SpreadScalerProperty, TargetConfig, Trait,
+    };
+    use wasmcloud_control_interface::{ComponentDescription, ProviderDescription};
+
+    use super::*;
+
+    fn manifest() -> Manifest {
+        Manifest {
+            api_version: "core.oam.dev/v1beta1".into(),
+            kind: "Application".into(),
+            metadata: Metadata {
+                name: "Dev App".into(),
+                annotations: BTreeMap::new(),
+                labels: BTreeMap::new(),
+            },
+            spec: Specification {
+                components: vec![
+                    Component {
+                        name: "http-component".into(),
+                        properties: Properties::Component {
+                            properties: ComponentProperties {
+                                image: Some("file:///tmp/component.wasm".into()),
+                                application: None,
+                                id: Some("abc123-http-component".into()),
+                                config: Vec::new(),
+                                secrets: Vec::new(),
+                            },
+                        },
+                        traits: Some(vec![
+                            Trait::new_spreadscaler(SpreadScalerProperty {
+                                instances: 5,
+                                spread: Vec::new(),
+                            }),
+                            Trait::new_link(LinkProperty {
+                                namespace: "wasi".into(),
+                                package: "keyvalue".into(),
+                                interfaces: vec!["store".into()],
+                                target: TargetConfig {
+                                    name: "keyvalue".into(),
+                                    config: vec![ConfigProperty {
+                                        name: "bucket".into(),
+                                        properties: Some(HashMap::from([(
+                                            "bucket".into(),
+                                            "wasmcloud".into(),
+                                        )])),
+                                    }],
+                                    secrets: Vec::new(),
+                                },
+                                ..Default::default()
+                            }),
+                        ]),
+                    },
+                    Component {
+                        name: "keyvalue".into(),
+                        properties: Properties::Capability {
+                            properties: CapabilityProperties {
+                                image: Some("ghcr.io/wasmcloud/keyvalue-nats:0.3.1".into()),
+                                application: None,
+                                id: None,
+                                config: Vec::new(),
+                                secrets: Vec::new(),
+                            },
+                        },
+                        traits: None,
+                    },
+                ],
+                policies: Vec::new(),
+            },
+        }
+    }
+
+    fn inventory(
+        host_id: &str,
+        components: Vec<ComponentDescription>,
+        providers: Vec<ProviderDescription>,
+    ) -> HostInventory {
+        HostInventory::builder()
+            .host_id(host_id.into())
+            .friendly_name(host_id.into())
+            .version("1.0.0".into())
+            .uptime_human("1s".into())
+            .uptime_seconds(1)
+            .components(components)
+            .providers(providers)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_links() {
+        let manifest = manifest();
+        let links = links(&manifest).expect("failed to collect links");
+        assert_eq!(links.len(), 1);
+        let (link, configs) = &links[0];
+        assert_eq!(link.source_id(), "abc123-http-component");
+        // Components without an ID are identified as wadm would identify them
+        assert_eq!(link.target(), "dev_app-keyvalue");
+        assert_eq!(link.name(), DEFAULT_LINK_NAME);
+        assert_eq!(link.interfaces(), &vec!["store".to_string()]);
+        assert_eq!(link.target_config(), &vec!["bucket".to_string()]);
+        assert_eq!(configs.len(), 1);
+    }
+
+    #[test]
+    fn test_ensure_no_secrets() {
+        ensure_no_secrets(&manifest()).expect("manifest without secrets should be supported");
+
+        let secret = SecretProperty {
+            name: "password".into(),
+            properties: SecretSourceProperty {
+                policy: "dev-policy".into(),
+                key: "password".into(),
+                field: None,
+                version: None,
+            },
+        };
+        let mut component_secrets = manifest();
+        if let Properties::Capability { properties } =
+            &mut component_secrets.spec.components[1].properties
+        {
+            properties.secrets.push(secret.clone());
+        }
+        let err = ensure_no_secrets(&component_secrets)
+            .expect_err("component secrets should be rejected");
+        assert!(err
+            .to_string()
+            .starts_with("component [keyvalue] of application [Dev App] uses secrets"));
+        // Secrets are never dropped silently when planning
+        assert!(plan(&component_secrets, &LatticeState::default()).is_err());
+
+        let mut link_secrets = manifest();
+        for t in link_secrets.spec.components[0].traits.iter_mut().flatten() {
+            if let TraitProperty::Link(link) = &mut t.properties {
+                link.target.secrets.push(secret.clone());
+            }
+        }
+        let err = ensure_no_secrets(&link_secrets).expect_err("link secrets should be rejected");
+        assert!(err.to_string().starts_with(
+            "link from component [http-component] to [keyvalue] of application [Dev App] uses secrets"
+        ));
+    }
+
+    #[test]
+    fn test_plan() {
+        let manifest = manifest();
+
+        // Everything is started on an empty lattice
+        let initial = plan(&manifest, &LatticeState::default()).unwrap();
+        let lines = initial
+            .actions
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "+ config [bucket]",
+                "+ component [abc123-http-component] (file:///tmp/component.wasm) with 5 instance(s) on a host selected by auction",
+                "+ provider [dev_app-keyvalue] (ghcr.io/wasmcloud/keyvalue-nats:0.3.1) on a host selected by auction",
+                "+ link [abc123-http-component] -(wasi:keyvalue/{store})-> [dev_app-keyvalue] (default)",
+            ]
+        );
+
+        // Running components are updated and scaled in place, and components that were applied
+        // before but are no longer in the manifest are removed along with their links
+        let app_annotations = BTreeMap::from([(APP_ANNOTATION.to_string(), "Dev App".to_string())]);
+        let state = LatticeState {
+            inventories: vec![inventory(
+                "host",
+                vec![
+                    ComponentDescription::builder()
+                        .id("abc123-http-component".into())
+                        .image_ref("file:///tmp/old.wasm".into())
+                        .max_instances(2)
+                        .build()
+                        .unwrap(),
+                    ComponentDescription::builder()
+                        .id("removed".into())
+                        .image_ref("file:///tmp/removed.wasm".into())
+                        .max_instances(1)
+                        .annotations(app_annotations)
+                        .build()
+                        .unwrap(),
+                ],
+                vec![ProviderDescription::builder()
+                    .id("dev_app-keyvalue")
+                    .image_ref("ghcr.io/wasmcloud/keyvalue-nats:0.3.1")
+                    .build()
+                    .unwrap()],
+            )],
+            links: vec![
+                links(&manifest).unwrap().remove(0).0,
+                Link::builder()
+                    .source_id("removed")
+                    .target("dev_app-keyvalue")
+                    .name(DEFAULT_LINK_NAME)
+                    .wit_namespace("wasi")
+                    .wit_package("keyvalue")
+                    .interfaces(vec!["store".into()])
+                    .build()
+                    .unwrap(),
+            ],
+            configs: HashMap::from([(
+                "bucket".to_string(),
+                HashMap::from([("bucket".to_string(), "wasmcloud".to_string())]),
+            )]),
+        };
+        let updated = plan(&manifest, &state).unwrap();
+        let lines = updated
+            .actions
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "~ component [abc123-http-component] on host [host]: file:///tmp/old.wasm -> file:///tmp/component.wasm",
+                "~ component [abc123-http-component] on host [host]: 2 -> 5 instance(s)",
+                "- link [removed] -(wasi:keyvalue/{store})-> [dev_app-keyvalue] (default)",
+                "- component [removed] on host [host]",
+            ]
+        );
+        assert!(updated.notes.is_empty());
+    }
+}
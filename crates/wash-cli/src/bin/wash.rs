@@ -26,6 +26,7 @@ use wash_lib::drain::Drain as DrainSelection;
 use wash_lib::plugin::subcommand::{DirMapping, SubcommandRunner};
 
 use wash_cli::app::{self, AppCliCommand};
+use wash_cli::apply::{self, ApplyCommand};
 use wash_cli::build::{self, BuildCommand};
 use wash_cli::call::{self, CallCli};
 use wash_cli::cmd::config::{self, ConfigCliCommand};
@@ -45,6 +46,7 @@ use wash_cli::par::{self, ParCliCommand};
 use wash_cli::plugin::{self, PluginCommand};
 use wash_cli::secrets::{self, SecretsCliCommand};
 use wash_cli::style::WASH_CLI_STYLE;
+use wash_cli::test::{self, TestCommand};
 use wash_cli::ui::{self, UiCommand};
 use wash_cli::util::ensure_plugin_dir;
 
@@ -143,6 +145,7 @@ ________________________________________________________________________________
                     "Tear down a local wasmCloud environment (launched with wash up)",
                 ),
                 ("app", "Manage declarative applications and deployments (wadm)"),
+                ("apply", "Apply a declarative application manifest to a lattice without wadm"),
                 ("spy", "Spy on all invocations a component sends and receives"),
                 ("ui", "Serve a web UI for wasmCloud"),
             ],
@@ -163,6 +166,7 @@ ________________________________________________________________________________
                 ),
                 ("link", "Link one component to another on a set of interfaces"),
                 ("call", "Invoke a simple function on a component running in a wasmCloud host"),
+                ("test", "Run integration tests against components running in a wasmCloud host"),
                 ("label", "Label (or un-label) a host with a key=value label pair"),
                 (
                     "config",
@@ -289,6 +293,9 @@ enum CliCommand {
     /// Manage declarative applications and deployments (wadm)
     #[clap(name = "app", subcommand)]
     App(AppCliCommand),
+    /// Apply a declarative application manifest to a lattice without wadm
+    #[clap(name = "apply")]
+    Apply(ApplyCommand),
     /// Build (and sign) a wasmCloud component or capability provider
     #[clap(name = "build")]
     Build(BuildCommand),
@@ -361,6 +368,9 @@ enum CliCommand {
     /// Stop a component, capability provider, or host
     #[clap(name = "stop", subcommand)]
     Stop(StopCommand),
+    /// Run integration tests against components running in a wasmCloud host
+    #[clap(name = "test")]
+    Test(TestCommand),
     /// Label (or un-label) a host with a key=value label pair
     #[clap(name = "label", alias = "tag")]
     Label(LabelHostCommand),
@@ -542,6 +552,7 @@ async fn main() {
     );
     let res: anyhow::Result<CommandOutput> = match cli_command {
         CliCommand::App(app_cli) => app::handle_command(app_cli, output_kind).await,
+        CliCommand::Apply(apply_cli) => apply::handle_command(apply_cli).await,
         CliCommand::Build(build_cli) => build::handle_command(build_cli).await,
         CliCommand::Call(call_cli) => call::handle_command(call_cli.command()).await,
         CliCommand::Capture(capture_cli) => {
@@ -594,6 +605,7 @@ async fn main() {
             common::start_cmd::handle_command(start_cli, output_kind).await
         }
         CliCommand::Stop(stop_cli) => common::stop_cmd::handle_command(stop_cli, output_kind).await,
+        CliCommand::Test(test_cli) => test::handle_command(test_cli).await,
         CliCommand::Label(label_cli) => {
             common::label_cmd::handle_command(label_cli, output_kind).await
         }
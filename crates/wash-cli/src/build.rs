@@ -1,20 +1,26 @@
 use std::{collections::HashMap, path::PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{ensure, Context, Result};
 use clap::Parser;
 use serde_json::json;
 
 use wash_lib::{
-    build::{build_project, sign_component_wasm, SignConfig},
+    build::{
+        build_project_with_hooks, provenance::generate_provenance, sign_component_wasm,
+        workspace::build_order, SignConfig,
+    },
     cli::{CommandOutput, CommonPackageArgs},
-    parser::{load_config, TypeConfig},
+    parser::{load_config, load_workspace_config, ProjectConfig, TypeConfig, WorkspaceConfig},
 };
 
-/// Build (and sign) a wasmCloud component, provider, or interface
+use crate::util::load_hooks;
+
+/// Build (and sign) a wasmCloud component, provider, or interface, or all members of a workspace
 #[derive(Debug, Parser, Clone)]
 #[clap(name = "build")]
 pub struct BuildCommand {
-    /// Path to the wasmcloud.toml file or parent folder to use for building
+    /// Path to the wasmcloud.toml file or parent folder to use for building. If it defines a
+    /// workspace, all of its members are built
     #[clap(short = 'p', long = "config-path")]
     config_path: Option<PathBuf>,
 
@@ -59,11 +65,63 @@ pub struct BuildCommand {
     /// (useful for airgapped or disconnected environments)
     #[clap(long = "skip-fetch")]
     pub skip_wit_fetch: bool,
+
+    /// Generate a provenance attestation describing how the artifact was built, signed with the
+    /// issuer key. The attestation is written next to the artifact as `<artifact>.intoto.json`,
+    /// and attached to the artifact by `wash push`
+    #[clap(
+        long = "provenance",
+        env = "WASH_BUILD_PROVENANCE",
+        conflicts_with = "build_only"
+    )]
+    pub provenance: bool,
 }
 
 pub async fn handle_command(command: BuildCommand) -> Result<CommandOutput> {
-    let config = load_config(command.config_path, Some(true)).await?;
+    if let Some(workspace) = load_workspace_config(command.config_path.clone(), Some(true)).await? {
+        return build_workspace(&command, &workspace).await;
+    }
+    let config = load_config(command.config_path.clone(), Some(true)).await?;
+    build(&command, &config).await
+}
+
+/// Build all members of a workspace, after the members they depend on
+async fn build_workspace(
+    command: &BuildCommand,
+    workspace: &WorkspaceConfig,
+) -> Result<CommandOutput> {
+    ensure!(
+        command.subject.is_none(),
+        "a subject key cannot be used to build a workspace, as every member needs its own subject key"
+    );
 
+    let mut text = Vec::with_capacity(workspace.members.len());
+    let mut members = Vec::with_capacity(workspace.members.len());
+    for config in build_order(workspace) {
+        eprintln!("Building workspace member [{}]...", config.common.name);
+        let CommandOutput {
+            mut map,
+            text: member_text,
+        } = build(command, config).await.with_context(|| {
+            format!("failed to build workspace member [{}]", config.common.name)
+        })?;
+        text.push(format!("[{}] {member_text}", config.common.name));
+        map.insert("name".to_string(), json!(config.common.name));
+        members.push(map);
+    }
+
+    Ok(CommandOutput::new(
+        text.join("\n"),
+        HashMap::from([
+            ("workspace".to_string(), json!(workspace.name)),
+            ("members".to_string(), json!(members)),
+        ]),
+    ))
+}
+
+/// Build a single project
+async fn build(command: &BuildCommand, config: &ProjectConfig) -> Result<CommandOutput> {
+    let mut hooks = load_hooks(config).await?;
     match config.project_type {
         TypeConfig::Component(ref component_config) => {
             let sign_config = if command.build_only {
@@ -74,8 +132,8 @@ pub async fn handle_command(command: BuildCommand) -> Result<CommandOutput> {
                         .keys_directory
                         .clone()
                         .or(Some(component_config.key_directory.to_path_buf())),
-                    issuer: command.issuer,
-                    subject: command.subject,
+                    issuer: command.issuer.clone(),
+                    subject: command.subject.clone(),
                     disable_keygen: command.disable_keygen,
                 })
             };
@@ -95,25 +153,38 @@ pub async fn handle_command(command: BuildCommand) -> Result<CommandOutput> {
                     &config.common,
                     component_config,
                     // We prevent supplying both fields in the CLI parser, so this `context` is just a safety fallback
-                    &sign_config.context("cannot supply --build-only and --sign-only")?,
+                    sign_config
+                        .as_ref()
+                        .context("cannot supply --build-only and --sign-only")?,
                     component_wasm_path,
                 )?;
                 config.common.build_dir.join(signed_path)
             } else {
-                build_project(
-                    &config,
+                build_project_with_hooks(
+                    config,
                     sign_config.as_ref(),
                     &command.package_args,
                     command.skip_wit_fetch,
+                    &mut hooks,
                 )
                 .await?
             };
 
-            let json_output = HashMap::from([
+            let provenance_path = match sign_config.as_ref() {
+                Some(sign_config) if command.provenance => {
+                    Some(generate_provenance(config, &component_path, sign_config).await?)
+                }
+                _ => None,
+            };
+
+            let mut json_output = HashMap::from([
                 ("component_path".to_string(), json!(component_path)),
                 ("built".to_string(), json!(!command.sign_only)),
                 ("signed".to_string(), json!(!command.build_only)),
             ]);
+            if let Some(provenance_path) = provenance_path {
+                json_output.insert("provenance_path".to_string(), json!(provenance_path));
+            }
             Ok(CommandOutput::new(
                 if command.build_only {
                     format!("Component built and can be found at {component_path:?}")
@@ -126,25 +197,32 @@ pub async fn handle_command(command: BuildCommand) -> Result<CommandOutput> {
             ))
         }
         TypeConfig::Provider(ref provider_config) => {
-            let path = build_project(
-                &config,
-                Some(&SignConfig {
-                    keys_directory: command
-                        .keys_directory
-                        .clone()
-                        .or(Some(provider_config.key_directory.to_path_buf())),
-                    issuer: command.issuer,
-                    subject: command.subject,
-                    disable_keygen: command.disable_keygen,
-                }),
+            let sign_config = SignConfig {
+                keys_directory: command
+                    .keys_directory
+                    .clone()
+                    .or(Some(provider_config.key_directory.to_path_buf())),
+                issuer: command.issuer.clone(),
+                subject: command.subject.clone(),
+                disable_keygen: command.disable_keygen,
+            };
+            let path = build_project_with_hooks(
+                config,
+                Some(&sign_config),
                 &command.package_args,
                 command.skip_wit_fetch,
+                &mut hooks,
             )
             .await
             .context("failed to build provider")?;
+            let mut json_output = HashMap::from([("path".to_string(), json!(path))]);
+            if command.provenance {
+                let provenance_path = generate_provenance(config, &path, &sign_config).await?;
+                json_output.insert("provenance_path".to_string(), json!(provenance_path));
+            }
             Ok(CommandOutput::new(
                 format!("Built artifact can be found at {path:?}"),
-                HashMap::from([("path".to_string(), json!(path))]),
+                json_output,
             ))
         }
     }
@@ -164,6 +242,7 @@ mod test {
         assert!(cmd.issuer.is_none());
         assert!(cmd.subject.is_none());
         assert!(cmd.keys_directory.is_none());
+        assert!(!cmd.provenance);
 
         let cmd: BuildCommand = Parser::try_parse_from([
             "build",
@@ -176,6 +255,7 @@ mod test {
             "/tmp/sub.nk",
             "--keys-directory",
             "/tmp",
+            "--provenance",
         ])
         .unwrap();
         assert_eq!(cmd.config_path, Some(PathBuf::from("/")));
@@ -183,5 +263,6 @@ mod test {
         assert_eq!(cmd.issuer, Some("/tmp/iss.nk".to_string()));
         assert_eq!(cmd.subject, Some("/tmp/sub.nk".to_string()));
         assert_eq!(cmd.keys_directory, Some(PathBuf::from("/tmp")));
+        assert!(cmd.provenance);
     }
 }
@@ -231,7 +231,7 @@ pub struct ConnectionOpts {
 
     /// Lattice for wasmcloud command interface, defaults to "default"
     #[clap(short = 'x', long = "lattice", env = "WASMCLOUD_LATTICE")]
-    lattice: Option<String>,
+    pub(crate) lattice: Option<String>,
 
     /// Timeout length for RPC, defaults to 2000 milliseconds
     #[clap(
@@ -240,7 +240,7 @@ pub struct ConnectionOpts {
         default_value_t = default_timeout_ms(),
         env = "WASMCLOUD_RPC_TIMEOUT_MS"
     )]
-    timeout_ms: u64,
+    pub(crate) timeout_ms: u64,
 
     /// Name of the context to use for RPC connection, authentication, and cluster seed invocation signing
     #[clap(long = "context")]
@@ -356,10 +356,10 @@ impl HttpHandlerInvocationOpts {
 
 /// Utility type used mostly for printing HTTP responses to the console as JSON
 #[derive(Debug, Clone, Serialize)]
-struct HttpResponse {
-    status: u16,
-    headers: HashMap<String, String>,
-    body: String,
+pub(crate) struct HttpResponse {
+    pub(crate) status: u16,
+    pub(crate) headers: HashMap<String, String>,
+    pub(crate) body: String,
 }
 
 /// Invoke a wRPC endpoint that takes a HTTP request (usually `wasi:http/incoming-handler.handle`);
@@ -371,6 +371,41 @@ async fn wrpc_invoke_http_handler(
     request: http::request::Request<String>,
     extract_json: bool,
 ) -> Result<CommandOutput> {
+    let http_resp = invoke_http_handler(client, lattice, component_id, timeout_ms, request).await?;
+
+    // If the option for parsing the response as JSON was provided, parse it directly,
+    // and return that as JSON
+    let output = if extract_json {
+        let body_json = serde_json::from_str(&http_resp.body)
+            .context("failed to parse response body bytes into a valid JSON object")?;
+        CommandOutput::new(
+            serde_json::to_string_pretty(&body_json)
+                .context("failed to print http response JSON")?,
+            HashMap::from([("response".into(), body_json)]),
+        )
+    } else {
+        CommandOutput::new(
+            serde_json::to_string_pretty(&http_resp)
+                .context("failed to print http response JSON")?,
+            HashMap::from([(
+                "response".into(),
+                serde_json::to_value(&http_resp)
+                    .context("failed to convert http response to value")?,
+            )]),
+        )
+    };
+
+    Ok(output)
+}
+
+/// Send a HTTP request to a component over wRPC, returning the response it produced
+pub(crate) async fn invoke_http_handler(
+    client: wrpc_transport_nats::Client,
+    lattice: &str,
+    component_id: &str,
+    timeout_ms: u64,
+    request: http::request::Request<String>,
+) -> Result<HttpResponse> {
     use futures::StreamExt;
     use wrpc_interface_http::InvokeIncomingHandler as _;
 
@@ -405,35 +440,12 @@ async fn wrpc_invoke_http_handler(
             }
             let body = body.freeze();
 
-            // If the option for parsing the response as JSON was provided, parse it directly,
-            // and return that as JSON
-            let output = if extract_json {
-                let body_json = serde_json::from_slice(&body)
-                    .context("failed to parse response body bytes into a valid JSON object")?;
-                CommandOutput::new(
-                    serde_json::to_string_pretty(&body_json)
-                        .context("failed to print http response JSON")?,
-                    HashMap::from([("response".into(), body_json)]),
-                )
-            } else {
-                let http_resp = HttpResponse {
-                    status,
-                    headers,
-                    body: String::from_utf8(Vec::from(body))
-                        .context("failed to parse returned bytes as string")?,
-                };
-                CommandOutput::new(
-                    serde_json::to_string_pretty(&http_resp)
-                        .context("failed to print http response JSON")?,
-                    HashMap::from([(
-                        "response".into(),
-                        serde_json::to_value(&http_resp)
-                            .context("failed to convert http response to value")?,
-                    )]),
-                )
-            };
-
-            Ok(output)
+            Ok(HttpResponse {
+                status,
+                headers,
+                body: String::from_utf8(Vec::from(body))
+                    .context("failed to parse returned bytes as string")?,
+            })
         }
         // For all other responses, something has gone wrong
         _ => bail!("unexpected response after HTTP wRPC invocation"),
@@ -449,6 +461,30 @@ async fn wrpc_invoke_simple(
     function_name: &str,
     timeout_ms: u64,
 ) -> Result<CommandOutput> {
+    let result = invoke_simple(
+        client,
+        lattice,
+        component_id,
+        instance,
+        function_name,
+        timeout_ms,
+    )
+    .await?;
+    Ok(CommandOutput::new(
+        result.clone(),
+        HashMap::from([("result".to_string(), json!(result))]),
+    ))
+}
+
+/// Invoke a function of a component over wRPC that takes nothing and returns a string
+pub(crate) async fn invoke_simple(
+    client: wrpc_transport_nats::Client,
+    lattice: &str,
+    component_id: &str,
+    instance: &str,
+    function_name: &str,
+    timeout_ms: u64,
+) -> Result<String> {
     let result = client
            .timeout(Duration::from_millis(timeout_ms))
            .invoke_values_blocking::<_, ((),), (String,)>(
@@ -462,9 +498,7 @@ async fn wrpc_invoke_simple(
    .with_context(|| format!("timed out invoking component, is component [{component_id}] running in lattice [{lattice}]?"));
 
     match result {
-       Ok((result,)) => {
-               Ok(CommandOutput::new(result.clone(), HashMap::from([("result".to_string(), json!(result))])))
-       }
+       Ok((result,)) => Ok(result),
        Err(e) if e.to_string().contains("transmission failed") => bail!("No component responded to your request, ensure component {component_id} is running in lattice {lattice}"),
        Err(e) => bail!("Error invoking component: {e}"),
    }
@@ -525,7 +559,9 @@ pub fn call_output(
 ///
 /// Normally we would use `create_nats_client_from_opts` here, but until the schism between [`async_nats_wrpc`]
 /// and [`async_nats`] is resolved, we must replicate that logic here, as upstream `async_nats` does not match.
-async fn create_client_from_opts_wrpc(opts: &ConnectionOpts) -> Result<async_nats::Client> {
+pub(crate) async fn create_client_from_opts_wrpc(
+    opts: &ConnectionOpts,
+) -> Result<async_nats::Client> {
     let ConnectionOpts {
         rpc_host: host,
         rpc_port: port,
@@ -608,7 +644,7 @@ async fn create_client_from_opts_wrpc(opts: &ConnectionOpts) -> Result<async_nat
     Ok(nc)
 }
 
-fn gen_wash_call_headers() -> async_nats::HeaderMap {
+pub(crate) fn gen_wash_call_headers() -> async_nats::HeaderMap {
     let mut headers = async_nats::HeaderMap::new();
     headers.insert("source-id", "wash");
     headers
@@ -1,8 +1,9 @@
+use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::hash::Hash;
 use std::path::{Path, PathBuf};
 
-use anyhow::{bail, Context as _, Result};
+use anyhow::{bail, ensure, Context as _, Result};
 use semver::Version;
 use tracing::{debug, trace};
 
@@ -12,7 +13,8 @@ use wadm_types::{
 };
 use wash_lib::generate::emoji;
 use wash_lib::parser::{
-    DevConfigSpec, DevSecretSpec, InterfaceComponentOverride, ProjectConfig, WitInterfaceSpec,
+    DevConfigSpec, DevSecretSpec, InterfaceComponentOverride, InterfaceMock, ProjectConfig,
+    WitInterfaceSpec,
 };
 use wasmcloud_core::{parse_wit_package_name, LinkName, WitInterface, WitNamespace, WitPackage};
 
@@ -26,6 +28,8 @@ use super::{
 
 // Collection of widely used WIT constants to avoid magic strings
 const DEFAULT_LINK_NAME: &str = "default";
+/// Name of the component linked to a provider under development
+const TEST_COMPONENT_NAME: &str = "test-component";
 const WIT_IFACE_ATOMICS: &str = "atomics";
 const WIT_IFACE_BATCH: &str = "batch";
 const WIT_IFACE_BLOBSTORE: &str = "blobstore";
@@ -322,6 +326,7 @@ impl DependencySpec {
                     is_component: false,
                     configs: Default::default(),
                     secrets: Default::default(),
+                    mock: None,
                 }))
             }
             // wasi:http/outgoing-handler -> http-client
@@ -342,6 +347,7 @@ impl DependencySpec {
                     is_component: false,
                     configs: Default::default(),
                     secrets: Default::default(),
+                    mock: None,
                 }))
             }
             // wasi:blobstore/blobstore -> blobstore-fs
@@ -363,6 +369,7 @@ impl DependencySpec {
                     is_component: false,
                     configs: Default::default(),
                     secrets: Default::default(),
+                    mock: None,
                 }))
             }
             // wasmcloud:messaging/consumer -> messaging-nats
@@ -383,6 +390,7 @@ impl DependencySpec {
                     is_component: false,
                     configs: Default::default(),
                     secrets: Default::default(),
+                    mock: None,
                 }))
             }
             // Support wildcard interfaces
@@ -400,6 +408,7 @@ impl DependencySpec {
                 is_component: false,
                 configs: Default::default(),
                 secrets: Default::default(),
+                mock: None,
             })),
             // Treat all other dependencies as custom, and track them as dependencies,
             // though they cannot be resolved to a proper dependency without an explicit override/
@@ -418,6 +427,7 @@ impl DependencySpec {
                 is_component: false,
                 configs: Default::default(),
                 secrets: Default::default(),
+                mock: None,
             })),
         }
     }
@@ -471,6 +481,7 @@ impl DependencySpec {
                     is_component: false,
                     configs: Default::default(),
                     secrets: Default::default(),
+                    mock: None,
                 }))
             }
             // wasmcloud:messaging/handler -> messaging-nats -> component
@@ -491,6 +502,7 @@ impl DependencySpec {
                     is_component: false,
                     configs: Default::default(),
                     secrets: Default::default(),
+                    mock: None,
                 }))
             }
             // Support wildcard interfaces
@@ -508,6 +520,7 @@ impl DependencySpec {
                 is_component: false,
                 configs: Default::default(),
                 secrets: Default::default(),
+                mock: None,
             })),
             // Treat all other dependencies as custom, and track them as dependencies,
             // though they cannot be resolved to a proper dependency without an explicit override/
@@ -526,6 +539,7 @@ impl DependencySpec {
                 is_component: false,
                 configs: Default::default(),
                 secrets: Default::default(),
+                mock: None,
             })),
         }
     }
@@ -653,6 +667,11 @@ pub(crate) struct DependencySpecInner {
     /// [`SecretProperty`] here support a special `policy` value which is 'env'.
     /// Paired with a key that looks like "$SOME_VALUE", the value will be extracted from ENV *prior* and
     pub(crate) secrets: Vec<wadm_types::SecretProperty>,
+
+    /// In-memory mock that serves this dependency in place of a component or provider
+    ///
+    /// Mocked dependencies are served by `wash dev` itself, so they are left out of generated manifests.
+    pub(crate) mock: Option<InterfaceMock>,
 }
 
 impl DependencySpecInner {
@@ -664,6 +683,8 @@ impl DependencySpecInner {
             Some(DEFAULT_HTTP_SERVER_PROVIDER_IMAGE) => "http-server".into(),
             Some(DEFAULT_BLOBSTORE_FS_PROVIDER_IMAGE) => "blobstore-fs".into(),
             Some(DEFAULT_MESSAGING_NATS_PROVIDER_IMAGE) => "messaging-nats".into(),
+            // Interfaces resolved to the test component of a provider share a single component
+            Some(_) if self.is_component => TEST_COMPONENT_NAME.into(),
             // Custom dependencies get the format `custom-<namespace>-<package>-<interfaces>`
             _ => format!(
                 "custom-{}-{}{}",
@@ -702,6 +723,7 @@ impl DependencySpecInner {
         self.delegated_to_workspace = other.delegated_to_workspace;
         self.image_ref = other.image_ref.clone();
         self.link_name = other.link_name.clone();
+        self.mock = other.mock.clone();
         // Extend interfaces with the other interfaces
         match (&mut self.wit.interfaces, other.wit.interfaces.as_ref()) {
             (Some(self_ifaces), Some(other_ifaces)) => {
@@ -805,11 +827,28 @@ impl ProjectDeps {
                 secrets,
                 image_ref,
                 link_name,
+                mock,
                 ..
             },
             mut dep_spec,
         ) in overrides_with_deps.drain(..)
         {
+            if let Some(mock) = mock {
+                ensure!(
+                    image_ref.is_none(),
+                    "interface override [{}] cannot specify both a mock and an image reference",
+                    dep_spec.name(),
+                );
+                ensure!(
+                    matches!(dep_spec, DependencySpec::Exports(_)),
+                    "interface override [{}] cannot be mocked, only imports can be mocked",
+                    dep_spec.name(),
+                );
+                let inner = dep_spec.inner_mut();
+                inner.image_ref = None;
+                inner.mock = Some(mock.clone());
+            }
+
             if let Some(image_ref) = image_ref {
                 dep_spec.set_image_ref(image_ref);
             }
@@ -872,6 +911,29 @@ impl ProjectDeps {
         Ok(())
     }
 
+    /// Resolve all dependencies that have no image reference to the component at `image_ref`
+    ///
+    /// This is used when developing a provider, so that its interfaces are linked to a single
+    /// component which exercises them.
+    pub(crate) fn resolve_with_test_component(&mut self, image_ref: &str) {
+        for dep in self.dependencies.values_mut().flatten() {
+            let inner = dep.inner_mut();
+            if inner.image_ref.is_none() && inner.mock.is_none() {
+                inner.image_ref = Some(image_ref.into());
+                inner.is_component = true;
+            }
+        }
+    }
+
+    /// Retrieve the dependencies that should be served by in-memory mocks
+    pub(crate) fn mocked_deps(&self) -> impl Iterator<Item = &DependencySpecInner> {
+        self.dependencies
+            .values()
+            .flatten()
+            .map(DependencySpec::inner)
+            .filter(|dep| dep.mock.is_some())
+    }
+
     /// Merge another bundle of dependencies (possibly derived from some other source of metadata)
     ///
     /// Note that the `other` will override the values `self`, where necessary.
@@ -933,12 +995,16 @@ impl ProjectDeps {
 
         // Generate components for all the dependencies, using a map from component name to component
         // to remove duplicates
-        let mut components = HashMap::new();
+        let mut components = HashMap::<String, Component>::new();
 
         let mut contains_secrets = false;
 
         // For each dependency, go through and generate the component along with necessary links
         for dep in self.dependencies.values().flatten() {
+            // Mocked dependencies are served by `wash dev`, rather than deployed
+            if dep.inner().mock.is_some() {
+                continue;
+            }
             let dep = dep.clone();
             // If a dependency could not be generated into a component, skip it
             let Ok(mut dep_component) = dep
@@ -1148,9 +1214,22 @@ impl ProjectDeps {
                 }
             }
 
-            // Add the dependency component after we've made necessary links
-            if let Some(c) = components.insert(dep_component.name.clone(), dep_component) {
-                debug!("replacing duplicate component [{}]", c.name);
+            // Add the dependency component after we've made necessary links, keeping the links
+            // of dependencies that resolved to the same component
+            match components.entry(dep_component.name.clone()) {
+                Entry::Occupied(mut existing) => {
+                    debug!("merging duplicate component [{}]", dep_component.name);
+                    if let Some(traits) = dep_component.traits {
+                        existing
+                            .get_mut()
+                            .traits
+                            .get_or_insert(Vec::new())
+                            .extend(traits);
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(dep_component);
+                }
             }
         }
 
@@ -1225,3 +1304,86 @@ impl ProjectDeps {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_with_test_component() {
+        let pkey = ProjectDependencyKey::from_project("provider", "/tmp/provider").unwrap();
+        let mut deps = ProjectDeps::from_known_deps(
+            pkey,
+            [
+                // Interfaces exported by the provider, which the test component invokes
+                DependencySpec::from_wit_export_iface("wasmcloud:example/store").unwrap(),
+                DependencySpec::from_wit_export_iface("wasmcloud:example/admin").unwrap(),
+                // Interface imported by the provider, which invokes the test component
+                DependencySpec::from_wit_import_iface("wasmcloud:example/handler").unwrap(),
+                // Known dependency which is not resolved to the test component
+                DependencySpec::from_wit_import_iface("wasi:keyvalue/store").unwrap(),
+            ],
+        )
+        .unwrap();
+        deps.session_id = Some("abc123".into());
+        deps.component = Some(Component {
+            name: "abc123-provider".into(),
+            properties: Properties::Capability {
+                properties: CapabilityProperties {
+                    image: Some("file:///tmp/provider.par.gz".into()),
+                    application: None,
+                    id: Some("abc123-provider".into()),
+                    config: Vec::new(),
+                    secrets: Vec::new(),
+                },
+            },
+            traits: None,
+        });
+        deps.resolve_with_test_component("file:///tmp/test_s.wasm");
+
+        let manifest = deps
+            .generate_wadm_manifests()
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap();
+        let test_component = manifest
+            .spec
+            .components
+            .iter()
+            .find(|c| c.name == "abc123-dep-test-component")
+            .expect("test component should be in the manifest");
+        assert!(matches!(
+            &test_component.properties,
+            Properties::Component { properties } if properties.image.as_deref() == Some("file:///tmp/test_s.wasm")
+        ));
+        // Both exported interfaces are linked from the test component to the provider
+        let test_component_links = test_component
+            .traits
+            .iter()
+            .flatten()
+            .filter(|t| t.is_link())
+            .count();
+        assert_eq!(test_component_links, 2);
+
+        let provider = manifest
+            .spec
+            .components
+            .iter()
+            .find(|c| c.name == "abc123-provider")
+            .expect("provider should be in the manifest");
+        let provider_link_targets = provider
+            .traits
+            .iter()
+            .flatten()
+            .filter_map(|t| match &t.properties {
+                TraitProperty::Link(link) => Some(link.target.name.as_str()),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        assert_eq!(
+            provider_link_targets,
+            HashSet::from(["abc123-dep-test-component", "abc123-dep-keyvalue-nats"])
+        );
+    }
+}
@@ -1,28 +1,32 @@
 use std::collections::{BTreeSet, HashMap};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use anyhow::{bail, ensure, Context as _, Result};
+use anyhow::{anyhow, bail, ensure, Context as _, Result};
 use console::style;
 use tracing::{debug, warn};
 use wash_lib::app::AppManifest;
 use wash_lib::cli::stop::stop_provider;
 use wash_lib::component::{scale_component, ScaleComponentArgs};
-use wasmcloud_control_interface::Client as CtlClient;
+use wasmcloud_control_interface::{Client as CtlClient, ProviderDescription, StartProviderCommand};
 
 use wadm_types::{ConfigProperty, Manifest, Properties, SecretProperty, SecretSourceProperty};
-use wash_lib::build::{build_project, SignConfig};
+use wash_lib::build::{build_project_with_hooks, SignConfig};
 use wash_lib::cli::{CommonPackageArgs, OutputKind};
 use wash_lib::generate::emoji;
 use wash_lib::parser::{
     load_config, DevConfigSpec, DevManifestComponentTarget, DevSecretSpec, ProjectConfig,
     TypeConfig,
 };
+use wash_lib::plugin::hooks::{Event, HookRunner};
 
 use crate::app::deploy_model_from_manifest;
 use crate::appearance::spinner::Spinner;
+use crate::util::load_hooks;
 
 use super::deps::{DependencySpec, ProjectDependencyKey, ProjectDeps};
+use super::embedded::DirectDeployment;
 use super::manifest::{generate_component_from_project_cfg, generate_help_text_for_manifest};
+use super::mock::{mock_specs, MockServer};
 use super::session::WashDevSession;
 use super::wit::{discover_dependencies_from_wit, parse_component_wit, parse_project_wit};
 use super::DEFAULT_PROVIDER_STOP_TIMEOUT_MS;
@@ -32,6 +36,7 @@ pub(crate) struct RunLoopState<'a> {
     pub(crate) dev_session: &'a mut WashDevSession,
     pub(crate) nats_client: &'a async_nats::Client,
     pub(crate) ctl_client: &'a CtlClient,
+    pub(crate) host_id: &'a str,
     pub(crate) project_cfg: &'a mut ProjectConfig,
     pub(crate) lattice: &'a str,
     pub(crate) session_id: &'a str,
@@ -43,11 +48,19 @@ pub(crate) struct RunLoopState<'a> {
     pub(crate) package_args: &'a CommonPackageArgs,
     pub(crate) skip_fetch: bool,
     pub(crate) output_kind: OutputKind,
+    /// Deployment of manifests directly to an embedded host, used in place of wadm
+    pub(crate) direct_deployment: Option<DirectDeployment>,
+    /// Server of the in-memory mocks that replace dependencies of the project
+    pub(crate) mocks: MockServer,
+    /// Names of the configs of the provider being developed, from the most recently generated
+    /// manifests
+    pub(crate) provider_config: Vec<String>,
 }
 
 /// Generate manifests that should be deployed, based on the current run loop state
 pub(crate) async fn generate_manifests(
     RunLoopState {
+        ctl_client,
         project_cfg,
         session_id,
         ref mut previous_deps,
@@ -55,6 +68,7 @@ pub(crate) async fn generate_manifests(
         component_id,
         component_ref,
         manifest_output_dir,
+        mocks,
         ..
     }: &mut RunLoopState<'_>,
 ) -> Result<Vec<Manifest>> {
@@ -73,7 +87,7 @@ pub(crate) async fn generate_manifests(
     };
 
     // Pull implied dependencies from WIT
-    let wit_implied_deps = discover_dependencies_from_wit(resolve, world_id)
+    let wit_implied_deps = discover_dependencies_from_wit(&resolve, world_id)
         .context("failed to resolve dependent components")?;
 
     let pkey = ProjectDependencyKey::from_project(
@@ -95,6 +109,16 @@ pub(crate) async fn generate_manifests(
     current_project_deps
         .merge_override(project_override_deps)
         .context("failed to merge & override project-specified deps")?;
+    // Link the interfaces of a provider under development to its test component, if one is set
+    if let (TypeConfig::Provider(_), Some(test_component)) =
+        (&project_cfg.project_type, &project_cfg.dev.test_component)
+    {
+        let test_component_ref =
+            resolve_test_component_ref(test_component, &project_cfg.common.project_dir)
+                .await
+                .context("failed to resolve test component")?;
+        current_project_deps.resolve_with_test_component(&test_component_ref);
+    }
     eprintln!(
         "{} Detected component dependencies: {:?}",
         emoji::INFO_SQUARE,
@@ -117,6 +141,14 @@ pub(crate) async fn generate_manifests(
             .map(Some)
             .context("failed to generate app component")?;
 
+    // Serve the mocked dependencies, which are left out of the generated manifests
+    let specs = mock_specs(component_id, &resolve, world_id, &current_project_deps)
+        .context("failed to build interface mocks")?;
+    mocks
+        .sync(ctl_client, specs)
+        .await
+        .context("failed to serve interface mocks")?;
+
     // If deps haven't changed, then we can simply restart the component and return
     let project_deps_unchanged = previous_deps
         .as_ref()
@@ -137,27 +169,7 @@ pub(crate) async fn generate_manifests(
 
     // Write out manifests to local files if a manifest output dir was specified
     if let Some(output_dir) = &manifest_output_dir {
-        for manifest in manifests.iter() {
-            ensure!(
-                tokio::fs::metadata(output_dir)
-                    .await
-                    .context("failed to get manifest output dir metadata")
-                    .is_ok_and(|f| f.is_dir()),
-                "manifest output directory [{}] must exist and be a folder",
-                output_dir.display()
-            );
-            tokio::fs::write(
-                output_dir.join(format!("{}.yaml", manifest.metadata.name)),
-                serde_yaml::to_string(&manifest).context("failed to convert manifest to YAML")?,
-            )
-            .await
-            .with_context(|| {
-                format!(
-                    "failed to write out manifest YAML to output dir [{}]",
-                    output_dir.display(),
-                )
-            })?
-        }
+        write_manifests(output_dir, &manifests).await?;
     }
 
     // Update deps, since they must be different
@@ -166,6 +178,58 @@ pub(crate) async fn generate_manifests(
     Ok(manifests)
 }
 
+/// Generate the ID of the component or provider of a project under development
+pub(crate) fn dev_component_id(session_id: &str, project_name: &str) -> String {
+    format!(
+        "{session_id}-{}",
+        project_name.to_lowercase().replace(" ", "-")
+    )
+}
+
+/// Write out manifests as YAML files to the given output directory
+pub(crate) async fn write_manifests(output_dir: &Path, manifests: &[Manifest]) -> Result<()> {
+    for manifest in manifests.iter() {
+        ensure!(
+            tokio::fs::metadata(output_dir)
+                .await
+                .context("failed to get manifest output dir metadata")
+                .is_ok_and(|f| f.is_dir()),
+            "manifest output directory [{}] must exist and be a folder",
+            output_dir.display()
+        );
+        tokio::fs::write(
+            output_dir.join(format!("{}.yaml", manifest.metadata.name)),
+            serde_yaml::to_string(&manifest).context("failed to convert manifest to YAML")?,
+        )
+        .await
+        .with_context(|| {
+            format!(
+                "failed to write out manifest YAML to output dir [{}]",
+                output_dir.display(),
+            )
+        })?
+    }
+    Ok(())
+}
+
+/// Resolve the reference to the test component of a provider project. Paths to components that
+/// exist (relative to the project directory) are converted to `file://` references, and anything
+/// else is assumed to be an image reference.
+pub(crate) async fn resolve_test_component_ref(
+    test_component: &str,
+    project_dir: &Path,
+) -> Result<String> {
+    let path = project_dir.join(test_component);
+    if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+        let path = tokio::fs::canonicalize(&path)
+            .await
+            .with_context(|| format!("failed to resolve path [{}]", path.display()))?;
+        Ok(format!("file://{}", path.display()))
+    } else {
+        Ok(test_component.into())
+    }
+}
+
 /// Load existing manifests specified
 ///
 /// # Arguments
@@ -333,27 +397,21 @@ pub(crate) async fn run(state: &mut RunLoopState<'_>) -> Result<()> {
         );
     }
     // Build the project (equivalent to `wash build`)
-    let built_artifact_path = match build_project(
-        state.project_cfg,
-        Some(&SignConfig::default()),
-        state.package_args,
-        state.skip_fetch,
-    )
-    .await
-    {
-        Ok(artifact_path) => artifact_path,
-        Err(e) => {
-            eprintln!(
-                "{} {}\n{}",
-                emoji::ERROR,
-                style("Failed to build project:").red(),
-                e
-            );
-            // Failing to build the project can be corrected by changing the code and shouldn't
-            // stop the development loop
-            return Ok(());
-        }
-    };
+    let (built_artifact_path, mut hooks) =
+        match build_with_hooks(state.project_cfg, state.package_args, state.skip_fetch).await {
+            Ok(built) => built,
+            Err(e) => {
+                eprintln!(
+                    "{} {}\n{}",
+                    emoji::ERROR,
+                    style("Failed to build project:").red(),
+                    e
+                );
+                // Failing to build the project can be corrected by changing the code and shouldn't
+                // stop the development loop
+                return Ok(());
+            }
+        };
     spinner.finish_and_clear();
     eprintln!(
         "{} Successfully built project at [{}]",
@@ -362,15 +420,9 @@ pub(crate) async fn run(state: &mut RunLoopState<'_>) -> Result<()> {
     );
 
     // Update the dev loop state for reuse
-    state.component_id = Some(format!(
-        "{}-{}",
+    state.component_id = Some(dev_component_id(
         state.session_id,
-        state
-            .project_cfg
-            .common
-            .name
-            .to_lowercase()
-            .replace(" ", "-"),
+        &state.project_cfg.common.name,
     ));
     state.component_ref = Some(format!("file://{}", built_artifact_path.display()));
     state.artifact_path = Some(built_artifact_path);
@@ -414,6 +466,10 @@ pub(crate) async fn run(state: &mut RunLoopState<'_>) -> Result<()> {
         .component_ref
         .as_ref()
         .context("unexpectedly missing component_ref")?;
+    let kind = match state.project_cfg.project_type {
+        TypeConfig::Component(_) => "component",
+        TypeConfig::Provider(_) => "provider",
+    };
 
     // If manifests are empty, let the user know we're not deploying anything, just reloading
     // the same component
@@ -422,7 +478,7 @@ pub(crate) async fn run(state: &mut RunLoopState<'_>) -> Result<()> {
             "{} {}",
             emoji::RECYCLE,
             style(format!(
-                "(Fast-)Reloading component [{component_id}] (no dependencies have changed)..."
+                "(Fast-)Reloading {kind} [{component_id}] (no dependencies have changed)..."
             ))
             .bold()
         );
@@ -430,27 +486,109 @@ pub(crate) async fn run(state: &mut RunLoopState<'_>) -> Result<()> {
         eprintln!(
             "{} {}",
             emoji::RECYCLE,
-            style(format!("Reloading component [{component_id}]...")).bold()
+            style(format!("Reloading {kind} [{component_id}]...")).bold()
         );
     }
 
-    // Scale the component to zero, trusting that wadm will re-create it
-    scale_down_component(
-        state.ctl_client,
+    let host_id = state.host_id;
+    match state.project_cfg.project_type {
+        // Scale the component to zero, trusting that wadm will re-create it
+        TypeConfig::Component(_) => {
+            scale_down_component(state.ctl_client, host_id, component_id, component_ref)
+                .await
+                .with_context(|| format!("failed to reload component [{component_id}]"))?
+        }
+        // Restart the provider, so that the newly built binary is used
+        TypeConfig::Provider(_) => {
+            if let Some(config) = provider_config(&manifests, component_id) {
+                state.provider_config = config;
+            }
+            restart_provider(
+                state.ctl_client,
+                host_id,
+                component_id,
+                component_ref,
+                state.provider_config.clone(),
+            )
+            .await
+            .with_context(|| format!("failed to reload provider [{component_id}]"))?
+        }
+    }
+
+    match state.direct_deployment.as_mut() {
+        Some(deployment) => {
+            deployment
+                .apply(state.ctl_client, host_id, manifests)
+                .await?
+        }
+        None => apply_manifests(state.nats_client, state.lattice, manifests).await?,
+    }
+
+    run_reload_hooks(
+        &mut hooks,
         state.project_cfg,
-        &state
-            .dev_session
-            .host_data
-            .as_ref()
-            .context("missing host ID for session")?
-            .0,
-        component_id,
+        state.artifact_path.as_deref(),
         component_ref,
     )
-    .await
-    .with_context(|| format!("failed to reload component [{component_id}]"))?;
+    .await;
+    Ok(())
+}
 
-    // Apply all manifests
+/// Build the project (equivalent to `wash build`), running the pre-build and post-build hooks
+/// declared by the project around the build.
+///
+/// The loaded hooks are returned along with the path to the built artifact, so that they can be
+/// run again once the project is reloaded.
+pub(crate) async fn build_with_hooks(
+    project_cfg: &ProjectConfig,
+    package_args: &CommonPackageArgs,
+    skip_fetch: bool,
+) -> Result<(PathBuf, HookRunner)> {
+    // Hooks are loaded on every build, as the project configuration may have changed
+    let mut hooks = load_hooks(project_cfg).await?;
+    let artifact_path = build_project_with_hooks(
+        project_cfg,
+        Some(&SignConfig::default()),
+        package_args,
+        skip_fetch,
+        &mut hooks,
+    )
+    .await?;
+    Ok((artifact_path, hooks))
+}
+
+/// Run the dev-reload hooks declared by the project, after it was reloaded.
+///
+/// Failing hooks only produce a warning, as the project has already been reloaded
+pub(crate) async fn run_reload_hooks(
+    hooks: &mut HookRunner,
+    project_cfg: &ProjectConfig,
+    artifact_path: Option<&Path>,
+    component_ref: &str,
+) {
+    if let Err(e) = hooks
+        .run(
+            Event::DevReload,
+            &project_cfg.common.project_dir,
+            artifact_path,
+            Some(component_ref),
+        )
+        .await
+    {
+        eprintln!(
+            "{} {}\n{e:?}",
+            emoji::WARN,
+            style("Failed to run dev-reload hooks:").yellow(),
+        );
+    }
+}
+
+/// Put and deploy manifests, printing help text for the components they contain
+pub(crate) async fn apply_manifests(
+    nats_client: &async_nats::Client,
+    lattice: &str,
+    manifests: impl IntoIterator<Item = Manifest>,
+) -> Result<()> {
     for manifest in manifests {
         // Generate all help text for this manifest
         let help_text_lines = generate_help_text_for_manifest(&manifest);
@@ -459,13 +597,7 @@ pub(crate) async fn run(state: &mut RunLoopState<'_>) -> Result<()> {
             serde_json::to_string(&manifest).context("failed to convert manifest to JSON")?;
 
         // Put the manifest
-        match wash_lib::app::put_model(
-            state.nats_client,
-            Some(state.lattice.to_string()),
-            &model_json,
-        )
-        .await
-        {
+        match wash_lib::app::put_model(nats_client, Some(lattice.to_string()), &model_json).await {
             Ok(_) => {
                 debug!(
                     name = manifest.metadata.name.as_str(),
@@ -488,8 +620,8 @@ pub(crate) async fn run(state: &mut RunLoopState<'_>) -> Result<()> {
 
         // Deploy the manifest
         deploy_model_from_manifest(
-            state.nats_client,
-            Some(state.lattice.to_string()),
+            nats_client,
+            Some(lattice.to_string()),
             AppManifest::ModelName(manifest.metadata.name.clone()),
             None,
         )
@@ -516,9 +648,8 @@ pub(crate) async fn run(state: &mut RunLoopState<'_>) -> Result<()> {
 }
 
 /// Scale a component to zero
-async fn scale_down_component(
+pub(crate) async fn scale_down_component(
     client: &CtlClient,
-    project_cfg: &ProjectConfig,
     host_id: &str,
     component_id: &str,
     component_ref: &str,
@@ -526,43 +657,227 @@ async fn scale_down_component(
     // Now that backing infrastructure has changed, we should scale the component
     // as the component (if it was running before) has *not* changed.
     //
-    // Scale the WADM component (which can be either a component or provider) down,
-    // expecting that WADM should restore it (and trigger a reload)
-    match project_cfg.project_type {
-        wash_lib::parser::TypeConfig::Component(_) => {
-            scale_component(ScaleComponentArgs {
-                client,
-                host_id,
-                component_id,
-                component_ref,
-                max_instances: 0,
-                annotations: None,
-                config: vec![],
-                skip_wait: false,
-                timeout_ms: None,
-            })
-            .await
-            .with_context(|| {
-                format!("failed to scale down component [{component_id}] for reload")
-            })?;
-        }
-        wash_lib::parser::TypeConfig::Provider(_) => {
-            if let Err(e) = stop_provider(
-                client,
-                Some(host_id),
-                component_id,
-                false,
-                DEFAULT_PROVIDER_STOP_TIMEOUT_MS,
-            )
-            .await
+    // Scale the WADM component down, expecting that WADM should restore it (and trigger a reload)
+    scale_component(ScaleComponentArgs {
+        client,
+        host_id,
+        component_id,
+        component_ref,
+        max_instances: 0,
+        annotations: None,
+        config: vec![],
+        skip_wait: false,
+        timeout_ms: None,
+    })
+    .await
+    .with_context(|| format!("failed to scale down component [{component_id}] for reload"))?;
+
+    Ok(())
+}
+
+/// Returns the names of the configs of the provider with ID `provider_id` in `manifests`, if the
+/// provider is part of them
+pub(crate) fn provider_config(manifests: &[Manifest], provider_id: &str) -> Option<Vec<String>> {
+    manifests
+        .iter()
+        .flat_map(|manifest| &manifest.spec.components)
+        .find_map(|component| match &component.properties {
+            Properties::Capability { properties }
+                if properties.id.as_deref() == Some(provider_id) =>
             {
-                eprintln!(
-                    "{} Failed to stop provider component [{component_id}] during wash dev: {e}",
-                    emoji::WARN,
-                );
+                Some(properties.config.iter().map(|c| c.name.clone()).collect())
             }
-        }
+            _ => None,
+        })
+}
+
+/// Returns the command that starts the provider with ID `provider_id` again after it is stopped,
+/// if it is one of the `running` providers.
+///
+/// The provider keeps the annotations it was running with, so that it remains managed by wadm.
+pub(crate) fn provider_restart_command(
+    running: &[ProviderDescription],
+    host_id: &str,
+    provider_id: &str,
+    provider_ref: &str,
+    config: Vec<String>,
+) -> Result<Option<StartProviderCommand>> {
+    let Some(provider) = running.iter().find(|p| p.id() == provider_id) else {
+        return Ok(None);
+    };
+    let mut command = StartProviderCommand::builder()
+        .host_id(host_id)
+        .provider_id(provider_id)
+        .provider_ref(provider_ref)
+        .config(config);
+    if let Some(annotations) = provider.annotations() {
+        command = command.annotations(annotations.clone());
     }
+    command
+        .build()
+        .map(Some)
+        .map_err(|e| anyhow!(e).context("failed to build provider start command"))
+}
 
+/// Restart a provider that is running on the host, so that it runs the binary most recently
+/// built into the archive at `provider_ref`.
+///
+/// The provider is started again with the command from [`provider_restart_command`], using the
+/// names of the configs in `config`, as found by [`provider_config`]. Providers that are not
+/// running yet are left to wadm to start.
+pub(crate) async fn restart_provider(
+    client: &CtlClient,
+    host_id: &str,
+    provider_id: &str,
+    provider_ref: &str,
+    config: Vec<String>,
+) -> Result<()> {
+    let inventory = client
+        .get_host_inventory(host_id)
+        .await
+        .map_err(|e| anyhow!(e).context("failed to get host inventory"))?;
+    let Some(command) = provider_restart_command(
+        inventory
+            .data()
+            .map(|inventory| inventory.providers().as_slice())
+            .unwrap_or_default(),
+        host_id,
+        provider_id,
+        provider_ref,
+        config,
+    )?
+    else {
+        return Ok(());
+    };
+
+    if let Err(e) = stop_provider(
+        client,
+        Some(host_id),
+        provider_id,
+        false,
+        DEFAULT_PROVIDER_STOP_TIMEOUT_MS,
+    )
+    .await
+    {
+        eprintln!(
+            "{} Failed to stop provider [{provider_id}] during wash dev: {e}",
+            emoji::WARN,
+        );
+    }
+
+    let ack = client
+        .start_provider(
+            command.host_id(),
+            command.provider_ref(),
+            command.provider_id(),
+            command.annotations().cloned(),
+            command.config().clone(),
+        )
+        .await
+        .map_err(|e| anyhow!(e).context("failed to start provider"))?;
+    ensure!(
+        ack.succeeded(),
+        "failed to start provider [{provider_id}]: {}",
+        ack.message()
+    );
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[test]
+    fn test_provider_config() {
+        let manifest: Manifest = serde_yaml::from_str(
+            r#"
+apiVersion: core.oam.dev/v1beta1
+kind: Application
+metadata:
+  name: dev
+  annotations:
+    version: v0.0.1
+spec:
+  components:
+    - name: component
+      type: component
+      properties:
+        image: file:///component.wasm
+        id: component
+        config:
+          - name: component-config
+    - name: provider
+      type: capability
+      properties:
+        image: file:///provider.par.gz
+        id: provider
+        config:
+          - name: provider-defaults
+          - name: provider-overrides
+            properties:
+              port: "8080"
+"#,
+        )
+        .expect("failed to parse manifest");
+        let manifests = [manifest];
+
+        // The provider is restarted with its configs, not with the configs of other components
+        assert_eq!(
+            provider_config(&manifests, "provider"),
+            Some(vec![
+                "provider-defaults".to_string(),
+                "provider-overrides".to_string()
+            ])
+        );
+        assert_eq!(provider_config(&manifests, "component"), None);
+        assert_eq!(provider_config(&manifests, "missing"), None);
+    }
+
+    #[test]
+    fn test_provider_restart_command() {
+        let running = [ProviderDescription::builder()
+            .id("provider")
+            .annotations(BTreeMap::from([(
+                "wasmcloud.dev/managed-by".to_string(),
+                "wadm".to_string(),
+            )]))
+            .build()
+            .expect("failed to build provider description")];
+        let config = vec!["provider-defaults".to_string()];
+
+        // The provider is started with the configs from the manifest and its annotations
+        let command = provider_restart_command(
+            &running,
+            "host",
+            "provider",
+            "file:///provider.par.gz",
+            config.clone(),
+        )
+        .expect("failed to build restart command")
+        .expect("running provider should be restarted");
+        assert_eq!(command.host_id(), "host");
+        assert_eq!(command.provider_id(), "provider");
+        assert_eq!(command.provider_ref(), "file:///provider.par.gz");
+        assert_eq!(command.config(), &config);
+        assert_eq!(
+            command
+                .annotations()
+                .and_then(|annotations| annotations.get("wasmcloud.dev/managed-by"))
+                .map(String::as_str),
+            Some("wadm")
+        );
+
+        // Providers that are not running are left to wadm to start
+        assert!(provider_restart_command(
+            &running,
+            "host",
+            "missing",
+            "file:///provider.par.gz",
+            config,
+        )
+        .expect("failed to build restart command")
+        .is_none());
+    }
+}
@@ -0,0 +1,346 @@
+//! Running a wasmCloud host inside of `wash dev`, with manifests applied directly to the host
+//! rather than through wadm
+//!
+//! Only wadm and the host process are replaced: the host's control interface, RPC and lattice
+//! data all go over NATS (the latter through JetStream), and there is no in-process transport for
+//! them, so a NATS server is still required and is started by `wash dev` if one is not running.
+//! Secrets are not supported, and manifests that use them are rejected rather than deployed
+//! without them.
+//!
+//! The host itself is only built into `wash` with the `embedded-host` feature.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{anyhow, ensure, Context as _, Result};
+use console::style;
+use tracing::warn;
+use wadm_types::{ConfigProperty, Manifest, Properties, TraitProperty};
+use wash_lib::generate::emoji;
+use wasmcloud_control_interface::Client as CtlClient;
+
+use crate::apply::{component_id, ensure_no_secrets, links};
+#[cfg(not(feature = "embedded-host"))]
+use crate::cmd::up::WasmcloudOpts;
+
+use super::manifest::generate_help_text_for_manifest;
+
+#[cfg(feature = "embedded-host")]
+mod host;
+
+#[cfg(feature = "embedded-host")]
+pub(crate) use host::EmbeddedHost;
+
+/// Stand-in for the embedded host when `wash` is built without the `embedded-host` feature, which
+/// can never be started
+#[cfg(not(feature = "embedded-host"))]
+pub(crate) enum EmbeddedHost {}
+
+#[cfg(not(feature = "embedded-host"))]
+impl EmbeddedHost {
+    pub(crate) async fn start(_wasmcloud_opts: &WasmcloudOpts) -> Result<Self> {
+        anyhow::bail!(
+            "wash was built without the `embedded-host` feature, run `wash dev` without `--embedded`"
+        )
+    }
+
+    pub(crate) fn host_id(&self) -> &str {
+        match *self {}
+    }
+
+    pub(crate) async fn stop(self) -> Result<()> {
+        match self {}
+    }
+}
+
+/// Manifests that have been deployed directly to a host through the control interface, in place
+/// of wadm
+#[derive(Debug, Default)]
+pub(crate) struct DirectDeployment {
+    /// Deployed manifests, by name
+    deployed: BTreeMap<String, Manifest>,
+}
+
+impl DirectDeployment {
+    /// Deploy the given manifests, replacing previously deployed manifests of the same name, and
+    /// ensure that everything deployed so far is running on the host.
+    ///
+    /// Ensuring that deployed manifests are running restores components that were scaled down
+    /// to be reloaded, which wadm would otherwise do.
+    pub(crate) async fn apply(
+        &mut self,
+        ctl_client: &CtlClient,
+        host_id: &str,
+        manifests: impl IntoIterator<Item = Manifest>,
+    ) -> Result<()> {
+        let manifests = manifests.into_iter().collect::<Vec<_>>();
+        // Check every manifest before changing anything on the host
+        for manifest in &manifests {
+            ensure_supported(manifest)?;
+        }
+
+        let mut updated = Vec::new();
+        for manifest in manifests {
+            if let Some(previous) = self.deployed.remove(&manifest.metadata.name) {
+                undeploy(ctl_client, host_id, &previous, Some(&manifest))
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "failed to remove previous version of application [{}]",
+                            previous.metadata.name
+                        )
+                    })?;
+            }
+            updated.push(manifest.metadata.name.clone());
+            self.deployed
+                .insert(manifest.metadata.name.clone(), manifest);
+        }
+
+        for manifest in self.deployed.values() {
+            deploy(ctl_client, host_id, manifest)
+                .await
+                .with_context(|| {
+                    format!("failed to deploy application [{}]", manifest.metadata.name)
+                })?;
+        }
+
+        for manifest in updated.iter().filter_map(|name| self.deployed.get(name)) {
+            eprintln!(
+                "{} {}",
+                emoji::RECYCLE,
+                style(format!(
+                    "Deployed updated manifest for application [{}] to embedded host",
+                    manifest.metadata.name,
+                ))
+                .bold(),
+            );
+            let help_text_lines = generate_help_text_for_manifest(manifest);
+            if !help_text_lines.is_empty() {
+                eprintln!("{}", help_text_lines.join("\n"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove everything that was deployed from the host
+    pub(crate) async fn delete_all(&mut self, ctl_client: &CtlClient, host_id: &str) -> Result<()> {
+        for (name, manifest) in std::mem::take(&mut self.deployed) {
+            undeploy(ctl_client, host_id, &manifest, None)
+                .await
+                .with_context(|| format!("failed to delete application [{name}]"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Ensure that a manifest only uses features that the embedded host supports
+fn ensure_supported(manifest: &Manifest) -> Result<()> {
+    ensure_no_secrets(manifest).context(
+        "manifest is not supported by the embedded host, run `wash dev` without `--embedded`",
+    )
+}
+
+/// Put configs that are defined inline, returning the names of all configs
+async fn put_configs(ctl_client: &CtlClient, configs: &[ConfigProperty]) -> Result<Vec<String>> {
+    for config in configs {
+        if let Some(properties) = &config.properties {
+            let ack = ctl_client
+                .put_config(&config.name, properties.clone())
+                .await
+                .map_err(|e| anyhow!(e).context("failed to put config"))?;
+            ensure!(
+                ack.succeeded(),
+                "failed to put config [{}]: {}",
+                config.name,
+                ack.message()
+            );
+        }
+    }
+    Ok(configs.iter().map(|c| c.name.clone()).collect())
+}
+
+/// Ensure that all components, providers and links of a manifest are running on the host
+async fn deploy(ctl_client: &CtlClient, host_id: &str, manifest: &Manifest) -> Result<()> {
+    let inventory = ctl_client
+        .get_host_inventory(host_id)
+        .await
+        .map_err(|e| anyhow!(e).context("failed to get host inventory"))?;
+    let running_providers = inventory
+        .data()
+        .map(|inventory| {
+            inventory
+                .providers()
+                .iter()
+                .map(|p| p.id().to_string())
+                .collect::<BTreeSet<_>>()
+        })
+        .unwrap_or_default();
+
+    for component in &manifest.spec.components {
+        let id = component_id(manifest, component);
+        let (image, application, configs) = match &component.properties {
+            Properties::Component { properties } => (
+                &properties.image,
+                &properties.application,
+                &properties.config,
+            ),
+            Properties::Capability { properties } => (
+                &properties.image,
+                &properties.application,
+                &properties.config,
+            ),
+        };
+        let Some(image) = image else {
+            if application.is_some() {
+                warn!(
+                    component = id,
+                    "shared application components are not supported by the embedded host, ignoring"
+                );
+            }
+            continue;
+        };
+        let config = put_configs(ctl_client, configs).await?;
+
+        let ack = match component.properties {
+            Properties::Component { .. } => {
+                let instances = component
+                    .traits
+                    .iter()
+                    .flatten()
+                    .find_map(|t| match &t.properties {
+                        TraitProperty::SpreadScaler(scaler) => Some(scaler.instances),
+                        _ => None,
+                    })
+                    .unwrap_or(1);
+                ctl_client
+                    .scale_component(
+                        host_id,
+                        image,
+                        &id,
+                        u32::try_from(instances).unwrap_or(u32::MAX),
+                        None,
+                        config,
+                    )
+                    .await
+                    .map_err(|e| anyhow!(e).context("failed to scale component"))?
+            }
+            Properties::Capability { .. } if running_providers.contains(&id) => continue,
+            Properties::Capability { .. } => ctl_client
+                .start_provider(host_id, image, &id, None, config)
+                .await
+                .map_err(|e| anyhow!(e).context("failed to start provider"))?,
+        };
+        ensure!(ack.succeeded(), "failed to start [{id}]: {}", ack.message());
+    }
+
+    for (link, configs) in links(manifest)? {
+        put_configs(
+            ctl_client,
+            &configs.into_iter().cloned().collect::<Vec<_>>(),
+        )
+        .await?;
+        let ack = ctl_client
+            .put_link(link.clone())
+            .await
+            .map_err(|e| anyhow!(e).context("failed to put link"))?;
+        ensure!(
+            ack.succeeded(),
+            "failed to link [{}] to [{}]: {}",
+            link.source_id(),
+            link.target(),
+            ack.message()
+        );
+    }
+    Ok(())
+}
+
+/// Remove the components, providers, links and configs of a manifest from the host, keeping
+/// those that are also part of the manifest that replaces it (if any)
+async fn undeploy(
+    ctl_client: &CtlClient,
+    host_id: &str,
+    manifest: &Manifest,
+    replacement: Option<&Manifest>,
+) -> Result<()> {
+    let kept_links = replacement
+        .map(links)
+        .transpose()?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(link, _)| link)
+        .collect::<Vec<_>>();
+    let mut configs = BTreeSet::new();
+    for (link, link_configs) in links(manifest)? {
+        configs.extend(
+            link_configs
+                .into_iter()
+                .filter(|c| c.properties.is_some())
+                .map(|c| c.name.clone()),
+        );
+        if kept_links.contains(&link) {
+            continue;
+        }
+        if let Err(e) = ctl_client
+            .delete_link(
+                link.source_id(),
+                link.name(),
+                link.wit_namespace(),
+                link.wit_package(),
+            )
+            .await
+        {
+            warn!(source = link.source_id(), err = ?e, "failed to delete link");
+        }
+    }
+
+    let kept_components = replacement
+        .map(|replacement| {
+            replacement
+                .spec
+                .components
+                .iter()
+                .map(|c| component_id(replacement, c))
+                .collect::<BTreeSet<_>>()
+        })
+        .unwrap_or_default();
+    for component in &manifest.spec.components {
+        let id = component_id(manifest, component);
+        let (image, component_configs) = match &component.properties {
+            Properties::Component { properties } => (&properties.image, &properties.config),
+            Properties::Capability { properties } => (&properties.image, &properties.config),
+        };
+        configs.extend(
+            component_configs
+                .iter()
+                .filter(|c| c.properties.is_some())
+                .map(|c| c.name.clone()),
+        );
+        let Some(image) = image else {
+            continue;
+        };
+        if kept_components.contains(&id) {
+            continue;
+        }
+        let result = match component.properties {
+            Properties::Component { .. } => {
+                ctl_client
+                    .scale_component(host_id, image, &id, 0, None, Vec::new())
+                    .await
+            }
+            Properties::Capability { .. } => ctl_client.stop_provider(host_id, &id).await,
+        };
+        if let Err(e) = result {
+            warn!(component = id, err = ?e, "failed to stop component");
+        }
+    }
+
+    // Configs are only deleted when the manifest is removed entirely, as they are replaced with
+    // new values when the replacement is deployed
+    if replacement.is_none() {
+        for config in configs {
+            if let Err(e) = ctl_client.delete_config(&config).await {
+                warn!(config, err = ?e, "failed to delete config");
+            }
+        }
+    }
+    Ok(())
+}
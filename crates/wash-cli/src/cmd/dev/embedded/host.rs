@@ -0,0 +1,102 @@
+//! A wasmCloud host running inside of the `wash` process
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{ensure, Context as _, Result};
+use nkeys::KeyPair;
+use wasmcloud_host::url::Url;
+use wasmcloud_host::{WasmbusHost, WasmbusHostConfig};
+
+use crate::cmd::up::WasmcloudOpts;
+use crate::config::{DEFAULT_LATTICE, DEFAULT_NATS_HOST, DEFAULT_NATS_PORT};
+
+/// Amount of time to wait for the embedded host to shut down
+const EMBEDDED_HOST_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A wasmCloud host running inside of the `wash` process
+pub(crate) struct EmbeddedHost {
+    host: Arc<WasmbusHost>,
+    shutdown: Pin<Box<dyn Future<Output = Result<()>> + Send>>,
+    host_id: String,
+}
+
+impl EmbeddedHost {
+    /// Start a host in this process, connected to the NATS server described by `wasmcloud_opts`
+    pub(crate) async fn start(wasmcloud_opts: &WasmcloudOpts) -> Result<Self> {
+        ensure!(
+            wasmcloud_opts.ctl_credsfile.is_none() && wasmcloud_opts.rpc_credsfile.is_none(),
+            "NATS credentials files are not supported by the embedded host, use a JWT and seed instead"
+        );
+        let nats_url = |host: Option<&String>, port: Option<u16>| {
+            let host = host.map_or(DEFAULT_NATS_HOST, String::as_str);
+            let port = port.map_or_else(|| DEFAULT_NATS_PORT.to_string(), |p| p.to_string());
+            Url::parse(&format!("nats://{host}:{port}"))
+                .with_context(|| format!("invalid NATS address [{host}:{port}]"))
+        };
+        let seed = |seed: Option<&String>| {
+            seed.map(|seed| KeyPair::from_seed(seed).map(Arc::new))
+                .transpose()
+                .context("failed to parse NATS seed")
+        };
+        let labels = wasmcloud_opts
+            .label
+            .iter()
+            .flatten()
+            .map(|label| {
+                label
+                    .split_once('=')
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .with_context(|| format!("invalid label [{label}], expected KEY=VALUE"))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        let host_key = Arc::new(match &wasmcloud_opts.host_seed {
+            Some(seed) => KeyPair::from_seed(seed).context("failed to parse host seed")?,
+            None => KeyPair::new_server(),
+        });
+        let host_id = host_key.public_key();
+        let (host, shutdown) = Box::pin(WasmbusHost::new(WasmbusHostConfig {
+            ctl_nats_url: nats_url(wasmcloud_opts.ctl_host.as_ref(), wasmcloud_opts.ctl_port)?,
+            ctl_jwt: wasmcloud_opts.ctl_jwt.clone(),
+            ctl_key: seed(wasmcloud_opts.ctl_seed.as_ref())?,
+            ctl_tls: wasmcloud_opts.ctl_tls,
+            rpc_nats_url: nats_url(wasmcloud_opts.rpc_host.as_ref(), wasmcloud_opts.rpc_port)?,
+            rpc_jwt: wasmcloud_opts.rpc_jwt.clone(),
+            rpc_key: seed(wasmcloud_opts.rpc_seed.as_ref())?,
+            rpc_tls: wasmcloud_opts.rpc_tls,
+            lattice: Arc::from(wasmcloud_opts.lattice.as_deref().unwrap_or(DEFAULT_LATTICE)),
+            js_domain: wasmcloud_opts.wasmcloud_js_domain.clone(),
+            labels,
+            host_key: Some(host_key),
+            allow_file_load: true,
+            config_service_enabled: wasmcloud_opts.config_service_enabled,
+            secrets_topic_prefix: wasmcloud_opts.secrets_topic.clone(),
+            max_execution_time: Duration::from_millis(wasmcloud_opts.max_execution_time),
+            ..Default::default()
+        }))
+        .await
+        .context("failed to start embedded host")?;
+        Ok(Self {
+            host,
+            shutdown: Box::pin(shutdown),
+            host_id,
+        })
+    }
+
+    /// ID of the embedded host
+    pub(crate) fn host_id(&self) -> &str {
+        &self.host_id
+    }
+
+    /// Stop the embedded host, waiting for it to stop all components and providers
+    pub(crate) async fn stop(self) -> Result<()> {
+        drop(self.host);
+        tokio::time::timeout(EMBEDDED_HOST_SHUTDOWN_TIMEOUT, self.shutdown)
+            .await
+            .context("embedded host shutdown timed out")?
+    }
+}
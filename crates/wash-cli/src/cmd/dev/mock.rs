@@ -0,0 +1,916 @@
+//! In-memory mocks that serve interfaces imported by a component under development, in place of
+//! the providers or components that would normally be linked to it.
+//!
+//! Mocks are served over wRPC on the lattice, and every invocation is recorded. Values are
+//! converted to and from JSON according to the WIT types of the mocked functions, as described
+//! in [`wash_lib::wrpc`].
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+use anyhow::{anyhow, ensure, Context as _, Result};
+use futures::StreamExt as _;
+use serde_json::{json, Value};
+use tokio::io::{AsyncRead, AsyncReadExt as _, AsyncWriteExt as _};
+use tokio::task::JoinSet;
+use tracing::{debug, warn};
+use wash_lib::generate::emoji;
+use wash_lib::wrpc::{
+    decode_params, default_results, encode_results, ensure_supported, instance_name,
+    push_wrpc_blobstore, push_wrpc_keyvalue, DecodeError,
+};
+use wasmcloud_control_interface::{Client as CtlClient, Link};
+use wit_parser::{Function, InterfaceId, Resolve, WorldId, WorldItem};
+use wrpc_transport::Serve as _;
+
+use super::deps::ProjectDeps;
+
+/// Contents of an in-memory `wasi:keyvalue` store, keyed by bucket and key
+type KeyValueStore = BTreeMap<(String, String), Value>;
+
+/// Containers of an in-memory `wasi:blobstore` store, with the time they were created at
+type BlobStore = BTreeMap<String, u64>;
+
+/// WASI packages that the host invokes over their wRPC counterparts, which are mocked by
+/// in-memory stores
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WasiMock {
+    /// `wasi:keyvalue`, invoked over `wrpc:keyvalue`
+    Keyvalue,
+    /// `wasi:blobstore`, invoked over `wrpc:blobstore`
+    Blobstore,
+}
+
+impl WasiMock {
+    fn from_package(namespace: &str, package: &str) -> Option<Self> {
+        match (namespace, package) {
+            ("wasi", "keyvalue") => Some(Self::Keyvalue),
+            ("wasi", "blobstore") => Some(Self::Blobstore),
+            _ => None,
+        }
+    }
+
+    /// Build the resolve of the wRPC package, returning it along with the wRPC interfaces that
+    /// are invoked in place of the `imported` WASI interfaces
+    fn resolve(self, imported: &[String]) -> Result<(Resolve, Vec<InterfaceId>)> {
+        let mut resolve = Resolve::default();
+        let pkg = match self {
+            Self::Keyvalue => push_wrpc_keyvalue(&mut resolve)?,
+            Self::Blobstore => push_wrpc_blobstore(&mut resolve)?,
+        };
+        let interfaces = &resolve.packages[pkg].interfaces;
+        let interfaces = match self {
+            Self::Keyvalue => imported
+                .iter()
+                .filter_map(|name| interfaces.get(name).copied())
+                .collect(),
+            // All `wasi:blobstore` interfaces are invoked over `wrpc:blobstore/blobstore`
+            Self::Blobstore => interfaces.get("blobstore").copied().into_iter().collect(),
+        };
+        Ok((resolve, interfaces))
+    }
+}
+
+/// Contents of the in-memory store backing a mock of a WASI package
+enum MockStore {
+    Keyvalue(KeyValueStore),
+    Blobstore(BlobStore),
+}
+
+impl MockStore {
+    fn new(kind: WasiMock) -> Self {
+        match kind {
+            WasiMock::Keyvalue => Self::Keyvalue(KeyValueStore::new()),
+            WasiMock::Blobstore => Self::Blobstore(BlobStore::new()),
+        }
+    }
+
+    /// Respond to a call using the contents of the store, if the function is supported
+    fn respond(&mut self, iface: &str, function: &str, params: &[Value]) -> Option<Value> {
+        match self {
+            Self::Keyvalue(store) => keyvalue_response(store, iface, function, params),
+            Self::Blobstore(store) => blobstore_response(store, iface, function, params),
+        }
+    }
+}
+
+/// Specification of a mock that serves one or more interfaces to a single component
+pub(crate) struct MockSpec {
+    /// ID of the lattice target that the mock is served as
+    target_id: String,
+    /// Link from the component to the mock
+    link: Link,
+    /// Resolve containing the types of the mocked functions
+    resolve: Arc<Resolve>,
+    /// Mocked functions, keyed by wRPC instance and function name
+    functions: BTreeMap<(String, String), Function>,
+    /// Scripted responses, keyed by (optionally interface-qualified) function name
+    responses: BTreeMap<String, Value>,
+    /// The WASI package that the mock serves, backed by an in-memory store
+    wasi: Option<WasiMock>,
+}
+
+#[cfg(test)]
+impl MockSpec {
+    /// ID of the lattice target that the mock is served as
+    pub(crate) fn target_id(&self) -> &str {
+        &self.target_id
+    }
+
+    /// Link from the component to the mock
+    pub(crate) fn link(&self) -> &Link {
+        &self.link
+    }
+}
+
+/// ID of the lattice target that serves the mock of a package imported by `source_id` over the
+/// link named `link_name`
+pub(crate) fn mock_target_id(
+    source_id: &str,
+    namespace: &str,
+    package: &str,
+    link_name: &str,
+) -> String {
+    let target_id = format!("{source_id}-mock-{namespace}-{package}");
+    if link_name == "default" {
+        target_id
+    } else {
+        format!("{target_id}-{link_name}")
+    }
+}
+
+/// Build the specifications of the mocks for the mocked dependencies of a component
+pub(crate) fn mock_specs(
+    source_id: &str,
+    resolve: &Resolve,
+    world_id: WorldId,
+    deps: &ProjectDeps,
+) -> Result<Vec<MockSpec>> {
+    let world = resolve
+        .worlds
+        .get(world_id)
+        .context("selected WIT world is missing")?;
+    let mut specs = Vec::new();
+    for dep in deps.mocked_deps() {
+        let mock = dep.mock.as_ref().context("missing mock")?;
+        let wit = &dep.wit;
+
+        // Find the interfaces of the mocked package that the component imports
+        let mut imported = Vec::new();
+        for item in world.imports.values() {
+            let WorldItem::Interface { id, .. } = item else {
+                continue;
+            };
+            let iface = &resolve.interfaces[*id];
+            let (Some(name), Some(pkg)) = (&iface.name, iface.package) else {
+                continue;
+            };
+            let pkg = &resolve.packages[pkg].name;
+            if pkg.namespace == wit.namespace
+                && pkg.name == wit.package
+                && wit.interfaces.as_ref().map_or(true, |i| i.contains(name))
+            {
+                imported.push((name.clone(), *id));
+            }
+        }
+        if imported.is_empty() {
+            debug!(
+                namespace = wit.namespace,
+                package = wit.package,
+                "skipping mock of package that is not imported"
+            );
+            continue;
+        }
+
+        // Imports of `wasi:keyvalue` and `wasi:blobstore` are invoked over their wRPC
+        // counterparts, other WASI interfaces are translated by the host in ways that can't be
+        // mocked
+        let wasi = WasiMock::from_package(&wit.namespace, &wit.package);
+        let (mock_resolve, interfaces) = if let Some(wasi) = wasi {
+            let names = imported
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>();
+            let (wrpc_resolve, interfaces) = wasi.resolve(&names)?;
+            (Arc::new(wrpc_resolve), interfaces)
+        } else {
+            ensure!(
+                wit.namespace != "wasi",
+                "imports of [wasi:{}] are translated by the host and cannot be mocked, only [wasi:keyvalue], [wasi:blobstore] and non-WASI interfaces can be mocked",
+                wit.package
+            );
+            (
+                Arc::new(resolve.clone()),
+                imported.iter().map(|(_, id)| *id).collect(),
+            )
+        };
+        // The host looks up the link of every `wasi:blobstore` interface as `wasi:blobstore/blobstore`
+        let link_interfaces = if wasi == Some(WasiMock::Blobstore) {
+            vec!["blobstore".to_string()]
+        } else {
+            imported.into_iter().map(|(name, _)| name).collect()
+        };
+
+        let mut functions = BTreeMap::new();
+        for id in interfaces {
+            let iface = &mock_resolve.interfaces[id];
+            let instance =
+                instance_name(&mock_resolve, id).context("interface missing package or name")?;
+            for (name, function) in &iface.functions {
+                if let Err(e) = ensure_supported(&mock_resolve, function) {
+                    warn!(instance, function = name, err = ?e, "skipping function that cannot be mocked");
+                    continue;
+                }
+                functions.insert((instance.clone(), name.clone()), function.clone());
+            }
+        }
+
+        let target_id = mock_target_id(source_id, &wit.namespace, &wit.package, &dep.link_name);
+        let link = Link::builder()
+            .source_id(source_id)
+            .target(&target_id)
+            .name(&dep.link_name)
+            .wit_namespace(&wit.namespace)
+            .wit_package(&wit.package)
+            .interfaces(link_interfaces)
+            .build()
+            .map_err(|e| anyhow!(e).context("failed to build link to mock"))?;
+        specs.push(MockSpec {
+            target_id,
+            link,
+            resolve: mock_resolve,
+            functions,
+            responses: mock.responses.clone(),
+            wasi,
+        });
+    }
+    Ok(specs)
+}
+
+/// Definitions of a running mock, which can be updated while the mock is served
+struct MockState {
+    resolve: Arc<Resolve>,
+    functions: BTreeMap<(String, String), Function>,
+    responses: BTreeMap<String, Value>,
+}
+
+/// A mock that is being served on the lattice
+struct RunningMock {
+    link: Link,
+    state: Arc<RwLock<MockState>>,
+    tasks: JoinSet<()>,
+}
+
+impl RunningMock {
+    /// Remove the link to the mock, and stop serving it
+    async fn stop(mut self, ctl_client: &CtlClient) {
+        if let Err(e) = ctl_client
+            .delete_link(
+                self.link.source_id(),
+                self.link.name(),
+                self.link.wit_namespace(),
+                self.link.wit_package(),
+            )
+            .await
+        {
+            warn!(target = self.link.target(), err = ?e, "failed to delete link to mock");
+        }
+        self.tasks.shutdown().await;
+    }
+}
+
+/// Server of the in-memory mocks used during a `wash dev` session
+pub(crate) struct MockServer {
+    nats_client: Arc<async_nats::Client>,
+    lattice: String,
+    /// Path to the file that calls to mocks are recorded in, as JSON lines
+    calls_path: PathBuf,
+    running: BTreeMap<String, RunningMock>,
+    /// Contents of the in-memory stores of WASI mocks, which are kept when mocks are restarted
+    stores: HashMap<String, Arc<Mutex<MockStore>>>,
+}
+
+impl MockServer {
+    pub(crate) fn new(
+        nats_client: async_nats::Client,
+        lattice: impl Into<String>,
+        calls_path: PathBuf,
+    ) -> Self {
+        Self {
+            nats_client: Arc::new(nats_client),
+            lattice: lattice.into(),
+            calls_path,
+            running: BTreeMap::new(),
+            stores: HashMap::new(),
+        }
+    }
+
+    /// Serve exactly the given mocks, starting, updating and stopping mocks as necessary
+    ///
+    /// Mocks that serve the same functions over the same link are updated in place, so that
+    /// they are not interrupted when only their types or responses change.
+    pub(crate) async fn sync(
+        &mut self,
+        ctl_client: &CtlClient,
+        specs: Vec<MockSpec>,
+    ) -> Result<()> {
+        let target_ids = specs
+            .iter()
+            .map(|spec| spec.target_id.clone())
+            .collect::<BTreeSet<_>>();
+        let stale = self
+            .running
+            .keys()
+            .filter(|id| !target_ids.contains(*id))
+            .cloned()
+            .collect::<Vec<_>>();
+        for id in stale {
+            if let Some(mock) = self.running.remove(&id) {
+                mock.stop(ctl_client).await;
+            }
+        }
+
+        for spec in specs {
+            if let Some(running) = self.running.get(&spec.target_id) {
+                let unchanged = running.link == spec.link
+                    && running
+                        .state
+                        .read()
+                        .map_err(|_| anyhow!("mock state lock poisoned"))?
+                        .functions
+                        .keys()
+                        .eq(spec.functions.keys());
+                if unchanged {
+                    let mut state = running
+                        .state
+                        .write()
+                        .map_err(|_| anyhow!("mock state lock poisoned"))?;
+                    state.resolve = spec.resolve;
+                    state.functions = spec.functions;
+                    state.responses = spec.responses;
+                    continue;
+                }
+                if let Some(running) = self.running.remove(&spec.target_id) {
+                    running.stop(ctl_client).await;
+                }
+            }
+            let target_id = spec.target_id.clone();
+            let running = self
+                .start(ctl_client, spec)
+                .await
+                .with_context(|| format!("failed to start mock [{target_id}]"))?;
+            self.running.insert(target_id, running);
+        }
+        Ok(())
+    }
+
+    /// Stop serving all mocks
+    pub(crate) async fn stop_all(&mut self, ctl_client: &CtlClient) {
+        while let Some((_, mock)) = self.running.pop_first() {
+            mock.stop(ctl_client).await;
+        }
+    }
+
+    /// Serve the functions of a mock, and link the component to it
+    async fn start(&mut self, ctl_client: &CtlClient, spec: MockSpec) -> Result<RunningMock> {
+        let client = wrpc_transport_nats::Client::new(
+            Arc::clone(&self.nats_client),
+            format!("{}.{}", self.lattice, spec.target_id),
+            None,
+        )
+        .await
+        .context("failed to create wRPC client")?;
+        let store = spec.wasi.map(|wasi| {
+            Arc::clone(
+                self.stores
+                    .entry(spec.target_id.clone())
+                    .or_insert_with(|| Arc::new(Mutex::new(MockStore::new(wasi)))),
+            )
+        });
+        let state = Arc::new(RwLock::new(MockState {
+            resolve: spec.resolve,
+            functions: spec.functions,
+            responses: spec.responses,
+        }));
+
+        let keys = state
+            .read()
+            .map_err(|_| anyhow!("mock state lock poisoned"))?
+            .functions
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut tasks = JoinSet::new();
+        for (instance, name) in keys {
+            let invocations = client
+                .serve(&instance, &name, Vec::<Box<[Option<usize>]>>::new())
+                .await
+                .with_context(|| format!("failed to serve [{instance}.{name}]"))?;
+            let state = Arc::clone(&state);
+            let store = store.clone();
+            let calls_path = self.calls_path.clone();
+            tasks.spawn(async move {
+                let mut invocations = Box::pin(invocations);
+                while let Some(invocation) = invocations.next().await {
+                    let (cx, mut tx, rx) = match invocation {
+                        Ok(invocation) => invocation,
+                        Err(e) => {
+                            warn!(instance, name, err = ?e, "failed to accept mock invocation");
+                            continue;
+                        }
+                    };
+                    let source_id = cx
+                        .as_ref()
+                        .and_then(|headers| headers.get("source-id"))
+                        .map(|v| v.as_str().to_string());
+                    let call = Call {
+                        state: &state,
+                        store: store.as_deref(),
+                        instance: &instance,
+                        name: &name,
+                        source_id,
+                    };
+                    match call.handle(rx).await {
+                        Ok((results, record)) => {
+                            if let Err(e) = async {
+                                tx.write_all(&results).await?;
+                                tx.shutdown().await
+                            }
+                            .await
+                            {
+                                warn!(instance, name, err = ?e, "failed to write mock results");
+                            }
+                            record_call(&calls_path, &record).await;
+                        }
+                        Err(e) => {
+                            eprintln!(
+                                "{} Mock of [{instance}.{name}] failed to handle call: {e:#}",
+                                emoji::WARN
+                            );
+                        }
+                    }
+                }
+            });
+        }
+
+        let ack = ctl_client
+            .put_link(spec.link.clone())
+            .await
+            .map_err(|e| anyhow!(e).context("failed to put link to mock"))?;
+        ensure!(
+            ack.succeeded(),
+            "failed to link [{}] to mock [{}]: {}",
+            spec.link.source_id(),
+            spec.target_id,
+            ack.message()
+        );
+        eprintln!(
+            "{} Serving mock of [{}:{}/{{{}}}] for [{}]",
+            emoji::WRENCH,
+            spec.link.wit_namespace(),
+            spec.link.wit_package(),
+            spec.link.interfaces().join(","),
+            spec.link.source_id(),
+        );
+        Ok(RunningMock {
+            link: spec.link,
+            state,
+            tasks,
+        })
+    }
+}
+
+/// A single invocation of a mocked function
+struct Call<'a> {
+    state: &'a RwLock<MockState>,
+    store: Option<&'a Mutex<MockStore>>,
+    instance: &'a str,
+    name: &'a str,
+    source_id: Option<String>,
+}
+
+impl Call<'_> {
+    /// Read the parameters of the call, and produce the encoded results along with a record of the call
+    async fn handle(self, mut rx: impl AsyncRead + Unpin) -> Result<(Vec<u8>, Value)> {
+        // Instances are named `namespace:package/interface@version`
+        let iface = self
+            .instance
+            .split_once('/')
+            .and_then(|(_, iface)| iface.split('@').next())
+            .unwrap_or_default();
+        let (resolve, function, scripted) = {
+            let state = self
+                .state
+                .read()
+                .map_err(|_| anyhow!("mock state lock poisoned"))?;
+            let function = state
+                .functions
+                .get(&(self.instance.to_string(), self.name.to_string()))
+                .cloned()
+                .context("function is no longer mocked")?;
+            let scripted = state
+                .responses
+                .get(&format!("{iface}.{}", self.name))
+                .or_else(|| state.responses.get(self.name))
+                .cloned();
+            (Arc::clone(&state.resolve), function, scripted)
+        };
+
+        let params = read_params(&mut rx, &resolve, &function).await?;
+        let response = match (scripted, self.store) {
+            (Some(response), _) => response,
+            (None, Some(store)) => {
+                let mut store = store
+                    .lock()
+                    .map_err(|_| anyhow!("mock store lock poisoned"))?;
+                store
+                    .respond(iface, self.name, &params)
+                    .unwrap_or_else(|| default_results(&resolve, &function.results))
+            }
+            (None, None) => default_results(&resolve, &function.results),
+        };
+
+        let mut results = Vec::new();
+        encode_results(&resolve, &function.results, &response, &mut results)
+            .context("failed to encode mock response")?;
+        eprintln!(
+            "{} Mock call [{}.{}]({}) -> {response}",
+            emoji::EYES,
+            self.instance,
+            self.name,
+            params
+                .iter()
+                .map(Value::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        let record = json!({
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "source_id": self.source_id,
+            "instance": self.instance,
+            "function": self.name,
+            "params": function
+                .params
+                .iter()
+                .map(|(name, _)| name.clone())
+                .zip(params)
+                .collect::<serde_json::Map<_, _>>(),
+            "response": response,
+        });
+        Ok((results, record))
+    }
+}
+
+/// Append a call to the file of recorded calls
+async fn record_call(path: &Path, record: &Value) {
+    let line = format!("{record}\n");
+    let written = async {
+        tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?
+            .write_all(line.as_bytes())
+            .await
+    }
+    .await;
+    if let Err(e) = written {
+        warn!(path = %path.display(), err = ?e, "failed to record mock call");
+    }
+}
+
+/// Respond to a call of a `wrpc:keyvalue` function using an in-memory store
+///
+/// Values are stored as they were received, and counters are stored as decimal strings.
+fn keyvalue_response(
+    store: &mut KeyValueStore,
+    iface: &str,
+    function: &str,
+    params: &[Value],
+) -> Option<Value> {
+    let string = |idx: usize| params.get(idx).and_then(Value::as_str).map(String::from);
+    let bucket = string(0)?;
+    let response = match (iface, function) {
+        ("store", "get") => json!({ "ok": store.get(&(bucket, string(1)?)) }),
+        ("store", "set") => {
+            store.insert((bucket, string(1)?), params.get(2)?.clone());
+            json!({ "ok": null })
+        }
+        ("store", "delete") => {
+            store.remove(&(bucket, string(1)?));
+            json!({ "ok": null })
+        }
+        ("store", "exists") => json!({ "ok": store.contains_key(&(bucket, string(1)?)) }),
+        ("store", "list-keys") => {
+            let keys = store
+                .keys()
+                .filter(|(b, _)| *b == bucket)
+                .map(|(_, key)| key.clone())
+                .collect::<Vec<_>>();
+            json!({ "ok": { "keys": keys, "cursor": null } })
+        }
+        ("atomics", "increment") => {
+            let key = (bucket, string(1)?);
+            let delta = params.get(2)?.as_u64()?;
+            // Values are arrays of bytes, which are incremented if they are a decimal number
+            let current = match store.get(&key) {
+                None => Some(0),
+                Some(value) => serde_json::from_value::<Vec<u8>>(value.clone())
+                    .ok()
+                    .and_then(|bytes| String::from_utf8(bytes).ok())
+                    .and_then(|s| s.parse::<u64>().ok()),
+            };
+            match current.and_then(|current| current.checked_add(delta)) {
+                Some(value) => {
+                    store.insert(key, json!(value.to_string().as_bytes()));
+                    json!({ "ok": value })
+                }
+                None => json!({ "err": { "other": "value is not a number, or overflowed" } }),
+            }
+        }
+        ("batch", "get-many") => {
+            let values = params
+                .get(1)?
+                .as_array()?
+                .iter()
+                .map(|key| {
+                    let key = key.as_str()?.to_string();
+                    let value = store.get(&(bucket.clone(), key.clone()))?;
+                    Some(json!([key, value]))
+                })
+                .collect::<Vec<_>>();
+            json!({ "ok": values })
+        }
+        ("batch", "set-many") => {
+            for pair in params.get(1)?.as_array()? {
+                let key = pair.get(0)?.as_str()?.to_string();
+                store.insert((bucket.clone(), key), pair.get(1)?.clone());
+            }
+            json!({ "ok": null })
+        }
+        ("batch", "delete-many") => {
+            for key in params.get(1)?.as_array()? {
+                store.remove(&(bucket.clone(), key.as_str()?.to_string()));
+            }
+            json!({ "ok": null })
+        }
+        _ => return None,
+    };
+    Some(response)
+}
+
+/// Respond to a call of a `wrpc:blobstore` function using in-memory containers
+///
+/// Object data is streamed, which mocks can't serve, so containers never hold any objects.
+fn blobstore_response(
+    store: &mut BlobStore,
+    iface: &str,
+    function: &str,
+    params: &[Value],
+) -> Option<Value> {
+    if iface != "blobstore" {
+        return None;
+    }
+    let missing =
+        |container: &str| json!({ "err": format!("container [{container}] does not exist") });
+    let response = match function {
+        "create-container" => {
+            let name = params.first()?.as_str()?;
+            if store.contains_key(name) {
+                json!({ "err": format!("container [{name}] already exists") })
+            } else {
+                let created_at = u64::try_from(chrono::Utc::now().timestamp()).unwrap_or_default();
+                store.insert(name.to_string(), created_at);
+                json!({ "ok": null })
+            }
+        }
+        "container-exists" => json!({ "ok": store.contains_key(params.first()?.as_str()?) }),
+        "delete-container" => {
+            store.remove(params.first()?.as_str()?);
+            json!({ "ok": null })
+        }
+        "clear-container" => {
+            let name = params.first()?.as_str()?;
+            if store.contains_key(name) {
+                json!({ "ok": null })
+            } else {
+                missing(name)
+            }
+        }
+        "get-container-info" => {
+            let name = params.first()?.as_str()?;
+            match store.get(name) {
+                Some(created_at) => json!({ "ok": { "created-at": created_at } }),
+                None => missing(name),
+            }
+        }
+        "has-object" => json!({ "ok": false }),
+        "delete-object" | "delete-objects" => json!({ "ok": null }),
+        "get-object-info" | "copy-object" | "move-object" => {
+            let id = params.first()?;
+            json!({ "err": format!(
+                "object [{}] does not exist in container [{}]",
+                id.get("object")?.as_str()?,
+                id.get("container")?.as_str()?,
+            ) })
+        }
+        _ => return None,
+    };
+    Some(response)
+}
+
+/// Read the parameters of a function from an incoming stream
+async fn read_params(
+    rx: &mut (impl AsyncRead + Unpin),
+    resolve: &Resolve,
+    function: &Function,
+) -> Result<Vec<Value>> {
+    let mut buf = Vec::new();
+    loop {
+        let mut cursor = buf.as_slice();
+        match decode_params(resolve, function, &mut cursor) {
+            Ok(params) => return Ok(params),
+            Err(DecodeError::Invalid(e)) => return Err(e.context("failed to decode parameters")),
+            Err(DecodeError::Incomplete) => {}
+        }
+        let n = rx
+            .read_buf(&mut buf)
+            .await
+            .context("failed to read parameters")?;
+        ensure!(n > 0, "unexpected end of parameters");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wash_lib::parser::InterfaceMock;
+
+    use super::super::deps::{DependencySpec, ProjectDependencyKey};
+    use super::*;
+
+    #[test]
+    fn test_keyvalue_response() {
+        let mut store = KeyValueStore::new();
+        let params = |values: &[Value]| values.to_vec();
+        assert_eq!(
+            keyvalue_response(
+                &mut store,
+                "store",
+                "get",
+                &params(&[json!("b"), json!("k")])
+            ),
+            Some(json!({ "ok": null }))
+        );
+        keyvalue_response(
+            &mut store,
+            "store",
+            "set",
+            &params(&[json!("b"), json!("k"), json!(b"v")]),
+        );
+        assert_eq!(
+            keyvalue_response(
+                &mut store,
+                "store",
+                "get",
+                &params(&[json!("b"), json!("k")])
+            ),
+            Some(json!({ "ok": b"v" }))
+        );
+        assert_eq!(
+            keyvalue_response(
+                &mut store,
+                "store",
+                "exists",
+                &params(&[json!("other"), json!("k")])
+            ),
+            Some(json!({ "ok": false }))
+        );
+        assert_eq!(
+            keyvalue_response(
+                &mut store,
+                "atomics",
+                "increment",
+                &params(&[json!("b"), json!("n"), json!(2)])
+            ),
+            Some(json!({ "ok": 2 }))
+        );
+        assert_eq!(
+            keyvalue_response(
+                &mut store,
+                "batch",
+                "get-many",
+                &params(&[json!("b"), json!(["k", "n", "missing"])])
+            ),
+            Some(json!({ "ok": [["k", b"v"], ["n", b"2"], null] }))
+        );
+        assert_eq!(
+            keyvalue_response(&mut store, "store", "unknown", &params(&[json!("b")])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_mock_specs_blobstore() {
+        let mut resolve = Resolve::default();
+        resolve
+            .push_str(
+                "wasi-blobstore.wit",
+                r#"
+package wasi:blobstore@0.2.0-draft;
+
+interface types {
+    type container-name = string;
+}
+
+interface container {
+    use types.{container-name};
+
+    name: func() -> container-name;
+}
+
+interface blobstore {
+    use types.{container-name};
+
+    container-exists: func(name: container-name) -> bool;
+}
+"#,
+            )
+            .expect("failed to parse wasi:blobstore WIT");
+        let pkg = resolve
+            .push_str(
+                "component.wit",
+                r#"
+package test:component;
+
+world component {
+    import wasi:blobstore/blobstore@0.2.0-draft;
+    import wasi:blobstore/container@0.2.0-draft;
+}
+"#,
+            )
+            .expect("failed to parse component WIT");
+        let world = resolve.packages[pkg].worlds["component"];
+
+        let mut dep = DependencySpec::from_wit_import_iface("wasi:blobstore/blobstore")
+            .expect("blobstore should be a known dependency");
+        dep.inner_mut().mock = Some(InterfaceMock::default());
+        let pkey = ProjectDependencyKey::from_project("component", "/tmp/component")
+            .expect("failed to build project key");
+        let deps = ProjectDeps::from_known_deps(pkey, [dep]).expect("failed to build deps");
+
+        let specs = mock_specs("component", &resolve, world, &deps).expect("failed to build mocks");
+        let [spec] = specs.as_slice() else {
+            panic!("expected a single mock, got {}", specs.len());
+        };
+        // The component is linked to the mock over `wasi:blobstore/blobstore`, which is served
+        // as `wrpc:blobstore`
+        assert_eq!(spec.wasi, Some(WasiMock::Blobstore));
+        assert_eq!(spec.link.wit_namespace(), "wasi");
+        assert_eq!(spec.link.wit_package(), "blobstore");
+        assert_eq!(spec.link.interfaces(), &vec!["blobstore".to_string()]);
+        assert!(spec.functions.contains_key(&(
+            "wrpc:blobstore/blobstore@0.1.0".to_string(),
+            "create-container".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_blobstore_response() {
+        let mut store = MockStore::new(WasiMock::Blobstore);
+        let mut respond = |function: &str, params: &[Value]| {
+            store
+                .respond("blobstore", function, params)
+                .expect("function should be supported")
+        };
+        assert_eq!(
+            respond("container-exists", &[json!("c")]),
+            json!({ "ok": false })
+        );
+        assert_eq!(
+            respond("create-container", &[json!("c")]),
+            json!({ "ok": null })
+        );
+        assert!(respond("create-container", &[json!("c")])["err"].is_string());
+        assert_eq!(
+            respond("container-exists", &[json!("c")]),
+            json!({ "ok": true })
+        );
+        assert!(respond("get-container-info", &[json!("c")])["ok"]["created-at"].is_u64());
+        assert_eq!(
+            respond("has-object", &[json!({ "container": "c", "object": "o" })]),
+            json!({ "ok": false })
+        );
+        assert!(respond(
+            "get-object-info",
+            &[json!({ "container": "c", "object": "o" })]
+        )["err"]
+            .is_string());
+        assert_eq!(
+            respond("delete-container", &[json!("c")]),
+            json!({ "ok": null })
+        );
+        assert!(respond("get-container-info", &[json!("c")])["err"].is_string());
+        assert_eq!(store.respond("blobstore", "unknown", &[json!("c")]), None);
+    }
+}
@@ -1,10 +1,13 @@
-use std::path::PathBuf;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use anyhow::{bail, Context as _, Result};
 use clap::Parser;
+use embedded::{DirectDeployment, EmbeddedHost};
+use mock::MockServer;
 use notify::event::ModifyKind;
 use notify::{event::EventKind, Event as NotifyEvent, RecursiveMode, Watcher};
 use semver::Version;
@@ -15,17 +18,20 @@ use tracing::trace;
 use wash_lib::cli::{CommandOutput, CommonPackageArgs};
 use wash_lib::generate::emoji;
 use wash_lib::id::ServerId;
-use wash_lib::parser::load_config;
+use wash_lib::parser::{load_config, load_workspace_config, ProjectConfig};
 
 use crate::cmd::up::{
     nats_client_from_wasmcloud_opts, remove_wadm_pidfile, NatsOpts, WadmOpts, WasmcloudOpts,
 };
 
-mod deps;
+pub(crate) mod deps;
 mod devloop;
+mod embedded;
 mod manifest;
+pub(crate) mod mock;
 mod session;
-mod wit;
+pub(crate) mod wit;
+mod workspace;
 
 const DEFAULT_KEYVALUE_PROVIDER_IMAGE: &str = "ghcr.io/wasmcloud/keyvalue-nats:0.3.1";
 const DEFAULT_HTTP_CLIENT_PROVIDER_IMAGE: &str = "ghcr.io/wasmcloud/http-client:0.12.1";
@@ -39,6 +45,8 @@ const DEFAULT_BLOBSTORE_ROOT_DIR: &str = "/tmp";
 const DEFAULT_KEYVALUE_BUCKET: &str = "wasmcloud";
 
 const WASH_SESSIONS_FILE_NAME: &str = "wash-dev-sessions.json";
+/// File in the session directory that calls to interface mocks are recorded in
+const MOCK_CALLS_FILE_NAME: &str = "mock-calls.jsonl";
 
 const SESSIONS_FILE_VERSION: Version = Version::new(0, 1, 0);
 const SESSION_ID_LEN: usize = 6;
@@ -86,7 +94,8 @@ pub struct DevCommand {
     #[clap(long = "host-id", name = "host-id", value_parser)]
     pub host_id: Option<ServerId>,
 
-    /// Path to code directory
+    /// Path to code directory. If it contains a workspace, all members of the workspace are
+    /// developed together
     #[clap(
         name = "code-dir",
         short = 'd',
@@ -119,6 +128,17 @@ pub struct DevCommand {
     /// (useful for airgapped or disconnected environments)
     #[clap(long = "skip-fetch")]
     pub skip_wit_fetch: bool,
+
+    /// Run the wasmCloud host inside of wash and apply manifests directly to it, rather than
+    /// starting a host and wadm. A NATS server is still required, and is started if one is not
+    /// already running. Manifests that use secrets are rejected. Requires wash to be built with
+    /// the `embedded-host` feature
+    #[clap(
+        long = "embedded",
+        env = "WASH_DEV_EMBEDDED",
+        conflicts_with_all = ["host-id", "leave-host-running"]
+    )]
+    pub embedded: bool,
 }
 
 /// Handle `wash dev`
@@ -129,7 +149,11 @@ pub async fn handle_command(
     let current_dir =
         std::env::current_dir().context("failed to get current directory for wash dev")?;
     let project_path = cmd.code_dir.unwrap_or(current_dir);
-    let mut project_cfg = load_config(Some(project_path.clone()), Some(true)).await?;
+    let workspace = load_workspace_config(Some(project_path.clone()), Some(true)).await?;
+    let mut project_cfg = match workspace {
+        Some(_) => None,
+        None => Some(load_config(Some(project_path.clone()), Some(true)).await?),
+    };
 
     let mut wash_dev_session = WashDevSession::from_sessions_file(&project_path)
         .await
@@ -140,104 +164,165 @@ pub async fn handle_command(
         emoji::INFO_SQUARE
     );
 
-    // Create NATS and control interface client to use to connect
-    let ctl_client = cmd.wasmcloud_opts.clone().into_ctl_client(None).await;
-    let host_id = match ctl_client {
-        Ok(ref ctl_client) => match ctl_client.get_hosts().await.as_ref().map(|r| r.as_slice()) {
-            // Failing to get hosts is acceptable if none are supposed to be running, or if NATS is not running
-            Ok([]) | Err(_) if cmd.host_id.is_none() => {
-                eprintln!(
-                    "{} No running hosts found, will start one...",
-                    emoji::INFO_SQUARE
-                );
-                None
-            }
-            Ok([]) | Err(_) => {
-                bail!("host ID specified but no running hosts found");
-            }
-            Ok([host]) if host.data().is_some() => {
-                // SAFETY: We know that the host exists and has data
-                Some(
-                    ServerId::from_str(host.data().unwrap().id())
-                        .map_err(|e| anyhow::anyhow!("failed to parse host ID: {e}"))?,
-                )
-            }
-            Ok(hosts) if cmd.host_id.is_some() => {
-                // SAFETY: We know that the host ID is Some as checked above
-                let host_id = cmd.host_id.unwrap();
-                if let Some(_host) = hosts
-                    .iter()
-                    .find(|h| h.data().map(|d| d.id()).is_some_and(|id| *id == *host_id))
-                {
-                    Some(host_id)
-                } else {
-                    bail!("specified host ID '{host_id}' not found in running hosts");
-                }
-            }
-            Ok(hosts) => {
-                bail!(
-                    "found multiple running hosts, please specify a host ID with --host-id. Eligible hosts: [{:?}]",
-                    hosts
-                        .iter()
-                        .filter_map(|h| h.data().map(|d| d.id()))
-                        .collect::<Vec<&str>>()
-                        .join(", ")
-                );
-            }
-        },
-        Err(_) if cmd.host_id.is_some() => bail!("host ID specified but could not connect to control interface, ensure host and NATS is running or omit host ID"),
-        Err(_) => None,
-    };
-
     let (mut nats_child, mut wadm_child, mut wasmcloud_child) = (None, None, None);
-
-    // If there is not a running host for this session, then we can start one
-    if wash_dev_session.host_data.is_none() {
-        (nats_child, wadm_child, wasmcloud_child) = wash_dev_session
-            .start_host(
-                cmd.wasmcloud_opts.clone(),
-                cmd.nats_opts.clone(),
-                cmd.wadm_opts.clone(),
-                host_id,
-            )
+    let mut embedded_host = None;
+    if cmd.embedded {
+        let (child, host) = wash_dev_session
+            .start_embedded_host(cmd.wasmcloud_opts.clone(), cmd.nats_opts.clone())
             .await
-            .with_context(|| format!("failed to start host for session [{session_id}]"))?;
+            .with_context(|| format!("failed to start embedded host for session [{session_id}]"))?;
+        nats_child = child;
+        embedded_host = Some(host);
+    } else {
+        // Create NATS and control interface client to use to connect
+        let ctl_client = cmd.wasmcloud_opts.clone().into_ctl_client(None).await;
+        let host_id = match ctl_client {
+            Ok(ref ctl_client) => match ctl_client.get_hosts().await.as_ref().map(|r| r.as_slice()) {
+                // Failing to get hosts is acceptable if none are supposed to be running, or if NATS is not running
+                Ok([]) | Err(_) if cmd.host_id.is_none() => {
+                    eprintln!(
+                        "{} No running hosts found, will start one...",
+                        emoji::INFO_SQUARE
+                    );
+                    None
+                }
+                Ok([]) | Err(_) => {
+                    bail!("host ID specified but no running hosts found");
+                }
+                Ok([host]) if host.data().is_some() => {
+                    // SAFETY: We know that the host exists and has data
+                    Some(
+                        ServerId::from_str(host.data().unwrap().id())
+                            .map_err(|e| anyhow::anyhow!("failed to parse host ID: {e}"))?,
+                    )
+                }
+                Ok(hosts) if cmd.host_id.is_some() => {
+                    // SAFETY: We know that the host ID is Some as checked above
+                    let host_id = cmd.host_id.unwrap();
+                    if let Some(_host) = hosts
+                        .iter()
+                        .find(|h| h.data().map(|d| d.id()).is_some_and(|id| *id == *host_id))
+                    {
+                        Some(host_id)
+                    } else {
+                        bail!("specified host ID '{host_id}' not found in running hosts");
+                    }
+                }
+                Ok(hosts) => {
+                    bail!(
+                        "found multiple running hosts, please specify a host ID with --host-id. Eligible hosts: [{:?}]",
+                        hosts
+                            .iter()
+                            .filter_map(|h| h.data().map(|d| d.id()))
+                            .collect::<Vec<&str>>()
+                            .join(", ")
+                    );
+                }
+            },
+            Err(_) if cmd.host_id.is_some() => bail!("host ID specified but could not connect to control interface, ensure host and NATS is running or omit host ID"),
+            Err(_) => None,
+        };
+
+        // If there is not a running host for this session, then we can start one
+        if wash_dev_session.host_data.is_none() {
+            (nats_child, wadm_child, wasmcloud_child) = wash_dev_session
+                .start_host(
+                    cmd.wasmcloud_opts.clone(),
+                    cmd.nats_opts.clone(),
+                    cmd.wadm_opts.clone(),
+                    host_id,
+                )
+                .await
+                .with_context(|| format!("failed to start host for session [{session_id}]"))?;
+        }
     }
-    let host_id = wash_dev_session
-        .host_data
-        .clone()
-        .context("missing host_id, after ensuring host has started")?
-        .0;
+    let host_id = match &embedded_host {
+        Some(host) => host.host_id().to_string(),
+        None => {
+            wash_dev_session
+                .host_data
+                .clone()
+                .context("missing host_id, after ensuring host has started")?
+                .0
+        }
+    };
 
     let nats_client = nats_client_from_wasmcloud_opts(&cmd.wasmcloud_opts).await?;
-    // If we failed to connect to the control interface earlier, we can retry now that NATS is up
-    let ctl_client = if let Ok(ctl_client) = ctl_client {
-        ctl_client
-    } else {
-        cmd.wasmcloud_opts
-            .clone()
-            .into_ctl_client(None)
-            .await
-            .context("failed to create control interface client")?
-    };
+    // Connect to the control interface now that NATS and the host are up
+    let ctl_client = cmd
+        .wasmcloud_opts
+        .clone()
+        .into_ctl_client(None)
+        .await
+        .context("failed to create control interface client")?;
     let lattice = ctl_client.lattice();
+    let mocks = MockServer::new(
+        nats_client.clone(),
+        lattice,
+        wash_dev_session
+            .base_dir()
+            .await
+            .context("failed to get session dir")?
+            .join(MOCK_CALLS_FILE_NAME),
+    );
 
     // Build state for the run loop
-    let mut run_loop_state = devloop::RunLoopState {
-        dev_session: &mut wash_dev_session,
-        nats_client: &nats_client,
-        ctl_client: &ctl_client,
-        project_cfg: &mut project_cfg,
-        lattice,
-        session_id: &session_id,
-        manifest_output_dir: cmd.manifest_output_dir.as_ref(),
-        previous_deps: None,
-        artifact_path: None,
-        component_id: None,
-        component_ref: None,
-        package_args: &cmd.package_args,
-        skip_fetch: cmd.skip_wit_fetch,
-        output_kind,
+    let watch_paths = match &workspace {
+        Some(workspace) => workspace_watch_paths(&workspace.wasmcloud_toml_dir, &workspace.members),
+        None => vec![project_path.clone()],
+    };
+    let mut run_loop_state = match (workspace, project_cfg.as_mut()) {
+        (Some(workspace), _) => {
+            eprintln!(
+                "{} Developing workspace [{}] with members [{}]",
+                emoji::INFO_SQUARE,
+                workspace.name,
+                workspace
+                    .members
+                    .iter()
+                    .map(|m| m.common.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            DevLoop::Workspace(workspace::WorkspaceLoopState {
+                dev_session: &mut wash_dev_session,
+                nats_client: &nats_client,
+                ctl_client: &ctl_client,
+                host_id: &host_id,
+                workspace,
+                lattice,
+                session_id: &session_id,
+                manifest_output_dir: cmd.manifest_output_dir.as_ref(),
+                previous_manifest: None,
+                built_members: Default::default(),
+                package_args: &cmd.package_args,
+                skip_fetch: cmd.skip_wit_fetch,
+                output_kind,
+                direct_deployment: cmd.embedded.then(DirectDeployment::default),
+                mocks,
+            })
+        }
+        (None, Some(project_cfg)) => DevLoop::Project(devloop::RunLoopState {
+            dev_session: &mut wash_dev_session,
+            nats_client: &nats_client,
+            ctl_client: &ctl_client,
+            host_id: &host_id,
+            project_cfg,
+            lattice,
+            session_id: &session_id,
+            manifest_output_dir: cmd.manifest_output_dir.as_ref(),
+            previous_deps: None,
+            artifact_path: None,
+            component_id: None,
+            component_ref: None,
+            package_args: &cmd.package_args,
+            skip_fetch: cmd.skip_wit_fetch,
+            output_kind,
+            direct_deployment: cmd.embedded.then(DirectDeployment::default),
+            mocks,
+            provider_config: Vec::new(),
+        }),
+        (None, None) => bail!("missing project configuration"),
     };
 
     // See if the host is running by retrieving an inventory
@@ -256,6 +341,7 @@ pub async fn handle_command(
             wasmcloud_child,
             wadm_child,
             nats_child,
+            embedded_host,
             cmd.leave_host_running,
         )
         .await
@@ -272,6 +358,9 @@ pub async fn handle_command(
     // Set up a oneshot channel to perform graceful shutdown, handle Ctrl + c w/ tokio
     let (stop_tx, mut stop_rx) = mpsc::channel::<()>(1);
     let (reload_tx, mut reload_rx) = mpsc::channel::<()>(1);
+    // Paths that changed since the last reload, so that only affected workspace members are rebuilt
+    let changed_paths = Arc::new(Mutex::new(BTreeSet::<PathBuf>::new()));
+    let watcher_changed_paths = changed_paths.clone();
     tokio::spawn(async move {
         tokio::signal::ctrl_c()
             .await
@@ -289,7 +378,7 @@ pub async fn handle_command(
     let watcher_paused = pause_watch.clone();
 
     // Spawn a file watcher to listen for changes and send on reload_tx
-    let project_path_notify = project_path.clone();
+    let watch_paths_notify = watch_paths.clone();
     let mut watcher = notify::recommended_watcher(move |res: _| match res {
         Ok(event) => match event {
             NotifyEvent {
@@ -311,8 +400,11 @@ pub async fn handle_command(
                 // This is primarily here to avoid recursively triggering reloads for files that are
                 // generated by the build process.
                 if paths.iter().any(|p| {
-                    p.strip_prefix(project_path_notify.as_path())
-                        .is_ok_and(|p| cmd.ignore_dirs.iter().any(|ignore| p.starts_with(ignore)))
+                    watch_paths_notify.iter().any(|root| {
+                        p.strip_prefix(root).is_ok_and(|p| {
+                            cmd.ignore_dirs.iter().any(|ignore| p.starts_with(ignore))
+                        })
+                    })
                 }) {
                     return;
                 }
@@ -321,6 +413,9 @@ pub async fn handle_command(
                     return;
                 }
                 trace!("file event triggered dev loop: {paths:?}");
+                if let Ok(mut changed_paths) = watcher_changed_paths.lock() {
+                    changed_paths.extend(paths);
+                }
 
                 // NOTE(brooksmtownsend): `try_send` here is used intentionally to prevent
                 // multiple file reloads from queuing up a backlog of reloads.
@@ -332,13 +427,15 @@ pub async fn handle_command(
             eprintln!("{} Watch failed: {:?}", emoji::ERROR, e);
         }
     })?;
-    watcher.watch(&project_path.clone(), RecursiveMode::Recursive)?;
+    for path in &watch_paths {
+        watcher.watch(path, RecursiveMode::Recursive)?;
+    }
 
     // NOTE(brooksmtownsend): Yes, it would make more sense to return here. For some reason unknown to me
     // trying to return any error here will just cause the dev loop to hang infinitely and require a force quit.
     // Even a panic will display a tokio error and then hang. Thankfully, the error will just probably happen
     // again when the dev loop runs and in that case it'll successfully exit out.
-    if let Err(e) = devloop::run(&mut run_loop_state).await {
+    if let Err(e) = run_loop_state.run(&BTreeSet::new()).await {
         eprintln!(
             "{} Failed to run first dev loop iteration, will retry: {e}",
             emoji::WARN
@@ -359,7 +456,12 @@ pub async fn handle_command(
             // Process a file change/reload
             _ = reload_rx.recv() => {
                 pause_watch.store(true, Ordering::SeqCst);
-                devloop::run(&mut run_loop_state)
+                let changed = changed_paths
+                    .lock()
+                    .map(|mut paths| std::mem::take(&mut *paths))
+                    .unwrap_or_default();
+                run_loop_state
+                    .run(&changed)
                     .await
                     .context("failed to run dev loop iteration")?;
                 eprintln!("\n{} Watching for file changes (press Ctrl+c to stop)...", emoji::EYES);
@@ -367,6 +469,9 @@ pub async fn handle_command(
                 tokio::time::sleep(std::time::Duration::from_millis(100)).await;
                 // Make sure that the reload channel is empty before unpausing the watcher
                 let _ = reload_rx.try_recv();
+                if let Ok(mut paths) = changed_paths.lock() {
+                    paths.clear();
+                }
                 pause_watch.store(false, Ordering::SeqCst);
             },
 
@@ -375,7 +480,7 @@ pub async fn handle_command(
                 pause_watch.store(true, Ordering::SeqCst);
                 eprintln!("\n{} Received Ctrl + c, stopping devloop...", emoji::STOP);
 
-                stop_dev_session(run_loop_state, &ctl_client, wasmcloud_child, wadm_child, nats_child, cmd.leave_host_running).await?;
+                stop_dev_session(run_loop_state, &ctl_client, wasmcloud_child, wadm_child, nats_child, embedded_host, cmd.leave_host_running).await?;
 
                 break Ok(CommandOutput::from_key_and_text(
                     "result",
@@ -389,28 +494,96 @@ pub async fn handle_command(
     }
 }
 
+/// The development loop of either a single project or a workspace of projects
+enum DevLoop<'a> {
+    Project(devloop::RunLoopState<'a>),
+    Workspace(workspace::WorkspaceLoopState<'a>),
+}
+
+impl DevLoop<'_> {
+    /// Run one iteration of the development loop, given the paths that changed since the last one
+    async fn run(&mut self, changed_paths: &BTreeSet<PathBuf>) -> Result<()> {
+        match self {
+            Self::Project(state) => devloop::run(state).await,
+            Self::Workspace(state) => workspace::run(state, changed_paths).await,
+        }
+    }
+
+    fn dev_session(&mut self) -> &mut WashDevSession {
+        match self {
+            Self::Project(state) => state.dev_session,
+            Self::Workspace(state) => state.dev_session,
+        }
+    }
+
+    /// Delete the manifests that were deployed by the development loop
+    async fn delete_manifests(
+        &mut self,
+        ctl_client: &wasmcloud_control_interface::Client,
+    ) -> Result<()> {
+        match self {
+            Self::Project(state) => state.mocks.stop_all(ctl_client).await,
+            Self::Workspace(state) => state.mocks.stop_all(ctl_client).await,
+        }
+        match self {
+            Self::Project(state) => {
+                if let Some(deployment) = state.direct_deployment.as_mut() {
+                    eprintln!(
+                        "{} Cleaning up deployed wasmCloud application(s)...",
+                        emoji::BROOM
+                    );
+                    deployment.delete_all(ctl_client, state.host_id).await?;
+                } else if let Some(dependencies) = &state.previous_deps {
+                    eprintln!(
+                        "{} Cleaning up deployed wasmCloud application(s)...",
+                        emoji::BROOM
+                    );
+                    dependencies
+                        .delete_manifests(&ctl_client.nats_client(), ctl_client.lattice())
+                        .await?;
+                }
+            }
+            Self::Workspace(state) => {
+                if state.previous_manifest.is_some() {
+                    eprintln!(
+                        "{} Cleaning up deployed wasmCloud application(s)...",
+                        emoji::BROOM
+                    );
+                    state.delete_manifest().await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Paths to watch for changes in a workspace: the workspace itself, and members outside of it
+fn workspace_watch_paths(workspace_path: &Path, members: &[ProjectConfig]) -> Vec<PathBuf> {
+    let mut paths = vec![workspace_path.to_path_buf()];
+    for member in members {
+        let dir = &member.common.project_dir;
+        if !paths.iter().any(|p| dir.starts_with(p)) {
+            paths.push(dir.clone());
+        }
+    }
+    paths
+}
+
 async fn stop_dev_session(
-    run_loop_state: devloop::RunLoopState<'_>,
+    mut run_loop_state: DevLoop<'_>,
     ctl_client: &wasmcloud_control_interface::Client,
     wasmcloud_child: Option<tokio::process::Child>,
     wadm_child: Option<tokio::process::Child>,
     nats_child: Option<tokio::process::Child>,
+    embedded_host: Option<EmbeddedHost>,
     leave_host_running: bool,
 ) -> Result<()> {
     // Update the sessions file with the fact that this session stopped
-    run_loop_state.dev_session.in_use = false;
-    SessionMetadata::persist_session(run_loop_state.dev_session).await?;
+    run_loop_state.dev_session().in_use = false;
+    SessionMetadata::persist_session(run_loop_state.dev_session()).await?;
 
     // Delete manifests related to the application
-    if let Some(dependencies) = run_loop_state.previous_deps {
-        eprintln!(
-            "{} Cleaning up deployed wasmCloud application(s)...",
-            emoji::BROOM
-        );
-        dependencies
-            .delete_manifests(&ctl_client.nats_client(), ctl_client.lattice())
-            .await?;
-    }
+    run_loop_state.delete_manifests(ctl_client).await?;
 
     // NOTE(brooksmtownsend): Wait here for a second or so to ensure that all links and config are cleaned up.
     // There's not a really easy way to ensure everything is cleaned up after deleting the old manifest, so we
@@ -424,8 +597,14 @@ async fn stop_dev_session(
             emoji::HOURGLASS_DRAINING
         );
 
-        // Stop host via the control interface
-        if let Some((ref host_id, _log_file)) = run_loop_state.dev_session.host_data.as_ref() {
+        // Stop an embedded host directly, or any other host via the control interface
+        if let Some(host) = embedded_host {
+            if let Err(e) = host.stop().await {
+                eprintln!("{} Failed to stop embedded host: {e}", emoji::WARN);
+            }
+        } else if let Some((ref host_id, _log_file)) =
+            run_loop_state.dev_session().host_data.as_ref()
+        {
             let receiver = ctl_client
                 .events_receiver(vec!["host_stopped".to_string()])
                 .await;
@@ -474,7 +653,7 @@ async fn stop_dev_session(
             wadm.kill()
                 .await
                 .context("failed to stop wadm child process")?;
-            remove_wadm_pidfile(run_loop_state.dev_session.base_dir().await?)
+            remove_wadm_pidfile(run_loop_state.dev_session().base_dir().await?)
                 .await
                 .context("failed to remove wadm pidfile")?;
         }
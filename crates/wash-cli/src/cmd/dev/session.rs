@@ -25,6 +25,7 @@ use crate::cmd::up::{remove_wadm_pidfile, start_nats, NatsOpts, WadmOpts, Wasmcl
 use crate::config::{configure_host_env, DEFAULT_NATS_HOST, WADM_VERSION, WASMCLOUD_HOST_VERSION};
 use crate::down::stop_nats;
 
+use super::embedded::EmbeddedHost;
 use super::{dev_dir, sessions_file_path, SESSIONS_FILE_VERSION, SESSION_ID_LEN};
 
 /// Metadata related to a single `wash dev` session
@@ -219,31 +220,15 @@ impl WashDevSession {
         Ok(session)
     }
 
-    /// Start a host for the given session, if one is not present. Providing a host ID will
-    /// cause the session to attempt to connect to the specified host, rather than starting a
-    /// new one
-    pub(crate) async fn start_host(
-        &mut self,
-        mut wasmcloud_opts: WasmcloudOpts,
+    /// Start a NATS server for the given session, unless one is already listening or the options
+    /// specify that an existing server should be used. Returns the NATS server process (if one
+    /// was started) and the address that it listens on
+    async fn ensure_nats(
+        &self,
+        wasmcloud_opts: &WasmcloudOpts,
         nats_opts: NatsOpts,
-        wadm_opts: WadmOpts,
-        host_id: Option<ServerId>,
-    ) -> Result<(Option<Child>, Option<Child>, Option<Child>)> {
-        if self.host_data.is_some() {
-            return Ok((None, None, None));
-        }
-
-        eprintln!(
-            "{} {}",
-            emoji::CONSTRUCTION_BARRIER,
-            style("Starting a new host...").bold()
-        );
-        // Ensure that file loads are allowed
-        wasmcloud_opts.allow_file_load = Some(true);
-        wasmcloud_opts.multi_local = true;
-
+    ) -> Result<(Option<Child>, String)> {
         let session_dir = self.base_dir().await?;
-
         let install_dir = downloads_dir()?;
         let nats_host = nats_opts.nats_host.clone().unwrap_or_else(|| {
             wasmcloud_opts
@@ -287,6 +272,74 @@ impl WashDevSession {
             }
         };
 
+        Ok((nats_child, nats_listen_address))
+    }
+
+    /// Start a host inside of this process for the given session, applying manifests directly to
+    /// it rather than through wadm. A NATS server is still required for the host and is started
+    /// if one is not running.
+    pub(crate) async fn start_embedded_host(
+        &mut self,
+        wasmcloud_opts: WasmcloudOpts,
+        nats_opts: NatsOpts,
+    ) -> Result<(Option<Child>, EmbeddedHost)> {
+        eprintln!(
+            "{} {}",
+            emoji::CONSTRUCTION_BARRIER,
+            style("Starting an embedded host...").bold()
+        );
+        let (nats_child, _nats_listen_address) =
+            self.ensure_nats(&wasmcloud_opts, nats_opts).await?;
+        match EmbeddedHost::start(&wasmcloud_opts).await {
+            Ok(host) => {
+                eprintln!(
+                    "{} {}",
+                    emoji::GREEN_CHECK,
+                    style(format!(
+                        "Successfully started embedded host [{}], logs are written with wash's own logs",
+                        host.host_id()
+                    ))
+                    .bold()
+                );
+                Ok((nats_child, host))
+            }
+            Err(e) => {
+                if let Some(mut nats) = nats_child {
+                    nats.kill().await.context("failed to stop NATS")?;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Start a host for the given session, if one is not present. Providing a host ID will
+    /// cause the session to attempt to connect to the specified host, rather than starting a
+    /// new one
+    pub(crate) async fn start_host(
+        &mut self,
+        mut wasmcloud_opts: WasmcloudOpts,
+        nats_opts: NatsOpts,
+        wadm_opts: WadmOpts,
+        host_id: Option<ServerId>,
+    ) -> Result<(Option<Child>, Option<Child>, Option<Child>)> {
+        if self.host_data.is_some() {
+            return Ok((None, None, None));
+        }
+
+        eprintln!(
+            "{} {}",
+            emoji::CONSTRUCTION_BARRIER,
+            style("Starting a new host...").bold()
+        );
+        // Ensure that file loads are allowed
+        wasmcloud_opts.allow_file_load = Some(true);
+        wasmcloud_opts.multi_local = true;
+
+        let session_dir = self.base_dir().await?;
+        let install_dir = downloads_dir()?;
+        let (nats_child, nats_listen_address) =
+            self.ensure_nats(&wasmcloud_opts, nats_opts.clone()).await?;
+
         // Start WADM
         let wadm_log_path = session_dir.join("wadm.log");
         let config = WadmConfig {
@@ -43,7 +43,7 @@ pub(crate) fn parse_project_wit(project_cfg: &ProjectConfig) -> Result<(Resolve,
 /// Normally, this means converting imports that the component depends on to
 /// components that can be run on the lattice.
 pub(crate) fn discover_dependencies_from_wit(
-    resolve: Resolve,
+    resolve: &Resolve,
     world_id: WorldId,
 ) -> Result<Vec<DependencySpec>> {
     let mut deps: Vec<DependencySpec> = Vec::new();
@@ -0,0 +1,596 @@
+use std::collections::{btree_map, BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use anyhow::{Context as _, Result};
+use console::style;
+use tracing::warn;
+use wadm_types::{Component, LinkProperty, Manifest, TargetConfig, TraitProperty};
+use wash_lib::build::workspace::{build_order, WitInterfaceKey, WorldInterfaces};
+use wash_lib::cli::{CommonPackageArgs, OutputKind};
+use wash_lib::generate::emoji;
+use wash_lib::parser::{load_workspace_config, ProjectConfig, TypeConfig, WorkspaceConfig};
+use wasmcloud_control_interface::Client as CtlClient;
+
+use crate::appearance::spinner::Spinner;
+
+use super::deps::{DependencySpec, ProjectDependencyKey, ProjectDeps};
+use super::devloop::{
+    apply_manifests, build_with_hooks, dev_component_id, provider_config,
+    resolve_test_component_ref, restart_provider, run_reload_hooks, scale_down_component,
+    write_manifests,
+};
+use super::embedded::DirectDeployment;
+use super::manifest::generate_component_from_project_cfg;
+use super::mock::{mock_specs, MockServer, MockSpec};
+use super::session::WashDevSession;
+use super::wit::{discover_dependencies_from_wit, parse_component_wit, parse_project_wit};
+
+/// A workspace member that has been built during the development loop
+#[derive(Debug, Clone)]
+pub(crate) struct BuiltMember {
+    pub(crate) project_dir: PathBuf,
+    pub(crate) component_id: String,
+    pub(crate) component_ref: String,
+    pub(crate) artifact_path: PathBuf,
+}
+
+/// State that is used/updated per loop of `wash dev` for a workspace
+pub(crate) struct WorkspaceLoopState<'a> {
+    pub(crate) dev_session: &'a mut WashDevSession,
+    pub(crate) nats_client: &'a async_nats::Client,
+    pub(crate) ctl_client: &'a CtlClient,
+    pub(crate) host_id: &'a str,
+    pub(crate) workspace: WorkspaceConfig,
+    pub(crate) lattice: &'a str,
+    pub(crate) session_id: &'a str,
+    pub(crate) manifest_output_dir: Option<&'a PathBuf>,
+    pub(crate) previous_manifest: Option<Manifest>,
+    /// Members that have been built, by project name
+    pub(crate) built_members: BTreeMap<String, BuiltMember>,
+    pub(crate) package_args: &'a CommonPackageArgs,
+    pub(crate) skip_fetch: bool,
+    pub(crate) output_kind: OutputKind,
+    /// Deployment of manifests directly to an embedded host, used in place of wadm
+    pub(crate) direct_deployment: Option<DirectDeployment>,
+    /// Server of the in-memory mocks that replace dependencies of members
+    pub(crate) mocks: MockServer,
+}
+
+impl WorkspaceLoopState<'_> {
+    /// Select the members that must be rebuilt, given the paths of changed files.
+    ///
+    /// Changed files are attributed to the member with the most specific project directory that
+    /// contains them. Every member is rebuilt when no paths are given, or when any changed file
+    /// does not belong to a member (for example, the workspace wasmcloud.toml).
+    fn members_to_build(&self, changed_paths: &BTreeSet<PathBuf>) -> BTreeSet<String> {
+        let all_members = || {
+            self.workspace
+                .members
+                .iter()
+                .map(|m| m.common.name.clone())
+                .collect()
+        };
+        let mut names = BTreeSet::new();
+        for path in changed_paths {
+            let Some(member) = self
+                .workspace
+                .members
+                .iter()
+                .filter(|m| path.starts_with(&m.common.project_dir))
+                .max_by_key(|m| m.common.project_dir.components().count())
+            else {
+                return all_members();
+            };
+            names.insert(member.common.name.clone());
+        }
+        if names.is_empty() {
+            return all_members();
+        }
+        // Members that have never been built successfully are always built
+        names.extend(
+            self.workspace
+                .members
+                .iter()
+                .filter(|m| !self.built_members.contains_key(&m.common.name))
+                .map(|m| m.common.name.clone()),
+        );
+        names
+    }
+
+    /// Delete the manifest deployed for the workspace, if any
+    pub(crate) async fn delete_manifest(&mut self) -> Result<()> {
+        if let Some(deployment) = self.direct_deployment.as_mut() {
+            return deployment.delete_all(self.ctl_client, self.host_id).await;
+        }
+        if let Some(manifest) = &self.previous_manifest {
+            wash_lib::app::delete_model_version(
+                self.nats_client,
+                Some(self.lattice.into()),
+                &manifest.metadata.name,
+                None,
+            )
+            .await
+            .with_context(|| {
+                format!("failed to delete application [{}]", manifest.metadata.name)
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Run one iteration of the development loop for a workspace, rebuilding the members that contain
+/// any of the `changed_paths`
+pub(crate) async fn run(
+    state: &mut WorkspaceLoopState<'_>,
+    changed_paths: &BTreeSet<PathBuf>,
+) -> Result<()> {
+    match load_workspace_config(Some(state.dev_session.project_path.clone()), Some(true)).await {
+        Ok(Some(workspace)) => state.workspace = workspace,
+        Ok(None) => {
+            warn!("project is no longer a workspace, using previous configuration");
+        }
+        Err(e) => {
+            warn!(err = ?e, "failed to load workspace configuration, using previous configuration");
+        }
+    }
+    // Forget members that were removed from the workspace
+    let member_names = state
+        .workspace
+        .members
+        .iter()
+        .map(|m| m.common.name.clone())
+        .collect::<BTreeSet<_>>();
+    state
+        .built_members
+        .retain(|name, _| member_names.contains(name));
+
+    // Build the members that changed, after the members they depend on
+    let to_build = state.members_to_build(changed_paths);
+    let mut rebuilt = Vec::new();
+    let mut hooks = BTreeMap::new();
+    for project_cfg in build_order(&state.workspace) {
+        let name = &project_cfg.common.name;
+        if !to_build.contains(name) {
+            continue;
+        }
+        let spinner = Spinner::new(&state.output_kind).context("failed to create spinner")?;
+        if matches!(state.output_kind, OutputKind::Text) {
+            spinner.update_spinner_message(format!("Building workspace member [{name}]..."));
+        } else {
+            eprintln!(
+                "{} {}",
+                emoji::CONSTRUCTION_BARRIER,
+                style(format!("Building workspace member [{name}]...")).bold(),
+            );
+        }
+        let artifact_path =
+            match build_with_hooks(project_cfg, state.package_args, state.skip_fetch).await {
+                Ok((artifact_path, member_hooks)) => {
+                    hooks.insert(name.clone(), member_hooks);
+                    artifact_path
+                }
+                Err(e) => {
+                    spinner.finish_and_clear();
+                    eprintln!(
+                        "{} {}\n{}",
+                        emoji::ERROR,
+                        style(format!("Failed to build workspace member [{name}]:")).red(),
+                        e
+                    );
+                    // Failing to build a member can be corrected by changing the code and shouldn't
+                    // stop the development loop, so the members that were built are still reloaded
+                    break;
+                }
+            };
+        spinner.finish_and_clear();
+        eprintln!(
+            "{} Successfully built workspace member [{name}] at [{}]",
+            emoji::GREEN_CHECK,
+            artifact_path.display()
+        );
+        state.built_members.insert(
+            name.clone(),
+            BuiltMember {
+                project_dir: project_cfg.common.project_dir.clone(),
+                component_id: dev_component_id(state.session_id, name),
+                component_ref: format!("file://{}", artifact_path.display()),
+                artifact_path,
+            },
+        );
+        rebuilt.push(name.clone());
+    }
+
+    if state.built_members.is_empty() {
+        return Ok(());
+    }
+
+    let (manifest, mocks) = generate_workspace_manifest(state)
+        .await
+        .context("failed to generate workspace manifest")?;
+    state
+        .mocks
+        .sync(state.ctl_client, mocks)
+        .await
+        .context("failed to serve interface mocks")?;
+    let manifest_unchanged = state.previous_manifest.as_ref() == Some(&manifest);
+    if let Some(output_dir) = state.manifest_output_dir {
+        if !manifest_unchanged {
+            write_manifests(output_dir, std::slice::from_ref(&manifest)).await?;
+        }
+    }
+
+    // Reload the members that were rebuilt
+    let host_id = state.host_id;
+    for name in rebuilt {
+        let Some(project_cfg) = state
+            .workspace
+            .members
+            .iter()
+            .find(|m| m.common.name == name)
+        else {
+            continue;
+        };
+        let BuiltMember {
+            component_id,
+            component_ref,
+            ..
+        } = &state.built_members[&name];
+        let (kind, reload) = match project_cfg.project_type {
+            TypeConfig::Component(_) => ("component", "Reloading"),
+            TypeConfig::Provider(_) => ("provider", "Restarting"),
+        };
+        eprintln!(
+            "{} {}",
+            emoji::RECYCLE,
+            style(format!(
+                "{reload} {kind} [{component_id}] of workspace member [{name}]..."
+            ))
+            .bold()
+        );
+        match project_cfg.project_type {
+            TypeConfig::Component(_) => {
+                scale_down_component(state.ctl_client, host_id, component_id, component_ref)
+                    .await
+                    .with_context(|| format!("failed to reload component [{component_id}]"))?
+            }
+            TypeConfig::Provider(_) => {
+                let config = provider_config(std::slice::from_ref(&manifest), component_id)
+                    .unwrap_or_default();
+                restart_provider(
+                    state.ctl_client,
+                    host_id,
+                    component_id,
+                    component_ref,
+                    config,
+                )
+                .await
+                .with_context(|| format!("failed to reload provider [{component_id}]"))?
+            }
+        }
+    }
+
+    // Deploy the manifest if it changed since the last iteration. Deploying directly to a host
+    // always happens, as it restores the members that were reloaded
+    if let Some(deployment) = state.direct_deployment.as_mut() {
+        let manifests = (!manifest_unchanged).then(|| manifest.clone());
+        deployment
+            .apply(state.ctl_client, host_id, manifests)
+            .await?;
+    } else if !manifest_unchanged {
+        apply_manifests(state.nats_client, state.lattice, [manifest.clone()]).await?;
+    }
+    state.previous_manifest = Some(manifest);
+
+    // Run the dev-reload hooks of the members that were rebuilt
+    for (name, member_hooks) in &mut hooks {
+        let Some(project_cfg) = state
+            .workspace
+            .members
+            .iter()
+            .find(|m| &m.common.name == name)
+        else {
+            continue;
+        };
+        let BuiltMember {
+            artifact_path,
+            component_ref,
+            ..
+        } = &state.built_members[name];
+        run_reload_hooks(
+            member_hooks,
+            project_cfg,
+            Some(artifact_path),
+            component_ref,
+        )
+        .await;
+    }
+    Ok(())
+}
+
+/// Generate a single manifest that deploys all built members of a workspace, along with their
+/// dependencies.
+///
+/// Interfaces that members import from each other are linked between the members, rather than to
+/// the components that would normally be generated for them. Mocked dependencies of members are
+/// returned separately, as they are not part of the manifest.
+async fn generate_workspace_manifest(
+    state: &WorkspaceLoopState<'_>,
+) -> Result<(Manifest, Vec<MockSpec>)> {
+    // Discover the interfaces and dependencies of every built member
+    let mut members = Vec::with_capacity(state.built_members.len());
+    for project_cfg in &state.workspace.members {
+        let Some(built) = state.built_members.get(&project_cfg.common.name) else {
+            continue;
+        };
+        let (resolve, world_id) = if let TypeConfig::Component(_) = project_cfg.project_type {
+            let component_bytes =
+                tokio::fs::read(&built.artifact_path)
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "failed to read component bytes from built artifact path {}",
+                            built.artifact_path.display()
+                        )
+                    })?;
+            parse_component_wit(&component_bytes).context("failed to parse WIT from component")?
+        } else {
+            parse_project_wit(project_cfg).context("failed to parse WIT from project dir")?
+        };
+        let interfaces = WorldInterfaces::from_world(&resolve, world_id)
+            .context("failed to collect interfaces of world")?;
+        let deps = discover_dependencies_from_wit(&resolve, world_id)
+            .context("failed to resolve dependent components")?;
+        members.push((project_cfg, built, interfaces, deps, (resolve, world_id)));
+    }
+    let interfaces = members
+        .iter()
+        .map(|(_, built, interfaces, _, _)| (built.component_id.as_str(), interfaces))
+        .collect::<Vec<_>>();
+
+    let mut components = BTreeMap::<String, Component>::new();
+    let mut policies = BTreeMap::new();
+    let mut metadata = None;
+    let mut mocks = Vec::new();
+    for (idx, (project_cfg, built, _, deps, (resolve, world_id))) in members.iter().enumerate() {
+        let deps = deps
+            .iter()
+            .filter(|dep| !provided_by_other_member(dep, idx, &interfaces))
+            .cloned();
+        let project_deps = project_deps(project_cfg, built, deps, state.session_id)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to resolve dependencies of workspace member [{}]",
+                    project_cfg.common.name
+                )
+            })?;
+        mocks.extend(
+            mock_specs(&built.component_id, resolve, *world_id, &project_deps).with_context(
+                || {
+                    format!(
+                        "failed to build interface mocks for workspace member [{}]",
+                        project_cfg.common.name
+                    )
+                },
+            )?,
+        );
+        for manifest in project_deps.generate_wadm_manifests().with_context(|| {
+            format!(
+                "failed to generate a WADM manifest for workspace member [{}]",
+                project_cfg.common.name
+            )
+        })? {
+            for component in manifest.spec.components {
+                match components.entry(component.name.clone()) {
+                    btree_map::Entry::Occupied(mut existing) => {
+                        // Dependencies shared by members are only generated once
+                        let existing_traits = existing.get_mut().traits.get_or_insert(Vec::new());
+                        for trt in component.traits.into_iter().flatten() {
+                            if !existing_traits.contains(&trt) {
+                                existing_traits.push(trt);
+                            }
+                        }
+                    }
+                    btree_map::Entry::Vacant(entry) => {
+                        entry.insert(component);
+                    }
+                }
+            }
+            for policy in manifest.spec.policies {
+                policies.insert(policy.name.clone(), policy);
+            }
+            metadata.get_or_insert(manifest.metadata);
+        }
+    }
+
+    // Link the members to each other
+    for (source, links) in member_links(&interfaces) {
+        if let Some(component) = components.get_mut(&source) {
+            component
+                .traits
+                .get_or_insert(Vec::new())
+                .extend(links.into_iter().map(|link| wadm_types::Trait {
+                    trait_type: "link".into(),
+                    properties: TraitProperty::Link(link),
+                }));
+        }
+    }
+
+    let mut metadata = metadata.context("workspace has no built members")?;
+    metadata.name = format!(
+        "dev-workspace-{}",
+        state.workspace.name.to_lowercase().replace(" ", "-")
+    );
+    let manifest = Manifest {
+        api_version: "core.oam.dev/v1beta1".into(),
+        kind: "Application".into(),
+        metadata,
+        spec: wadm_types::Specification {
+            components: components.into_values().collect(),
+            policies: policies.into_values().collect(),
+        },
+    };
+    Ok((manifest, mocks))
+}
+
+/// Build the dependencies of a single workspace member, the same way they are built for a
+/// project that is developed on its own
+async fn project_deps(
+    project_cfg: &ProjectConfig,
+    built: &BuiltMember,
+    deps: impl IntoIterator<Item = DependencySpec>,
+    session_id: &str,
+) -> Result<ProjectDeps> {
+    let pkey = ProjectDependencyKey::from_project(&project_cfg.common.name, &built.project_dir)
+        .context("failed to build key for project")?;
+    let mut project_deps = ProjectDeps::from_known_deps(pkey.clone(), deps)
+        .context("failed to build project dependencies")?;
+    let override_deps = ProjectDeps::from_project_config_overrides(pkey, project_cfg)
+        .context("failed to discover project dependencies from config")?;
+    project_deps
+        .merge_override(override_deps)
+        .context("failed to merge & override project-specified deps")?;
+    if let (TypeConfig::Provider(_), Some(test_component)) =
+        (&project_cfg.project_type, &project_cfg.dev.test_component)
+    {
+        let test_component_ref =
+            resolve_test_component_ref(test_component, &project_cfg.common.project_dir)
+                .await
+                .context("failed to resolve test component")?;
+        project_deps.resolve_with_test_component(&test_component_ref);
+    }
+    if !project_cfg.dev.manifests.is_empty() {
+        eprintln!(
+            "{} Ignoring manifests of workspace member [{}], a manifest is generated for the whole workspace",
+            emoji::WARN,
+            project_cfg.common.name
+        );
+    }
+    project_deps.session_id = Some(session_id.into());
+    project_deps.component = Some(
+        generate_component_from_project_cfg(project_cfg, &built.component_id, &built.component_ref)
+            .context("failed to generate app component")?,
+    );
+    Ok(project_deps)
+}
+
+/// Whether a dependency of the member at `idx` is fulfilled by another member, i.e. whether the
+/// other member exports an interface of the dependency that the member imports, or vice versa
+fn provided_by_other_member(
+    dep: &DependencySpec,
+    idx: usize,
+    members: &[(&str, &WorldInterfaces)],
+) -> bool {
+    let (_, interfaces) = members[idx];
+    let in_package =
+        |(ns, pkg, _): &&WitInterfaceKey| *ns == dep.wit().namespace && *pkg == dep.wit().package;
+    members
+        .iter()
+        .enumerate()
+        .filter(|(other_idx, _)| *other_idx != idx)
+        .any(|(_, (_, other))| match dep {
+            DependencySpec::Exports(_) => interfaces
+                .imports
+                .intersection(&other.exports)
+                .any(|key| in_package(&key)),
+            DependencySpec::Imports(_) => interfaces
+                .exports
+                .intersection(&other.imports)
+                .any(|key| in_package(&key)),
+        })
+}
+
+/// Generate the links between workspace members, keyed by the name of the component that is the
+/// source of the links.
+///
+/// Members are linked to the members that export the interfaces they import. When more than one
+/// member exports an interface, the member that is listed first is used.
+fn member_links(members: &[(&str, &WorldInterfaces)]) -> BTreeMap<String, Vec<LinkProperty>> {
+    let mut links = BTreeMap::<String, Vec<LinkProperty>>::new();
+    for (source_idx, (source, source_ifaces)) in members.iter().enumerate() {
+        let mut linked = BTreeSet::new();
+        for (target_idx, (target, target_ifaces)) in members.iter().enumerate() {
+            if source_idx == target_idx {
+                continue;
+            }
+            // Group the interfaces that are linked by package
+            let mut packages = BTreeMap::<(&str, &str), Vec<String>>::new();
+            for key @ (ns, pkg, iface) in source_ifaces.imports.intersection(&target_ifaces.exports)
+            {
+                if linked.insert(key) {
+                    packages.entry((ns, pkg)).or_default().push(iface.clone());
+                }
+            }
+            for ((ns, pkg), interfaces) in packages {
+                links
+                    .entry(source.to_string())
+                    .or_default()
+                    .push(LinkProperty {
+                        namespace: ns.into(),
+                        package: pkg.into(),
+                        interfaces,
+                        target: TargetConfig {
+                            name: target.to_string(),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    });
+            }
+        }
+    }
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(ns: &str, pkg: &str, iface: &str) -> WitInterfaceKey {
+        (ns.into(), pkg.into(), iface.into())
+    }
+
+    #[test]
+    fn test_member_links() {
+        let api = WorldInterfaces {
+            imports: BTreeSet::from([
+                key("wasi", "keyvalue", "store"),
+                key("wasi", "keyvalue", "atomics"),
+                key("example", "jobs", "queue"),
+            ]),
+            exports: BTreeSet::from([key("wasi", "http", "incoming-handler")]),
+        };
+        let store = WorldInterfaces {
+            imports: BTreeSet::new(),
+            exports: BTreeSet::from([
+                key("wasi", "keyvalue", "store"),
+                key("wasi", "keyvalue", "atomics"),
+            ]),
+        };
+        let other_store = WorldInterfaces {
+            imports: BTreeSet::from([key("example", "jobs", "queue")]),
+            exports: BTreeSet::from([key("wasi", "keyvalue", "store")]),
+        };
+        let members = [
+            ("api", &api),
+            ("store", &store),
+            ("other-store", &other_store),
+        ];
+
+        let links = member_links(&members);
+        assert_eq!(links.len(), 1);
+        let api_links = &links["api"];
+        assert_eq!(api_links.len(), 1);
+        assert_eq!(api_links[0].namespace, "wasi");
+        assert_eq!(api_links[0].package, "keyvalue");
+        assert_eq!(api_links[0].interfaces, vec!["atomics", "store"]);
+        assert_eq!(api_links[0].target.name, "store");
+
+        // Interfaces exported by other members are not dependencies, but the rest still are
+        let keyvalue = DependencySpec::from_wit_import_iface("wasi:keyvalue/store")
+            .expect("failed to build keyvalue dependency");
+        assert!(provided_by_other_member(&keyvalue, 0, &members));
+        let http = DependencySpec::from_wit_export_iface("wasi:http/incoming-handler")
+            .expect("failed to build http dependency");
+        assert!(!provided_by_other_member(&http, 0, &members));
+    }
+}
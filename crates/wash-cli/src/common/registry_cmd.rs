@@ -8,9 +8,11 @@ use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 use tracing::warn;
 
+use wash_lib::build::provenance::provenance_path;
 use wash_lib::cli::registry::{RegistryPullCommand, RegistryPushCommand};
 use wash_lib::cli::{input_vec_to_hashmap, CommandOutput, OutputKind};
 use wash_lib::parser::{load_config, ProjectConfig};
+use wash_lib::plugin::hooks::Event;
 use wash_lib::registry::{
     identify_artifact, pull_oci_artifact, push_oci_artifact, ArtifactType, OciPullOptions,
     OciPushOptions,
@@ -18,6 +20,7 @@ use wash_lib::registry::{
 use wasmcloud_control_interface::RegistryCredential;
 
 use crate::appearance::spinner::Spinner;
+use crate::util::load_hooks;
 
 pub const SHOWER_EMOJI: &str = "\u{1F6BF}";
 pub const PROVIDER_ARCHIVE_FILE_EXTENSION: &str = ".par.gz";
@@ -102,6 +105,23 @@ pub async fn registry_push(
         warn!(" Unless an SSL certificate has been installed, pushing to localhost without the --insecure option will fail")
     }
 
+    // Run the hooks declared by the project around the push
+    let mut hooks = match project_config.as_ref() {
+        Some(config) => Some((load_hooks(config).await?, config.common.project_dir.clone())),
+        None => None,
+    };
+    let artifact_path = PathBuf::from(&cmd.artifact);
+    if let Some((hooks, project_dir)) = hooks.as_mut() {
+        hooks
+            .run(
+                Event::PrePush,
+                project_dir,
+                Some(&artifact_path),
+                Some(&artifact_url),
+            )
+            .await?;
+    }
+
     let spinner = Spinner::new(&output_kind)?;
     spinner.update_spinner_message(format!(" Pushing {} to {} ...", cmd.artifact, artifact_url));
 
@@ -121,6 +141,11 @@ pub async fn registry_push(
         )
     });
 
+    let provenance = cmd.provenance.or_else(|| {
+        let path = provenance_path(&cmd.artifact);
+        path.is_file().then_some(path)
+    });
+
     let (maybe_tag, digest) = push_oci_artifact(
         artifact_url.clone(),
         cmd.artifact,
@@ -134,16 +159,32 @@ pub async fn registry_push(
             insecure_skip_tls_verify: cmd.opts.insecure_skip_tls_verify,
             annotations,
             monolithic_push: cmd.monolithic_push,
+            multi_arch: cmd.multi_arch,
+            provenance: provenance.clone(),
         },
     )
     .await?;
 
     spinner.finish_and_clear();
 
+    if let Some((hooks, project_dir)) = hooks.as_mut() {
+        hooks
+            .run(
+                Event::PostPush,
+                project_dir,
+                Some(&artifact_path),
+                Some(&artifact_url),
+            )
+            .await?;
+    }
+
     let mut map = HashMap::from_iter([
         ("url".to_string(), json!(artifact_url)),
         ("digest".to_string(), json!(digest)),
     ]);
+    if let Some(provenance) = provenance {
+        map.insert("provenance".to_string(), json!(provenance));
+    }
     let text = if let Some(tag) = maybe_tag {
         map.insert("tag".to_string(), json!(tag));
         format!("{SHOWER_EMOJI} Successfully pushed {artifact_url}\n{tag}: digest: {digest}")
@@ -219,6 +260,8 @@ async fn resolve_registry_credentials(registry: &str) -> Result<RegistryCredenti
 
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+
     use anyhow::{ensure, Context as _, Result};
     use clap::Parser;
     use wash_lib::cli::registry::{RegistryCommand, RegistryPullCommand};
@@ -337,6 +380,9 @@ mod tests {
             &format!("{TESTDIR}/logging.par.gz"),
             "--insecure",
             "--allow-latest",
+            "--multi-arch",
+            "--provenance",
+            &format!("{TESTDIR}/logging.par.gz.intoto.json"),
         ])
         .unwrap();
         match push_all_flags.sub {
@@ -345,12 +391,21 @@ mod tests {
                 artifact,
                 opts,
                 allow_latest,
+                multi_arch,
+                provenance,
                 ..
             }) => {
                 assert_eq!(&url, logging_push_all_flags);
                 assert_eq!(artifact, format!("{TESTDIR}/logging.par.gz"));
                 assert!(opts.insecure);
                 assert!(allow_latest);
+                assert!(multi_arch);
+                assert_eq!(
+                    provenance,
+                    Some(PathBuf::from(format!(
+                        "{TESTDIR}/logging.par.gz.intoto.json"
+                    )))
+                );
             }
             _ => panic!("`wash push` constructed incorrect command"),
         };
@@ -5,6 +5,7 @@ pub(crate) mod errors;
 
 pub mod app;
 pub mod appearance;
+pub mod apply;
 pub mod build;
 pub mod call;
 pub mod cmd;
@@ -21,5 +22,6 @@ pub mod par;
 pub mod plugin;
 pub mod secrets;
 pub mod style;
+pub mod test;
 pub mod ui;
 pub mod util;
@@ -0,0 +1,1022 @@
+//! Run declarative integration tests against components running in a wasmCloud lattice
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, ensure, Context, Result};
+use clap::{Args, ValueEnum};
+use console::style;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+use tracing::debug;
+use wadm_types::api::StatusType;
+use wit_bindgen_wrpc::wrpc_transport::InvokeExt as _;
+use wit_parser::{Resolve, WorldId};
+use wrpc_transport::Invoke as _;
+
+use wash_lib::cli::CommandOutput;
+use wash_lib::config::DEFAULT_LATTICE;
+use wash_lib::generate::emoji;
+use wash_lib::parser::InterfaceMock;
+use wash_lib::wrpc::{encode, WitFunctions};
+use wasmcloud_control_interface::ClientBuilder as CtlClientBuilder;
+use wasmcloud_core::parse_wit_meta_from_operation;
+
+use crate::call::{
+    create_client_from_opts_wrpc, gen_wash_call_headers, invoke_http_handler, invoke_simple,
+    ConnectionOpts,
+};
+use crate::cmd::dev::deps::{DependencySpec, ProjectDependencyKey, ProjectDeps};
+use crate::cmd::dev::mock::{mock_specs, mock_target_id, MockServer, MockSpec};
+use crate::cmd::dev::wit::parse_component_wit;
+
+/// File that test cases are read from when no path is given
+pub const DEFAULT_TESTS_FILE: &str = "wasmcloud-tests.toml";
+
+/// Function of test components that is invoked to run their tests
+const TEST_COMPONENT_RUN_FUNCTION: &str = "wasi:cli/run.run";
+
+/// Default amount of time to wait for the manifest of a test file to be deployed
+const DEFAULT_DEPLOY_TIMEOUT_MS: u64 = 30_000;
+
+/// File in the temporary directory that calls to mocks are recorded in when no path is given
+const DEFAULT_MOCK_CALLS_FILE: &str = "wash-test-mock-calls.jsonl";
+
+/// wRPC instance invoked by keyvalue scenarios
+const KEYVALUE_STORE_INSTANCE: &str = "wrpc:keyvalue/store@0.2.0-draft";
+
+#[derive(Debug, Clone, Args)]
+pub struct TestCommand {
+    #[clap(flatten)]
+    opts: ConnectionOpts,
+
+    /// Path to the file containing the test cases to run
+    #[clap(name = "tests", default_value = DEFAULT_TESTS_FILE)]
+    pub tests: PathBuf,
+
+    /// Only run tests with names that contain the given filter
+    #[clap(long = "filter")]
+    pub filter: Option<String>,
+
+    /// Path to write a report of the test results to
+    #[clap(long = "report")]
+    pub report: Option<PathBuf>,
+
+    /// Format of the report of the test results
+    #[clap(long = "report-format", default_value = "junit", requires = "report")]
+    pub report_format: ReportFormat,
+
+    /// Amount of time to wait for the manifest of the test file to be deployed
+    #[clap(long = "deploy-timeout-ms", default_value_t = DEFAULT_DEPLOY_TIMEOUT_MS)]
+    pub deploy_timeout_ms: u64,
+
+    /// Path to record calls to the mocks of the test file in, as JSON lines. Defaults to a file in
+    /// the temporary directory
+    #[clap(long = "mock-calls")]
+    pub mock_calls: Option<PathBuf>,
+}
+
+/// Format of a report of test results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Junit,
+    Json,
+}
+
+/// A file containing test cases
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestsFile {
+    /// Path to a wadm manifest (relative to the tests file) that is deployed before the tests run,
+    /// and deleted once they have finished
+    #[serde(default)]
+    pub manifest: Option<PathBuf>,
+    /// Imports of components under test that are served by in-memory mocks, in place of the
+    /// providers or components they would be linked to
+    #[serde(default, rename = "mock")]
+    pub mocks: Vec<MockedLink>,
+    /// Test cases to run, in order
+    #[serde(default, rename = "test")]
+    pub tests: Vec<TestCase>,
+}
+
+/// An import of a component under test that is linked to an in-memory mock, like the mocks of
+/// `wash dev`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MockedLink {
+    /// ID of the component whose import is mocked
+    pub component: String,
+    /// Path to the Wasm component (relative to the tests file) that the types of the imported
+    /// interface are read from
+    pub wasm: PathBuf,
+    /// Mocked interface (ex. `wasi:keyvalue/store`)
+    pub interface: String,
+    /// Name of the link to the mock, defaults to `default`
+    #[serde(default)]
+    pub link_name: Option<String>,
+    /// Scripted responses of the mock, keyed by (optionally interface-qualified) function name
+    #[serde(default)]
+    pub responses: BTreeMap<String, Value>,
+}
+
+/// A single test case
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestCase {
+    /// Name of the test case
+    pub name: String,
+    /// ID of the component to invoke, required for all scenarios other than messages
+    #[serde(default)]
+    pub component: Option<String>,
+    /// What the test case does
+    #[serde(flatten)]
+    pub scenario: Scenario,
+    /// What the outcome of the scenario is expected to be
+    #[serde(default)]
+    pub expect: Expectations,
+    /// Amount of time to wait for the scenario to complete, defaults to the RPC timeout
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// The action performed by a test case
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scenario {
+    /// Send a HTTP request to the component
+    Http(HttpScenario),
+    /// Send a message on a NATS subject and wait for the reply
+    Message(MessageScenario),
+    /// Invoke a function of the component that takes nothing and returns a string.
+    ///
+    /// Invoking `wasi:cli/run.run` runs a test component, which passes if it returns successfully
+    Function(String),
+    /// Read or write a key of a keyvalue store served over `wrpc:keyvalue`
+    Keyvalue(KeyvalueScenario),
+}
+
+/// A HTTP request sent to a component
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HttpScenario {
+    /// Method of the request, defaults to `GET`
+    #[serde(default)]
+    pub method: Option<String>,
+    /// Path (and query) of the request, defaults to `/`
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Headers of the request
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Body of the request
+    #[serde(default)]
+    pub body: String,
+}
+
+/// A message sent on a NATS subject
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MessageScenario {
+    /// Subject to send the message on
+    pub subject: String,
+    /// Body of the message
+    #[serde(default)]
+    pub body: String,
+}
+
+/// A key of a keyvalue store that is read, or written if a value is given
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeyvalueScenario {
+    /// ID of the lattice target serving the store, defaults to the mock of `wasi:keyvalue` imported
+    /// by the component of the test case
+    #[serde(default)]
+    pub target: Option<String>,
+    /// Bucket containing the key
+    #[serde(default)]
+    pub bucket: String,
+    /// Key that is read or written
+    pub key: String,
+    /// Value to write to the key. When not set, the value of the key is read into the body
+    #[serde(default)]
+    pub set: Option<String>,
+}
+
+/// Assertions on the outcome of a scenario
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectations {
+    /// Expected HTTP status code
+    #[serde(default)]
+    pub status: Option<u16>,
+    /// Expected HTTP response headers (names are case insensitive)
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Exact expected body of the response, message reply or function result
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Text that the body is expected to contain
+    #[serde(default)]
+    pub body_contains: Option<String>,
+    /// JSON that the body is expected to contain. Objects in the body may contain more fields than
+    /// the ones that are expected
+    #[serde(default)]
+    pub json: Option<serde_json::Value>,
+}
+
+/// The observed outcome of a scenario
+#[derive(Debug, Clone, Default)]
+struct Outcome {
+    status: Option<u16>,
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+/// The result of running a single test case
+#[derive(Debug, Clone, Serialize)]
+pub struct TestCaseResult {
+    pub name: String,
+    pub passed: bool,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure: Option<String>,
+}
+
+/// Handle `wash test`
+pub async fn handle_command(cmd: TestCommand) -> Result<CommandOutput> {
+    let tests_file = load_tests_file(&cmd.tests).await?;
+    let tests = tests_file
+        .tests
+        .iter()
+        .filter(|t| cmd.filter.as_ref().map_or(true, |f| t.name.contains(f)))
+        .collect::<Vec<_>>();
+    ensure!(
+        !tests.is_empty(),
+        "no test cases to run in [{}]",
+        cmd.tests.display()
+    );
+
+    let lattice = cmd
+        .opts
+        .lattice
+        .clone()
+        .unwrap_or_else(|| DEFAULT_LATTICE.to_string());
+    let nc = create_client_from_opts_wrpc(&cmd.opts)
+        .await
+        .context("failed to create async nats client")?;
+
+    // Deploy the manifest that sets up the components under test, if there is one
+    let deployed_manifest = match &tests_file.manifest {
+        Some(manifest) => {
+            let manifest = cmd
+                .tests
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .join(manifest);
+            Some(deploy_manifest(&nc, &lattice, &manifest).await?)
+        }
+        None => None,
+    };
+
+    // Serve the mocked imports of the components under test
+    let tests_dir = cmd.tests.parent().unwrap_or_else(|| Path::new("."));
+    let ctl_client = CtlClientBuilder::new(nc.clone())
+        .lattice(&lattice)
+        .timeout(Duration::from_millis(cmd.opts.timeout_ms))
+        .build();
+    let mut mocks = MockServer::new(
+        nc.clone(),
+        &lattice,
+        cmd.mock_calls
+            .clone()
+            .unwrap_or_else(|| std::env::temp_dir().join(DEFAULT_MOCK_CALLS_FILE)),
+    );
+    // Everything after deploying runs before cleaning up, so that a failed run does not leave the
+    // test application deployed or mocks running
+    let results: Result<Vec<TestCaseResult>> = async {
+        if let Some(name) = &deployed_manifest {
+            wait_for_deployment(&nc, &lattice, name, cmd.deploy_timeout_ms).await?;
+        }
+
+        if !tests_file.mocks.is_empty() {
+            let mut specs = Vec::new();
+            for mock in &tests_file.mocks {
+                let wasm = tokio::fs::read(tests_dir.join(&mock.wasm))
+                    .await
+                    .with_context(|| {
+                        format!("failed to read component [{}]", mock.wasm.display())
+                    })?;
+                let (resolve, world) = parse_component_wit(&wasm)?;
+                specs.extend(mocked_link_specs(mock, &resolve, world)?);
+            }
+            mocks
+                .sync(&ctl_client, specs)
+                .await
+                .context("failed to serve mocks")?;
+        }
+
+        let mut results = Vec::with_capacity(tests.len());
+        for test in tests {
+            let start = Instant::now();
+            let outcome = run_scenario(&nc, &lattice, test, &cmd.opts).await;
+            let failure = match outcome {
+                Ok(outcome) => check_expectations(&test.expect, &outcome).err(),
+                Err(e) => Some(format!("{e:#}")),
+            };
+            let result = TestCaseResult {
+                name: test.name.clone(),
+                passed: failure.is_none(),
+                duration_ms: u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX),
+                failure,
+            };
+            match &result.failure {
+                None => eprintln!(
+                    "{} {} ({}ms)",
+                    style("PASS").green().bold(),
+                    result.name,
+                    result.duration_ms
+                ),
+                Some(failure) => eprintln!(
+                    "{} {} ({}ms): {failure}",
+                    style("FAIL").red().bold(),
+                    result.name,
+                    result.duration_ms
+                ),
+            }
+            results.push(result);
+        }
+        Ok(results)
+    }
+    .await;
+
+    mocks.stop_all(&ctl_client).await;
+    if let Some(name) = deployed_manifest {
+        if let Err(e) =
+            wash_lib::app::delete_model_version(&nc, Some(lattice.clone()), &name, None).await
+        {
+            eprintln!(
+                "{} Failed to delete test application [{name}]: {e}",
+                emoji::WARN
+            );
+        }
+    }
+    let results = results?;
+
+    let suite_name = cmd
+        .tests
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "wash-test".into());
+    if let Some(report) = &cmd.report {
+        let contents = match cmd.report_format {
+            ReportFormat::Junit => junit_report(&suite_name, &results),
+            ReportFormat::Json => serde_json::to_string_pretty(&results)
+                .context("failed to serialize test results")?,
+        };
+        tokio::fs::write(report, contents)
+            .await
+            .with_context(|| format!("failed to write test report to [{}]", report.display()))?;
+    }
+
+    let total = results.len();
+    let failed = results.iter().filter(|r| !r.passed).count();
+    if failed > 0 {
+        bail!("{failed} of {total} test(s) failed");
+    }
+    Ok(CommandOutput::new(
+        format!("{} {total} test(s) passed", emoji::GREEN_CHECK),
+        HashMap::from([
+            ("passed".to_string(), json!(total)),
+            ("failed".to_string(), json!(failed)),
+            ("results".to_string(), json!(results)),
+        ]),
+    ))
+}
+
+/// Read test cases from a TOML file
+async fn load_tests_file(path: &Path) -> Result<TestsFile> {
+    let contents = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("failed to read tests file [{}]", path.display()))?;
+    toml::from_str(&contents)
+        .with_context(|| format!("failed to parse tests file [{}]", path.display()))
+}
+
+/// Build the specification of the mock serving an import of a component, whose world is `world`
+fn mocked_link_specs(
+    mock: &MockedLink,
+    resolve: &Resolve,
+    world: WorldId,
+) -> Result<Vec<MockSpec>> {
+    let mut dep = DependencySpec::from_wit_import_iface(&mock.interface)
+        .with_context(|| format!("invalid mocked interface [{}]", mock.interface))?;
+    if let Some(link_name) = &mock.link_name {
+        dep.set_link_name(link_name);
+    }
+    dep.inner_mut().mock = Some(InterfaceMock {
+        responses: mock.responses.clone(),
+    });
+    let pkey = ProjectDependencyKey::from_project(&mock.component, &mock.wasm)?;
+    let deps = ProjectDeps::from_known_deps(pkey, [dep])?;
+    let specs = mock_specs(&mock.component, resolve, world, &deps)
+        .with_context(|| format!("failed to mock [{}]", mock.interface))?;
+    ensure!(
+        !specs.is_empty(),
+        "component [{}] does not import [{}]",
+        mock.component,
+        mock.interface
+    );
+    Ok(specs)
+}
+
+/// Put and deploy a manifest with wadm, without waiting until it is deployed. Returns the name of
+/// the deployed application
+async fn deploy_manifest(nc: &async_nats::Client, lattice: &str, path: &Path) -> Result<String> {
+    let model = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("failed to read manifest [{}]", path.display()))?;
+    let (name, _version) =
+        wash_lib::app::put_and_deploy_model(nc, Some(lattice.to_string()), &model)
+            .await
+            .with_context(|| format!("failed to deploy manifest [{}]", path.display()))?;
+    eprintln!(
+        "{} Deployed test application [{name}], waiting for it to be ready...",
+        emoji::HOURGLASS_DRAINING
+    );
+    Ok(name)
+}
+
+/// Wait for a deployed application to be ready
+async fn wait_for_deployment(
+    nc: &async_nats::Client,
+    lattice: &str,
+    name: &str,
+    timeout_ms: u64,
+) -> Result<()> {
+    tokio::time::timeout(Duration::from_millis(timeout_ms), async {
+        loop {
+            let status = wash_lib::app::get_model_status(nc, Some(lattice.to_string()), name)
+                .await
+                .with_context(|| format!("failed to get status of application [{name}]"))?;
+            match status.info.status_type {
+                StatusType::Deployed => return Ok(()),
+                StatusType::Failed => bail!(
+                    "test application [{name}] failed to deploy: {}",
+                    status.info.message
+                ),
+                _ => tokio::time::sleep(Duration::from_millis(250)).await,
+            }
+        }
+    })
+    .await
+    .with_context(|| format!("timed out waiting for test application [{name}] to deploy"))?
+}
+
+/// Run the scenario of a test case, returning what it produced
+async fn run_scenario(
+    nc: &async_nats::Client,
+    lattice: &str,
+    test: &TestCase,
+    opts: &ConnectionOpts,
+) -> Result<Outcome> {
+    let timeout_ms = test.timeout_ms.unwrap_or(opts.timeout_ms);
+    let component_id = || {
+        test.component
+            .as_deref()
+            .context("test case must specify the component to invoke")
+    };
+    let wrpc_client = || async {
+        wrpc_transport_nats::Client::new(nc.clone(), format!("{lattice}.{}", component_id()?), None)
+            .await
+            .context("failed to create wRPC client")
+    };
+    debug!(test = test.name, ?test.scenario, "running test scenario");
+
+    match &test.scenario {
+        Scenario::Http(http) => {
+            let mut request = http::Request::builder()
+                .uri(format!(
+                    "http://localhost{}",
+                    http.path.as_deref().unwrap_or("/")
+                ))
+                .method(
+                    http::Method::from_str(http.method.as_deref().unwrap_or("GET"))
+                        .context("invalid HTTP method")?,
+                );
+            for (name, value) in &http.headers {
+                request = request.header(name, value);
+            }
+            let request = request
+                .body(http.body.clone())
+                .context("failed to build HTTP request")?;
+            let response = invoke_http_handler(
+                wrpc_client().await?,
+                lattice,
+                component_id()?,
+                timeout_ms,
+                request,
+            )
+            .await?;
+            Ok(Outcome {
+                status: Some(response.status),
+                headers: response.headers,
+                body: response.body,
+            })
+        }
+        Scenario::Message(message) => {
+            let reply = tokio::time::timeout(
+                Duration::from_millis(timeout_ms),
+                nc.request(message.subject.clone(), message.body.clone().into()),
+            )
+            .await
+            .with_context(|| {
+                format!(
+                    "timed out waiting for a reply on subject [{}]",
+                    message.subject
+                )
+            })?
+            .with_context(|| format!("failed to send message on subject [{}]", message.subject))?;
+            Ok(Outcome {
+                body: String::from_utf8_lossy(&reply.payload).to_string(),
+                ..Default::default()
+            })
+        }
+        Scenario::Function(function) if function == TEST_COMPONENT_RUN_FUNCTION => {
+            let component_id = component_id()?;
+            let (result,) = wrpc_client()
+                .await?
+                .timeout(Duration::from_millis(timeout_ms))
+                .invoke_values_blocking::<_, ((),), (Result<(), ()>,)>(
+                    Some(gen_wash_call_headers()),
+                    "wasi:cli/run",
+                    "run",
+                    ((),),
+                    &[[]; 0],
+                )
+                .await
+                .with_context(|| {
+                    format!("failed to run test component [{component_id}] in lattice [{lattice}]")
+                })?;
+            ensure!(
+                result.is_ok(),
+                "test component [{component_id}] reported a failure"
+            );
+            Ok(Outcome::default())
+        }
+        Scenario::Keyvalue(keyvalue) => {
+            let target = match &keyvalue.target {
+                Some(target) => target.clone(),
+                None => mock_target_id(component_id()?, "wasi", "keyvalue", "default"),
+            };
+            let (function, params) = match &keyvalue.set {
+                Some(value) => (
+                    "set",
+                    vec![
+                        json!(keyvalue.bucket),
+                        json!(keyvalue.key),
+                        json!(value.as_bytes()),
+                    ],
+                ),
+                None => ("get", vec![json!(keyvalue.bucket), json!(keyvalue.key)]),
+            };
+            let results =
+                invoke_keyvalue(nc, lattice, &target, timeout_ms, function, &params).await?;
+            keyvalue_outcome(keyvalue, results)
+        }
+        Scenario::Function(function) => {
+            let (namespace, package, interface, name) = parse_wit_meta_from_operation(function)
+                .context(
+                    "Invalid function supplied. Must be in the form of `namespace:package/interface.function`",
+                )?;
+            let name = name.context(
+                "Invalid function supplied. Must be in the form of `namespace:package/interface.function`",
+            )?;
+            let body = invoke_simple(
+                wrpc_client().await?,
+                lattice,
+                component_id()?,
+                &format!("{namespace}:{package}/{interface}"),
+                &name,
+                timeout_ms,
+            )
+            .await?;
+            Ok(Outcome {
+                body,
+                ..Default::default()
+            })
+        }
+    }
+}
+
+/// Invoke a function of `wrpc:keyvalue/store` served by `target`, returning its results
+async fn invoke_keyvalue(
+    nc: &async_nats::Client,
+    lattice: &str,
+    target: &str,
+    timeout_ms: u64,
+    function: &str,
+    params: &[Value],
+) -> Result<Value> {
+    let functions = WitFunctions::from_resolve(Resolve::default())?;
+    let (resolve, func) = functions
+        .get(KEYVALUE_STORE_INSTANCE, function)
+        .with_context(|| format!("unknown keyvalue function [{function}]"))?;
+    let mut payload = Vec::new();
+    for ((name, ty), value) in func.params.iter().zip(params) {
+        encode(resolve, ty, value, &mut payload)
+            .with_context(|| format!("failed to encode parameter [{name}]"))?;
+    }
+
+    let client = wrpc_transport_nats::Client::new(nc.clone(), format!("{lattice}.{target}"), None)
+        .await
+        .context("failed to create wRPC client")?;
+    let invocation = async {
+        let (mut outgoing, mut incoming) = client
+            .invoke(
+                Some(gen_wash_call_headers()),
+                KEYVALUE_STORE_INSTANCE,
+                function,
+                payload.into(),
+                &[] as &[&[Option<usize>]],
+            )
+            .await?;
+        let mut results = Vec::new();
+        tokio::try_join!(outgoing.shutdown(), incoming.read_to_end(&mut results))?;
+        anyhow::Ok(results)
+    };
+    let results = tokio::time::timeout(Duration::from_millis(timeout_ms), invocation)
+        .await
+        .with_context(|| format!("timed out invoking keyvalue store [{target}]"))?
+        .with_context(|| format!("failed to invoke keyvalue store [{target}]"))?;
+    functions
+        .decode_results(&format!("{KEYVALUE_STORE_INSTANCE}.{function}"), &results)
+        .context("keyvalue function is not indexed")?
+        .with_context(|| format!("failed to decode results of keyvalue store [{target}]"))
+}
+
+/// Convert the results of a keyvalue scenario into its outcome, with the value that was read as
+/// the body
+fn keyvalue_outcome(keyvalue: &KeyvalueScenario, results: Value) -> Result<Outcome> {
+    if let Some(err) = results.get("err") {
+        bail!("keyvalue store returned an error: {err}");
+    }
+    if keyvalue.set.is_some() {
+        return Ok(Outcome::default());
+    }
+    let value = match results.get("ok") {
+        Some(Value::Null) | None => bail!(
+            "key [{}] does not exist in bucket [{}]",
+            keyvalue.key,
+            keyvalue.bucket
+        ),
+        Some(value) => value,
+    };
+    let bytes = serde_json::from_value::<Vec<u8>>(value.clone())
+        .context("keyvalue store returned a value that is not an array of bytes")?;
+    // Values that are not valid UTF-8 are compared as their array of bytes
+    let body = String::from_utf8(bytes).unwrap_or_else(|_| value.to_string());
+    Ok(Outcome {
+        body,
+        ..Default::default()
+    })
+}
+
+/// Check the outcome of a scenario against what was expected, describing the first mismatch
+fn check_expectations(expect: &Expectations, outcome: &Outcome) -> Result<(), String> {
+    if let Some(status) = expect.status {
+        if outcome.status != Some(status) {
+            return Err(format!(
+                "expected status [{status}], got [{}]",
+                outcome
+                    .status
+                    .map_or_else(|| "none".to_string(), |s| s.to_string())
+            ));
+        }
+    }
+    for (name, value) in &expect.headers {
+        let actual = outcome
+            .headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v);
+        if actual != Some(value) {
+            return Err(format!(
+                "expected header [{name}] to be [{value}], got [{}]",
+                actual.map_or("none", String::as_str)
+            ));
+        }
+    }
+    if let Some(body) = &expect.body {
+        if outcome.body != *body {
+            return Err(format!("expected body [{body}], got [{}]", outcome.body));
+        }
+    }
+    if let Some(text) = &expect.body_contains {
+        if !outcome.body.contains(text.as_str()) {
+            return Err(format!(
+                "expected body to contain [{text}], got [{}]",
+                outcome.body
+            ));
+        }
+    }
+    if let Some(expected) = &expect.json {
+        let actual = serde_json::from_str::<serde_json::Value>(&outcome.body)
+            .map_err(|e| format!("expected body to be JSON: {e}"))?;
+        if !json_contains(&actual, expected) {
+            return Err(format!(
+                "expected body to contain JSON [{expected}], got [{actual}]"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `actual` contains `expected`, where objects may have fields that are not expected
+fn json_contains(actual: &serde_json::Value, expected: &serde_json::Value) -> bool {
+    match (actual, expected) {
+        (serde_json::Value::Object(actual), serde_json::Value::Object(expected)) => {
+            expected.iter().all(|(k, expected)| {
+                actual
+                    .get(k)
+                    .is_some_and(|actual| json_contains(actual, expected))
+            })
+        }
+        (serde_json::Value::Array(actual), serde_json::Value::Array(expected)) => {
+            actual.len() == expected.len()
+                && actual
+                    .iter()
+                    .zip(expected)
+                    .all(|(actual, expected)| json_contains(actual, expected))
+        }
+        (actual, expected) => actual == expected,
+    }
+}
+
+/// Render test results as a JUnit XML report
+fn junit_report(suite_name: &str, results: &[TestCaseResult]) -> String {
+    fn escape(s: &str) -> String {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .replace('\'', "&apos;")
+    }
+    let seconds = |ms: u64| format!("{:.3}", ms as f64 / 1000.0);
+
+    let failures = results.iter().filter(|r| !r.passed).count();
+    let time = seconds(results.iter().map(|r| r.duration_ms).sum());
+    let suite_name = escape(suite_name);
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites tests=\"{tests}\" failures=\"{failures}\" time=\"{time}\">\n  <testsuite name=\"{suite_name}\" tests=\"{tests}\" failures=\"{failures}\" time=\"{time}\">\n",
+        tests = results.len(),
+    );
+    for result in results {
+        let name = escape(&result.name);
+        let time = seconds(result.duration_ms);
+        match &result.failure {
+            None => xml.push_str(&format!(
+                "    <testcase name=\"{name}\" classname=\"{suite_name}\" time=\"{time}\"/>\n"
+            )),
+            Some(failure) => xml.push_str(&format!(
+                "    <testcase name=\"{name}\" classname=\"{suite_name}\" time=\"{time}\">\n      <failure message=\"{}\"/>\n    </testcase>\n",
+                escape(failure)
+            )),
+        }
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tests_file() -> Result<()> {
+        let tests_file: TestsFile = toml::from_str(
+            r#"
+manifest = "wadm.yaml"
+
+[[test]]
+name = "says hello"
+component = "http-hello-world"
+http = { method = "POST", path = "/greet?name=wash", body = "hi" }
+expect = { status = 200, body_contains = "Hello" }
+
+[[test]]
+name = "echoes messages"
+message = { subject = "wasmcloud.echo", body = "ping" }
+expect = { body = "ping" }
+timeout_ms = 500
+
+[[test]]
+name = "runs test component"
+component = "tests"
+function = "wasi:cli/run.run"
+"#,
+        )?;
+        assert_eq!(tests_file.manifest, Some(PathBuf::from("wadm.yaml")));
+        assert_eq!(tests_file.tests.len(), 3);
+        assert!(matches!(
+            &tests_file.tests[0].scenario,
+            Scenario::Http(HttpScenario { method: Some(method), path: Some(path), .. })
+                if method == "POST" && path == "/greet?name=wash"
+        ));
+        assert_eq!(tests_file.tests[0].expect.status, Some(200));
+        assert!(matches!(
+            &tests_file.tests[1].scenario,
+            Scenario::Message(MessageScenario { subject, .. }) if subject == "wasmcloud.echo"
+        ));
+        assert_eq!(tests_file.tests[1].timeout_ms, Some(500));
+        assert!(matches!(
+            &tests_file.tests[2].scenario,
+            Scenario::Function(f) if f == TEST_COMPONENT_RUN_FUNCTION
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_expectations() {
+        let outcome = Outcome {
+            status: Some(200),
+            headers: HashMap::from([("Content-Type".into(), "application/json".into())]),
+            body: r#"{"greeting":"hello","count":2}"#.into(),
+        };
+        let expect = Expectations {
+            status: Some(200),
+            headers: BTreeMap::from([("content-type".into(), "application/json".into())]),
+            body_contains: Some("hello".into()),
+            json: Some(json!({ "greeting": "hello" })),
+            ..Default::default()
+        };
+        assert_eq!(check_expectations(&expect, &outcome), Ok(()));
+
+        let expect = Expectations {
+            status: Some(404),
+            ..Default::default()
+        };
+        assert!(check_expectations(&expect, &outcome).is_err());
+
+        let expect = Expectations {
+            json: Some(json!({ "greeting": "goodbye" })),
+            ..Default::default()
+        };
+        assert!(check_expectations(&expect, &outcome).is_err());
+    }
+
+    #[test]
+    fn test_junit_report() {
+        let report = junit_report(
+            "tests",
+            &[
+                TestCaseResult {
+                    name: "passes".into(),
+                    passed: true,
+                    duration_ms: 1500,
+                    failure: None,
+                },
+                TestCaseResult {
+                    name: "fails".into(),
+                    passed: false,
+                    duration_ms: 20,
+                    failure: Some("expected body [<a>], got [b]".into()),
+                },
+            ],
+        );
+        assert!(report.contains(r#"<testsuite name="tests" tests="2" failures="1" time="1.520">"#));
+        assert!(report.contains(r#"<testcase name="passes" classname="tests" time="1.500"/>"#));
+        assert!(report.contains(r#"<failure message="expected body [&lt;a&gt;], got [b]"/>"#));
+    }
+
+    #[test]
+    fn test_parse_mocks_and_keyvalue() -> Result<()> {
+        let tests_file: TestsFile = toml::from_str(
+            r#"
+[[mock]]
+component = "http-keyvalue-counter"
+wasm = "build/http_keyvalue_counter_s.wasm"
+interface = "wasi:keyvalue/store"
+responses = { exists = { ok = true } }
+
+[[test]]
+name = "stores count"
+component = "http-keyvalue-counter"
+keyvalue = { bucket = "counters", key = "hits" }
+expect = { body = "1" }
+
+[[test]]
+name = "resets count"
+keyvalue = { target = "kv-redis", key = "hits", set = "0" }
+"#,
+        )?;
+        assert_eq!(tests_file.mocks.len(), 1);
+        assert_eq!(tests_file.mocks[0].interface, "wasi:keyvalue/store");
+        assert_eq!(
+            tests_file.mocks[0].responses["exists"],
+            json!({ "ok": true })
+        );
+        assert!(matches!(
+            &tests_file.tests[0].scenario,
+            Scenario::Keyvalue(KeyvalueScenario { target: None, bucket, key, set: None })
+                if bucket == "counters" && key == "hits"
+        ));
+        assert!(matches!(
+            &tests_file.tests[1].scenario,
+            Scenario::Keyvalue(KeyvalueScenario { target: Some(target), set: Some(set), .. })
+                if target == "kv-redis" && set == "0"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mocked_link_specs() -> Result<()> {
+        let mut resolve = Resolve::default();
+        resolve.push_str(
+            "wasi-keyvalue.wit",
+            r#"
+package wasi:keyvalue@0.2.0-draft;
+
+interface store {
+    get: func(bucket: string, key: string) -> option<list<u8>>;
+}
+"#,
+        )?;
+        let pkg = resolve.push_str(
+            "component.wit",
+            r#"
+package test:component;
+
+world component {
+    import wasi:keyvalue/store@0.2.0-draft;
+}
+"#,
+        )?;
+        let world = resolve.packages[pkg].worlds["component"];
+        let mock = MockedLink {
+            component: "counter".into(),
+            wasm: "counter.wasm".into(),
+            interface: "wasi:keyvalue/store".into(),
+            link_name: None,
+            responses: BTreeMap::new(),
+        };
+
+        // Keyvalue scenarios of the component use the mock by default
+        let specs = mocked_link_specs(&mock, &resolve, world)?;
+        let [spec] = specs.as_slice() else {
+            panic!("expected a single mock, got {}", specs.len());
+        };
+        assert_eq!(
+            spec.target_id(),
+            mock_target_id("counter", "wasi", "keyvalue", "default")
+        );
+        assert_eq!(spec.link().source_id(), "counter");
+        assert_eq!(spec.link().interfaces(), &vec!["store".to_string()]);
+
+        // Interfaces that the component doesn't import can't be mocked
+        let mock = MockedLink {
+            interface: "wasi:blobstore/blobstore".into(),
+            ..mock
+        };
+        assert!(mocked_link_specs(&mock, &resolve, world).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_keyvalue_outcome() {
+        let get = KeyvalueScenario {
+            target: None,
+            bucket: "counters".into(),
+            key: "hits".into(),
+            set: None,
+        };
+        assert_eq!(
+            keyvalue_outcome(&get, json!({ "ok": b"1" }))
+                .expect("value should be read")
+                .body,
+            "1"
+        );
+        assert_eq!(
+            keyvalue_outcome(&get, json!({ "ok": [255, 0] }))
+                .expect("value should be read")
+                .body,
+            "[255,0]"
+        );
+        assert!(keyvalue_outcome(&get, json!({ "ok": null })).is_err());
+        assert!(keyvalue_outcome(&get, json!({ "ok": "1" })).is_err());
+        assert!(keyvalue_outcome(&get, json!({ "err": "no-such-store" })).is_err());
+
+        let set = KeyvalueScenario {
+            set: Some("0".into()),
+            ..get
+        };
+        assert_eq!(
+            keyvalue_outcome(&set, json!({ "ok": null }))
+                .expect("value should be written")
+                .body,
+            ""
+        );
+    }
+}
@@ -8,7 +8,12 @@ use anyhow::{Context, Result};
 use term_table::{Table, TableStyle};
 use wash_lib::{
     config::{cfg_dir, DEFAULT_NATS_TIMEOUT_MS},
-    plugin::{subcommand::SubcommandRunner, PLUGIN_DIR},
+    parser::ProjectConfig,
+    plugin::{
+        hooks::{Event, HookRunner},
+        subcommand::SubcommandRunner,
+        PLUGIN_DIR,
+    },
 };
 
 const MAX_TERMINAL_WIDTH: usize = 120;
@@ -87,6 +92,27 @@ pub async fn load_plugins(plugin_dir: impl AsRef<Path>) -> anyhow::Result<Subcom
     Ok(plugins)
 }
 
+/// Helper for loading the hook plugins declared in the given project configuration
+///
+/// Unlike subcommand plugins, a hook that fails to load is an error, as skipping it could skip
+/// checks the project relies on
+pub async fn load_hooks(config: &ProjectConfig) -> anyhow::Result<HookRunner> {
+    let mut hooks = HookRunner::new().context("Could not initialize hook runner")?;
+    for hook in &config.hooks {
+        let events = hook
+            .events
+            .iter()
+            .map(|event| event.parse())
+            .collect::<anyhow::Result<Vec<Event>>>()
+            .with_context(|| format!("Invalid events for hook plugin {}", hook.plugin.display()))?;
+        hooks
+            .add_plugin(&hook.plugin, &events, hook.args.clone())
+            .await
+            .with_context(|| format!("Unable to load hook plugin {}", hook.plugin.display()))?;
+    }
+    Ok(hooks)
+}
+
 use once_cell::sync::OnceCell;
 static BIN_STR: OnceCell<char> = OnceCell::new();
 
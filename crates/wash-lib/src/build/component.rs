@@ -20,7 +20,10 @@ use crate::{
         claims::{sign_file, ComponentMetadata, GenerateCommon, SignCommand},
         OutputKind,
     },
-    parser::{CommonConfig, ComponentConfig, LanguageConfig, RustConfig, TinyGoConfig, WasmTarget},
+    parser::{
+        CommonConfig, ComponentConfig, JavaScriptConfig, LanguageConfig, PythonConfig, RustConfig,
+        TinyGoConfig, WasmTarget,
+    },
 };
 
 /// Builds a wasmCloud component using the installed language toolchain, then signs the component
@@ -83,16 +86,14 @@ pub async fn build_component(
                     }
                 }
             }
-            LanguageConfig::Go(_) | LanguageConfig::Other(_)
-                if component_config.build_command.is_some() =>
-            {
-                // SAFETY: We checked that the build command is not None above
-                build_custom_component(
-                    common_config,
-                    component_config,
-                    component_config.build_command.as_ref().unwrap(),
-                )
-                .await?
+            LanguageConfig::Python(python_config) => {
+                build_python_component(common_config, python_config, component_config).await?
+            }
+            LanguageConfig::JavaScript(js_config) => {
+                build_js_component(common_config, js_config, component_config, false).await?
+            }
+            LanguageConfig::TypeScript(js_config) => {
+                build_js_component(common_config, js_config, component_config, true).await?
             }
             LanguageConfig::Go(_) => {
                 bail!("build command is required for unsupported language go");
@@ -365,6 +366,153 @@ async fn build_tinygo_component(
     Ok(wasm_file_path)
 }
 
+/// Run a command to completion, failing if it could not be found or did not succeed
+async fn run_build_command(mut command: tokio::process::Command, description: &str) -> Result<()> {
+    let result = command.status().await.map_err(|e| {
+        if e.kind() == ErrorKind::NotFound {
+            anyhow!("{:?} command is not found", command.as_std().get_program())
+        } else {
+            anyhow!(e)
+        }
+    })?;
+    if !result.success() {
+        bail!("{description} failed: {}", result.to_string())
+    }
+    Ok(())
+}
+
+/// Builds a Python component with `componentize-py` and returns the path to the file.
+async fn build_python_component(
+    common_config: &CommonConfig,
+    python_config: &PythonConfig,
+    component_config: &ComponentConfig,
+) -> Result<PathBuf> {
+    let wasm_file_path = common_config
+        .build_dir
+        .join(format!("{}.wasm", common_config.wasm_bin_name()));
+    fs::create_dir_all(&common_config.build_dir)?;
+
+    // Change directory into the project directory
+    std::env::set_current_dir(&common_config.project_dir)?;
+
+    let mut command = match &python_config.componentize_py_path {
+        Some(path) => tokio::process::Command::new(path),
+        None => tokio::process::Command::new("componentize-py"),
+    };
+    command.arg("--wit-path").arg(&common_config.wit_dir);
+    if let Some(world) = &component_config.wit_world {
+        command.args(["--world", world]);
+    }
+    command
+        .args(["componentize", python_config.entry_module()])
+        .arg("--python-path")
+        .arg(&common_config.project_dir);
+    for path in &python_config.python_path {
+        command
+            .arg("--python-path")
+            .arg(common_config.project_dir.join(path));
+    }
+    command.arg("--output").arg(&wasm_file_path);
+    run_build_command(command, "Compiling component").await?;
+
+    if !wasm_file_path.exists() {
+        bail!(
+            "Could not find compiled wasm file to sign: {}",
+            wasm_file_path.display()
+        );
+    }
+    Ok(wasm_file_path)
+}
+
+/// Builds a JavaScript or TypeScript component with `jco componentize` and returns the path to
+/// the file.
+///
+/// Dependencies are installed with npm if they haven't been yet, and the configured build script
+/// (or `tsc`, for TypeScript) is run to produce the entry module before it is componentized.
+async fn build_js_component(
+    common_config: &CommonConfig,
+    js_config: &JavaScriptConfig,
+    component_config: &ComponentConfig,
+    typescript: bool,
+) -> Result<PathBuf> {
+    let project_dir = &common_config.project_dir;
+    let wasm_file_path = common_config
+        .build_dir
+        .join(format!("{}.wasm", common_config.wasm_bin_name()));
+    fs::create_dir_all(&common_config.build_dir)?;
+
+    // Change directory into the project directory
+    std::env::set_current_dir(project_dir)?;
+
+    let npm = || match &js_config.npm_path {
+        Some(path) => tokio::process::Command::new(path),
+        None => tokio::process::Command::new("npm"),
+    };
+    // Prefer binaries installed as dependencies of the project over global ones
+    let node_bin = |name: &str| {
+        let local = project_dir.join("node_modules").join(".bin").join(name);
+        if local.exists() {
+            tokio::process::Command::new(local)
+        } else {
+            tokio::process::Command::new(name)
+        }
+    };
+
+    if !js_config.skip_install
+        && project_dir.join("package.json").exists()
+        && !project_dir.join("node_modules").exists()
+    {
+        let mut command = npm();
+        command.arg("install");
+        run_build_command(command, "Installing npm dependencies").await?;
+    }
+
+    match &js_config.build_script {
+        Some(script) => {
+            let mut command = npm();
+            command.args(["run", script]);
+            run_build_command(command, &format!("Running npm script [{script}]")).await?;
+        }
+        None if typescript => {
+            run_build_command(node_bin("tsc"), "Compiling TypeScript").await?;
+        }
+        None => {}
+    }
+
+    let entry_module = js_config.entry_module.clone().unwrap_or_else(|| {
+        if typescript {
+            PathBuf::from("dist/index.js")
+        } else {
+            PathBuf::from("index.js")
+        }
+    });
+    let mut command = match &js_config.jco_path {
+        Some(path) => tokio::process::Command::new(path),
+        None => node_bin("jco"),
+    };
+    command
+        .arg("componentize")
+        .arg(project_dir.join(entry_module))
+        .arg("--wit")
+        .arg(&common_config.wit_dir);
+    if let Some(world) = &component_config.wit_world {
+        command.args(["--world-name", world]);
+    }
+    for feature in &js_config.disable_features {
+        command.args(["--disable", feature]);
+    }
+    command.arg("--out").arg(&wasm_file_path);
+    run_build_command(command, "Compiling component").await?;
+
+    if !wasm_file_path.exists() {
+        bail!(
+            "Could not find compiled wasm file to sign: {}",
+            wasm_file_path.display()
+        );
+    }
+    Ok(wasm_file_path)
+}
+
 /// Builds a wasmCloud component using a custom override command, then returns the path to the file.
 async fn build_custom_component(
     common_config: &CommonConfig,
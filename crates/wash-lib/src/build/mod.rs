@@ -16,7 +16,9 @@ use crate::{
 mod component;
 pub use component::*;
 mod provider;
-use provider::build_provider;
+use provider::{build_provider, sign_provider};
+pub mod provenance;
+pub mod workspace;
 
 /// This tag indicates that a Wasm module uses experimental features of wasmCloud
 /// and/or the surrounding ecosystem.
@@ -166,6 +168,50 @@ pub async fn build_project(
     }
 }
 
+/// Sign an artifact built from a project with [`build_project`] without signing, returning the
+/// path to the signed artifact: a signed Wasm component or a signed provider archive.
+pub async fn sign_artifact(
+    config: &ProjectConfig,
+    artifact: impl AsRef<Path>,
+    signing: &SignConfig,
+) -> Result<PathBuf> {
+    match &config.project_type {
+        TypeConfig::Component(component_config) => {
+            sign_component_wasm(&config.common, component_config, signing, artifact)
+        }
+        TypeConfig::Provider(provider_config) => {
+            sign_provider(provider_config, &config.common, artifact.as_ref(), signing).await
+        }
+    }
+}
+
+/// Build a project like [`build_project`], running the pre-build and post-build hooks of `hooks`
+/// around the build.
+///
+/// Post-build hooks are run on the artifact before it is signed, so that the signature covers any
+/// change the hooks make to the artifact.
+#[cfg(feature = "plugin")]
+pub async fn build_project_with_hooks(
+    config: &ProjectConfig,
+    signing: Option<&SignConfig>,
+    package_args: &CommonPackageArgs,
+    skip_fetch: bool,
+    hooks: &mut crate::plugin::hooks::HookRunner,
+) -> Result<PathBuf> {
+    use crate::plugin::hooks::Event;
+
+    let project_dir = &config.common.project_dir;
+    hooks.run(Event::PreBuild, project_dir, None, None).await?;
+    let artifact = build_project(config, None, package_args, skip_fetch).await?;
+    hooks
+        .run(Event::PostBuild, project_dir, Some(&artifact), None)
+        .await?;
+    match signing {
+        Some(signing) => sign_artifact(config, &artifact, signing).await,
+        None => Ok(artifact),
+    }
+}
+
 /// Build a [`wit_parser::Resolve`] from a provided directory
 /// and select a given world
 fn convert_wit_dir_to_world(
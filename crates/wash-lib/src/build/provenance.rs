@@ -0,0 +1,536 @@
+//! Generate and verify signed provenance attestations for built artifacts.
+//!
+//! Provenance is recorded as an [in-toto statement](https://github.com/in-toto/attestation/blob/main/spec/v1/statement.md)
+//! with a [SLSA provenance](https://slsa.dev/spec/v1.0/provenance) predicate, wrapped in a
+//! [DSSE envelope](https://github.com/secure-systems-lab/dsse/blob/master/envelope.md) signed with
+//! the account key that signed the artifact's claims.
+//!
+//! Statements don't contain timestamps (unless `SOURCE_DATE_EPOCH` is set) or absolute paths. The
+//! subject of a statement is the signed artifact though, and the claims embedded in it record when
+//! it was signed, so every build yields a different attestation.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use nkeys::{KeyPair, KeyPairType};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::process::Command;
+
+use crate::build::SignConfig;
+use crate::cli::{extract_keypair, OutputKind};
+use crate::parser::{LanguageConfig, ProjectConfig, TypeConfig};
+
+/// The suffix appended to the file name of an artifact to form the name of its provenance file
+pub const PROVENANCE_FILE_SUFFIX: &str = ".intoto.json";
+/// The DSSE payload type of in-toto statements
+pub const IN_TOTO_PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
+const IN_TOTO_STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+const SLSA_PROVENANCE_PREDICATE_TYPE: &str = "https://slsa.dev/provenance/v1";
+const WASH_BUILD_TYPE: &str = "https://wasmcloud.com/wash/build/v1";
+const WASH_BUILDER_ID: &str = "https://wasmcloud.com/wash";
+
+/// Digests of a resource, keyed by algorithm
+pub type DigestSet = BTreeMap<String, String>;
+
+/// A DSSE envelope containing a signed in-toto statement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvenanceEnvelope {
+    /// The type of the payload, always [`IN_TOTO_PAYLOAD_TYPE`]
+    pub payload_type: String,
+    /// The base64-encoded statement
+    pub payload: String,
+    /// Signatures over the payload
+    pub signatures: Vec<EnvelopeSignature>,
+}
+
+/// A signature of a [`ProvenanceEnvelope`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvelopeSignature {
+    /// The public key of the account that signed the envelope
+    pub keyid: String,
+    /// The base64-encoded ed25519 signature
+    pub sig: String,
+}
+
+/// An in-toto statement about one or more artifacts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Statement {
+    #[serde(rename = "_type")]
+    pub statement_type: String,
+    pub subject: Vec<ResourceDescriptor>,
+    pub predicate_type: String,
+    pub predicate: Provenance,
+}
+
+/// A reference to an artifact or source by name and digest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceDescriptor {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    pub digest: DigestSet,
+}
+
+/// A SLSA provenance predicate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Provenance {
+    pub build_definition: BuildDefinition,
+    pub run_details: RunDetails,
+}
+
+/// How the artifact was built
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildDefinition {
+    pub build_type: String,
+    /// Parameters of the project, such as its name and version
+    pub external_parameters: BTreeMap<String, serde_json::Value>,
+    /// Versions of the toolchain used to build the project, keyed by tool
+    pub internal_parameters: BTreeMap<String, String>,
+    /// The source revision and `wasmcloud.toml` the artifact was built from
+    pub resolved_dependencies: Vec<ResourceDescriptor>,
+}
+
+/// What built the artifact
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunDetails {
+    pub builder: Builder,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BuildMetadata>,
+}
+
+/// The builder of an artifact
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Builder {
+    pub id: String,
+    pub version: BTreeMap<String, String>,
+}
+
+/// Metadata of a build, only recorded when it can be reproduced
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildMetadata {
+    /// When the build finished, taken from `SOURCE_DATE_EPOCH`
+    pub finished_on: String,
+}
+
+impl ProvenanceEnvelope {
+    /// Sign `statement` with `key`, which must be an account key
+    pub fn sign(statement: &Statement, key: &KeyPair) -> Result<Self> {
+        let payload =
+            serde_json::to_vec(statement).context("failed to serialize provenance statement")?;
+        let sig = key
+            .sign(&pre_authentication_encoding(IN_TOTO_PAYLOAD_TYPE, &payload))
+            .context("failed to sign provenance statement")?;
+        Ok(Self {
+            payload_type: IN_TOTO_PAYLOAD_TYPE.to_string(),
+            payload: STANDARD.encode(payload),
+            signatures: vec![EnvelopeSignature {
+                keyid: key.public_key(),
+                sig: STANDARD.encode(sig),
+            }],
+        })
+    }
+
+    /// Verify that the envelope was signed by `public_key`, returning the statement it contains
+    pub fn verify(&self, public_key: &str) -> Result<Statement> {
+        if self.payload_type != IN_TOTO_PAYLOAD_TYPE {
+            bail!(
+                "unsupported provenance payload type [{}]",
+                self.payload_type
+            );
+        }
+        let payload = STANDARD
+            .decode(&self.payload)
+            .context("failed to decode provenance payload")?;
+        let key = KeyPair::from_public_key(public_key).context("invalid public key")?;
+        let signature = self
+            .signatures
+            .iter()
+            .find(|sig| sig.keyid == public_key)
+            .with_context(|| format!("provenance is not signed by [{public_key}]"))?;
+        let sig = STANDARD
+            .decode(&signature.sig)
+            .context("failed to decode provenance signature")?;
+        key.verify(
+            &pre_authentication_encoding(&self.payload_type, &payload),
+            &sig,
+        )
+        .context("invalid provenance signature")?;
+        serde_json::from_slice(&payload).context("failed to parse provenance statement")
+    }
+}
+
+/// The DSSE pre-authentication encoding of a payload, which is what is actually signed
+fn pre_authentication_encoding(payload_type: &str, payload: &[u8]) -> Vec<u8> {
+    let mut pae = format!(
+        "DSSEv1 {} {payload_type} {} ",
+        payload_type.len(),
+        payload.len()
+    )
+    .into_bytes();
+    pae.extend_from_slice(payload);
+    pae
+}
+
+/// The path of the provenance file of the artifact at `artifact_path`
+pub fn provenance_path(artifact_path: impl AsRef<Path>) -> PathBuf {
+    let mut path = artifact_path.as_ref().as_os_str().to_owned();
+    path.push(PROVENANCE_FILE_SUFFIX);
+    PathBuf::from(path)
+}
+
+/// Generate a signed provenance attestation for the artifact at `artifact_path`, built from the
+/// project described by `config`, and write it next to the artifact. The attestation is signed
+/// with the same account (issuer) key used to sign the artifact.
+///
+/// Returns the path to the written provenance file
+pub async fn generate_provenance(
+    config: &ProjectConfig,
+    artifact_path: impl AsRef<Path>,
+    signing_config: &SignConfig,
+) -> Result<PathBuf> {
+    let artifact_path = artifact_path.as_ref();
+    let statement = provenance_statement(config, artifact_path).await?;
+    let issuer = extract_keypair(
+        signing_config.issuer.as_deref(),
+        Some(&artifact_path.to_string_lossy()),
+        signing_config.keys_directory.clone(),
+        KeyPairType::Account,
+        signing_config.disable_keygen,
+        OutputKind::Json,
+    )?;
+    let envelope = ProvenanceEnvelope::sign(&statement, &issuer)?;
+    let path = provenance_path(artifact_path);
+    let envelope =
+        serde_json::to_vec_pretty(&envelope).context("failed to serialize provenance")?;
+    tokio::fs::write(&path, envelope)
+        .await
+        .with_context(|| format!("failed to write provenance to [{}]", path.display()))?;
+    Ok(path)
+}
+
+/// Describe how the artifact at `artifact_path` was built from the project described by `config`
+pub async fn provenance_statement(
+    config: &ProjectConfig,
+    artifact_path: impl AsRef<Path>,
+) -> Result<Statement> {
+    let artifact_path = artifact_path.as_ref();
+    let artifact = tokio::fs::read(artifact_path)
+        .await
+        .with_context(|| format!("failed to read artifact [{}]", artifact_path.display()))?;
+    let subject = ResourceDescriptor {
+        name: artifact_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string()),
+        uri: None,
+        digest: sha256_digest_set(&artifact),
+    };
+
+    let mut resolved_dependencies = Vec::new();
+    if let Some(source) = git_source(&config.common.project_dir).await {
+        resolved_dependencies.push(source);
+    }
+    let wasmcloud_toml = config.wasmcloud_toml_dir.join("wasmcloud.toml");
+    if let Ok(contents) = tokio::fs::read(&wasmcloud_toml).await {
+        resolved_dependencies.push(ResourceDescriptor {
+            name: Some("wasmcloud.toml".to_string()),
+            uri: None,
+            digest: sha256_digest_set(&contents),
+        });
+    }
+
+    let project_type = match config.project_type {
+        TypeConfig::Component(_) => "component",
+        TypeConfig::Provider(_) => "provider",
+    };
+    let language = match &config.language {
+        LanguageConfig::Rust(_) => "rust",
+        LanguageConfig::TinyGo(_) => "tinygo",
+        LanguageConfig::Go(_) => "go",
+        LanguageConfig::Python(_) => "python",
+        LanguageConfig::JavaScript(_) => "javascript",
+        LanguageConfig::TypeScript(_) => "typescript",
+        LanguageConfig::Other(other) => other,
+    };
+    let external_parameters = BTreeMap::from([
+        ("name".to_string(), config.common.name.clone().into()),
+        (
+            "version".to_string(),
+            config.common.version.to_string().into(),
+        ),
+        ("revision".to_string(), config.common.revision.into()),
+        ("type".to_string(), project_type.into()),
+        ("language".to_string(), language.into()),
+    ]);
+
+    let metadata = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .map(|epoch| {
+            let epoch = epoch
+                .parse()
+                .context("SOURCE_DATE_EPOCH must be a number of seconds")?;
+            let finished_on = chrono::DateTime::from_timestamp(epoch, 0)
+                .context("SOURCE_DATE_EPOCH is out of range")?;
+            anyhow::Ok(BuildMetadata {
+                finished_on: finished_on.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            })
+        })
+        .transpose()?;
+
+    Ok(Statement {
+        statement_type: IN_TOTO_STATEMENT_TYPE.to_string(),
+        subject: vec![subject],
+        predicate_type: SLSA_PROVENANCE_PREDICATE_TYPE.to_string(),
+        predicate: Provenance {
+            build_definition: BuildDefinition {
+                build_type: WASH_BUILD_TYPE.to_string(),
+                external_parameters,
+                internal_parameters: toolchain_versions(&config.language).await,
+                resolved_dependencies,
+            },
+            run_details: RunDetails {
+                builder: Builder {
+                    id: WASH_BUILDER_ID.to_string(),
+                    version: BTreeMap::from([(
+                        "wash-lib".to_string(),
+                        env!("CARGO_PKG_VERSION").to_string(),
+                    )]),
+                },
+                metadata,
+            },
+        },
+    })
+}
+
+fn sha256_digest_set(bytes: &[u8]) -> DigestSet {
+    BTreeMap::from([("sha256".to_string(), format!("{:x}", Sha256::digest(bytes)))])
+}
+
+/// Run `program` with `args` and return the first line of its output, if it succeeded
+async fn command_output(program: impl AsRef<std::ffi::OsStr>, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout)
+        .ok()?
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+}
+
+/// The versions of the tools used to build a project in `language`
+async fn toolchain_versions(language: &LanguageConfig) -> BTreeMap<String, String> {
+    let tools: Vec<(&str, PathBuf, &[&str])> = match language {
+        LanguageConfig::Rust(rust_config) => vec![
+            (
+                "cargo",
+                rust_config
+                    .cargo_path
+                    .clone()
+                    .unwrap_or_else(|| "cargo".into()),
+                &["--version"],
+            ),
+            ("rustc", "rustc".into(), &["--version"]),
+        ],
+        LanguageConfig::TinyGo(tinygo_config) => vec![
+            (
+                "tinygo",
+                tinygo_config
+                    .tinygo_path
+                    .clone()
+                    .unwrap_or_else(|| "tinygo".into()),
+                &["version"],
+            ),
+            ("go", "go".into(), &["version"]),
+        ],
+        LanguageConfig::Go(go_config) => vec![(
+            "go",
+            go_config.go_path.clone().unwrap_or_else(|| "go".into()),
+            &["version"],
+        )],
+        LanguageConfig::Python(python_config) => vec![
+            (
+                "componentize-py",
+                python_config
+                    .componentize_py_path
+                    .clone()
+                    .unwrap_or_else(|| "componentize-py".into()),
+                &["--version"],
+            ),
+            ("python", "python3".into(), &["--version"]),
+        ],
+        LanguageConfig::JavaScript(js_config) | LanguageConfig::TypeScript(js_config) => vec![
+            (
+                "jco",
+                js_config.jco_path.clone().unwrap_or_else(|| "jco".into()),
+                &["--version"],
+            ),
+            ("node", "node".into(), &["--version"]),
+        ],
+        LanguageConfig::Other(_) => vec![],
+    };
+    let mut versions = BTreeMap::new();
+    for (name, program, args) in tools {
+        if let Some(version) = command_output(program, args).await {
+            versions.insert(name.to_string(), version);
+        }
+    }
+    versions
+}
+
+/// The git revision `project_dir` is checked out at, if it is in a git repository
+async fn git_source(project_dir: &Path) -> Option<ResourceDescriptor> {
+    let dir = project_dir.to_string_lossy();
+    let revision = command_output("git", &["-C", &dir, "rev-parse", "HEAD"]).await?;
+    let dirty = command_output("git", &["-C", &dir, "status", "--porcelain"])
+        .await
+        .is_some_and(|status| !status.is_empty());
+    let remote = command_output("git", &["-C", &dir, "config", "--get", "remote.origin.url"]).await;
+    Some(ResourceDescriptor {
+        name: None,
+        uri: remote.map(|remote| git_source_uri(&remote, &revision, dirty)),
+        digest: BTreeMap::from([("gitCommit".to_string(), revision)]),
+    })
+}
+
+/// The URI of `revision` in the git repository at `remote`. Revisions with uncommitted changes are
+/// marked as dirty, since they cannot be reproduced from the revision alone. Repositories without a
+/// remote have no URI the source can be retrieved from, so only their revision is recorded
+fn git_source_uri(remote: &str, revision: &str, dirty: bool) -> String {
+    let mut uri = format!("git+{remote}@{revision}");
+    if dirty {
+        uri.push_str("+dirty");
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statement() -> Statement {
+        Statement {
+            statement_type: IN_TOTO_STATEMENT_TYPE.to_string(),
+            subject: vec![ResourceDescriptor {
+                name: Some("hello_s.wasm".to_string()),
+                uri: None,
+                digest: sha256_digest_set(b"hello"),
+            }],
+            predicate_type: SLSA_PROVENANCE_PREDICATE_TYPE.to_string(),
+            predicate: Provenance {
+                build_definition: BuildDefinition {
+                    build_type: WASH_BUILD_TYPE.to_string(),
+                    external_parameters: BTreeMap::from([("name".to_string(), "hello".into())]),
+                    internal_parameters: BTreeMap::from([(
+                        "cargo".to_string(),
+                        "cargo 1.83.0".to_string(),
+                    )]),
+                    resolved_dependencies: vec![],
+                },
+                run_details: RunDetails {
+                    builder: Builder {
+                        id: WASH_BUILDER_ID.to_string(),
+                        version: BTreeMap::new(),
+                    },
+                    metadata: None,
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn sign_and_verify_provenance() {
+        let account = KeyPair::new_account();
+        let statement = statement();
+        let envelope = ProvenanceEnvelope::sign(&statement, &account).expect("failed to sign");
+        // Signing is deterministic, so the same statement always yields the same envelope
+        assert_eq!(
+            envelope,
+            ProvenanceEnvelope::sign(&statement, &account).expect("failed to sign")
+        );
+        assert_eq!(
+            envelope
+                .verify(&account.public_key())
+                .expect("failed to verify"),
+            statement
+        );
+        assert!(envelope
+            .verify(&KeyPair::new_account().public_key())
+            .is_err());
+
+        let mut tampered = statement.clone();
+        tampered.subject[0].digest = sha256_digest_set(b"goodbye");
+        let mut tampered_envelope = envelope.clone();
+        tampered_envelope.payload =
+            STANDARD.encode(serde_json::to_vec(&tampered).expect("failed to serialize"));
+        assert!(tampered_envelope.verify(&account.public_key()).is_err());
+    }
+
+    #[test]
+    fn provenance_path_appends_suffix() {
+        assert_eq!(
+            provenance_path("build/hello_s.wasm"),
+            PathBuf::from("build/hello_s.wasm.intoto.json")
+        );
+    }
+
+    #[test]
+    fn git_source_uri_marks_dirty_revisions() {
+        assert_eq!(
+            git_source_uri(
+                "https://github.com/wasmCloud/wasmCloud.git",
+                "abc123",
+                false
+            ),
+            "git+https://github.com/wasmCloud/wasmCloud.git@abc123"
+        );
+        assert_eq!(
+            git_source_uri("https://github.com/wasmCloud/wasmCloud.git", "abc123", true),
+            "git+https://github.com/wasmCloud/wasmCloud.git@abc123+dirty"
+        );
+    }
+
+    #[tokio::test]
+    async fn git_source_without_remote_has_no_uri() {
+        let repo = tempfile::tempdir().unwrap();
+        let dir = repo.path().to_string_lossy();
+        for args in [
+            &["init", "--quiet"][..],
+            &[
+                "-c",
+                "user.name=wash",
+                "-c",
+                "user.email=wash@example.com",
+                "commit",
+                "--quiet",
+                "--allow-empty",
+                "--message",
+                "initial",
+            ],
+        ] {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(dir.as_ref())
+                .args(args)
+                .status()
+                .await
+                .unwrap();
+            assert!(status.success());
+        }
+
+        let source = git_source(repo.path()).await.unwrap();
+        assert_eq!(source.uri, None);
+        assert!(source.digest.contains_key("gitCommit"));
+    }
+}
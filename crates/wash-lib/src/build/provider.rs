@@ -1,10 +1,10 @@
 use std::io::ErrorKind;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 
 use anyhow::{anyhow, bail, Context, Result};
 use nkeys::KeyPairType;
-use tracing::{trace, warn};
+use tracing::{debug, trace, warn};
 
 use crate::build::SignConfig;
 use crate::cli::par::{create_provider_archive, detect_arch, ParCreateArgs};
@@ -18,6 +18,43 @@ pub(crate) async fn build_provider(
     language_config: &LanguageConfig,
     common_config: &CommonConfig,
     signing_config: Option<&SignConfig>,
+) -> Result<PathBuf> {
+    let provider_path_buf = match language_config {
+        LanguageConfig::Rust(rust_config) => {
+            build_rust_provider(provider_config, rust_config, common_config)?
+        }
+        LanguageConfig::Go(go_config) => {
+            build_go_provider(provider_config, go_config, common_config)?
+        }
+        _ => bail!("Unsupported language for provider: {:?}", language_config),
+    };
+
+    trace!("Retrieving provider binary from {:?}", provider_path_buf);
+    let provider_path_buf = provider_path_buf
+        .canonicalize()
+        .context("failed to resolve file path")?;
+
+    // If no signing config supplied, just return the path to the provider
+    let Some(sign_config) = signing_config else {
+        debug!("No signing configuration supplied, only building provider");
+        return Ok(provider_path_buf);
+    };
+    sign_provider(
+        provider_config,
+        common_config,
+        &provider_path_buf,
+        sign_config,
+    )
+    .await
+}
+
+/// Package the provider binary at `provider_path` in a provider archive signed with the keys of
+/// `sign_config`, returning the path to the provider archive
+pub(crate) async fn sign_provider(
+    provider_config: &ProviderConfig,
+    common_config: &CommonConfig,
+    provider_path: &Path,
+    sign_config: &SignConfig,
 ) -> Result<PathBuf> {
     // Attempt to get the WIT for this provider
     let wit_interface_bytes = if common_config.wit_dir.exists() {
@@ -39,26 +76,9 @@ pub(crate) async fn build_provider(
         None
     };
 
-    let (provider_path_buf, bin_name) = match language_config {
-        LanguageConfig::Rust(rust_config) => {
-            build_rust_provider(provider_config, rust_config, common_config)?
-        }
-        LanguageConfig::Go(go_config) => {
-            build_go_provider(provider_config, go_config, common_config)?
-        }
-        _ => bail!("Unsupported language for provider: {:?}", language_config),
-    };
-
-    trace!("Retrieving provider binary from {:?}", provider_path_buf);
-    let provider_path_buf = provider_path_buf
-        .canonicalize()
-        .context("failed to resolve file path")?;
-    let provider_bytes = tokio::fs::read(&provider_path_buf).await.with_context(|| {
-        format!(
-            "missing provider binary at [{}]",
-            provider_path_buf.display()
-        )
-    })?;
+    let provider_bytes = tokio::fs::read(provider_path)
+        .await
+        .with_context(|| format!("missing provider binary at [{}]", provider_path.display()))?;
 
     let mut par = create_provider_archive(
         ParCreateArgs {
@@ -74,12 +94,11 @@ pub(crate) async fn build_provider(
     )
     .context("failed to create initial provider archive with built provider")?;
 
-    // If no signing config supplied, just return the path to the provider
-    let Some(sign_config) = signing_config else {
-        warn!("No signing configuration supplied, could only build provider");
-        return Ok(provider_path_buf);
-    };
-
+    // The provider archive is named after the provider binary
+    let bin_name = provider_path
+        .file_name()
+        .context("provider binary path has no file name")?
+        .to_string_lossy();
     let destination = common_config.build_dir.join(format!("{bin_name}.par.gz"));
     if let Some(parent) = destination.parent() {
         tokio::fs::create_dir_all(parent)
@@ -88,7 +107,7 @@ pub(crate) async fn build_provider(
     }
     let issuer = extract_keypair(
         sign_config.issuer.as_deref(),
-        Some(&provider_path_buf.to_string_lossy()),
+        Some(&provider_path.to_string_lossy()),
         sign_config.keys_directory.clone(),
         KeyPairType::Account,
         sign_config.disable_keygen,
@@ -96,7 +115,7 @@ pub(crate) async fn build_provider(
     )?;
     let subject = extract_keypair(
         sign_config.subject.as_deref(),
-        Some(&provider_path_buf.to_string_lossy()),
+        Some(&provider_path.to_string_lossy()),
         sign_config.keys_directory.clone(),
         KeyPairType::Service,
         sign_config.disable_keygen,
@@ -115,12 +134,12 @@ pub(crate) async fn build_provider(
 
 /// Build a Rust provider for the current machine's architecture
 ///
-/// Returns a tuple of the path to the built provider binary and the binary name
+/// Returns the path to the built provider binary, which is named after the binary name
 fn build_rust_provider(
     provider_config: &ProviderConfig,
     rust_config: &RustConfig,
     common_config: &CommonConfig,
-) -> Result<(PathBuf, String)> {
+) -> Result<PathBuf> {
     let mut command = match rust_config.cargo_path.as_ref() {
         Some(path) => process::Command::new(path),
         None => process::Command::new("cargo"),
@@ -186,17 +205,17 @@ fn build_rust_provider(
     }
     provider_path_buf.push(&bin_name);
 
-    Ok((provider_path_buf, bin_name))
+    Ok(provider_path_buf)
 }
 
 /// Build a Go provider for the current machine's architecture
 ///
-/// Returns a tuple of the path to the built provider binary and the binary name
+/// Returns the path to the built provider binary, which is named after the binary name
 fn build_go_provider(
     provider_config: &ProviderConfig,
     go_config: &GoConfig,
     common_config: &CommonConfig,
-) -> Result<(PathBuf, String)> {
+) -> Result<PathBuf> {
     let mut generate_command = match go_config.go_path.as_ref() {
         Some(path) => process::Command::new(path),
         None => process::Command::new("go"),
@@ -260,5 +279,5 @@ fn build_go_provider(
         bail!("Compiling provider failed: {result}")
     }
 
-    Ok((PathBuf::from(&bin_name), bin_name))
+    Ok(PathBuf::from(bin_name))
 }
@@ -0,0 +1,166 @@
+//! Ordering and linking of the members of a workspace, based on the interfaces in their WIT worlds
+
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result};
+use tracing::warn;
+use wit_parser::{Resolve, WorldId, WorldItem};
+
+use crate::parser::{ProjectConfig, WorkspaceConfig};
+
+/// An interface, identified by its namespace, package and name (e.g. `("wasi", "keyvalue", "store")`)
+pub type WitInterfaceKey = (String, String, String);
+
+/// The interfaces that are imported and exported by a WIT world
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorldInterfaces {
+    /// Interfaces that the world imports
+    pub imports: BTreeSet<WitInterfaceKey>,
+    /// Interfaces that the world exports
+    pub exports: BTreeSet<WitInterfaceKey>,
+}
+
+impl WorldInterfaces {
+    /// Collect the interfaces imported and exported by the given world
+    pub fn from_world(resolve: &Resolve, world_id: WorldId) -> Result<Self> {
+        let world = resolve
+            .worlds
+            .get(world_id)
+            .context("selected WIT world is missing")?;
+        let keys = |items: &mut dyn Iterator<Item = &WorldItem>| -> Result<BTreeSet<_>> {
+            let mut keys = BTreeSet::new();
+            for item in items {
+                let WorldItem::Interface { id, .. } = item else {
+                    continue;
+                };
+                let iface = resolve
+                    .interfaces
+                    .get(*id)
+                    .context("unexpectedly missing iface")?;
+                // Interfaces defined inline in the world can't be provided by another project
+                let (Some(pkg), Some(name)) = (iface.package, iface.name.as_ref()) else {
+                    continue;
+                };
+                let pkg = resolve
+                    .packages
+                    .get(pkg)
+                    .context("failed to find package")?;
+                keys.insert((
+                    pkg.name.namespace.clone(),
+                    pkg.name.name.clone(),
+                    name.clone(),
+                ));
+            }
+            Ok(keys)
+        };
+        Ok(Self {
+            imports: keys(&mut world.imports.values())?,
+            exports: keys(&mut world.exports.values())?,
+        })
+    }
+
+    /// Collect the interfaces imported and exported by the world of a project, as found in its
+    /// WIT directory
+    pub fn from_project(config: &ProjectConfig) -> Result<Self> {
+        let mut resolve = Resolve::default();
+        let (package_id, _paths) = resolve.push_dir(&config.common.wit_dir).with_context(|| {
+            format!(
+                "failed to add WIT directory @ [{}]",
+                config.common.wit_dir.display()
+            )
+        })?;
+        let world_id = resolve
+            .select_world(package_id, config.project_type.wit_world().as_deref())
+            .context("failed to select world from built resolver")?;
+        Self::from_world(&resolve, world_id)
+    }
+
+    /// Whether this world imports an interface that is exported by `other`
+    pub fn depends_on(&self, other: &Self) -> bool {
+        !self.imports.is_disjoint(&other.exports)
+    }
+}
+
+/// Order the members of a workspace so that members are built after the members they depend on,
+/// i.e. after the members that export interfaces they import.
+///
+/// Members that depend on each other are built in the order they are listed in, and members whose
+/// WIT could not be parsed are assumed to have no dependencies.
+pub fn build_order(workspace: &WorkspaceConfig) -> Vec<&ProjectConfig> {
+    let interfaces = workspace
+        .members
+        .iter()
+        .map(|member| match WorldInterfaces::from_project(member) {
+            Ok(interfaces) => Some(interfaces),
+            Err(e) => {
+                warn!(
+                    member = member.common.name,
+                    err = ?e,
+                    "failed to parse WIT of workspace member, assuming it has no dependencies"
+                );
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    dependency_order(&interfaces)
+        .into_iter()
+        .map(|idx| &workspace.members[idx])
+        .collect()
+}
+
+/// Order indices into `interfaces` so that every world comes after the worlds it depends on,
+/// breaking cycles in favor of the world listed first
+fn dependency_order(interfaces: &[Option<WorldInterfaces>]) -> Vec<usize> {
+    let depends_on = |a: usize, b: usize| match (&interfaces[a], &interfaces[b]) {
+        (Some(a_ifaces), Some(b_ifaces)) if a != b => a_ifaces.depends_on(b_ifaces),
+        _ => false,
+    };
+    let mut remaining = (0..interfaces.len()).collect::<Vec<_>>();
+    let mut order = Vec::with_capacity(interfaces.len());
+    while !remaining.is_empty() {
+        let next = remaining
+            .iter()
+            .position(|&a| !remaining.iter().any(|&b| depends_on(a, b)))
+            .unwrap_or(0);
+        order.push(remaining.remove(next));
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world(imports: &[&str], exports: &[&str]) -> Option<WorldInterfaces> {
+        let keys = |ifaces: &[&str]| {
+            ifaces
+                .iter()
+                .map(|i| ("test".to_string(), "pkg".to_string(), i.to_string()))
+                .collect()
+        };
+        Some(WorldInterfaces {
+            imports: keys(imports),
+            exports: keys(exports),
+        })
+    }
+
+    #[test]
+    fn test_dependency_order() {
+        // api depends on store, which depends on cache
+        let interfaces = [
+            world(&["store"], &["handler"]),
+            world(&["cache"], &["store"]),
+            world(&[], &["cache"]),
+        ];
+        assert_eq!(dependency_order(&interfaces), vec![2, 1, 0]);
+
+        // Unknown worlds have no dependencies, and cycles are broken in the listed order
+        let interfaces = [
+            world(&["b"], &["a"]),
+            None,
+            world(&["a"], &["b"]),
+            world(&[], &[]),
+        ];
+        assert_eq!(dependency_order(&interfaces), vec![1, 3, 0, 2]);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::io::Cursor;
 use std::path::{Path, PathBuf};
@@ -16,6 +17,10 @@ use wasmcloud_control_interface::HostInventory;
 
 pub const INVENTORY_FILE: &str = "inventory.json";
 pub const MESSAGES_DIR: &str = "messages";
+/// Suffix of the subjects that wRPC results are published on, following the reply subject of the invocation
+pub const RESULTS_SUBJECT_SUFFIX: &str = ".results";
+/// Subjects of wRPC results, i.e. `<inbox>.<invocation>.results`
+pub const RESULTS_SUBJECT: &str = "_INBOX.*.*.results";
 
 /// A subset of NATS message info that we need to serialize for now. Basically it is all the types that easily
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +51,23 @@ impl TryFrom<async_nats::jetstream::Message> for SerializableMessage {
     }
 }
 
+impl SerializableMessage {
+    /// Whether this message is a wRPC invocation, i.e. `<lattice>.<component>.wrpc.<version>.<operation>`
+    #[must_use]
+    pub fn is_invocation(&self) -> bool {
+        self.subject.split('.').nth(2) == Some("wrpc")
+    }
+}
+
+/// A wRPC invocation in a capture, along with the response that was captured for it
+#[derive(Debug, Clone)]
+pub struct CapturedInvocation<'a> {
+    pub message: &'a SerializableMessage,
+    /// The wRPC-encoded results of the invocation. This is `None` if no results were captured,
+    /// which is always the case for captures made before results were recorded.
+    pub response: Option<bytes::Bytes>,
+}
+
 /// A read capture is a parsed tarball that contains all of the messages and inventory for a given
 /// capture.
 ///
@@ -105,6 +127,33 @@ impl ReadCapture {
         }
         Ok(capture)
     }
+
+    /// Returns the wRPC invocations in the capture, in order, paired with their captured results
+    #[must_use]
+    pub fn invocations(&self) -> Vec<CapturedInvocation<'_>> {
+        // Results may be split across several messages, which are concatenated in order
+        let mut responses = HashMap::<&str, bytes::BytesMut>::new();
+        for msg in &self.messages {
+            if let Some(reply) = msg.subject.strip_suffix(RESULTS_SUBJECT_SUFFIX) {
+                responses
+                    .entry(reply)
+                    .or_default()
+                    .extend_from_slice(&msg.payload);
+            }
+        }
+        self.messages
+            .iter()
+            .filter(|msg| msg.is_invocation())
+            .map(|message| CapturedInvocation {
+                message,
+                response: message
+                    .reply
+                    .as_deref()
+                    .and_then(|reply| responses.get(reply))
+                    .map(|response| response.clone().freeze()),
+            })
+            .collect()
+    }
 }
 
 pub struct WriteCapture {
@@ -244,4 +293,50 @@ mod test {
             "Should have the right ordering"
         );
     }
+
+    fn message(subject: &str, reply: Option<&str>, payload: &'static str) -> SerializableMessage {
+        SerializableMessage {
+            subject: subject.to_string(),
+            reply: reply.map(ToString::to_string),
+            payload: bytes::Bytes::from(payload),
+            description: None,
+            length: payload.len(),
+            published: time::OffsetDateTime::now_utc(),
+            headers: None,
+        }
+    }
+
+    #[test]
+    fn test_invocations() {
+        let capture = ReadCapture {
+            inventory: Vec::new(),
+            messages: vec![
+                message(
+                    "default.echo.wrpc.0.0.1.wasi:http/incoming-handler@0.2.0.handle",
+                    Some("_INBOX.a.1"),
+                    "first",
+                ),
+                message(
+                    "default.echo.wrpc.0.0.1.wasi:http/incoming-handler@0.2.0.handle",
+                    Some("_INBOX.a.2"),
+                    "second",
+                ),
+                message("_INBOX.a.1.results", None, "hello "),
+                message("_INBOX.a.1.results", None, "world"),
+                message("_INBOX.a.1.results", None, ""),
+            ],
+        };
+        let invocations = capture.invocations();
+        assert_eq!(invocations.len(), 2, "Should only contain invocations");
+        assert_eq!(invocations[0].message.payload, "first");
+        assert_eq!(
+            invocations[0].response.as_deref(),
+            Some(b"hello world".as_slice()),
+            "Should concatenate results in order"
+        );
+        assert!(
+            invocations[1].response.is_none(),
+            "Should not have results that were not captured"
+        );
+    }
 }
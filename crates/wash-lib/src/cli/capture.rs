@@ -1,24 +1,35 @@
-use std::{path::PathBuf, time::Duration};
+use std::{collections::HashSet, path::PathBuf, time::Duration};
 
-use anyhow::Result;
+use anyhow::{bail, Context as _, Result};
 use async_nats::jetstream::{
     consumer::{pull::Config as ConsumerConfig, AckPolicy, DeliverPolicy},
     stream::Config,
 };
 use clap::{Parser, Subcommand};
 use futures::TryStreamExt;
+use serde::Serialize;
+use serde_json::json;
 use tokio::io::{stdin, stdout, AsyncReadExt, AsyncWriteExt};
 use tokio::time::Instant;
 use tracing::debug;
+use wrpc_transport::Invoke as _;
 
 use super::{CliConnectionOpts, CommandOutput};
 use crate::config::WashConnectionOptions;
 use crate::{
-    capture::{ReadCapture, WriteCapture},
-    spier::{ObservedInvocation, ObservedMessage},
+    capture::{
+        ReadCapture, SerializableMessage, WriteCapture, RESULTS_SUBJECT, RESULTS_SUBJECT_SUFFIX,
+    },
+    spier::{fetch_component_functions, ObservedInvocation, ObservedMessage, OperationFilter},
+    wrpc::WitFunctions,
 };
 
 pub const CAPTURE_STREAM_NAME: &str = "wash-capture";
+/// Name of the stream that captures the results of wRPC invocations, which is shared by all lattices
+///
+/// Results are published on the inbox of the invoker, which is not specific to a lattice, and
+/// streams may not capture overlapping subjects.
+pub const CAPTURE_RESULTS_STREAM_NAME: &str = "wash-capture-results";
 
 #[derive(Debug, Parser, Clone)]
 pub struct CaptureCommand {
@@ -60,6 +71,26 @@ pub struct CaptureReplayCommand {
     #[clap(name = "target_id", long = "target-id", value_parser)]
     pub target_id: Option<String>,
 
+    /// A component ID to re-send the captured invocations to, for example a new version of the
+    /// component that was captured. Responses are compared to the captured responses, and the
+    /// command fails if any of them differ.
+    #[clap(name = "replay_to", long = "replay-to", value_parser)]
+    pub replay_to: Option<String>,
+
+    /// Only replay invocations of functions in this interface, with or without a version, e.g.
+    /// `wasi:keyvalue/store`
+    #[clap(name = "interface", long = "interface")]
+    pub interface: Option<String>,
+
+    /// Only replay invocations of this function
+    #[clap(name = "function", long = "function")]
+    pub function: Option<String>,
+
+    /// Path to a WIT file or directory used to decode captured invocations. By default, the WIT
+    /// embedded in the captured components is used
+    #[clap(name = "wit", long = "wit")]
+    pub wit: Option<PathBuf>,
+
     /// Whether or not to step through the replay one message at a time
     #[clap(name = "interactive", long = "interactive")]
     pub interactive: bool,
@@ -67,27 +98,158 @@ pub struct CaptureReplayCommand {
     /// The file path to the capture file to read from
     #[clap(name = "capturefile")]
     pub capture_file_path: PathBuf,
+
+    #[clap(flatten)]
+    pub opts: CliConnectionOpts,
+}
+
+/// Outcome of re-sending a captured invocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ReplayOutcome {
+    /// The response matched the captured response
+    Matched,
+    /// The response differed from the captured response
+    Differed,
+    /// No response was captured to compare against
+    Uncompared,
+    /// The invocation failed
+    Failed,
+}
+
+/// A captured invocation that was re-sent to a component
+#[derive(Debug, Clone, Serialize)]
+struct ReplayedInvocation {
+    operation: String,
+    from: String,
+    to: String,
+    outcome: ReplayOutcome,
+}
+
+/// Summarize the outcomes of replaying invocations to `replay_to`, returning an error if any
+/// replayed invocation failed or its response differed from the captured response
+fn replay_summary(replay_to: &str, outcomes: &[ReplayedInvocation]) -> Result<CommandOutput> {
+    let count = |outcome: ReplayOutcome| outcomes.iter().filter(|o| o.outcome == outcome).count();
+    let (matched, differed, uncompared, failed) = (
+        count(ReplayOutcome::Matched),
+        count(ReplayOutcome::Differed),
+        count(ReplayOutcome::Uncompared),
+        count(ReplayOutcome::Failed),
+    );
+    let summary = format!(
+        "Replayed {} invocation(s) to [{replay_to}]: {matched} matched, {differed} differed, {uncompared} without a captured response, {failed} failed",
+        outcomes.len()
+    );
+    if differed + failed > 0 {
+        bail!("{summary}");
+    }
+    Ok(CommandOutput::new(
+        summary,
+        [
+            ("replayed".to_string(), json!(outcomes.len())),
+            ("matched".to_string(), json!(matched)),
+            ("uncompared".to_string(), json!(uncompared)),
+            ("invocations".to_string(), json!(outcomes)),
+        ]
+        .into(),
+    ))
+}
+
+/// Sends captured wRPC invocations to a component
+struct Replayer {
+    client: wrpc_transport_nats::Client,
+    timeout: Duration,
+}
+
+impl Replayer {
+    async fn new(opts: CliConnectionOpts, component_id: &str) -> Result<Self> {
+        let wco: WashConnectionOptions = opts.try_into()?;
+        let timeout = Duration::from_millis(wco.timeout_ms);
+        let lattice = wco.lattice.clone().unwrap_or_else(|| "default".to_string());
+        let nats_client = wco.into_nats_client().await?;
+        let client = wrpc_transport_nats::Client::new(
+            nats_client,
+            format!("{lattice}.{component_id}"),
+            None,
+        )
+        .await
+        .context("failed to create wRPC client")?;
+        Ok(Self { client, timeout })
+    }
+
+    /// Re-send a captured invocation of `operation` (`<instance>.<function>`), returning the
+    /// wRPC-encoded results
+    async fn replay(&self, operation: &str, msg: &SerializableMessage) -> Result<Vec<u8>> {
+        // The invocation subject joins the instance and function with a `.`, so it is the same
+        // wherever the operation is split
+        let (instance, func) = operation
+            .rsplit_once('.')
+            .with_context(|| format!("invalid operation [{operation}]"))?;
+        let invocation = async {
+            let (mut outgoing, mut incoming) = self
+                .client
+                .invoke(
+                    msg.headers.clone(),
+                    instance,
+                    func,
+                    msg.payload.clone(),
+                    &[] as &[&[Option<usize>]],
+                )
+                .await?;
+            let mut results = Vec::new();
+            tokio::try_join!(outgoing.shutdown(), incoming.read_to_end(&mut results))?;
+            anyhow::Ok(results)
+        };
+        tokio::time::timeout(self.timeout, invocation)
+            .await
+            .context("invocation timed out")?
+    }
 }
 
 pub async fn handle_replay_command(cmd: CaptureReplayCommand) -> Result<CommandOutput> {
-    let capture = ReadCapture::load(cmd.capture_file_path).await?;
+    let capture = ReadCapture::load(&cmd.capture_file_path).await?;
+    let replayer = match &cmd.replay_to {
+        Some(component_id) => Some(Replayer::new(cmd.opts.clone(), component_id).await?),
+        None => None,
+    };
+    let functions = match &cmd.wit {
+        Some(path) => WitFunctions::from_wit_path(path)?,
+        None => {
+            fetch_component_functions(
+                capture
+                    .inventory
+                    .iter()
+                    .flat_map(|inventory| inventory.components())
+                    .map(|component| component.image_ref()),
+            )
+            .await
+        }
+    };
+    let filter = OperationFilter {
+        interface: cmd.interface.clone(),
+        function: cmd.function.clone(),
+    };
 
-    let filtered = capture.messages.into_iter().filter_map(|msg| {
-        // lattice.component.wrpc.0.0.1.operation.function
-        let subject_parts = msg.subject.split('.').collect::<Vec<_>>();
-        let component_id = subject_parts.get(1);
-        let operation = subject_parts.get(6);
-        let function = subject_parts.get(7);
+    let filtered = capture.invocations().into_iter().filter_map(|invocation| {
+        // lattice.component.wrpc.0.0.1.instance.function
+        let msg = invocation.message;
+        let mut subject_parts = msg.subject.splitn(7, '.');
+        let component_id = subject_parts.nth(1);
+        let operation = subject_parts.nth(4);
 
-        if component_id.is_none() || operation.is_none() || function.is_none() {
+        if component_id.is_none() || operation.is_none() {
             debug!("Received invocation with invalid subject: {}", msg.subject);
             return None;
         }
         let target = component_id.unwrap();
-        let operation = format!("{}.{}", operation.unwrap(), function.unwrap());
+        let operation = operation.unwrap().to_string();
+        if !filter.matches(&functions, &operation) {
+            return None;
+        }
 
         let source = msg
             .headers
+            .as_ref()
             .and_then(|headers| {
                 headers
                     .get("source-id")
@@ -108,7 +270,7 @@ pub async fn handle_replay_command(cmd: CaptureReplayCommand) -> Result<CommandO
             Some(_) => {
                 return None;
             }
-            None => (*target).to_string(),
+            None => target.to_string(),
         };
 
         Some((
@@ -116,15 +278,21 @@ pub async fn handle_replay_command(cmd: CaptureReplayCommand) -> Result<CommandO
                 timestamp: chrono::Local::now(),
                 from,
                 to,
+                message: ObservedMessage::decode_params(
+                    &functions,
+                    &operation,
+                    msg.payload.to_vec(),
+                ),
                 operation,
-                message: ObservedMessage::parse(msg.payload.to_vec()),
+                response: false,
             },
-            msg.published,
+            invocation,
         ))
     });
 
     let mut out = stdout();
-    for (msg, published) in filtered {
+    let mut outcomes = Vec::new();
+    for (msg, invocation) in filtered {
         println!(
             r#"
 [{}]
@@ -132,15 +300,59 @@ From: {}  To: {}
 
 Operation: {}
 Message: {}"#,
-            published, msg.from, msg.to, msg.operation, msg.message
+            invocation.message.published, msg.from, msg.to, msg.operation, msg.message
         );
+        let decode_results =
+            |data: Vec<u8>| ObservedMessage::decode_results(&functions, &msg.operation, data);
+        if let Some(replayer) = &replayer {
+            let outcome = match (
+                replayer.replay(&msg.operation, invocation.message).await,
+                invocation.response,
+            ) {
+                (Err(e), _) => {
+                    println!("Replay failed: {e:#}");
+                    ReplayOutcome::Failed
+                }
+                (Ok(response), None) => {
+                    println!(
+                        "Replayed response (no captured response to compare): {}",
+                        decode_results(response)
+                    );
+                    ReplayOutcome::Uncompared
+                }
+                (Ok(response), Some(captured)) if response == captured => {
+                    println!("Replayed response matches the captured response");
+                    ReplayOutcome::Matched
+                }
+                (Ok(response), Some(captured)) => {
+                    println!(
+                        "Replayed response differs from the captured response\n- captured: {}\n+ replayed: {}",
+                        decode_results(captured.to_vec()),
+                        decode_results(response)
+                    );
+                    ReplayOutcome::Differed
+                }
+            };
+            outcomes.push(ReplayedInvocation {
+                operation: msg.operation,
+                from: msg.from,
+                to: msg.to,
+                outcome,
+            });
+        } else if let Some(response) = &invocation.response {
+            println!("Response: {}", decode_results(response.to_vec()));
+        }
         if cmd.interactive {
             out.write_all(b"Press Enter to continue...").await.unwrap();
             out.flush().await.unwrap();
             stdin().read_exact(&mut [0]).await.unwrap();
         }
     }
-    Ok(CommandOutput::default())
+
+    let Some(replay_to) = cmd.replay_to else {
+        return Ok(CommandOutput::default());
+    };
+    replay_summary(&replay_to, &outcomes)
 }
 
 /// Handles the spy command, printing all output to stdout until the command is interrupted
@@ -180,7 +392,7 @@ pub async fn enable(
     window_size: Duration,
 ) -> Result<CommandOutput> {
     // Until we get concrete errors, we should check for the stream and if it exists return a nice message that we're already enabled
-    if ctx.get_stream(CAPTURE_STREAM_NAME).await.is_ok() {
+    if ctx.get_stream(stream_name(lattice_id)).await.is_ok() {
         return Ok(CommandOutput::from_key_and_text(
             "message",
             format!("Capture is already enabled for lattice {lattice_id}"),
@@ -192,11 +404,23 @@ pub async fn enable(
         max_age: window_size,
         // This needs to be set or it breaks invocations
         no_ack: true,
-        subjects: vec![format!("wasmbus.rpc.{}.>", lattice_id)],
+        subjects: vec![format!("{lattice_id}.*.wrpc.>")],
         ..Default::default()
     })
     .await
     .map_err(|e| anyhow::anyhow!("{e:?}"))?;
+    if ctx.get_stream(CAPTURE_RESULTS_STREAM_NAME).await.is_err() {
+        ctx.create_stream(Config {
+            name: CAPTURE_RESULTS_STREAM_NAME.to_string(),
+            storage: async_nats::jetstream::stream::StorageType::File,
+            max_age: window_size,
+            no_ack: true,
+            subjects: vec![RESULTS_SUBJECT.to_string()],
+            ..Default::default()
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("{e:?}"))?;
+    }
 
     Ok(CommandOutput::from_key_and_text(
         "message",
@@ -212,6 +436,21 @@ pub async fn disable(
         .await
         .map_err(|e| anyhow::anyhow!("{e:?}"))?;
 
+    // The results stream is shared, so only remove it once no lattice is being captured
+    let lattice_prefix = format!("{CAPTURE_STREAM_NAME}-");
+    let capturing = ctx
+        .stream_names()
+        .try_collect::<Vec<_>>()
+        .await
+        .map_err(|e| anyhow::anyhow!("{e:?}"))?
+        .into_iter()
+        .any(|name| name != CAPTURE_RESULTS_STREAM_NAME && name.starts_with(&lattice_prefix));
+    if !capturing && ctx.get_stream(CAPTURE_RESULTS_STREAM_NAME).await.is_ok() {
+        ctx.delete_stream(CAPTURE_RESULTS_STREAM_NAME)
+            .await
+            .map_err(|e| anyhow::anyhow!("{e:?}"))?;
+    }
+
     Ok(CommandOutput::from_key_and_text(
         "message",
         "Successfully disabled capture mode for lattice",
@@ -226,12 +465,68 @@ pub async fn capture(
     let stream = ctx.get_stream(stream_name(lattice_id)).await.map_err(|e| {
         anyhow::anyhow!("Unable to find stream. Have you run `wash capture --enable`? Error: {e:?}")
     })?;
+    let results_stream = ctx.get_stream(CAPTURE_RESULTS_STREAM_NAME).await.ok();
+    if results_stream.is_none() {
+        eprintln!("WARN: Responses are not being captured. Run `wash capture --disable` and `wash capture --enable` to capture them");
+    }
 
     // Timestamp for cutoff of messages to capture
     let capture_start_time = time::OffsetDateTime::now_utc();
 
     let inventory = get_all_inventory(&ctl_client).await?;
 
+    let filename = format!(
+        "{}.{}.washcapture",
+        chrono::Local::now().to_rfc3339(),
+        lattice_id
+    );
+    let mut capture = WriteCapture::start(inventory, &filename).await?;
+
+    let mut replies = HashSet::new();
+    write_stream(stream, capture_start_time, &mut capture, |msg| {
+        if let Some(reply) = &msg.reply {
+            replies.insert(reply.clone());
+        }
+        true
+    })
+    .await?;
+    // The results stream contains the results of all lattices, so only keep the responses to
+    // captured invocations
+    if let Some(results_stream) = results_stream {
+        write_stream(results_stream, capture_start_time, &mut capture, |msg| {
+            msg.subject
+                .strip_suffix(RESULTS_SUBJECT_SUFFIX)
+                .is_some_and(|reply| replies.contains(reply))
+        })
+        .await?;
+    }
+
+    capture.finish().await?;
+
+    Ok(CommandOutput::new(
+        format!("Completed capture and output to file {filename}"),
+        [
+            (
+                "message".to_string(),
+                serde_json::Value::String("Completed capture".to_owned()),
+            ),
+            (
+                "output_path".to_string(),
+                serde_json::Value::String(filename),
+            ),
+        ]
+        .into(),
+    ))
+}
+
+/// Write the messages in `stream` published before `capture_start_time` for which `keep` returns
+/// true to the capture
+async fn write_stream(
+    stream: async_nats::jetstream::stream::Stream,
+    capture_start_time: time::OffsetDateTime,
+    capture: &mut WriteCapture,
+    mut keep: impl FnMut(&SerializableMessage) -> bool,
+) -> Result<()> {
     let consumer = stream
         .create_consumer(ConsumerConfig {
             description: Some("Wash capture consumer".to_string()),
@@ -253,13 +548,6 @@ pub async fn capture(
         max_time_without_message,
     );
 
-    let filename = format!(
-        "{}.{}.washcapture",
-        chrono::Local::now().to_rfc3339(),
-        lattice_id
-    );
-    let mut capture = WriteCapture::start(inventory, &filename).await?;
-
     loop {
         tokio::select! {
             _ = expiry.tick() => {
@@ -285,29 +573,15 @@ pub async fn capture(
                         break;
                     }
                 }
-                if let Ok(m) = msg.try_into() {
-                    capture.add_message(m).await?;
+                if let Ok(m) = SerializableMessage::try_from(msg) {
+                    if keep(&m) {
+                        capture.add_message(m).await?;
+                    }
                 }
             }
         }
     }
-
-    capture.finish().await?;
-
-    Ok(CommandOutput::new(
-        format!("Completed capture and output to file {filename}"),
-        [
-            (
-                "message".to_string(),
-                serde_json::Value::String("Completed capture".to_owned()),
-            ),
-            (
-                "output_path".to_string(),
-                serde_json::Value::String(filename),
-            ),
-        ]
-        .into(),
-    ))
+    Ok(())
 }
 
 async fn get_all_inventory(
@@ -339,3 +613,59 @@ async fn get_all_inventory(
 fn stream_name(lattice_id: &str) -> String {
     format!("{CAPTURE_STREAM_NAME}-{lattice_id}")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replayed(operation: &str, outcome: ReplayOutcome) -> ReplayedInvocation {
+        ReplayedInvocation {
+            operation: operation.to_string(),
+            from: "source".to_string(),
+            to: "target".to_string(),
+            outcome,
+        }
+    }
+
+    #[test]
+    fn test_replay_summary() {
+        let outcomes = [
+            replayed("wasi:http/incoming-handler.handle", ReplayOutcome::Matched),
+            replayed("wasi:http/incoming-handler.handle", ReplayOutcome::Matched),
+            replayed("wasi:keyvalue/store.get", ReplayOutcome::Uncompared),
+        ];
+        let output = replay_summary("target", &outcomes).expect("replay should succeed");
+        assert_eq!(
+            output.text,
+            "Replayed 3 invocation(s) to [target]: 2 matched, 0 differed, 1 without a captured response, 0 failed"
+        );
+        assert_eq!(output.map["replayed"], json!(3));
+        assert_eq!(output.map["matched"], json!(2));
+        assert_eq!(output.map["uncompared"], json!(1));
+        assert_eq!(
+            output.map["invocations"][2],
+            json!({
+                "operation": "wasi:keyvalue/store.get",
+                "from": "source",
+                "to": "target",
+                "outcome": "uncompared",
+            })
+        );
+
+        // Differing responses and failures fail the replay
+        let Err(err) = replay_summary(
+            "target",
+            &[
+                replayed("a", ReplayOutcome::Matched),
+                replayed("b", ReplayOutcome::Differed),
+                replayed("c", ReplayOutcome::Failed),
+            ],
+        ) else {
+            panic!("replay should fail");
+        };
+        assert_eq!(
+            err.to_string(),
+            "Replayed 3 invocation(s) to [target]: 1 matched, 1 differed, 0 without a captured response, 1 failed"
+        );
+    }
+}
@@ -103,4 +103,14 @@ pub struct RegistryPushCommand {
     /// Push the artifact monolithically instead of chunked
     #[clap(long = "monolithic-push", env = "WASH_MONOLITHIC_PUSH")]
     pub monolithic_push: bool,
+
+    /// Push a provider archive as an OCI image index with one manifest per target, so that hosts
+    /// only download the binary for their own target
+    #[clap(long = "multi-arch", env = "WASH_MULTI_ARCH_PUSH")]
+    pub multi_arch: bool,
+
+    /// Path to a signed provenance attestation to attach to the artifact. Defaults to the
+    /// `<artifact>.intoto.json` file generated by `wash build --provenance`, if it exists
+    #[clap(long = "provenance")]
+    pub provenance: Option<PathBuf>,
 }
@@ -1,9 +1,16 @@
+use std::path::PathBuf;
+
 use anyhow::Result;
 use clap::Parser;
 use futures::StreamExt;
 
 use super::{validate_component_id, CliConnectionOpts, CommandOutput};
-use crate::{config::WashConnectionOptions, spier::Spier};
+use crate::{
+    common::get_all_inventories,
+    config::WashConnectionOptions,
+    spier::{fetch_component_functions, OperationFilter, Spier},
+    wrpc::WitFunctions,
+};
 
 #[derive(Debug, Parser, Clone)]
 pub struct SpyCommand {
@@ -12,6 +19,20 @@ pub struct SpyCommand {
     #[clap(name = "component_id", value_parser = validate_component_id)]
     pub component_id: String,
 
+    /// Only show invocations of functions in this interface, with or without a version, e.g.
+    /// `wasi:keyvalue/store`
+    #[clap(name = "interface", long = "interface")]
+    pub interface: Option<String>,
+
+    /// Only show invocations of this function
+    #[clap(name = "function", long = "function")]
+    pub function: Option<String>,
+
+    /// Path to a WIT file or directory used to decode invocations. By default, the WIT embedded
+    /// in the component is used
+    #[clap(name = "wit", long = "wit")]
+    pub wit: Option<PathBuf>,
+
     #[clap(flatten)]
     pub opts: CliConnectionOpts,
 }
@@ -22,7 +43,27 @@ pub async fn handle_command(cmd: SpyCommand) -> Result<CommandOutput> {
     let ctl_client = wco.clone().into_ctl_client(None).await?;
     let nats_client = wco.into_nats_client().await?;
 
-    let mut spier = Spier::new(&cmd.component_id, &ctl_client, &nats_client).await?;
+    let functions = match &cmd.wit {
+        Some(path) => WitFunctions::from_wit_path(path)?,
+        None => {
+            let inventories = get_all_inventories(&ctl_client).await?;
+            fetch_component_functions(
+                inventories
+                    .iter()
+                    .flat_map(|inventory| inventory.components())
+                    .filter(|component| component.id() == cmd.component_id)
+                    .map(|component| component.image_ref()),
+            )
+            .await
+        }
+    };
+    let mut spier = Spier::new(&cmd.component_id, &ctl_client, &nats_client)
+        .await?
+        .with_functions(functions)
+        .with_filter(OperationFilter {
+            interface: cmd.interface,
+            function: cmd.function,
+        });
 
     println!("Spying on component {}\n", spier.component_id());
 
@@ -32,9 +73,14 @@ pub async fn handle_command(cmd: SpyCommand) -> Result<CommandOutput> {
 [{}]
 From: {:<25} To: {:<25}
 
-Operation: {}
+Operation: {}{}
 Message: {}"#,
-            msg.timestamp, msg.from, msg.to, msg.operation, msg.message
+            msg.timestamp,
+            msg.from,
+            msg.to,
+            msg.operation,
+            if msg.response { " (response)" } else { "" },
+            msg.message
         );
     }
 
@@ -48,6 +48,7 @@ pub mod registry;
 pub mod spier;
 #[cfg(feature = "nats")]
 pub mod wait;
+pub mod wrpc;
 
 #[cfg(feature = "plugin")]
 pub mod plugin;
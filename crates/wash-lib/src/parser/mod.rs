@@ -26,6 +26,11 @@ pub enum LanguageConfig {
     Rust(RustConfig),
     TinyGo(TinyGoConfig),
     Go(GoConfig),
+    Python(PythonConfig),
+    #[serde(rename = "javascript")]
+    JavaScript(JavaScriptConfig),
+    #[serde(rename = "typescript")]
+    TypeScript(JavaScriptConfig),
     Other(String),
 }
 
@@ -516,6 +521,50 @@ impl TinyGoConfig {
     }
 }
 
+/// Configuration related to Python components, which are built with `componentize-py`
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct PythonConfig {
+    /// The path to the componentize-py binary. Optional, will default to `componentize-py` if not specified.
+    pub componentize_py_path: Option<PathBuf>,
+    /// The Python module that implements the component. Defaults to `app`.
+    pub entry_module: Option<String>,
+    /// Additional directories to search for Python modules (e.g. a directory that dependencies were
+    /// installed to with `pip install --target`), relative to the project directory
+    #[serde(default)]
+    pub python_path: Vec<PathBuf>,
+}
+
+impl PythonConfig {
+    /// The Python module that implements the component
+    #[must_use]
+    pub fn entry_module(&self) -> &str {
+        self.entry_module.as_deref().unwrap_or("app")
+    }
+}
+
+/// Configuration related to JavaScript and TypeScript components, which are built with `jco componentize`
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct JavaScriptConfig {
+    /// The path to the jco binary. Optional, will default to the `jco` installed in the project's
+    /// `node_modules` if there is one, or `jco` otherwise.
+    pub jco_path: Option<PathBuf>,
+    /// The path to the npm binary. Optional, will default to `npm` if not specified.
+    pub npm_path: Option<PathBuf>,
+    /// The JavaScript module that implements the component, relative to the project directory.
+    /// Defaults to `index.js` for JavaScript, and `dist/index.js` for TypeScript.
+    pub entry_module: Option<PathBuf>,
+    /// The npm script to run to produce the entry module, for example to bundle dependencies. For
+    /// TypeScript, `tsc` is run if no script is specified.
+    pub build_script: Option<String>,
+    /// Whether to skip running `npm install` when the project has a `package.json` but no
+    /// `node_modules` directory. Defaults to false.
+    #[serde(default)]
+    pub skip_install: bool,
+    /// WASI features to disable in the component (e.g. `http` or `stdio`)
+    #[serde(default)]
+    pub disable_features: Vec<String>,
+}
+
 /// Specification for how to wire up configuration
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
 #[serde(untagged)]
@@ -619,6 +668,29 @@ pub struct InterfaceComponentOverride {
     ///
     /// This is only required when there are *more than one* overrides that conflict (i.e. there is no "default")
     pub link_name: Option<String>,
+
+    /// In-memory mock that should serve the interface in place of a component or provider
+    ///
+    /// Mocks can only be used for imports, and cannot be combined with an `image_ref`.
+    pub mock: Option<InterfaceMock>,
+}
+
+/// An in-memory mock of an interface, which records calls and returns scripted responses
+///
+/// ```toml
+/// [[dev.overrides.imports]]
+/// interface = "wasi:keyvalue/store"
+/// mock = { responses = { exists = { ok = true } } }
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct InterfaceMock {
+    /// Results returned by functions of the mocked interface, keyed by function name
+    ///
+    /// Function names may be qualified with their interface (ex. `store.get`) to disambiguate
+    /// functions with the same name. Functions without a scripted response return a default value,
+    /// or in the case of `wasi:keyvalue` and `wasi:blobstore`, the contents of an in-memory store.
+    #[serde(default)]
+    pub responses: BTreeMap<String, serde_json::Value>,
 }
 
 /// String that represents a specification of a WIT interface (normally used when specifying [`InterfaceComponentOverride`]s)
@@ -893,6 +965,31 @@ pub struct DevConfig {
     /// Normally keyed by strings that represent an interface specification (e.g. `wasi:keyvalue/store@0.2.0-draft`)
     #[serde(default)]
     pub overrides: InterfaceOverrides,
+
+    /// Component to link to a provider under development, as an image reference or a path to a
+    /// Wasm component (relative to the project directory)
+    ///
+    /// The component is linked to every interface of the provider that is not covered by another
+    /// dependency, so that the provider can be exercised. Ignored for component projects.
+    #[serde(default)]
+    pub test_component: Option<String>,
+}
+
+/// A hook plugin to run around lifecycle events of the project
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
+pub struct HookConfig {
+    /// Path to the hook plugin, a Wasm component (relative to the wasmcloud.toml file)
+    pub plugin: PathBuf,
+
+    /// Names of the events to run the plugin for, as declared by the `event` enum of the
+    /// `wasmcloud:wash/hook` interface, e.g. `pre-build`. Defaults to all the events the plugin
+    /// registers for
+    #[serde(default)]
+    pub events: Vec<String>,
+
+    /// Arguments to pass to the plugin when it is run
+    #[serde(default)]
+    pub args: BTreeMap<String, String>,
 }
 
 /// Gets the wasmCloud project (component or provider) config.
@@ -940,6 +1037,13 @@ pub async fn load_config(
         );
     };
 
+    if read_workspace_toml(&wasmcloud_toml_path).await?.is_some() {
+        bail!(
+            "{} defines a workspace rather than a component or provider project",
+            wasmcloud_toml_path.display()
+        );
+    }
+
     let mut config = Config::builder().add_source(config::File::from(wasmcloud_toml_path.clone()));
 
     if use_env.unwrap_or(true) {
@@ -998,6 +1102,151 @@ pub async fn load_config(
         .map_err(|e: anyhow::Error| anyhow!("{} in {}", e, wasmcloud_toml_path.display()))
 }
 
+/// A workspace of wasmCloud projects, normally specified in the `[workspace]` table of a
+/// wasmcloud.toml file
+///
+/// ```toml
+/// [workspace]
+/// name = "pet-clinic"
+/// members = ["api", "store-provider"]
+/// ```
+#[derive(Debug, Clone)]
+pub struct WorkspaceConfig {
+    /// Name of the workspace. Defaults to the name of the directory of the workspace.
+    pub name: String,
+    /// Configuration of the member projects, in the order in which they were listed
+    pub members: Vec<ProjectConfig>,
+    /// The directory where the workspace wasmcloud.toml file is located
+    pub wasmcloud_toml_dir: PathBuf,
+}
+
+/// The `[workspace]` table of a wasmcloud.toml file
+#[derive(Deserialize, Debug)]
+struct WorkspaceDotToml {
+    workspace: WorkspaceTable,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct WorkspaceTable {
+    name: Option<String>,
+    members: Vec<PathBuf>,
+}
+
+/// Read the `[workspace]` table of a wasmcloud.toml file, if it has one
+async fn read_workspace_toml(path: &Path) -> Result<Option<WorkspaceDotToml>> {
+    let contents = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("failed to read [{}]", path.display()))?;
+    let Ok(table) = contents.parse::<toml::Table>() else {
+        // Leave reporting invalid files to the project config loader
+        return Ok(None);
+    };
+    if !table.contains_key("workspace") {
+        return Ok(None);
+    }
+    toml::from_str(&contents)
+        .map(Some)
+        .with_context(|| format!("invalid workspace in [{}]", path.display()))
+}
+
+/// Gets the wasmCloud workspace config, if the wasmcloud.toml file at `opt_path` (or in the
+/// current directory) defines a workspace. Returns `None` if it defines a single project instead.
+///
+/// Members are paths to project directories (or their wasmcloud.toml files) relative to the
+/// workspace, and are loaded with [`load_config`]. Workspaces cannot be nested.
+///
+/// # Arguments
+/// * `opt_path` - The path to the config file. If None, it will look for a wasmcloud.toml file in the current directory.
+/// * `use_env` - Whether to use the environment variables or not when loading member projects. Defaults to true.
+pub async fn load_workspace_config(
+    opt_path: Option<PathBuf>,
+    use_env: Option<bool>,
+) -> Result<Option<WorkspaceConfig>> {
+    let path = match opt_path {
+        Some(p) => p,
+        None => std::env::current_dir().context("failed to get current directory")?,
+    };
+    let path = fs::canonicalize(&path)
+        .with_context(|| format!("failed to canonicalize path [{}]", path.display()))?;
+    let (wasmcloud_toml_dir, wasmcloud_toml_path) = if path.is_dir() {
+        (path.clone(), path.join("wasmcloud.toml"))
+    } else {
+        (
+            path.parent()
+                .ok_or_else(|| anyhow!("Could not get parent path of wasmcloud.toml file"))?
+                .to_path_buf(),
+            path,
+        )
+    };
+    if !wasmcloud_toml_path.is_file() {
+        return Ok(None);
+    }
+
+    let Some(WorkspaceDotToml {
+        workspace: WorkspaceTable { name, members },
+    }) = read_workspace_toml(&wasmcloud_toml_path).await?
+    else {
+        return Ok(None);
+    };
+    if members.is_empty() {
+        bail!(
+            "workspace in [{}] has no members",
+            wasmcloud_toml_path.display()
+        );
+    }
+
+    let mut member_configs: Vec<ProjectConfig> = Vec::with_capacity(members.len());
+    for member in members {
+        let member_path = wasmcloud_toml_dir.join(&member);
+        let member_toml_path = if member_path.is_dir() {
+            member_path.join("wasmcloud.toml")
+        } else {
+            member_path.clone()
+        };
+        if member_toml_path.is_file() && read_workspace_toml(&member_toml_path).await?.is_some() {
+            bail!(
+                "workspace member [{}] is itself a workspace, which is not supported",
+                member.display()
+            );
+        }
+        let config = load_config(Some(member_path), use_env)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to load workspace member [{}] of [{}]",
+                    member.display(),
+                    wasmcloud_toml_path.display()
+                )
+            })?;
+        if let Some(other) = member_configs
+            .iter()
+            .find(|c| c.common.name == config.common.name)
+        {
+            bail!(
+                "workspace members [{}] and [{}] have the same name [{}]",
+                other.common.project_dir.display(),
+                config.common.project_dir.display(),
+                config.common.name
+            );
+        }
+        member_configs.push(config);
+    }
+
+    let name = match name {
+        Some(name) => name,
+        None => wasmcloud_toml_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .context("failed to infer workspace name from its directory")?,
+    };
+    Ok(Some(WorkspaceConfig {
+        name,
+        members: member_configs,
+        wasmcloud_toml_dir,
+    }))
+}
+
 /// The wasmcloud.toml specification format as de-serialization friendly project configuration data
 ///
 /// This structure is normally directly de-serialized from `wasmcloud.toml`,
@@ -1070,6 +1319,18 @@ pub struct WasmcloudDotToml {
     #[serde(default)]
     pub go: GoConfig,
 
+    /// Python related configuration and options
+    #[serde(default)]
+    pub python: PythonConfig,
+
+    /// JavaScript related configuration and options
+    #[serde(default)]
+    pub javascript: JavaScriptConfig,
+
+    /// TypeScript related configuration and options
+    #[serde(default)]
+    pub typescript: JavaScriptConfig,
+
     /// Configuration for development environments and/or DX related plugins
     #[serde(default)]
     pub dev: DevConfig,
@@ -1083,6 +1344,10 @@ pub struct WasmcloudDotToml {
     /// Configuration for image registry usage
     #[serde(default)]
     pub registry: RegistryConfig,
+
+    /// Hook plugins to run around lifecycle events of the project, in order
+    #[serde(default, rename = "hook")]
+    pub hooks: Vec<HookConfig>,
 }
 
 impl WasmcloudDotToml {
@@ -1153,6 +1418,9 @@ impl WasmcloudDotToml {
             "rust" => LanguageConfig::Rust(self.rust),
             "go" => LanguageConfig::Go(self.go),
             "tinygo" => LanguageConfig::TinyGo(self.tinygo),
+            "python" => LanguageConfig::Python(self.python),
+            "javascript" | "js" => LanguageConfig::JavaScript(self.javascript),
+            "typescript" | "ts" => LanguageConfig::TypeScript(self.typescript),
             other => LanguageConfig::Other(other.to_string()),
         };
 
@@ -1238,22 +1506,25 @@ impl WasmcloudDotToml {
                 }
             }
 
-            LanguageConfig::Go(_) | LanguageConfig::TinyGo(_) | LanguageConfig::Other(_) => {
-                CommonConfig {
-                    name: self
-                        .name
-                        .ok_or_else(|| anyhow!("Missing name in wasmcloud.toml"))?,
-                    version: self
-                        .version
-                        .ok_or_else(|| anyhow!("Missing version in wasmcloud.toml"))?,
-                    revision: self.revision,
-                    project_dir: project_path,
-                    wasm_bin_name: None,
-                    wit_dir,
-                    build_dir,
-                    registry: self.registry,
-                }
-            }
+            LanguageConfig::Go(_)
+            | LanguageConfig::TinyGo(_)
+            | LanguageConfig::Python(_)
+            | LanguageConfig::JavaScript(_)
+            | LanguageConfig::TypeScript(_)
+            | LanguageConfig::Other(_) => CommonConfig {
+                name: self
+                    .name
+                    .ok_or_else(|| anyhow!("Missing name in wasmcloud.toml"))?,
+                version: self
+                    .version
+                    .ok_or_else(|| anyhow!("Missing version in wasmcloud.toml"))?,
+                revision: self.revision,
+                project_dir: project_path,
+                wasm_bin_name: None,
+                wit_dir,
+                build_dir,
+                registry: self.registry,
+            },
         };
 
         let package_config = self
@@ -1291,8 +1562,18 @@ impl WasmcloudDotToml {
             })
             .unwrap_or_default();
 
+        let hooks = self
+            .hooks
+            .into_iter()
+            .map(|hook| HookConfig {
+                plugin: wasmcloud_toml_dir.join(hook.plugin),
+                ..hook
+            })
+            .collect();
+
         Ok(ProjectConfig {
             dev: self.dev,
+            hooks,
             project_type: project_type_config,
             language: language_config,
             common: common_config,
@@ -1315,6 +1596,8 @@ pub struct ProjectConfig {
     pub common: CommonConfig,
     /// Configuration for development environments and/or DX related plugins
     pub dev: DevConfig,
+    /// Hook plugins to run around lifecycle events of the project, in order
+    pub hooks: Vec<HookConfig>,
     /// Configuration for package tooling
     pub package_config: PackageConfig,
     /// The directory where the project wasmcloud.toml file is located
@@ -0,0 +1,241 @@
+mod bindings {
+    wasmtime::component::bindgen!({
+        world: "hooks",
+        async: true,
+    });
+}
+
+use bindings::exports::wasmcloud::wash::hook::Context as EventContext;
+pub use bindings::exports::wasmcloud::wash::hook::{Event, Metadata};
+use bindings::Hooks;
+
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+use wasmtime::component::{Component, Linker};
+use wasmtime::Engine;
+use wasmtime_wasi::{DirPerms, FilePerms, WasiCtxBuilder};
+use wasmtime_wasi_http::WasiHttpCtx;
+
+use super::Data;
+
+/// Implements conversions of [`Event`] from and to the names of the events in WIT, which are the
+/// names used for the events in `wasmcloud.toml`
+macro_rules! event_names {
+    ($($event:ident => $name:literal),* $(,)?) => {
+        impl Display for Event {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(match self {
+                    $(Event::$event => $name,)*
+                })
+            }
+        }
+
+        impl FromStr for Event {
+            type Err = anyhow::Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($name => Ok(Event::$event),)*
+                    _ => bail!("unknown hook event `{s}`"),
+                }
+            }
+        }
+    };
+}
+
+event_names! {
+    PreBuild => "pre-build",
+    PostBuild => "post-build",
+    PrePush => "pre-push",
+    PostPush => "post-push",
+    DevReload => "dev-reload",
+}
+
+struct InstanceData {
+    instance: Hooks,
+    metadata: Metadata,
+    events: Vec<Event>,
+    args: Vec<(String, String)>,
+    store: wasmtime::Store<Data>,
+}
+
+/// A struct that manages loading and running hook plugins
+///
+/// Hooks are run in the order they were added to the runner
+pub struct HookRunner {
+    engine: Engine,
+    plugins: Vec<InstanceData>,
+}
+
+impl HookRunner {
+    /// Creates a new hook runner with no plugins loaded.
+    pub fn new() -> anyhow::Result<Self> {
+        Ok(Self {
+            engine: super::engine()?,
+            plugins: Vec::new(),
+        })
+    }
+
+    /// Adds a hook plugin to the runner, returning the metadata for the plugin.
+    ///
+    /// The plugin is run for the given events that it registered for, or for all the events it
+    /// registered for if no events are given. The given args are passed to the plugin every time it
+    /// is run.
+    pub async fn add_plugin(
+        &mut self,
+        path: impl AsRef<Path>,
+        events: &[Event],
+        args: impl IntoIterator<Item = (String, String)>,
+    ) -> anyhow::Result<Metadata> {
+        let path = path.as_ref();
+        // We create a bare context here for registration and then update the store with a new context before running
+        let ctx = Data {
+            table: wasmtime::component::ResourceTable::default(),
+            ctx: WasiCtxBuilder::new().build(),
+            http: WasiHttpCtx::new(),
+        };
+        let mut store = wasmtime::Store::new(&self.engine, ctx);
+
+        let component = Component::from_file(&self.engine, path)
+            .with_context(|| format!("failed to load hook plugin [{}]", path.display()))?;
+        let mut linker = Linker::new(&self.engine);
+        wasmtime_wasi::add_to_linker_async(&mut linker)
+            .context("failed to link core WASI interfaces")?;
+        wasmtime_wasi_http::add_only_http_to_linker_async(&mut linker)
+            .context("failed to link `wasi:http`")?;
+
+        let instance = Hooks::instantiate_async(&mut store, &component, &linker)
+            .await
+            .with_context(|| format!("failed to instantiate hook plugin [{}]", path.display()))?;
+        let metadata = instance
+            .wasmcloud_wash_hook()
+            .call_register(&mut store)
+            .await?;
+
+        let events = if events.is_empty() {
+            metadata.events.clone()
+        } else {
+            for event in events.iter().filter(|e| !metadata.events.contains(e)) {
+                tracing::warn!(
+                    plugin = metadata.id,
+                    %event,
+                    "hook plugin did not register for event, it will not be run for it"
+                );
+            }
+            events
+                .iter()
+                .copied()
+                .filter(|e| metadata.events.contains(e))
+                .collect()
+        };
+        self.plugins.push(InstanceData {
+            instance,
+            metadata: metadata.clone(),
+            events,
+            args: args.into_iter().collect(),
+            store,
+        });
+        Ok(metadata)
+    }
+
+    /// Returns a list of all metadata for all plugins.
+    pub fn all_metadata(&self) -> Vec<&Metadata> {
+        self.plugins.iter().map(|data| &data.metadata).collect()
+    }
+
+    /// Returns whether no plugins are loaded.
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Run all hooks registered for the given event, stopping at the first hook that fails.
+    ///
+    /// Every hook has read and write access to the project directory and to the directory
+    /// containing the artifact (if any), at their canonicalized paths. Hooks inherit
+    /// stdout/stderr/stdin and are passed environment variables starting with
+    /// `WASH_PLUGIN_${plugin_id.to_upper()}_` from the current process. Other vars will be ignored
+    pub async fn run(
+        &mut self,
+        event: Event,
+        project_dir: impl AsRef<Path>,
+        artifact: Option<&Path>,
+        reference: Option<&str>,
+    ) -> anyhow::Result<()> {
+        if self.plugins.iter().all(|p| !p.events.contains(&event)) {
+            return Ok(());
+        }
+
+        let project_dir = tokio::fs::canonicalize(project_dir.as_ref())
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to canonicalize project directory [{}]",
+                    project_dir.as_ref().display()
+                )
+            })?;
+        let artifact = match artifact {
+            Some(artifact) => Some(tokio::fs::canonicalize(artifact).await.with_context(|| {
+                format!("failed to canonicalize artifact [{}]", artifact.display())
+            })?),
+            None => None,
+        };
+        let mut dirs = vec![project_dir.clone()];
+        if let Some(parent) = artifact.as_ref().and_then(|a| a.parent()) {
+            if !parent.starts_with(&project_dir) {
+                dirs.push(parent.to_path_buf());
+            }
+        }
+
+        for plugin in self
+            .plugins
+            .iter_mut()
+            .filter(|p| p.events.contains(&event))
+        {
+            let id = &plugin.metadata.id;
+            let env_prefix = format!("WASH_PLUGIN_{}_", id.to_uppercase());
+            let vars: Vec<_> = std::env::vars()
+                .filter(|(k, _)| k.starts_with(&env_prefix))
+                .collect();
+            let mut ctx = WasiCtxBuilder::new();
+            for dir in &dirs {
+                ctx.preopened_dir(dir, guest_path(dir)?, DirPerms::all(), FilePerms::all())
+                    .with_context(|| format!("failed to preopen [{}]", dir.display()))?;
+            }
+            // Disable socket connections for now. We may gradually open this up later
+            ctx.socket_addr_check(|_, _| Box::pin(async { false }))
+                .inherit_stdio()
+                .envs(&vars);
+            plugin.store.data_mut().ctx = ctx.build();
+
+            let context = EventContext {
+                project_dir: guest_path(&project_dir)?,
+                artifact: artifact.as_deref().map(guest_path).transpose()?,
+                reference: reference.map(String::from),
+                args: plugin.args.clone(),
+            };
+            if let Err(e) = plugin
+                .instance
+                .wasmcloud_wash_hook()
+                .call_run(&mut plugin.store, event, &context)
+                .await
+                .with_context(|| format!("failed to run hook plugin [{id}]"))?
+            {
+                bail!("hook plugin [{id}] failed on {event}: {e}");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Converts a canonicalized host path to the path it is available at in the plugin
+fn guest_path(path: &Path) -> anyhow::Result<String> {
+    let path = path.to_str().ok_or_else(|| anyhow::anyhow!("Canonicalized path cannot be converted to a string for use in a plugin. This is a limitation of the WASI API"))?;
+    // On Windows, we need to normalize the path separators to "/" since that is what is
+    // expected by things like `PathBuf` when built for WASI.
+    #[cfg(target_family = "windows")]
+    let path = path.replace('\\', "/");
+    Ok(path.to_string())
+}
@@ -1,6 +1,8 @@
+use wasmtime::{Config, Engine};
 use wasmtime_wasi::{WasiCtx, WasiView};
 use wasmtime_wasi_http::{WasiHttpCtx, WasiHttpView};
 
+pub mod hooks;
 pub mod subcommand;
 
 /// The directory where plugins are stored.
@@ -31,3 +33,15 @@ impl WasiHttpView for Data {
         &mut self.http
     }
 }
+
+/// Creates an engine suitable for running plugins
+fn engine() -> anyhow::Result<Engine> {
+    let mut config = Config::new();
+    // Attempt to use caching, but only warn if it fails
+    if let Err(e) = config.cache_config_load_default() {
+        tracing::warn!(err = ?e, "Failed to load wasm cache");
+    }
+    config.wasm_component_model(true);
+    config.async_support(true);
+    Engine::new(&config)
+}
@@ -13,7 +13,7 @@ use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Ok};
 use wasmtime::component::{Component, Linker};
-use wasmtime::{Config, Engine};
+use wasmtime::Engine;
 use wasmtime_wasi::{DirPerms, FilePerms, WasiCtxBuilder};
 use wasmtime_wasi_http::WasiHttpCtx;
 
@@ -48,14 +48,7 @@ pub struct DirMapping {
 impl SubcommandRunner {
     /// Creates a new subcommand runner with no plugins loaded.
     pub fn new() -> anyhow::Result<Self> {
-        let mut config = Config::new();
-        // Attempt to use caching, but only warn if it fails
-        if let Err(e) = config.cache_config_load_default() {
-            tracing::warn!(err = ?e, "Failed to load wasm cache");
-        }
-        config.wasm_component_model(true);
-        config.async_support(true);
-        let engine = Engine::new(&config)?;
+        let engine = super::engine()?;
         Ok(Self {
             engine,
             plugins: HashMap::new(),
@@ -6,11 +6,14 @@ use std::{
 };
 
 use anyhow::{bail, Context as _, Result};
-use oci_client::manifest::OciImageManifest;
+use oci_client::manifest::{
+    ImageIndexEntry, OciDescriptor, OciImageIndex, OciImageManifest, Platform,
+    OCI_IMAGE_INDEX_MEDIA_TYPE, OCI_IMAGE_MEDIA_TYPE,
+};
 use oci_client::{
     client::{Client, ClientConfig, ClientProtocol, Config, ImageLayer},
     secrets::RegistryAuth,
-    Reference,
+    Reference, RegistryOperation,
 };
 use oci_wasm::{ToConfig, WasmConfig, WASM_LAYER_MEDIA_TYPE, WASM_MANIFEST_MEDIA_TYPE};
 use provider_archive::ProviderArchive;
@@ -24,6 +27,9 @@ const PROVIDER_ARCHIVE_CONFIG_MEDIA_TYPE: &str =
     "application/vnd.wasmcloud.provider.archive.config";
 const WASM_MEDIA_TYPE: &str = "application/vnd.module.wasm.content.layer.v1+wasm";
 const OCI_MEDIA_TYPE: &str = "application/vnd.oci.image.layer.v1.tar";
+const OCI_EMPTY_MEDIA_TYPE: &str = "application/vnd.oci.empty.v1+json";
+const DSSE_ENVELOPE_MEDIA_TYPE: &str = "application/vnd.dsse.envelope.v1+json";
+const IN_TOTO_ARTIFACT_TYPE: &str = "application/vnd.in-toto+json";
 
 /// Additional options for pulling an OCI artifact
 #[derive(Default)]
@@ -61,6 +67,11 @@ pub struct OciPushOptions {
     pub annotations: Option<BTreeMap<String, String>>,
     /// Whether to use monolithic push instead of chunked push
     pub monolithic_push: bool,
+    /// Whether to push provider archives as an OCI image index with one manifest per target, so
+    /// that hosts only download the binary for their own target
+    pub multi_arch: bool,
+    /// A path to a signed provenance attestation to attach to the pushed artifact
+    pub provenance: Option<PathBuf>,
 }
 
 /// The types of artifacts that wash supports
@@ -239,6 +250,34 @@ pub async fn push_oci_artifact(
         _ => RegistryAuth::Anonymous,
     };
 
+    let provenance = match options.provenance {
+        Some(path) => Some(
+            tokio::fs::read(&path)
+                .await
+                .with_context(|| format!("failed to read provenance file [{}]", path.display()))?,
+        ),
+        None => None,
+    };
+
+    if options.multi_arch && !is_wasm {
+        let par = ProviderArchive::try_load(&layers[0].data)
+            .await
+            .map_err(|e| anyhow::anyhow!("Invalid provider archive: {e}"))?;
+        let (digest, size) =
+            push_provider_archive_index(&client, &auth, &image, &par, config, options.annotations)
+                .await?;
+        if let Some(provenance) = provenance {
+            let subject = OciDescriptor {
+                media_type: OCI_IMAGE_INDEX_MEDIA_TYPE.to_string(),
+                digest: digest.clone(),
+                size,
+                ..Default::default()
+            };
+            attach_provenance(&client, &auth, &image, subject, provenance).await?;
+        }
+        return Ok((image.tag().map(ToString::to_string), digest));
+    }
+
     let mut manifest = OciImageManifest::build(&layers, &config, options.annotations);
     if is_wasm {
         manifest.media_type = Some(WASM_MANIFEST_MEDIA_TYPE.to_string());
@@ -253,15 +292,167 @@ pub async fn push_oci_artifact(
     //
     // This attempts to approximate the ordering as provided by the Go-based registry implementations, which
     // is/are the prevailing implementation.
-    let digest =
-        serde_json::to_value(&manifest).map(|value| sha256_digest(value.to_string().as_bytes()))?;
+    let manifest_json = serde_json::to_value(&manifest)?.to_string();
+    let digest = sha256_digest(manifest_json.as_bytes());
+    let subject = OciDescriptor {
+        media_type: manifest
+            .media_type
+            .clone()
+            .unwrap_or_else(|| OCI_IMAGE_MEDIA_TYPE.to_string()),
+        digest: digest.clone(),
+        size: manifest_json.len() as i64,
+        ..Default::default()
+    };
 
     client
         .push(&image, &layers, config, &auth, Some(manifest))
         .await?;
+    if let Some(provenance) = provenance {
+        attach_provenance(&client, &auth, &image, subject, provenance).await?;
+    }
     Ok((image.tag().map(ToString::to_string), digest))
 }
 
+/// Attaches a provenance attestation to the manifest described by `subject`.
+///
+/// The attestation is pushed as an OCI artifact whose `subject` is the attested manifest, so that
+/// registries implementing the referrers API list it. Since not all registries do, it is also
+/// tagged as `sha256-<digest>.att`, following the convention used by cosign.
+async fn attach_provenance(
+    client: &Client,
+    auth: &RegistryAuth,
+    image: &Reference,
+    subject: OciDescriptor,
+    provenance: Vec<u8>,
+) -> Result<()> {
+    let empty_config = b"{}";
+    let empty_config_digest = sha256_digest(empty_config);
+    let provenance_digest = sha256_digest(&provenance);
+    client
+        .auth(image, auth, RegistryOperation::Push)
+        .await
+        .context("failed to authenticate to push provenance")?;
+    client
+        .push_blob(image, empty_config, &empty_config_digest)
+        .await
+        .context("failed to push provenance config")?;
+    client
+        .push_blob(image, &provenance, &provenance_digest)
+        .await
+        .context("failed to push provenance")?;
+
+    // `OciImageManifest` has no `subject` field, so the manifest is written out manually
+    let manifest = serde_json::json!({
+        "schemaVersion": 2,
+        "mediaType": OCI_IMAGE_MEDIA_TYPE,
+        "artifactType": IN_TOTO_ARTIFACT_TYPE,
+        "config": {
+            "mediaType": OCI_EMPTY_MEDIA_TYPE,
+            "digest": empty_config_digest,
+            "size": empty_config.len(),
+        },
+        "layers": [{
+            "mediaType": DSSE_ENVELOPE_MEDIA_TYPE,
+            "digest": provenance_digest,
+            "size": provenance.len(),
+        }],
+        "subject": {
+            "mediaType": subject.media_type,
+            "digest": subject.digest,
+            "size": subject.size,
+        },
+    });
+    let tag = subject.digest.replacen(':', "-", 1) + ".att";
+    let attestation = Reference::with_tag(
+        image.registry().to_string(),
+        image.repository().to_string(),
+        tag,
+    );
+    client
+        .push_manifest_raw(
+            &attestation,
+            manifest.to_string().into_bytes(),
+            OCI_IMAGE_MEDIA_TYPE
+                .parse()
+                .context("invalid manifest media type")?,
+        )
+        .await
+        .context("failed to push provenance manifest")?;
+    Ok(())
+}
+
+/// Pushes a provider archive as an OCI image index with one manifest per target. The layer of
+/// each manifest is a provider archive containing only the binary for that target, along with the
+/// original signed claims. Returns the digest and size of the index.
+async fn push_provider_archive_index(
+    client: &Client,
+    auth: &RegistryAuth,
+    image: &Reference,
+    par: &ProviderArchive,
+    config: Config,
+    annotations: Option<BTreeMap<String, String>>,
+) -> Result<(String, i64)> {
+    let mut manifests = Vec::new();
+    let mut targets = par.targets();
+    targets.sort();
+    for target in targets {
+        let Some((os, architecture)) = provider_archive::oci_platform(&target) else {
+            bail!("provider archive target [{target}] is not of the form `arch-os`");
+        };
+        let layer = ImageLayer {
+            data: par
+                .target_archive(&target)
+                .await
+                .map_err(|e| anyhow::anyhow!("failed to create archive for [{target}]: {e}"))?,
+            media_type: PROVIDER_ARCHIVE_MEDIA_TYPE.to_string(),
+            annotations: None,
+        };
+        let layers = [layer];
+        let manifest = OciImageManifest::build(&layers, &config, annotations.clone());
+        // See `push_oci_artifact` for why the digest is calculated from a `serde_json::Value`
+        let manifest_json = serde_json::to_value(&manifest)?.to_string();
+        let digest = sha256_digest(manifest_json.as_bytes());
+        client
+            .push(
+                &image.clone_with_digest(digest.clone()),
+                &layers,
+                config.clone(),
+                auth,
+                Some(manifest),
+            )
+            .await
+            .with_context(|| format!("failed to push manifest for [{target}]"))?;
+        manifests.push(ImageIndexEntry {
+            media_type: OCI_IMAGE_MEDIA_TYPE.to_string(),
+            digest,
+            size: manifest_json.len() as i64,
+            platform: Some(Platform {
+                architecture,
+                os,
+                os_version: None,
+                os_features: None,
+                variant: None,
+                features: None,
+            }),
+            annotations: None,
+        });
+    }
+
+    let index = OciImageIndex {
+        schema_version: 2,
+        media_type: Some(OCI_IMAGE_INDEX_MEDIA_TYPE.to_string()),
+        manifests,
+        annotations,
+    };
+    let index_json = serde_json::to_value(&index)?.to_string();
+    let digest = sha256_digest(index_json.as_bytes());
+    client
+        .push_manifest_list(image, auth, index)
+        .await
+        .context("failed to push image index")?;
+    Ok((digest, index_json.len() as i64))
+}
+
 /// Helper function to determine artifact type and parse it into a config and layer ready for use in
 /// pushing to OCI
 pub async fn parse_and_validate_artifact(artifact: &[u8]) -> Result<SupportedArtifacts> {
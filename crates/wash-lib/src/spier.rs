@@ -1,9 +1,18 @@
-use std::task::Poll;
+use std::collections::{BTreeSet, HashMap};
+use std::task::{ready, Poll};
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use chrono::{DateTime, Local};
 use futures::{Stream, StreamExt};
-use tracing::debug;
+use tracing::{debug, warn};
+
+use crate::capture::{RESULTS_SUBJECT, RESULTS_SUBJECT_SUFFIX};
+use crate::registry::{get_oci_artifact, OciPullOptions};
+use crate::wrpc::WitFunctions;
+
+/// How long to wait for the response to an invocation before forgetting about it
+const MAX_RESPONSE_WAIT: Duration = Duration::from_secs(60);
 
 /// A struct that represents an invocation that was observed by the spier.
 #[derive(Debug)]
@@ -16,9 +25,12 @@ pub struct ObservedInvocation {
     pub to: String,
     /// The operation that was invoked
     pub operation: String,
-    /// The inner message that was received. We will attempt to parse the inner message from CBOR
-    /// and JSON into a JSON string and fall back to the raw bytes if we are unable to do so
+    /// The inner message that was received. We will attempt to decode the inner message using the
+    /// WIT of the invoked function and fall back to the raw bytes if we are unable to do so
     pub message: ObservedMessage,
+    /// Whether this is the response to an earlier invocation of `operation`, in which case `from`
+    /// is the entity that was invoked
+    pub response: bool,
 }
 
 /// A inner message that we've seen in an invocation message. This will either be a raw bytes or a
@@ -30,6 +42,7 @@ pub struct ObservedInvocation {
 pub enum ObservedMessage {
     Raw(Vec<u8>),
     Parsed(String),
+    Decoded(serde_json::Value),
 }
 
 impl std::fmt::Display for ObservedMessage {
@@ -39,6 +52,9 @@ impl std::fmt::Display for ObservedMessage {
             ObservedMessage::Parsed(v) => {
                 write!(f, "{v}")
             }
+            ObservedMessage::Decoded(v) => {
+                write!(f, "{v}")
+            }
         }
     }
 }
@@ -48,6 +64,100 @@ impl ObservedMessage {
     pub fn parse(data: Vec<u8>) -> Self {
         Self::Parsed(String::from_utf8_lossy(&data).to_string())
     }
+
+    /// Decode the parameters of an invocation of `operation` using the WIT of the function,
+    /// falling back to [`ObservedMessage::parse`] if the function is unknown or decoding fails
+    #[must_use]
+    pub fn decode_params(functions: &WitFunctions, operation: &str, data: Vec<u8>) -> Self {
+        match functions.decode_params(operation, &data) {
+            Some(Ok(params)) => Self::Decoded(params),
+            Some(Err(e)) => {
+                debug!(operation, err = ?e, "failed to decode parameters");
+                Self::parse(data)
+            }
+            None => Self::parse(data),
+        }
+    }
+
+    /// Decode the results of an invocation of `operation` using the WIT of the function,
+    /// falling back to [`ObservedMessage::parse`] if the function is unknown or decoding fails
+    #[must_use]
+    pub fn decode_results(functions: &WitFunctions, operation: &str, data: Vec<u8>) -> Self {
+        match functions.decode_results(operation, &data) {
+            Some(Ok(results)) => Self::Decoded(results),
+            Some(Err(e)) => {
+                debug!(operation, err = ?e, "failed to decode results");
+                Self::parse(data)
+            }
+            None => Self::parse(data),
+        }
+    }
+}
+
+/// A filter of invocations by the interface and name of the invoked function
+#[derive(Debug, Clone, Default)]
+pub struct OperationFilter {
+    /// Interface to match, with or without a version, e.g. `wasi:keyvalue/store`. A bare interface
+    /// name, e.g. `store`, matches that interface in any package
+    pub interface: Option<String>,
+    /// Name of the function to match
+    pub function: Option<String>,
+}
+
+impl OperationFilter {
+    /// Returns true if an operation, i.e. `<instance>.<function>`, matches the filter
+    #[must_use]
+    pub fn matches(&self, functions: &WitFunctions, operation: &str) -> bool {
+        if self.interface.is_none() && self.function.is_none() {
+            return true;
+        }
+        let Some((instance, func)) = functions.split_operation(operation) else {
+            return false;
+        };
+        let unversioned = instance.split('@').next().unwrap_or(instance);
+        let name = unversioned.rsplit('/').next().unwrap_or(unversioned);
+        self.interface
+            .as_deref()
+            .map_or(true, |i| i == instance || i == unversioned || i == name)
+            && self.function.as_deref().map_or(true, |f| f == func)
+    }
+}
+
+/// Index the functions of the WIT embedded in component images, skipping images that cannot be
+/// fetched or decoded
+pub async fn fetch_component_functions<'a>(
+    image_refs: impl IntoIterator<Item = &'a str>,
+) -> WitFunctions {
+    let mut functions = WitFunctions::default();
+    for image_ref in image_refs.into_iter().collect::<BTreeSet<_>>() {
+        let path = image_ref.strip_prefix("file://").unwrap_or(image_ref);
+        let fetched = get_oci_artifact(
+            path.to_string(),
+            None,
+            OciPullOptions {
+                allow_latest: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .and_then(|wasm| WitFunctions::from_component(&wasm));
+        match fetched {
+            Ok(fetched) => functions.extend(fetched),
+            Err(e) => {
+                warn!(image_ref, err = ?e, "unable to fetch component WIT to decode invocations")
+            }
+        }
+    }
+    functions
+}
+
+/// An invocation that is waiting for its results
+struct PendingResponse {
+    started: Instant,
+    from: String,
+    to: String,
+    operation: String,
+    results: Vec<u8>,
 }
 
 /// A struct that can spy on the RPC messages sent to and from an component, consumable as a stream
@@ -55,6 +165,10 @@ pub struct Spier {
     stream: futures::stream::SelectAll<async_nats::Subscriber>,
     component_id: String,
     friendly_name: Option<String>,
+    functions: WitFunctions,
+    filter: OperationFilter,
+    /// Invocations that are waiting for their results, keyed by reply subject
+    pending: HashMap<String, PendingResponse>,
 }
 
 impl Spier {
@@ -79,6 +193,7 @@ impl Spier {
         .into_iter()
         .collect::<Result<Vec<_>, _>>()?;
         subs.push(component_stream);
+        subs.push(nats_client.subscribe(RESULTS_SUBJECT).await?);
 
         let stream = futures::stream::select_all(subs);
 
@@ -86,9 +201,26 @@ impl Spier {
             stream,
             component_id: component_id.to_string(),
             friendly_name: None,
+            functions: WitFunctions::default(),
+            filter: OperationFilter::default(),
+            pending: HashMap::new(),
         })
     }
 
+    /// Decode invocations using the WIT of the given functions
+    #[must_use]
+    pub fn with_functions(mut self, functions: WitFunctions) -> Self {
+        self.functions = functions;
+        self
+    }
+
+    /// Only observe invocations that match the given filter
+    #[must_use]
+    pub fn with_filter(mut self, filter: OperationFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
     /// Returns the component name, or id if no name is set, that this spier is spying on
     pub fn component_id(&self) -> &str {
         self.friendly_name
@@ -103,48 +235,96 @@ impl Stream for Spier {
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
-        match self.stream.poll_next_unpin(cx) {
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Ready(Some(msg)) => {
-                // <lattice>.<component>.wrpc.0.0.1.<operation>@<versionX.Y.Z>.<function>
-                let mut subject_parts = msg.subject.split('.');
-                subject_parts.next(); // Skip the lattice
-                let component_id = subject_parts.next();
-                // Skip "wrpc.0.0.1", collect the rest
-                let operation = subject_parts.skip(4).collect::<Vec<_>>();
-
-                // The length assertion is to ensure that at least the `operation.function` is present since the
-                // version is technically optional.
-                if component_id.is_none() || operation.len() < 2 {
-                    debug!("Received invocation with invalid subject: {}", msg.subject);
-                    cx.waker().wake_by_ref();
-                    return Poll::Pending;
+        loop {
+            let Some(msg) = ready!(self.stream.poll_next_unpin(cx)) else {
+                return Poll::Ready(None);
+            };
+
+            // Results are published to `<reply>.results`, and terminated by an empty message
+            if let Some(reply) = msg.subject.strip_suffix(RESULTS_SUBJECT_SUFFIX) {
+                let Some(pending) = self.pending.get_mut(reply) else {
+                    continue;
+                };
+                if !msg.payload.is_empty() {
+                    pending.results.extend_from_slice(&msg.payload);
+                    continue;
                 }
-                let component_id = component_id.unwrap();
-
-                let (from, to) = if component_id == self.component_id {
-                    // Attempt to get the source from the message header
-                    let from = msg
-                        .headers
-                        .and_then(|headers| headers.get("source-id").map(ToString::to_string))
-                        .unwrap_or_else(|| "linked component".to_string());
-                    (from, (*component_id).to_string())
-                } else {
-                    (self.component_id.to_string(), (*component_id).to_string())
+                let Some(pending) = self.pending.remove(reply) else {
+                    continue;
                 };
-
-                // NOTE(thomastaylor312): Ideally we'd consume `msg.payload` above with a
-                // `Cursor` and `from_reader` and then manually reconstruct the acking using the
-                // message context, but I didn't want to waste time optimizing yet
-                Poll::Ready(Some(ObservedInvocation {
+                let message = ObservedMessage::decode_results(
+                    &self.functions,
+                    &pending.operation,
+                    pending.results,
+                );
+                return Poll::Ready(Some(ObservedInvocation {
                     timestamp: Local::now(),
-                    from,
-                    to,
-                    operation: operation.join("."),
-                    message: ObservedMessage::parse(msg.payload.to_vec()),
-                }))
+                    from: pending.to,
+                    to: pending.from,
+                    operation: pending.operation,
+                    message,
+                    response: true,
+                }));
+            }
+
+            // <lattice>.<component>.wrpc.0.0.1.<operation>@<versionX.Y.Z>.<function>
+            let mut subject_parts = msg.subject.split('.');
+            subject_parts.next(); // Skip the lattice
+            let component_id = subject_parts.next();
+            // Skip "wrpc.0.0.1", collect the rest
+            let operation = subject_parts.skip(4).collect::<Vec<_>>();
+
+            // The length assertion is to ensure that at least the `operation.function` is present since the
+            // version is technically optional.
+            if component_id.is_none() || operation.len() < 2 {
+                debug!("Received invocation with invalid subject: {}", msg.subject);
+                continue;
             }
-            Poll::Pending => Poll::Pending,
+            let component_id = component_id.unwrap();
+            let operation = operation.join(".");
+            if !self.filter.matches(&self.functions, &operation) {
+                continue;
+            }
+
+            let (from, to) = if component_id == self.component_id {
+                // Attempt to get the source from the message header
+                let from = msg
+                    .headers
+                    .and_then(|headers| headers.get("source-id").map(ToString::to_string))
+                    .unwrap_or_else(|| "linked component".to_string());
+                (from, (*component_id).to_string())
+            } else {
+                (self.component_id.to_string(), (*component_id).to_string())
+            };
+
+            if let Some(reply) = msg.reply {
+                self.pending
+                    .retain(|_, pending| pending.started.elapsed() < MAX_RESPONSE_WAIT);
+                self.pending.insert(
+                    reply.to_string(),
+                    PendingResponse {
+                        started: Instant::now(),
+                        from: from.clone(),
+                        to: to.clone(),
+                        operation: operation.clone(),
+                        results: Vec::new(),
+                    },
+                );
+            }
+
+            // NOTE(thomastaylor312): Ideally we'd consume `msg.payload` above with a
+            // `Cursor` and `from_reader` and then manually reconstruct the acking using the
+            // message context, but I didn't want to waste time optimizing yet
+            let message =
+                ObservedMessage::decode_params(&self.functions, &operation, msg.payload.to_vec());
+            return Poll::Ready(Some(ObservedInvocation {
+                timestamp: Local::now(),
+                from,
+                to,
+                operation,
+                message,
+                response: false,
+            }));
         }
     }
 }
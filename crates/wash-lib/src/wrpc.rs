@@ -0,0 +1,982 @@
+//! Conversion between wRPC-encoded values and JSON, using the WIT types of the functions that
+//! values are passed to and returned from.
+//!
+//! Values are represented in JSON as follows:
+//!
+//! - lists, including `list<u8>`, are arrays
+//! - `option<T>` is `null` or the value, or `{ "some": T }` when `T` is itself an option
+//! - `result<T, E>` is `{ "ok": T }` or `{ "err": E }` (any other value is encoded as `ok`)
+//! - variants are `{ "<case>": payload }` or `"<case>"`, enums are `"<case>"`
+//! - flags are an array of the flags that are set
+//! - records are objects, and tuples are arrays
+//!
+//! Resources, futures and streams are not supported.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, ensure, Context as _, Result};
+use serde_json::{json, Value};
+use wit_parser::{
+    Enum, Flags, Function, FunctionKind, InterfaceId, PackageId, Record, Resolve, Result_, Results,
+    SourceMap, Tuple, Type, TypeDefKind, Variant,
+};
+
+/// WIT files of the `wrpc:keyvalue` package, which the host invokes over wRPC when a component
+/// imports `wasi:keyvalue`
+const WRPC_KEYVALUE_WIT: &[(&str, &str)] = &[
+    (
+        "world.wit",
+        include_str!("../../runtime/wit/deps/keyvalue-wrpc/world.wit"),
+    ),
+    (
+        "store.wit",
+        include_str!("../../runtime/wit/deps/keyvalue-wrpc/store.wit"),
+    ),
+    (
+        "atomic.wit",
+        include_str!("../../runtime/wit/deps/keyvalue-wrpc/atomic.wit"),
+    ),
+    (
+        "batch.wit",
+        include_str!("../../runtime/wit/deps/keyvalue-wrpc/batch.wit"),
+    ),
+    (
+        "watch.wit",
+        include_str!("../../runtime/wit/deps/keyvalue-wrpc/watch.wit"),
+    ),
+];
+
+/// WIT files of the `wasi:io` package, which `wasi:blobstore` depends on
+const WASI_IO_WIT: &[(&str, &str)] = &[
+    (
+        "world.wit",
+        include_str!("../../runtime/wit/deps/io/world.wit"),
+    ),
+    (
+        "error.wit",
+        include_str!("../../runtime/wit/deps/io/error.wit"),
+    ),
+    (
+        "poll.wit",
+        include_str!("../../runtime/wit/deps/io/poll.wit"),
+    ),
+    (
+        "streams.wit",
+        include_str!("../../runtime/wit/deps/io/streams.wit"),
+    ),
+];
+
+/// WIT files of the `wasi:blobstore` package, which `wrpc:blobstore` depends on
+const WASI_BLOBSTORE_WIT: &[(&str, &str)] = &[
+    (
+        "world.wit",
+        include_str!("../../runtime/wit/deps/blobstore/world.wit"),
+    ),
+    (
+        "types.wit",
+        include_str!("../../runtime/wit/deps/blobstore/types.wit"),
+    ),
+    (
+        "container.wit",
+        include_str!("../../runtime/wit/deps/blobstore/container.wit"),
+    ),
+    (
+        "blobstore.wit",
+        include_str!("../../runtime/wit/deps/blobstore/blobstore.wit"),
+    ),
+];
+
+/// WIT files of the `wrpc:blobstore` package, which the host invokes over wRPC when a component
+/// imports `wasi:blobstore`
+///
+/// Functions that stream object data are not indexed, since streams can't be converted to JSON.
+const WRPC_BLOBSTORE_WIT: &[(&str, &str)] = &[
+    (
+        "world.wit",
+        include_str!("../../runtime/wit/deps/blobstore-wrpc-0-1-0/world.wit"),
+    ),
+    (
+        "types.wit",
+        include_str!("../../runtime/wit/deps/blobstore-wrpc-0-1-0/types.wit"),
+    ),
+    (
+        "blobstore.wit",
+        include_str!("../../runtime/wit/deps/blobstore-wrpc-0-1-0/blobstore.wit"),
+    ),
+];
+
+/// Parse a WIT package made up of multiple files into a [`Resolve`]
+fn push_package(resolve: &mut Resolve, files: &[(&str, &str)]) -> Result<PackageId> {
+    let mut map = SourceMap::new();
+    for (name, contents) in files {
+        map.push(Path::new(name), *contents);
+    }
+    resolve.push_group(map.parse()?)
+}
+
+/// Parse the `wrpc:keyvalue` package into a [`Resolve`], as vendored by the runtime
+pub fn push_wrpc_keyvalue(resolve: &mut Resolve) -> Result<PackageId> {
+    push_package(resolve, WRPC_KEYVALUE_WIT).context("failed to parse wRPC keyvalue WIT")
+}
+
+/// Parse the `wrpc:blobstore` package and the packages it depends on into a [`Resolve`], as
+/// vendored by the runtime
+pub fn push_wrpc_blobstore(resolve: &mut Resolve) -> Result<PackageId> {
+    push_package(resolve, WASI_IO_WIT).context("failed to parse WASI I/O WIT")?;
+    push_package(resolve, WASI_BLOBSTORE_WIT).context("failed to parse WASI blobstore WIT")?;
+    push_package(resolve, WRPC_BLOBSTORE_WIT).context("failed to parse wRPC blobstore WIT")
+}
+
+/// Name of the wRPC instance that serves an interface, i.e. `namespace:package/interface@version`
+pub fn instance_name(resolve: &Resolve, id: InterfaceId) -> Option<String> {
+    let iface = resolve.interfaces.get(id)?;
+    let pkg = &resolve.packages.get(iface.package?)?.name;
+    Some(format!(
+        "{}:{}/{}{}",
+        pkg.namespace,
+        pkg.name,
+        iface.name.as_deref()?,
+        pkg.version
+            .as_ref()
+            .map(|v| format!("@{v}"))
+            .unwrap_or_default(),
+    ))
+}
+
+/// Index of the functions that can be invoked over wRPC, keyed by instance and function name
+#[derive(Clone, Debug, Default)]
+pub struct WitFunctions {
+    functions: BTreeMap<(String, String), (Arc<Resolve>, Function)>,
+}
+
+impl WitFunctions {
+    /// Index the functions of all interfaces in a [`Resolve`]
+    ///
+    /// The `wrpc:keyvalue` and `wrpc:blobstore` interfaces, which the host invokes in place of
+    /// `wasi:keyvalue` and `wasi:blobstore` imports, are always included.
+    pub fn from_resolve(resolve: Resolve) -> Result<Self> {
+        let mut functions = Self::default();
+        functions.insert(resolve);
+        let mut keyvalue = Resolve::default();
+        push_wrpc_keyvalue(&mut keyvalue)?;
+        functions.insert(keyvalue);
+        let mut blobstore = Resolve::default();
+        push_wrpc_blobstore(&mut blobstore)?;
+        functions.insert(blobstore);
+        Ok(functions)
+    }
+
+    /// Index the functions of the WIT embedded in a component
+    pub fn from_component(wasm: &[u8]) -> Result<Self> {
+        let decoded = wit_component::decode(wasm).context("failed to decode component WIT")?;
+        Self::from_resolve(decoded.resolve().clone())
+    }
+
+    /// Index the functions of the WIT package at a path, which may be a file or a directory
+    pub fn from_wit_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut resolve = Resolve::default();
+        resolve
+            .push_path(path)
+            .with_context(|| format!("failed to parse WIT at [{}]", path.display()))?;
+        Self::from_resolve(resolve)
+    }
+
+    fn insert(&mut self, resolve: Resolve) {
+        let resolve = Arc::new(resolve);
+        for (id, iface) in &resolve.interfaces {
+            let Some(instance) = instance_name(&resolve, id) else {
+                continue;
+            };
+            for (name, function) in &iface.functions {
+                if ensure_supported(&resolve, function).is_ok() {
+                    self.functions.insert(
+                        (instance.clone(), name.clone()),
+                        (Arc::clone(&resolve), function.clone()),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Add all functions of another index to this one
+    pub fn extend(&mut self, other: Self) {
+        self.functions.extend(other.functions);
+    }
+
+    /// Returns true if no functions are indexed
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+
+    /// Split an operation, i.e. `<instance>.<function>`, into its instance and function name
+    ///
+    /// Both versions and resource functions may contain a `.`, so indexed functions are matched
+    /// first, falling back to splitting at the last `.`.
+    pub fn split_operation<'a>(&self, operation: &'a str) -> Option<(&'a str, &'a str)> {
+        operation
+            .match_indices('.')
+            .map(|(idx, _)| (&operation[..idx], &operation[idx + 1..]))
+            .find(|(instance, func)| {
+                self.functions
+                    .contains_key(&(instance.to_string(), func.to_string()))
+            })
+            .or_else(|| operation.rsplit_once('.'))
+    }
+
+    /// Look up an indexed function
+    pub fn get(&self, instance: &str, func: &str) -> Option<(&Resolve, &Function)> {
+        self.functions
+            .get(&(instance.to_string(), func.to_string()))
+            .map(|(resolve, function)| (resolve.as_ref(), function))
+    }
+
+    /// Decode the parameters of an invocation of an operation into an object keyed by parameter
+    /// name, returning `None` if the function is not indexed
+    pub fn decode_params(&self, operation: &str, mut payload: &[u8]) -> Option<Result<Value>> {
+        let (instance, func) = self.split_operation(operation)?;
+        let (resolve, function) = self.get(instance, func)?;
+        let decoded = decode_params(resolve, function, &mut payload)
+            .map_err(DecodeError::into_anyhow)
+            .and_then(|params| {
+                ensure!(payload.is_empty(), "parameters have trailing bytes");
+                Ok(function
+                    .params
+                    .iter()
+                    .map(|(name, _)| name.clone())
+                    .zip(params)
+                    .collect::<serde_json::Map<_, _>>()
+                    .into())
+            });
+        Some(decoded)
+    }
+
+    /// Decode the results of an invocation of an operation, returning `None` if the function is
+    /// not indexed
+    pub fn decode_results(&self, operation: &str, mut payload: &[u8]) -> Option<Result<Value>> {
+        let (instance, func) = self.split_operation(operation)?;
+        let (resolve, function) = self.get(instance, func)?;
+        let decoded = decode_results(resolve, &function.results, &mut payload)
+            .map_err(DecodeError::into_anyhow)
+            .and_then(|results| {
+                ensure!(payload.is_empty(), "results have trailing bytes");
+                Ok(results)
+            });
+        Some(decoded)
+    }
+}
+
+/// Ensure that the parameters and results of a function can be converted to and from JSON
+pub fn ensure_supported(resolve: &Resolve, function: &Function) -> Result<()> {
+    ensure!(
+        matches!(function.kind, FunctionKind::Freestanding),
+        "resource functions are not supported"
+    );
+    for ty in function
+        .params
+        .iter()
+        .map(|(_, ty)| ty)
+        .chain(function.results.iter_types())
+    {
+        ensure_supported_type(resolve, ty)?;
+    }
+    Ok(())
+}
+
+/// Ensure that values of a type can be converted to and from JSON
+fn ensure_supported_type(resolve: &Resolve, ty: &Type) -> Result<()> {
+    let Type::Id(id) = ty else {
+        return Ok(());
+    };
+    match &resolve.types[*id].kind {
+        TypeDefKind::Type(ty) | TypeDefKind::List(ty) | TypeDefKind::Option(ty) => {
+            ensure_supported_type(resolve, ty)
+        }
+        TypeDefKind::Result(Result_ { ok, err }) => ok
+            .iter()
+            .chain(err)
+            .try_for_each(|ty| ensure_supported_type(resolve, ty)),
+        TypeDefKind::Variant(Variant { cases }) => cases
+            .iter()
+            .filter_map(|case| case.ty.as_ref())
+            .try_for_each(|ty| ensure_supported_type(resolve, ty)),
+        TypeDefKind::Record(Record { fields }) => fields
+            .iter()
+            .try_for_each(|field| ensure_supported_type(resolve, &field.ty)),
+        TypeDefKind::Tuple(Tuple { types }) => types
+            .iter()
+            .try_for_each(|ty| ensure_supported_type(resolve, ty)),
+        TypeDefKind::Enum(_) | TypeDefKind::Flags(_) => Ok(()),
+        kind => bail!(
+            "values of type [{}] cannot be converted to JSON",
+            kind.as_str()
+        ),
+    }
+}
+
+/// Error while decoding a value from a buffer of bytes
+#[derive(Debug)]
+pub enum DecodeError {
+    /// More bytes are needed to decode the value
+    Incomplete,
+    /// The bytes do not contain a valid value
+    Invalid(anyhow::Error),
+}
+
+impl From<anyhow::Error> for DecodeError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Invalid(e)
+    }
+}
+
+impl DecodeError {
+    /// Convert into an error, treating missing bytes as invalid
+    pub fn into_anyhow(self) -> anyhow::Error {
+        match self {
+            Self::Incomplete => anyhow!("unexpected end of value"),
+            Self::Invalid(e) => e,
+        }
+    }
+}
+
+/// Take `n` bytes from the front of a buffer
+fn take<'a>(buf: &mut &'a [u8], n: usize) -> std::result::Result<&'a [u8], DecodeError> {
+    if buf.len() < n {
+        return Err(DecodeError::Incomplete);
+    }
+    let (bytes, rest) = buf.split_at(n);
+    *buf = rest;
+    Ok(bytes)
+}
+
+fn read_uleb128(buf: &mut &[u8]) -> std::result::Result<u64, DecodeError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = take(buf, 1)?[0];
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(anyhow!("LEB128 value overflows 64 bits").into())
+}
+
+fn read_sleb128(buf: &mut &[u8]) -> std::result::Result<i64, DecodeError> {
+    let mut value = 0i64;
+    for shift in (0..64).step_by(7) {
+        let byte = take(buf, 1)?[0];
+        value |= i64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            if shift + 7 < 64 && byte & 0x40 != 0 {
+                value |= -1 << (shift + 7);
+            }
+            return Ok(value);
+        }
+    }
+    Err(anyhow!("LEB128 value overflows 64 bits").into())
+}
+
+fn write_uleb128(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+fn write_sleb128(buf: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+/// Write a length prefix, as used for strings and lists
+fn write_len(buf: &mut Vec<u8>, len: usize) -> Result<()> {
+    let len = u32::try_from(len).context("length does not fit in u32")?;
+    write_uleb128(buf, len.into());
+    Ok(())
+}
+
+/// Decode a value of type `ty` from the front of a buffer
+pub fn decode(
+    resolve: &Resolve,
+    ty: &Type,
+    buf: &mut &[u8],
+) -> std::result::Result<Value, DecodeError> {
+    let value = match ty {
+        Type::Bool => json!(take(buf, 1)?[0] != 0),
+        Type::U8 => json!(take(buf, 1)?[0]),
+        Type::S8 => json!(take(buf, 1)?[0] as i8),
+        Type::U16 | Type::U32 | Type::U64 => json!(read_uleb128(buf)?),
+        Type::S16 | Type::S32 | Type::S64 => json!(read_sleb128(buf)?),
+        Type::F32 => json!(f32::from_le_bytes(
+            take(buf, 4)?.try_into().map_err(anyhow::Error::from)?
+        )),
+        Type::F64 => json!(f64::from_le_bytes(
+            take(buf, 8)?.try_into().map_err(anyhow::Error::from)?
+        )),
+        Type::Char => {
+            let len = match buf.first().ok_or(DecodeError::Incomplete)? {
+                0x00..=0x7f => 1,
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => return Err(anyhow!("invalid UTF-8 in char").into()),
+            };
+            let s = std::str::from_utf8(take(buf, len)?).map_err(anyhow::Error::from)?;
+            json!(s)
+        }
+        Type::String => {
+            let len = read_uleb128(buf)? as usize;
+            let s = std::str::from_utf8(take(buf, len)?).map_err(anyhow::Error::from)?;
+            json!(s)
+        }
+        Type::Id(id) => match &resolve.types[*id].kind {
+            TypeDefKind::Type(ty) => decode(resolve, ty, buf)?,
+            TypeDefKind::List(ty) => {
+                let len = read_uleb128(buf)?;
+                let mut items = Vec::new();
+                for _ in 0..len {
+                    items.push(decode(resolve, ty, buf)?);
+                }
+                Value::Array(items)
+            }
+            TypeDefKind::Option(ty) => match take(buf, 1)?[0] {
+                0 => Value::Null,
+                _ if is_option(resolve, ty) => json!({ "some": decode(resolve, ty, buf)? }),
+                _ => decode(resolve, ty, buf)?,
+            },
+            TypeDefKind::Result(Result_ { ok, err }) => {
+                let (case, ty) = match take(buf, 1)?[0] {
+                    0 => ("ok", ok),
+                    _ => ("err", err),
+                };
+                let payload = match ty {
+                    Some(ty) => decode(resolve, ty, buf)?,
+                    None => Value::Null,
+                };
+                json!({ case: payload })
+            }
+            TypeDefKind::Variant(Variant { cases }) => {
+                let disc = read_uleb128(buf)? as usize;
+                let case = cases
+                    .get(disc)
+                    .ok_or_else(|| anyhow!("invalid variant discriminant [{disc}]"))?;
+                match &case.ty {
+                    Some(ty) => json!({ case.name.as_str(): decode(resolve, ty, buf)? }),
+                    None => json!(case.name),
+                }
+            }
+            TypeDefKind::Enum(Enum { cases }) => {
+                let disc = read_uleb128(buf)? as usize;
+                let case = cases
+                    .get(disc)
+                    .ok_or_else(|| anyhow!("invalid enum discriminant [{disc}]"))?;
+                json!(case.name)
+            }
+            TypeDefKind::Flags(Flags { flags }) => {
+                let bits = take(buf, flags.len().div_ceil(8).max(1))?;
+                let set = flags
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| bits[i / 8] & (1 << (i % 8)) != 0)
+                    .map(|(_, flag)| json!(flag.name))
+                    .collect();
+                Value::Array(set)
+            }
+            TypeDefKind::Record(Record { fields }) => {
+                let mut obj = serde_json::Map::new();
+                for field in fields {
+                    obj.insert(field.name.clone(), decode(resolve, &field.ty, buf)?);
+                }
+                Value::Object(obj)
+            }
+            TypeDefKind::Tuple(Tuple { types }) => Value::Array(
+                types
+                    .iter()
+                    .map(|ty| decode(resolve, ty, buf))
+                    .collect::<std::result::Result<_, _>>()?,
+            ),
+            kind => {
+                return Err(anyhow!(
+                    "values of type [{}] cannot be converted to JSON",
+                    kind.as_str()
+                )
+                .into())
+            }
+        },
+    };
+    Ok(value)
+}
+
+/// Decode the parameters of a function from the front of a buffer
+pub fn decode_params(
+    resolve: &Resolve,
+    function: &Function,
+    buf: &mut &[u8],
+) -> std::result::Result<Vec<Value>, DecodeError> {
+    function
+        .params
+        .iter()
+        .map(|(_, ty)| decode(resolve, ty, buf))
+        .collect()
+}
+
+/// Decode the results of a function from the front of a buffer
+pub fn decode_results(
+    resolve: &Resolve,
+    results: &Results,
+    buf: &mut &[u8],
+) -> std::result::Result<Value, DecodeError> {
+    match results {
+        Results::Anon(ty) => decode(resolve, ty, buf),
+        Results::Named(results) => {
+            let mut obj = serde_json::Map::new();
+            for (name, ty) in results {
+                obj.insert(name.clone(), decode(resolve, ty, buf)?);
+            }
+            Ok(Value::Object(obj))
+        }
+    }
+}
+
+/// Encode the results of a function
+pub fn encode_results(
+    resolve: &Resolve,
+    results: &Results,
+    value: &Value,
+    buf: &mut Vec<u8>,
+) -> Result<()> {
+    match results {
+        Results::Anon(ty) => encode(resolve, ty, value, buf),
+        Results::Named(params) => {
+            for (name, ty) in params {
+                match value.get(name) {
+                    Some(value) => encode(resolve, ty, value, buf),
+                    None => encode(resolve, ty, &default_value(resolve, ty), buf),
+                }
+                .with_context(|| format!("failed to encode result [{name}]"))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Retrieve an unsigned integer that is at most `max`
+fn uint(value: &Value, max: u64) -> Result<u64> {
+    let v = value.as_u64().context("expected an unsigned integer")?;
+    ensure!(v <= max, "integer [{v}] is out of range");
+    Ok(v)
+}
+
+/// Retrieve a signed integer that is between `min` and `max`
+fn sint(value: &Value, min: i64, max: i64) -> Result<i64> {
+    let v = value.as_i64().context("expected an integer")?;
+    ensure!((min..=max).contains(&v), "integer [{v}] is out of range");
+    Ok(v)
+}
+
+/// Returns true if `ty` is an option, in which case the `some` case of an option of `ty` is tagged
+fn is_option(resolve: &Resolve, ty: &Type) -> bool {
+    match ty {
+        Type::Id(id) => match &resolve.types[*id].kind {
+            TypeDefKind::Type(ty) => is_option(resolve, ty),
+            TypeDefKind::Option(_) => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Encode a value of type `ty`
+pub fn encode(resolve: &Resolve, ty: &Type, value: &Value, buf: &mut Vec<u8>) -> Result<()> {
+    match ty {
+        Type::Bool => buf.push(value.as_bool().context("expected a boolean")?.into()),
+        Type::U8 => buf.push(uint(value, u8::MAX.into())? as u8),
+        Type::U16 => write_uleb128(buf, uint(value, u16::MAX.into())?),
+        Type::U32 => write_uleb128(buf, uint(value, u32::MAX.into())?),
+        Type::U64 => write_uleb128(buf, uint(value, u64::MAX)?),
+        Type::S8 => buf.push(sint(value, i8::MIN.into(), i8::MAX.into())? as i8 as u8),
+        Type::S16 => write_sleb128(buf, sint(value, i16::MIN.into(), i16::MAX.into())?),
+        Type::S32 => write_sleb128(buf, sint(value, i32::MIN.into(), i32::MAX.into())?),
+        Type::S64 => write_sleb128(buf, sint(value, i64::MIN, i64::MAX)?),
+        Type::F32 => {
+            let v = value.as_f64().context("expected a number")? as f32;
+            buf.extend(v.to_le_bytes());
+        }
+        Type::F64 => buf.extend(value.as_f64().context("expected a number")?.to_le_bytes()),
+        Type::Char => {
+            let s = value.as_str().context("expected a string")?;
+            let mut chars = s.chars();
+            let (Some(c), None) = (chars.next(), chars.next()) else {
+                bail!("expected a single character");
+            };
+            buf.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
+        }
+        Type::String => {
+            let s = value.as_str().context("expected a string")?;
+            write_len(buf, s.len())?;
+            buf.extend(s.as_bytes());
+        }
+        Type::Id(id) => match &resolve.types[*id].kind {
+            TypeDefKind::Type(ty) => encode(resolve, ty, value, buf)?,
+            TypeDefKind::List(ty) => {
+                let items = value.as_array().context("expected an array")?;
+                write_len(buf, items.len())?;
+                for item in items {
+                    encode(resolve, ty, item, buf)?;
+                }
+            }
+            TypeDefKind::Option(ty) => {
+                if value.is_null() {
+                    buf.push(0);
+                } else if is_option(resolve, ty) {
+                    let value = value
+                        .as_object()
+                        .filter(|obj| obj.len() == 1)
+                        .and_then(|obj| obj.get("some"))
+                        .context("expected `null`, or an object with a `some` value")?;
+                    buf.push(1);
+                    encode(resolve, ty, value, buf)?;
+                } else {
+                    buf.push(1);
+                    encode(resolve, ty, value, buf)?;
+                }
+            }
+            TypeDefKind::Result(Result_ { ok, err }) => {
+                let (disc, ty, payload) = match value.as_object() {
+                    Some(obj) if obj.len() == 1 && obj.contains_key("ok") => (0, ok, &obj["ok"]),
+                    Some(obj) if obj.len() == 1 && obj.contains_key("err") => (1, err, &obj["err"]),
+                    _ => (0, ok, value),
+                };
+                buf.push(disc);
+                if let Some(ty) = ty {
+                    encode(resolve, ty, payload, buf)?;
+                }
+            }
+            TypeDefKind::Variant(Variant { cases }) => {
+                let (name, payload) = match value {
+                    Value::String(name) => (name.as_str(), None),
+                    Value::Object(obj) if obj.len() == 1 => {
+                        let (name, payload) = obj.iter().next().context("missing variant case")?;
+                        (name.as_str(), Some(payload))
+                    }
+                    _ => bail!("expected a variant case name, or an object with a single case"),
+                };
+                let (disc, case) = cases
+                    .iter()
+                    .enumerate()
+                    .find(|(_, case)| case.name == name)
+                    .with_context(|| format!("unknown variant case [{name}]"))?;
+                write_uleb128(buf, disc as u64);
+                if let Some(ty) = &case.ty {
+                    match payload {
+                        Some(payload) => encode(resolve, ty, payload, buf)?,
+                        None => encode(resolve, ty, &default_value(resolve, ty), buf)?,
+                    }
+                }
+            }
+            TypeDefKind::Enum(Enum { cases }) => {
+                let name = value.as_str().context("expected an enum case name")?;
+                let disc = cases
+                    .iter()
+                    .position(|case| case.name == name)
+                    .with_context(|| format!("unknown enum case [{name}]"))?;
+                write_uleb128(buf, disc as u64);
+            }
+            TypeDefKind::Flags(Flags { flags }) => {
+                let mut bits = vec![0u8; flags.len().div_ceil(8).max(1)];
+                for name in value.as_array().context("expected an array of flags")? {
+                    let name = name.as_str().context("expected a flag name")?;
+                    let idx = flags
+                        .iter()
+                        .position(|flag| flag.name == name)
+                        .with_context(|| format!("unknown flag [{name}]"))?;
+                    bits[idx / 8] |= 1 << (idx % 8);
+                }
+                buf.extend(bits);
+            }
+            TypeDefKind::Record(Record { fields }) => {
+                let obj = value.as_object().context("expected an object")?;
+                for field in fields {
+                    match obj.get(&field.name) {
+                        Some(value) => encode(resolve, &field.ty, value, buf),
+                        None => encode(resolve, &field.ty, &default_value(resolve, &field.ty), buf),
+                    }
+                    .with_context(|| format!("failed to encode field [{}]", field.name))?;
+                }
+            }
+            TypeDefKind::Tuple(Tuple { types }) => {
+                let items = value.as_array().context("expected an array")?;
+                ensure!(
+                    items.len() == types.len(),
+                    "expected a tuple of {} values",
+                    types.len()
+                );
+                for (ty, item) in types.iter().zip(items) {
+                    encode(resolve, ty, item, buf)?;
+                }
+            }
+            kind => bail!(
+                "values of type [{}] cannot be converted to JSON",
+                kind.as_str()
+            ),
+        },
+    }
+    Ok(())
+}
+
+/// The default results of a function, made up of the default values of its result types
+pub fn default_results(resolve: &Resolve, results: &Results) -> Value {
+    match results {
+        Results::Anon(ty) => default_value(resolve, ty),
+        Results::Named(params) => Value::Object(
+            params
+                .iter()
+                .map(|(name, ty)| (name.clone(), default_value(resolve, ty)))
+                .collect(),
+        ),
+    }
+}
+
+/// The default value of a type, i.e. zero, empty, `none` or `ok`, or the first case
+pub fn default_value(resolve: &Resolve, ty: &Type) -> Value {
+    match ty {
+        Type::Bool => json!(false),
+        Type::U8 | Type::U16 | Type::U32 | Type::U64 => json!(0),
+        Type::S8 | Type::S16 | Type::S32 | Type::S64 => json!(0),
+        Type::F32 | Type::F64 => json!(0.0),
+        Type::Char => json!("\0"),
+        Type::String => json!(""),
+        Type::Id(id) => match &resolve.types[*id].kind {
+            TypeDefKind::Type(ty) => default_value(resolve, ty),
+            TypeDefKind::List(_) => json!([]),
+            TypeDefKind::Option(_) => Value::Null,
+            TypeDefKind::Result(Result_ { ok, .. }) => {
+                json!({ "ok": ok.as_ref().map(|ty| default_value(resolve, ty)) })
+            }
+            TypeDefKind::Variant(Variant { cases }) => match cases.first() {
+                Some(case) => match &case.ty {
+                    Some(ty) => json!({ case.name.as_str(): default_value(resolve, ty) }),
+                    None => json!(case.name),
+                },
+                None => Value::Null,
+            },
+            TypeDefKind::Enum(Enum { cases }) => cases
+                .first()
+                .map(|case| json!(case.name))
+                .unwrap_or_default(),
+            TypeDefKind::Flags(_) => json!([]),
+            TypeDefKind::Record(Record { fields }) => Value::Object(
+                fields
+                    .iter()
+                    .map(|field| (field.name.clone(), default_value(resolve, &field.ty)))
+                    .collect(),
+            ),
+            TypeDefKind::Tuple(Tuple { types }) => {
+                Value::Array(types.iter().map(|ty| default_value(resolve, ty)).collect())
+            }
+            _ => Value::Null,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyvalue_function(resolve: &Resolve, iface: &str, name: &str) -> Function {
+        let (_, pkg) = resolve.packages.iter().next().unwrap();
+        resolve.interfaces[pkg.interfaces[iface]].functions[name].clone()
+    }
+
+    fn keyvalue_resolve() -> Resolve {
+        let mut resolve = Resolve::default();
+        push_wrpc_keyvalue(&mut resolve).unwrap();
+        resolve
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let resolve = keyvalue_resolve();
+        let get = keyvalue_function(&resolve, "store", "get");
+
+        // result<option<list<u8>>, error>
+        let mut buf = Vec::new();
+        encode_results(
+            &resolve,
+            &get.results,
+            &json!({ "ok": [104, 105] }),
+            &mut buf,
+        )
+        .unwrap();
+        assert_eq!(buf, [0, 1, 2, b'h', b'i']);
+        assert_eq!(
+            decode_results(&resolve, &get.results, &mut buf.as_slice()).unwrap(),
+            json!({ "ok": [104, 105] })
+        );
+        // Bytes are always arrays, even if they are valid UTF-8
+        assert!(encode_results(&resolve, &get.results, &json!({ "ok": "hi" }), &mut buf).is_err());
+        buf.clear();
+        encode_results(
+            &resolve,
+            &get.results,
+            &json!({ "err": { "other": "oops" } }),
+            &mut buf,
+        )
+        .unwrap();
+        assert_eq!(buf, [1, 2, 4, b'o', b'o', b'p', b's']);
+        buf.clear();
+        encode_results(
+            &resolve,
+            &get.results,
+            &json!({ "err": "access-denied" }),
+            &mut buf,
+        )
+        .unwrap();
+        assert_eq!(buf, [1, 1]);
+        assert!(
+            encode_results(&resolve, &get.results, &json!({ "err": "nope" }), &mut buf).is_err()
+        );
+
+        // Defaults and records
+        let list_keys = keyvalue_function(&resolve, "store", "list-keys");
+        let default = default_results(&resolve, &list_keys.results);
+        assert_eq!(default, json!({ "ok": { "keys": [], "cursor": null } }));
+        buf.clear();
+        encode_results(&resolve, &list_keys.results, &default, &mut buf).unwrap();
+        assert_eq!(buf, [0, 0, 0]);
+
+        // Parameters can be decoded incrementally
+        let increment = keyvalue_function(&resolve, "atomics", "increment");
+        let mut params = Vec::new();
+        encode(&resolve, &Type::String, &json!("b"), &mut params).unwrap();
+        encode(&resolve, &Type::String, &json!("k"), &mut params).unwrap();
+        encode(&resolve, &Type::U64, &json!(300), &mut params).unwrap();
+        assert!(matches!(
+            decode_params(&resolve, &increment, &mut &params[..params.len() - 1]),
+            Err(DecodeError::Incomplete)
+        ));
+        assert_eq!(
+            decode_params(&resolve, &increment, &mut params.as_slice()).unwrap(),
+            vec![json!("b"), json!("k"), json!(300)]
+        );
+
+        // Nested options are tagged, so that `some(none)` can be told apart from `none`
+        let mut resolve = Resolve::default();
+        resolve
+            .push_str(
+                "test.wit",
+                r#"
+package test:options;
+
+interface options {
+    type maybe = option<u8>;
+
+    get: func() -> option<maybe>;
+}
+"#,
+            )
+            .unwrap();
+        let get = keyvalue_function(&resolve, "options", "get");
+        for (value, expected) in [
+            (json!(null), vec![0]),
+            (json!({ "some": null }), vec![1, 0]),
+            (json!({ "some": 7 }), vec![1, 1, 7]),
+        ] {
+            buf.clear();
+            encode_results(&resolve, &get.results, &value, &mut buf).unwrap();
+            assert_eq!(buf, expected);
+            assert_eq!(
+                decode_results(&resolve, &get.results, &mut buf.as_slice()).unwrap(),
+                value
+            );
+        }
+        assert!(encode_results(&resolve, &get.results, &json!(7), &mut buf).is_err());
+
+        // Signed integers round-trip through LEB128
+        for v in [0, -1, 63, -64, 64, i64::MIN, i64::MAX] {
+            let mut buf = Vec::new();
+            write_sleb128(&mut buf, v);
+            assert_eq!(read_sleb128(&mut buf.as_slice()).unwrap(), v);
+        }
+    }
+
+    #[test]
+    fn test_wit_functions() {
+        let mut resolve = Resolve::default();
+        resolve
+            .push_str(
+                "test.wit",
+                r#"
+package test:greeter@0.1.0;
+
+interface greet {
+    greet: func(name: string, times: u8) -> list<string>;
+    split: func(s: string) -> (head: string, tail: string);
+}
+"#,
+            )
+            .unwrap();
+        let functions = WitFunctions::from_resolve(resolve).unwrap();
+
+        // The version contains a `.`, so the operation is split at the indexed function
+        assert_eq!(
+            functions.split_operation("test:greeter/greet@0.1.0.greet"),
+            Some(("test:greeter/greet@0.1.0", "greet"))
+        );
+        assert_eq!(
+            functions.split_operation("unknown:pkg/iface@0.1.0.[method]res.get"),
+            Some(("unknown:pkg/iface@0.1.0.[method]res", "get"))
+        );
+
+        assert_eq!(
+            functions
+                .decode_params("test:greeter/greet@0.1.0.greet", b"\x02hi\x03")
+                .unwrap()
+                .unwrap(),
+            json!({ "name": "hi", "times": 3 })
+        );
+        assert!(functions
+            .decode_params("test:greeter/greet@0.1.0.greet", b"\x02hi")
+            .unwrap()
+            .is_err());
+        assert!(functions
+            .decode_params("test:greeter/greet@0.1.0.greet", b"\x02hi\x03\x00")
+            .unwrap()
+            .is_err());
+        assert_eq!(
+            functions
+                .decode_results("test:greeter/greet@0.1.0.split", b"\x01a\x01b")
+                .unwrap()
+                .unwrap(),
+            json!({ "head": "a", "tail": "b" })
+        );
+        assert!(functions
+            .decode_params("test:greeter/greet@0.1.0.unknown", b"")
+            .is_none());
+
+        // wRPC keyvalue and blobstore are always indexed, except for functions that stream data
+        assert!(functions
+            .get("wrpc:keyvalue/store@0.2.0-draft", "get")
+            .is_some());
+        assert!(functions
+            .get("wrpc:blobstore/blobstore@0.1.0", "create-container")
+            .is_some());
+        assert!(functions
+            .get("wrpc:blobstore/blobstore@0.1.0", "get-container-data")
+            .is_none());
+    }
+}
@@ -1,10 +1,15 @@
-use std::{collections::HashSet, fs, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::PathBuf,
+};
 
 use claims::{assert_err, assert_ok};
 use semver::Version;
 use wash_lib::parser::{
-    load_config, CommonConfig, ComponentConfig, LanguageConfig, RegistryConfig, RustConfig,
-    TinyGoConfig, TinyGoGarbageCollector, TinyGoScheduler, TypeConfig, WasmTarget,
+    load_config, load_workspace_config, CommonConfig, ComponentConfig, InterfaceMock,
+    JavaScriptConfig, LanguageConfig, PythonConfig, RegistryConfig, RustConfig, TinyGoConfig,
+    TinyGoGarbageCollector, TinyGoScheduler, TypeConfig, WasmTarget,
 };
 
 #[tokio::test]
@@ -561,3 +566,158 @@ async fn separate_project_paths() {
         && target_path == PathBuf::from("./target")
     ));
 }
+
+#[tokio::test]
+async fn python_component() {
+    let result = load_config(
+        Some(PathBuf::from("./tests/parser/files/python_component.toml")),
+        None,
+    )
+    .await;
+    let config = assert_ok!(result);
+    assert_eq!(
+        config.language,
+        LanguageConfig::Python(PythonConfig {
+            componentize_py_path: Some("path/to/componentize-py".into()),
+            entry_module: Some("component".into()),
+            python_path: vec!["deps".into()],
+        })
+    );
+    assert!(matches!(
+        config.project_type,
+        TypeConfig::Component(ComponentConfig {
+            wit_world: Some(ref world),
+            wasm_target: WasmTarget::WasiP2,
+            ..
+        }) if world == "hello"
+    ));
+}
+
+#[tokio::test]
+async fn javascript_components() {
+    let result = load_config(
+        Some(PathBuf::from(
+            "./tests/parser/files/typescript_component.toml",
+        )),
+        None,
+    )
+    .await;
+    let config = assert_ok!(result);
+    assert_eq!(
+        config.language,
+        LanguageConfig::TypeScript(JavaScriptConfig {
+            build_script: Some("build:ts".into()),
+            entry_module: Some("dist/component.js".into()),
+            disable_features: vec!["stdio".into()],
+            ..JavaScriptConfig::default()
+        })
+    );
+
+    let result = load_config(
+        Some(PathBuf::from(
+            "./tests/parser/files/javascript_component.toml",
+        )),
+        None,
+    )
+    .await;
+    let config = assert_ok!(result);
+    assert_eq!(
+        config.language,
+        LanguageConfig::JavaScript(JavaScriptConfig::default())
+    );
+}
+
+#[tokio::test]
+async fn dev_overrides_mock() {
+    let result = load_config(
+        Some(PathBuf::from(
+            "./tests/parser/files/dev_overrides_mock.toml",
+        )),
+        None,
+    )
+    .await;
+    let config = assert_ok!(result);
+    let imports = &config.dev.overrides.imports;
+    assert_eq!(imports.len(), 3);
+
+    assert_eq!(imports[0].interface_spec, "wasi:keyvalue/store");
+    assert_eq!(imports[0].mock, Some(InterfaceMock::default()));
+
+    assert_eq!(imports[1].link_name.as_deref(), Some("reports"));
+    assert_eq!(
+        imports[1].mock,
+        Some(InterfaceMock {
+            responses: BTreeMap::from([
+                ("query".into(), serde_json::json!({ "ok": [] })),
+                (
+                    "query.query-batch".into(),
+                    serde_json::json!({ "err": { "unexpected": "connection refused" } })
+                ),
+            ]),
+        })
+    );
+
+    assert!(imports[2].mock.is_none());
+    assert!(imports[2].image_ref.is_some());
+}
+
+#[tokio::test]
+async fn hooks() {
+    let result = load_config(Some(PathBuf::from("./tests/parser/files/hooks.toml")), None).await;
+    let config = assert_ok!(result);
+    assert_eq!(config.hooks.len(), 2);
+
+    assert!(config.hooks[0]
+        .plugin
+        .ends_with("tests/parser/files/plugins/lint.wasm"));
+    assert_eq!(config.hooks[0].events, vec!["pre-build".to_string()]);
+    assert!(config.hooks[0].args.is_empty());
+
+    assert_eq!(config.hooks[1].plugin, PathBuf::from("/opt/wash/scan.wasm"));
+    assert!(config.hooks[1].events.is_empty());
+    assert_eq!(
+        config.hooks[1].args,
+        BTreeMap::from([("severity".to_string(), "high".to_string())])
+    );
+}
+
+#[tokio::test]
+async fn workspace() {
+    let config = load_workspace_config(Some(PathBuf::from("./tests/parser/files/workspace")), None)
+        .await
+        .expect("failed to load workspace")
+        .expect("should be a workspace");
+    assert_eq!(config.name, "test-workspace");
+    assert_eq!(
+        config.wasmcloud_toml_dir,
+        PathBuf::from("./tests/parser/files/workspace")
+            .canonicalize()
+            .expect("failed to canonicalize test path")
+    );
+    assert_eq!(
+        config
+            .members
+            .iter()
+            .map(|m| m.common.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["testcomponent", "testprovider"]
+    );
+
+    // Workspaces can't be loaded as projects, and projects aren't workspaces
+    assert_err!(load_config(Some(PathBuf::from("./tests/parser/files/workspace")), None).await);
+    let result = load_workspace_config(
+        Some(PathBuf::from("./tests/parser/files/rust_component.toml")),
+        None,
+    )
+    .await;
+    assert!(assert_ok!(result).is_none());
+
+    // Members must have distinct names
+    assert_err!(
+        load_workspace_config(
+            Some(PathBuf::from("./tests/parser/files/duplicate_workspace")),
+            None
+        )
+        .await
+    );
+}
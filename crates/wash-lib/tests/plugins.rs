@@ -1,6 +1,11 @@
 use std::path::PathBuf;
 
+use wash_lib::build::{build_project_with_hooks, SignConfig};
+use wash_lib::cli::CommonPackageArgs;
+use wash_lib::parser::load_config;
+use wash_lib::plugin::hooks::{Event, HookRunner};
 use wash_lib::plugin::subcommand::DirMapping;
+use wit_component::{ComponentEncoder, StringEncoding};
 
 #[tokio::test]
 async fn test_subcommand() {
@@ -66,3 +71,202 @@ async fn test_subcommand() {
         .unwrap();
     assert_eq!(file, "Hello from the plugin");
 }
+
+/// A hook plugin registered for `pre-build` and `post-build`, which succeeds on `pre-build` and
+/// fails on `post-build` with the project directory it was given as the error
+const HOOK_PLUGIN_WAT: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "Hello Hook")
+  (data (i32.const 16) "hello-hook")
+  (data (i32.const 32) "0.1.0")
+  (data (i32.const 48) "wasmCloud")
+  (data (i32.const 64) "Test hook")
+  ;; The `pre-build` and `post-build` events
+  (data (i32.const 80) "\00\01")
+  (global $heap (mut i32) (i32.const 4096))
+
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr
+      (i32.and
+        (i32.add (global.get $heap) (i32.sub (local.get 2) (i32.const 1)))
+        (i32.xor (i32.sub (local.get 2) (i32.const 1)) (i32.const -1))))
+    (global.set $heap (i32.add (local.get $ptr) (local.get 3)))
+    (local.get $ptr))
+
+  (func (export "wasmcloud:wash/hook@0.1.0#register") (result i32)
+    (i32.store (i32.const 256) (i32.const 0))
+    (i32.store (i32.const 260) (i32.const 10))
+    (i32.store (i32.const 264) (i32.const 16))
+    (i32.store (i32.const 268) (i32.const 10))
+    (i32.store (i32.const 272) (i32.const 32))
+    (i32.store (i32.const 276) (i32.const 5))
+    (i32.store (i32.const 280) (i32.const 48))
+    (i32.store (i32.const 284) (i32.const 9))
+    (i32.store (i32.const 288) (i32.const 64))
+    (i32.store (i32.const 292) (i32.const 9))
+    (i32.store (i32.const 296) (i32.const 80))
+    (i32.store (i32.const 300) (i32.const 2))
+    (i32.const 256))
+
+  ;; Parameters are the event, the project directory, the artifact, the reference and the args
+  (func (export "wasmcloud:wash/hook@0.1.0#run")
+    (param $event i32) (param $dir i32) (param $dir_len i32)
+    (param i32 i32 i32) (param i32 i32 i32) (param i32 i32)
+    (result i32)
+    (if (i32.eq (local.get $event) (i32.const 1))
+      (then
+        (i32.store8 (i32.const 512) (i32.const 1))
+        (i32.store (i32.const 516) (local.get $dir))
+        (i32.store (i32.const 520) (local.get $dir_len)))
+      (else
+        (i32.store8 (i32.const 512) (i32.const 0))))
+    (i32.const 512))
+)
+"#;
+
+/// Builds the hook plugin of [`HOOK_PLUGIN_WAT`] as a component of the `hooks` world
+fn hook_plugin() -> Vec<u8> {
+    let mut resolve = wit_parser::Resolve::default();
+    let (pkg, _) = resolve
+        .push_dir(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("wit"))
+        .expect("failed to parse plugin WIT");
+    let world = resolve
+        .select_world(pkg, Some("hooks"))
+        .expect("failed to select hooks world");
+    let mut module = wat::parse_str(HOOK_PLUGIN_WAT).expect("failed to parse hook module");
+    wit_component::embed_component_metadata(&mut module, &resolve, world, StringEncoding::UTF8)
+        .expect("failed to embed component metadata");
+    ComponentEncoder::default()
+        .module(&module)
+        .expect("failed to set hook module")
+        .validate(true)
+        .encode()
+        .expect("failed to encode hook component")
+}
+
+#[tokio::test]
+async fn test_hook() {
+    let plugin_dir = tempfile::tempdir().unwrap();
+    let plugin_path = plugin_dir.path().join("hello_hook.wasm");
+    tokio::fs::write(&plugin_path, hook_plugin()).await.unwrap();
+
+    let mut hooks = HookRunner::new().unwrap();
+    let metadata = hooks
+        .add_plugin(
+            &plugin_path,
+            &[],
+            [("greeting".to_string(), "hi".to_string())],
+        )
+        .await
+        .expect("Should be able to add hook plugin");
+    assert_eq!(metadata.id, "hello-hook");
+    assert_eq!(metadata.name, "Hello Hook");
+    assert_eq!(metadata.events, vec![Event::PreBuild, Event::PostBuild]);
+
+    let project = tempfile::tempdir().unwrap();
+    hooks
+        .run(Event::PreBuild, project.path(), None, None)
+        .await
+        .expect("Should be able to run hook plugin");
+    // The hook is not run for events it didn't register for
+    hooks
+        .run(
+            Event::PrePush,
+            project.path(),
+            None,
+            Some("localhost:5000/hook:0.1.0"),
+        )
+        .await
+        .expect("Should skip hook plugin");
+
+    // The hook is called with the canonical project directory, which it fails with
+    let project_dir = tokio::fs::canonicalize(project.path()).await.unwrap();
+    let err = hooks
+        .run(Event::PostBuild, project.path(), None, None)
+        .await
+        .expect_err("Hook plugin failure should be reported");
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "hook plugin [hello-hook] failed on post-build: {}",
+            project_dir.display()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_hook_modifies_signed_artifact() {
+    // A hook registered for `post-build` that appends a custom section named `hook` to the
+    // artifact. This is pre-compiled to save on test time. To rebuild this plugin when changes are
+    // needed (assuming relative paths from this file) run:
+    // `pushd plugins/modify_hook && cargo build --release --target wasm32-wasip2 && cp target/wasm32-wasip2/release/modify_hook.wasm ../../fixtures/modify_hook.wasm && popd`
+    let plugin_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("modify_hook.wasm");
+    let mut hooks = HookRunner::new().unwrap();
+    hooks
+        .add_plugin(&plugin_path, &[], [])
+        .await
+        .expect("Should be able to add hook plugin");
+
+    // A project that is "built" by copying a component into the build directory
+    let project = tempfile::tempdir().unwrap();
+    tokio::fs::write(
+        project.path().join("component.wasm"),
+        wat::parse_str("(component)").unwrap(),
+    )
+    .await
+    .unwrap();
+    tokio::fs::create_dir(project.path().join("build"))
+        .await
+        .unwrap();
+    tokio::fs::write(
+        project.path().join("wasmcloud.toml"),
+        r#"
+name = "hooked"
+language = "rust"
+type = "component"
+version = "0.1.0"
+
+[component]
+build_command = "cp component.wasm build/hooked.wasm"
+build_artifact = "build/hooked.wasm"
+"#,
+    )
+    .await
+    .unwrap();
+    let config = load_config(Some(project.path().to_path_buf()), Some(false))
+        .await
+        .unwrap();
+
+    let keys = tempfile::tempdir().unwrap();
+    let signed = build_project_with_hooks(
+        &config,
+        Some(&SignConfig {
+            keys_directory: Some(keys.path().to_path_buf()),
+            ..Default::default()
+        }),
+        &CommonPackageArgs::default(),
+        true,
+        &mut hooks,
+    )
+    .await
+    .expect("Should be able to build project with hooks");
+
+    // The signature covers the artifact modified by the hook
+    let wasm = tokio::fs::read(&signed).await.unwrap();
+    wascap::wasm::extract_claims(&wasm)
+        .expect("Signed artifact should have valid claims")
+        .expect("Signed artifact should have claims");
+    let sections: Vec<_> = wasmparser::Parser::new(0)
+        .parse_all(&wasm)
+        .filter_map(|payload| match payload.unwrap() {
+            wasmparser::Payload::CustomSection(section) => Some(section.name().to_string()),
+            _ => None,
+        })
+        .collect();
+    assert!(sections.iter().any(|name| name == "hook"));
+}
@@ -0,0 +1,36 @@
+wit_bindgen::generate!({
+    path: "../../../wit",
+    world: "hooks",
+    generate_all,
+});
+
+use exports::wasmcloud::wash::hook::{Context, Event, Guest, Metadata};
+
+/// An empty custom section named `hook`, which is appended to the artifact
+const HOOK_SECTION: &[u8] = &[0, 5, 4, b'h', b'o', b'o', b'k'];
+
+struct ModifyHook;
+
+impl Guest for ModifyHook {
+    fn register() -> Metadata {
+        Metadata {
+            name: "Modify Hook".to_string(),
+            id: "modify-hook".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            author: "wasmCloud".to_string(),
+            description: "Appends a custom section to the built artifact".to_string(),
+            events: vec![Event::PostBuild],
+        }
+    }
+
+    fn run(_event: Event, context: Context) -> Result<(), String> {
+        let artifact = context.artifact.ok_or("no artifact to modify")?;
+        let mut wasm = std::fs::read(&artifact)
+            .map_err(|e| format!("failed to read artifact [{artifact}]: {e}"))?;
+        wasm.extend_from_slice(HOOK_SECTION);
+        std::fs::write(&artifact, wasm)
+            .map_err(|e| format!("failed to write artifact [{artifact}]: {e}"))
+    }
+}
+
+export!(ModifyHook);
@@ -108,6 +108,20 @@ struct Args {
         env = "WASMCLOUD_ALLOW_FILE_LOAD"
     )]
     allow_file_load: bool,
+    /// Denotes if a wasmCloud host should only start components and providers that are signed with embedded claims
+    #[clap(
+        long = "require-signed-artifacts",
+        default_value_t = false,
+        env = "WASMCLOUD_REQUIRE_SIGNED_ARTIFACTS"
+    )]
+    require_signed_artifacts: bool,
+    /// A comma-separated list of account public keys of the issuers whose components and providers may be started, defaults to trusting any issuer
+    #[clap(
+        long = "trusted-issuers",
+        env = "WASMCLOUD_TRUSTED_ISSUERS",
+        value_delimiter = ','
+    )]
+    trusted_issuers: Vec<String>,
     /// Enable JSON structured logging from the wasmCloud host
     #[clap(
         long = "enable-structured-logging",
@@ -230,6 +244,15 @@ struct Args {
     #[clap(long = "secrets-topic", env = "WASMCLOUD_SECRETS_TOPIC")]
     secrets_topic_prefix: Option<String>,
 
+    /// How long secrets fetched from the secrets backend are reused, in milliseconds. Rotated secrets are refreshed regardless. Set to 0 to disable caching.
+    #[clap(
+        long = "secrets-cache-ttl-ms",
+        default_value = "30000",
+        env = "WASMCLOUD_SECRETS_CACHE_TTL_MS",
+        value_parser = parse_duration_millis,
+    )]
+    secrets_cache_ttl: Duration,
+
     /// Used in tandem with `oci_user` and `oci_password` to override credentials for a specific OCI registry.
     #[clap(
         long = "oci-registry",
@@ -254,6 +277,13 @@ struct Args {
         requires = "oci_user"
     )]
     oci_password: Option<String>,
+    /// A comma-separated list of paths to PEM files with cosign public keys. If provided, artifacts fetched from OCI registries must be signed by one of these keys
+    #[clap(
+        long = "oci-signature-public-keys",
+        env = "WASMCLOUD_OCI_SIGNATURE_PUBLIC_KEYS",
+        value_delimiter = ','
+    )]
+    oci_signature_public_keys: Vec<PathBuf>,
 
     /// Determines whether observability should be enabled.
     #[clap(
@@ -457,6 +487,7 @@ async fn main() -> anyhow::Result<()> {
         oci_registry: args.oci_registry,
         oci_user: args.oci_user,
         oci_password: args.oci_password,
+        signature_public_keys: args.oci_signature_public_keys,
     };
     if let Some(policy_topic) = args.policy_topic.as_deref() {
         anyhow::ensure!(
@@ -517,11 +548,14 @@ async fn main() -> anyhow::Result<()> {
         rpc_key: rpc_key.or_else(|| nats_key.clone()),
         rpc_tls: args.rpc_tls,
         allow_file_load: args.allow_file_load,
+        require_signed_artifacts: args.require_signed_artifacts,
+        trusted_issuers: args.trusted_issuers,
         log_level,
         enable_structured_logging: args.enable_structured_logging,
         otel_config,
         policy_service_config,
         secrets_topic_prefix: args.secrets_topic_prefix,
+        secrets_cache_ttl: args.secrets_cache_ttl,
         version: env!("CARGO_PKG_VERSION").to_string(),
         max_execution_time: args.max_execution_time,
         max_linear_memory: args.max_linear_memory,